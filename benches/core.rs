@@ -0,0 +1,67 @@
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+use parallels::buffer::{OutputBuffer, OutputKind, OutputLine};
+use parallels::search::SearchState;
+use parallels::stress::sample_line;
+
+/// Number of lines used to fill buffers
+const LINES: u64 = 10_000;
+
+fn output_line_new(c: &mut Criterion) {
+    let plain = "request id=42 path=/api/items/42 took=12ms".to_string();
+    let ansi = sample_line(42);
+
+    c.bench_function("output_line_new_plain", |b| {
+        b.iter(|| OutputLine::new(OutputKind::Stdout, black_box(plain.clone())))
+    });
+    c.bench_function("output_line_new_ansi", |b| {
+        b.iter(|| OutputLine::new(OutputKind::Stdout, black_box(ansi.clone())))
+    });
+
+    // Spans are parsed when a line is drawn
+    let line = OutputLine::new(OutputKind::Stdout, ansi);
+    c.bench_function("output_line_spans_ansi", |b| {
+        b.iter(|| black_box(&line).spans())
+    });
+}
+
+fn output_buffer_push(c: &mut Criterion) {
+    let lines: Vec<OutputLine> = (0..LINES)
+        .map(|i| OutputLine::new(OutputKind::Stdout, sample_line(i)))
+        .collect();
+
+    // Buffer smaller than the input, so pushes also evict old lines
+    c.bench_function("output_buffer_push_10k_evicting", |b| {
+        b.iter_batched(
+            || lines.clone(),
+            |lines| {
+                let mut buffer = OutputBuffer::new(1_000);
+                for line in lines {
+                    buffer.push(line);
+                }
+                buffer
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn search(c: &mut Criterion) {
+    let mut buffer = OutputBuffer::new(0);
+    for i in 0..LINES {
+        buffer.push(OutputLine::new(OutputKind::Stdout, sample_line(i)));
+    }
+
+    c.bench_function("search_10k_case_insensitive", |b| {
+        let mut state = SearchState::new();
+        b.iter(|| state.search(black_box("error"), &buffer))
+    });
+    c.bench_function("search_10k_case_sensitive", |b| {
+        let mut state = SearchState::new();
+        b.iter(|| state.search(black_box("ERROR"), &buffer))
+    });
+}
+
+criterion_group!(benches, output_line_new, output_buffer_push, search);
+criterion_main!(benches);
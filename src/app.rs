@@ -1,14 +1,65 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::io;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
 
+use chrono::{DateTime, Local, NaiveTime, TimeDelta};
 use nix::sys::signal::{Signal, killpg};
 use nix::unistd::Pid;
-use tokio::process::Child;
+
+use tokio::io::AsyncWriteExt;
+use tokio::process::ChildStdin;
 use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::task::JoinHandle;
+use tui_input::{Input, InputRequest};
 
-use crate::command::CommandRunner;
+use crate::buffer::{OutputBuffer, OutputKind, OutputLine};
+use crate::clipboard::{self, ClipboardBackend};
+use crate::command::{
+    ChildWaiter, CommandRunner, CommandSpec, FileTailer, KeyAction, Running, SocketSource, Source,
+    SpawnFailure, Spawner, check_free, detached, is_listening, template,
+};
+use crate::config::Config;
 use crate::event::AppEvent;
-use crate::search::SearchState;
-use crate::tui::{CommandStatus, TabManager};
+use crate::export::{self, TabLog};
+use crate::history::History;
+#[cfg(feature = "lua")]
+use crate::script::{Action, Script};
+use crate::search::{MatchJump, SearchJob, SearchState, path_at, word_at};
+use crate::sink::{LostRecords, MAX_FAILURES, OutputSink, SinkLine, SinkReport, Sinks};
+use crate::tui::{
+    CommandLine, CommandStatus, Completion, Renderer, StatusBarConfig, TabLayout, TabManager,
+    TimestampMode, path_executables,
+};
+
+/// Capacity of the internal event channel
+const EVENT_CHANNEL_CAPACITY: usize = 1000;
+
+/// Lines queued for a command's stdin (`stdin_from`) before more are dropped
+const PIPE_CAPACITY: usize = 1000;
+
+/// Capacity of each subscriber channel
+const SUBSCRIBER_CHANNEL_CAPACITY: usize = 1000;
+
+/// Entries kept in the events panel before the oldest are dropped
+const MAX_EVENTS: usize = 200;
+
+/// Time after which a hang probe counts as failed
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time between checks of running commands' ports for readiness
+const READY_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Program opening a URL or file with the default application
+#[cfg(target_os = "macos")]
+const OPENER: &str = "open";
+#[cfg(not(target_os = "macos"))]
+const OPENER: &str = "xdg-open";
+
+/// Environment variable holding the exit code for `after_exit` hooks
+const EXIT_CODE_VAR: &str = "PARALLELS_EXIT_CODE";
 
 /// Application mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -17,6 +68,12 @@ pub enum Mode {
     Normal,
     /// Search mode
     Search,
+    /// Visual line selection mode
+    Visual,
+    /// Output cursor mode (move a cursor over the output text)
+    Cursor,
+    /// Command-line mode (`:`)
+    Command,
 }
 
 /// Application state
@@ -24,301 +81,4107 @@ pub struct App {
     tab_manager: TabManager,
     mode: Mode,
     search_state: SearchState,
+    /// Tab the current search matches belong to
+    search_tab: Option<usize>,
     should_quit: bool,
     /// Receiver for events from background tasks
     event_rx: mpsc::Receiver<AppEvent>,
     /// Sender for events (kept to clone for spawned tasks)
     event_tx: mpsc::Sender<AppEvent>,
-    /// Child processes indexed by tab index
-    children: HashMap<usize, Child>,
+    /// Waiters of running child processes indexed by tab index
+    children: HashMap<usize, ChildWaiter>,
+    /// Reading tasks of `tail` and `source` tabs, and scripted runs
+    tasks: HashMap<usize, JoinHandle<()>>,
+    /// Starts the tabs' commands (real processes unless replaced)
+    spawner: Box<dyn Spawner>,
+    /// Tabs whose stdin each tab's stdout feeds (`stdin_from`)
+    pipe_targets: HashMap<usize, Vec<usize>>,
+    /// Writers of the stdin of running commands fed by another tab
+    stdin_writers: HashMap<usize, mpsc::Sender<String>>,
+    /// Lines piped to commands fed by another tab that haven't started yet
+    pipe_backlog: HashMap<usize, VecDeque<String>>,
+    /// Lines dropped per command fed by another tab, until reported
+    pipe_dropped: HashMap<usize, u64>,
+    /// Exit events still in flight for killed runs, to be ignored per tab
+    stale_exits: HashMap<usize, usize>,
     /// Pending restart request (tab index)
     pending_restart: Option<usize>,
+    /// Tabs whose scheduled run is due
+    pending_runs: Vec<usize>,
+    /// Tabs whose hang probe is running
+    probing: HashSet<usize>,
+    /// Tabs found hung, to be restarted
+    hung: Vec<usize>,
+    /// When running commands' ports are next checked for readiness
+    next_ready_check: Instant,
+    /// Tabs whose port is being checked for readiness
+    port_checks: HashSet<usize>,
+    /// Tabs whose `url` was opened in the browser
+    opened: HashSet<usize>,
+    /// Tab running the global setup command, until it has succeeded
+    setup_tab: Option<usize>,
+    /// Tabs whose `before_start` hook has finished for the next start
+    prepared: HashSet<usize>,
+    /// Running `before_start`/`before_restart` hooks, which start their tab
+    hooks: HashMap<usize, JoinHandle<()>>,
+    /// `Start` events still in flight from hooks that were stopped, to be
+    /// ignored per tab
+    stale_starts: HashMap<usize, usize>,
+    /// Tabs whose next spawn is a restart, which uses `restart_command`
+    restarting: HashSet<usize>,
+    /// Number of restarts per tab, for `AppEvent::Restarted`
+    restart_counts: HashMap<usize, usize>,
+    /// Command-line input (Command mode)
+    command_input: Input,
+    /// Tab completion being cycled through on the command line
+    completion: Option<Completion>,
+    /// Commands run with `:new`, persisted across sessions
+    command_history: History,
+    /// Commands imported from shell history, offered for `:new` completion
+    suggested_commands: Vec<String>,
+    /// History entry shown on the command line and the input typed before
+    /// browsing (Up/Down)
+    history_browse: Option<(usize, String)>,
+    /// Transient message shown in the status bar
+    message: Option<String>,
+    /// Whether the next key picks a pipe command (after `|`)
+    pipe_pending: bool,
+    /// External subscribers receiving a copy of every handled event
+    subscribers: Vec<mpsc::Sender<AppEvent>>,
+    /// Output sinks receiving every output line (besides the TUI buffer)
+    sinks: Sinks,
+    /// Errors and recoveries of sinks and exports, with their time, for `E`
+    events: VecDeque<(DateTime<Local>, String)>,
+    /// Lua script reacting to events (`--script`)
+    #[cfg(feature = "lua")]
+    script: Option<Script>,
+    /// Whether the screen needs to be redrawn
+    dirty: bool,
+    /// Whether mouse events are captured (off lets the terminal select text)
+    mouse_capture: bool,
+    /// Whether the current tab's info panel is shown
+    info_visible: bool,
+    /// Whether the statistics panel of all tabs is shown
+    stats_visible: bool,
+    /// Whether the events panel is shown
+    events_visible: bool,
+    /// Row of the statistics panel highlighted for `Enter`
+    stats_selected: usize,
+    /// Status bar settings
+    status_bar: StatusBarConfig,
+    /// Where the list of commands is shown
+    layout: TabLayout,
+    /// Replace the tab bar with a one-line header while there is one command
+    compact: bool,
+    /// Times shown in front of output lines
+    timestamps: TimestampMode,
+    /// Where yanked lines are copied to
+    clipboard: ClipboardBackend,
+    /// Whether `Y` copies a line with its prefix and times
+    copy_prefix: bool,
+    /// Quit this long after all commands have finished (`--exit-when-done`)
+    exit_when_done: Option<Duration>,
+    /// When the pending automatic exit happens
+    exit_at: Option<Instant>,
 }
 
 impl App {
     /// Initialize the application
-    pub fn new(commands: Vec<String>, max_buffer_lines: usize) -> Self {
-        let (event_tx, event_rx) = mpsc::channel(1000);
+    pub fn new(commands: Vec<CommandSpec>, max_buffer_lines: usize) -> Self {
+        let (event_tx, event_rx) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
+        let tab_manager = TabManager::new(commands, max_buffer_lines);
         Self {
-            tab_manager: TabManager::new(commands, max_buffer_lines),
+            pipe_targets: Self::pipe_targets(&tab_manager),
+            stdin_writers: HashMap::new(),
+            pipe_backlog: HashMap::new(),
+            pipe_dropped: HashMap::new(),
+            tab_manager,
             mode: Mode::Normal,
             search_state: SearchState::new(),
+            search_tab: None,
             should_quit: false,
             event_rx,
             event_tx,
             children: HashMap::new(),
+            tasks: HashMap::new(),
+            spawner: Box::new(CommandRunner),
+            stale_exits: HashMap::new(),
             pending_restart: None,
+            pending_runs: Vec::new(),
+            probing: HashSet::new(),
+            next_ready_check: Instant::now(),
+            port_checks: HashSet::new(),
+            opened: HashSet::new(),
+            hung: Vec::new(),
+            setup_tab: None,
+            prepared: HashSet::new(),
+            hooks: HashMap::new(),
+            stale_starts: HashMap::new(),
+            restarting: HashSet::new(),
+            restart_counts: HashMap::new(),
+            command_input: Input::default(),
+            completion: None,
+            command_history: History::default(),
+            suggested_commands: Vec::new(),
+            history_browse: None,
+            message: None,
+            pipe_pending: false,
+            subscribers: Vec::new(),
+            sinks: Sinks::default(),
+            events: VecDeque::new(),
+            #[cfg(feature = "lua")]
+            script: None,
+            dirty: true,
+            mouse_capture: false,
+            info_visible: false,
+            stats_visible: false,
+            events_visible: false,
+            stats_selected: 0,
+            status_bar: StatusBarConfig::default(),
+            layout: TabLayout::default(),
+            timestamps: TimestampMode::default(),
+            clipboard: ClipboardBackend::default(),
+            copy_prefix: false,
+            compact: false,
+            exit_when_done: None,
+            exit_at: None,
         }
     }
 
     /// Spawn all commands asynchronously with background output processing
     pub async fn spawn_commands(&mut self) {
-        // Collect commands first to avoid borrow conflict
-        let commands: Vec<String> = self
+        // Other commands wait for the setup command to succeed
+        if let Some(setup_tab) = self.setup_tab {
+            for (tab_index, tab) in self.tab_manager.iter_mut().enumerate() {
+                if tab_index != setup_tab {
+                    tab.set_status(CommandStatus::Waiting);
+                }
+            }
+            self.start_run(setup_tab).await;
+            return;
+        }
+
+        // Collect delays first to avoid borrow conflict
+        let delays: Vec<Option<Duration>> = self
             .tab_manager
             .iter()
-            .map(|tab| tab.command().to_string())
+            .map(|tab| tab.spec().delay)
             .collect();
 
-        for (tab_index, command) in commands.into_iter().enumerate() {
-            let tx = self.event_tx.clone();
-            match CommandRunner::spawn(tx.clone(), &command, tab_index).await {
-                Ok(child) => {
-                    self.children.insert(tab_index, child);
+        for (tab_index, delay) in delays.into_iter().enumerate() {
+            match delay.filter(|delay| !delay.is_zero()) {
+                Some(delay) => {
+                    if let Some(tab) = self.tab_manager.get_tab_mut(tab_index) {
+                        tab.set_status(CommandStatus::Waiting);
+                    }
+                    self.schedule_run(tab_index, delay);
+                }
+                None => self.start_run(tab_index).await,
+            }
+        }
+    }
+
+    /// Map each tab to the tabs reading its stdout (`stdin_from`)
+    ///
+    /// Unknown names and tabs naming themselves are left out.
+    fn pipe_targets(tab_manager: &TabManager) -> HashMap<usize, Vec<usize>> {
+        let mut targets: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (tab_index, tab) in tab_manager.iter().enumerate() {
+            if let Some(from) = &tab.spec().stdin_from
+                && let Some(source) = tab_manager.find_tab(from)
+                && source != tab_index
+            {
+                targets.entry(source).or_default().push(tab_index);
+            }
+        }
+        targets
+    }
+
+    /// Feed lines piped from another tab into a command's stdin, starting
+    /// with those printed before it started
+    fn open_pipe(&mut self, tab_index: usize, mut stdin: ChildStdin) {
+        let (tx, mut rx) = mpsc::channel::<String>(PIPE_CAPACITY);
+        // The backlog is no longer than the channel's capacity
+        for line in self.pipe_backlog.remove(&tab_index).unwrap_or_default() {
+            let _ = tx.try_send(line);
+        }
+        tokio::spawn(async move {
+            while let Some(line) = rx.recv().await {
+                if stdin.write_all(line.as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+        });
+        self.report_pipe_drops(tab_index);
+        // A source that has ended for good leaves it at the backlog and end of file
+        let source_ended = self
+            .pipe_targets
+            .iter()
+            .find(|(_, targets)| targets.contains(&tab_index))
+            .and_then(|(&source, _)| self.tab_manager.get_tab(source))
+            .is_some_and(|source| source.status().is_done() && source.next_run().is_none());
+        if !source_ended {
+            self.stdin_writers.insert(tab_index, tx);
+        }
+    }
+
+    /// Copy a stdout line to the commands reading this tab's output
+    ///
+    /// Lines for a command that hasn't started yet are kept until it does.
+    /// The event loop can't wait for a command that doesn't keep up with
+    /// reading, so lines beyond [`PIPE_CAPACITY`] are dropped and reported.
+    fn pipe_line(&mut self, tab_index: usize, line: &OutputLine) {
+        let Some(targets) = self.pipe_targets.get(&tab_index) else {
+            return;
+        };
+        let text = format!("{}\n", line.plain());
+        let mut dropping = Vec::new();
+        let mut caught_up = Vec::new();
+        for &target in targets {
+            let dropped = match self.stdin_writers.get(&target) {
+                Some(writer) => match writer.try_send(text.clone()) {
+                    Ok(()) => {
+                        if self.pipe_dropped.contains_key(&target) {
+                            caught_up.push(target);
+                        }
+                        false
+                    }
+                    Err(TrySendError::Full(_)) => true,
+                    // The command stopped reading its input
+                    Err(TrySendError::Closed(_)) => false,
+                },
+                None if self
+                    .tab_manager
+                    .get_tab(target)
+                    .is_some_and(|tab| tab.status() == &CommandStatus::Waiting) =>
+                {
+                    let backlog = self.pipe_backlog.entry(target).or_default();
+                    backlog.push_back(text.clone());
+                    backlog.len() > PIPE_CAPACITY && backlog.pop_front().is_some()
+                }
+                None => false,
+            };
+            if dropped {
+                let count = self.pipe_dropped.entry(target).or_default();
+                *count += 1;
+                if *count == 1 {
+                    dropping.push(target);
+                }
+            }
+        }
+        for target in dropping {
+            if let Some(tab) = self.tab_manager.get_tab(target) {
+                let event = format!(
+                    "Error: {}: not reading its input fast enough - dropping lines",
+                    tab.name()
+                );
+                self.log_event(event);
+            }
+        }
+        for target in caught_up {
+            self.report_pipe_drops(target);
+        }
+    }
+
+    /// Log how many piped lines a command missed, if any
+    fn report_pipe_drops(&mut self, tab_index: usize) {
+        let Some(count) = self.pipe_dropped.remove(&tab_index) else {
+            return;
+        };
+        if let Some(tab) = self.tab_manager.get_tab(tab_index) {
+            let event = format!("{}: {} piped lines dropped", tab.name(), count);
+            self.log_event(event);
+        }
+    }
+
+    /// Close the stdin of a command whose run ended
+    ///
+    /// The commands reading its output see end of file once it won't run
+    /// again; while a rerun is scheduled (`every`, `until_success`) or it is
+    /// restarted, they keep reading and get the output of the next run.
+    fn close_pipes(&mut self, tab_index: usize) {
+        self.stdin_writers.remove(&tab_index);
+        self.pipe_backlog.remove(&tab_index);
+        self.report_pipe_drops(tab_index);
+        let reruns = self
+            .tab_manager
+            .get_tab(tab_index)
+            .is_some_and(|tab| tab.next_run().is_some());
+        if reruns {
+            return;
+        }
+        let targets = self
+            .pipe_targets
+            .get(&tab_index)
+            .cloned()
+            .unwrap_or_default();
+        for target in targets {
+            self.stdin_writers.remove(&target);
+            self.report_pipe_drops(target);
+        }
+    }
+
+    /// Start commands with this spawner instead of running real processes
+    ///
+    /// Hooks and probes still run as processes.
+    pub fn set_spawner(&mut self, spawner: impl Spawner + 'static) {
+        self.spawner = Box::new(spawner);
+    }
+
+    /// Run this tab's command before all others, which only start once it
+    /// has exited successfully
+    pub fn set_setup_tab(&mut self, tab_index: usize) {
+        self.setup_tab = Some(tab_index);
+    }
+
+    /// Start the other commands once the setup command has succeeded
+    ///
+    /// If it failed, the waiting commands are marked failed, so `q` and
+    /// `--exit-when-done` see the session as done; they still start once a
+    /// re-run of the setup command succeeds.
+    fn finish_setup(&mut self, tab_index: usize) {
+        if self.setup_tab != Some(tab_index) {
+            return;
+        }
+        let succeeded = self
+            .tab_manager
+            .get_tab(tab_index)
+            .is_some_and(|tab| tab.status() == &CommandStatus::Finished { exit_code: 0 });
+        if !succeeded {
+            for tab in self.tab_manager.iter_mut() {
+                if tab.status() == &CommandStatus::Waiting {
+                    tab.set_status(CommandStatus::Failed {
+                        reason: "setup failed".into(),
+                    });
+                }
+            }
+            self.set_message(
+                "Error: setup failed - commands not started (press Enter on its tab to re-run)",
+            );
+            return;
+        }
+        self.setup_tab = None;
+
+        let delays: Vec<Option<Duration>> = self
+            .tab_manager
+            .iter()
+            .map(|tab| tab.spec().delay)
+            .collect();
+        // Runs are taken last-in first-out, so queue them in reverse
+        for (index, delay) in delays.into_iter().enumerate().rev() {
+            match delay.filter(|delay| !delay.is_zero()) {
+                _ if index == tab_index => {}
+                Some(delay) => self.schedule_run(index, delay),
+                None => self.pending_runs.push(index),
+            }
+        }
+    }
+
+    /// Schedule the next run of a tab
+    ///
+    /// A timer task sends a `Start` event once the delay has elapsed.
+    fn schedule_run(&mut self, tab_index: usize, delay: Duration) {
+        let Some(tab) = self.tab_manager.get_tab_mut(tab_index) else {
+            return;
+        };
+        tab.set_next_run(Some(Instant::now() + delay));
+        let run = tab.run_count();
+
+        let tx = self.event_tx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let _ = tx.send(AppEvent::Start { tab_index, run }).await;
+        });
+    }
+
+    /// Take the next tab whose scheduled run is due
+    pub fn take_pending_run(&mut self) -> Option<usize> {
+        self.pending_runs.pop()
+    }
+
+    /// Start a new run of a tab's command, keeping previous output
+    ///
+    /// Does nothing if the tab's process is still running. With
+    /// `before_start`, the tab waits while that command runs and is started
+    /// once it has finished.
+    pub async fn start_run(&mut self, tab_index: usize) {
+        // A running hook starts the tab itself once it has finished
+        if self.children.contains_key(&tab_index)
+            || self.tasks.contains_key(&tab_index)
+            || self.hooks.contains_key(&tab_index)
+        {
+            return;
+        }
+        let Some(tab) = self.tab_manager.get_tab_mut(tab_index) else {
+            return;
+        };
+        if let Some(hook) = tab.spec().before_start.clone()
+            && !self.prepared.remove(&tab_index)
+        {
+            tab.set_status(CommandStatus::Waiting);
+            let run = tab.run_count();
+            self.prepared.insert(tab_index);
+            let start = AppEvent::Start { tab_index, run };
+            self.run_hook(tab_index, "before_start", hook, &[], Some(start));
+            return;
+        }
+        tab.begin_run(Local::now());
+        self.exit_at = None;
+        self.dirty = true;
+        self.spawn_process(tab_index).await;
+    }
+
+    /// Spawn the process for a tab, reporting spawn errors as `Failed`
+    async fn spawn_process(&mut self, tab_index: usize) {
+        let Some(tab) = self.tab_manager.get_tab(tab_index) else {
+            return;
+        };
+        let mut spec = if self.restarting.remove(&tab_index) {
+            tab.spec().for_restart()
+        } else {
+            tab.spec().clone()
+        };
+
+        let tx = self.event_tx.clone();
+        let started = match self
+            .expand_variables(tab_index, &mut spec)
+            // Ports of a remote command are on the remote host
+            .and_then(|()| match spec.ssh {
+                Some(_) => Ok(()),
+                None => check_free(&spec.ports),
+            }) {
+            Err(e) => Err(e),
+            Ok(()) if spec.source.is_some() => {
+                SocketSource.start(tx.clone(), &spec, tab_index).await
+            }
+            Ok(()) if spec.tail.is_some() => FileTailer.start(tx.clone(), &spec, tab_index).await,
+            Ok(()) => self.spawner.spawn(tx.clone(), &spec, tab_index).await,
+        };
+        match started {
+            Ok(Running::Process(mut child)) => {
+                if let Some(stdin) = child.stdin.take() {
+                    self.open_pipe(tab_index, stdin);
                 }
-                Err(e) => {
-                    let _ = tx
-                        .send(AppEvent::Failed {
-                            tab_index,
-                            reason: e.to_string(),
-                        })
-                        .await;
+                let waiter = ChildWaiter::spawn(child, &spec, tab_index, tx);
+                let pid = waiter.pid();
+                self.children.insert(tab_index, waiter);
+                self.publish(AppEvent::Started { tab_index, pid });
+            }
+            Ok(Running::Task(task)) => {
+                self.tasks.insert(tab_index, task);
+                self.publish(AppEvent::Started {
+                    tab_index,
+                    pid: None,
+                });
+            }
+            // Keep the reason, error number and a suggested fix on the tab,
+            // which shows them until it is started again
+            Err(e) => {
+                let failure = SpawnFailure::diagnose(&e, &spec);
+                let reason = failure.to_string();
+                if let Some(tab) = self.tab_manager.get_tab_mut(tab_index) {
+                    tab.set_spawn_failure(failure);
                 }
+                let _ = tx.send(AppEvent::Failed { tab_index, reason }).await;
             }
         }
     }
 
+    /// Fill in a tab's template variables (`{port}`, `{tmpdir}`)
+    ///
+    /// Values are allocated on first use and kept on the tab.
+    fn expand_variables(&mut self, tab_index: usize, spec: &mut CommandSpec) -> io::Result<()> {
+        let Some(tab) = self.tab_manager.get_tab_mut(tab_index) else {
+            return Ok(());
+        };
+        let mut values = tab.variables().to_vec();
+        template::allocate_missing(spec, tab_index, &mut values)?;
+        template::expand(spec, &values);
+        tab.set_variables(values);
+        Ok(())
+    }
+
     /// Receive an event asynchronously (for use with select!)
     pub async fn recv_event(&mut self) -> Option<AppEvent> {
         self.event_rx.recv().await
     }
 
+    /// Subscribe to events handled by the application
+    ///
+    /// Every event passed to `handle_app_event` is forwarded to the returned
+    /// receiver, so embedding applications can observe output and exit events
+    /// without driving the TUI. If a subscriber falls behind and its channel
+    /// is full, events are dropped for that subscriber only. Dropping the
+    /// receiver unsubscribes.
+    pub fn subscribe(&mut self) -> mpsc::Receiver<AppEvent> {
+        let (tx, rx) = mpsc::channel(SUBSCRIBER_CHANNEL_CAPACITY);
+        self.subscribers.push(tx);
+        rx
+    }
+
+    /// Register an output sink
+    pub fn add_sink(&mut self, sink: Box<dyn OutputSink>) {
+        self.sinks.push(sink);
+    }
+
+    /// Write the records kept for failing sinks that are due to be retried
+    pub fn retry_sinks(&mut self) {
+        let reports = self.sinks.retry(Instant::now());
+        self.report_sinks(reports);
+    }
+
+    /// Write what failing sinks still keep and close all sinks
+    ///
+    /// Returns the records that could not be written, to report once the
+    /// terminal is restored.
+    pub fn finish_sinks(&mut self) -> Vec<LostRecords> {
+        self.sinks.finish(Instant::now())
+    }
+
+    /// Show what happened to sinks in the status bar and the events panel
+    fn report_sinks(&mut self, reports: Vec<SinkReport>) {
+        for report in reports {
+            let event = match report {
+                SinkReport::Failed {
+                    name,
+                    error,
+                    retry_in,
+                } => format!(
+                    "Error: {}: {} - keeping lines, retrying in {}s",
+                    name,
+                    error,
+                    retry_in.as_secs()
+                ),
+                SinkReport::Recovered { name, dropped: 0 } => {
+                    format!("{}: caught up after write errors", name)
+                }
+                SinkReport::Recovered { name, dropped } => format!(
+                    "{}: caught up after write errors, {} records dropped",
+                    name, dropped
+                ),
+                SinkReport::Disabled { name, error } => format!(
+                    "Error: {} disabled after {} failed writes: {}",
+                    name, MAX_FAILURES, error
+                ),
+            };
+            self.log_event(event);
+        }
+    }
+
+    /// Show a message in the status bar and keep it in the events panel
+    pub fn log_event(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back((Local::now(), message.clone()));
+        self.set_message(message);
+        self.dirty = true;
+    }
+
+    /// Get the logged events, oldest first
+    pub fn events(&self) -> impl Iterator<Item = &(DateTime<Local>, String)> {
+        self.events.iter()
+    }
+
+    /// Load a Lua script to pass events to
+    #[cfg(feature = "lua")]
+    pub fn set_script(&mut self, script: Script) {
+        self.script = Some(script);
+    }
+
+    /// Pass an event to the script, returning the actions it asked for
+    ///
+    /// A script that fails is disabled, with the error in the status bar.
+    #[cfg(feature = "lua")]
+    fn script_actions(&mut self, event: &AppEvent) -> Vec<Action> {
+        let Some(script) = &self.script else {
+            return Vec::new();
+        };
+        let tab_index = match event {
+            AppEvent::Output { tab_index, line } if line.kind.is_output() => *tab_index,
+            AppEvent::Exited { tab_index, .. }
+            | AppEvent::Signaled { tab_index, .. }
+            | AppEvent::Restarted { tab_index, .. } => *tab_index,
+            _ => return Vec::new(),
+        };
+        let Some(tab) = self.tab_manager.get_tab(tab_index) else {
+            return Vec::new();
+        };
+        let result = match event {
+            AppEvent::Output { line, .. } => script.on_line(tab.name(), &line.plain()),
+            AppEvent::Exited { exit_code, .. } => script.on_exit(tab.name(), *exit_code),
+            AppEvent::Signaled { signal, .. } => script.on_exit(tab.name(), 128 + signal),
+            AppEvent::Restarted { attempt, .. } => script.on_restart(tab.name(), *attempt),
+            _ => return Vec::new(),
+        };
+        result.unwrap_or_else(|e| {
+            self.set_message(format!("Error: script disabled: {}", e));
+            self.script = None;
+            Vec::new()
+        })
+    }
+
+    /// Carry out the actions a script asked for
+    #[cfg(feature = "lua")]
+    fn apply_script_actions(&mut self, actions: Vec<Action>) {
+        for action in actions {
+            self.dirty = true;
+            match action {
+                Action::Notify(message) => self.set_message(message),
+                Action::Mark { tab, label } => {
+                    if let Some(tab_index) = self.script_tab(&tab)
+                        && let Some(tab) = self.tab_manager.get_tab_mut(tab_index)
+                    {
+                        tab.insert_marker(Some(&label), Local::now());
+                    }
+                }
+                Action::Restart(tab) => {
+                    if let Some(tab_index) = self.script_tab(&tab) {
+                        self.request_restart(tab_index);
+                    }
+                }
+                // Leave a search being typed alone
+                Action::Highlight(pattern) if self.mode != Mode::Search => {
+                    self.search_in_current_tab(&pattern);
+                }
+                Action::Highlight(_) => {}
+            }
+        }
+    }
+
+    /// Find a tab named by a script, reporting unknown ones
+    #[cfg(feature = "lua")]
+    fn script_tab(&mut self, name_or_index: &str) -> Option<usize> {
+        let found = self.tab_manager.find_tab(name_or_index);
+        if found.is_none() {
+            self.set_message(format!("Error: script: no tab named '{}'", name_or_index));
+        }
+        found
+    }
+
+    /// Publish a lifecycle event originating in the app to subscribers and sinks
+    fn publish(&mut self, event: AppEvent) {
+        if !self.subscribers.is_empty() {
+            self.notify_subscribers(&event);
+        }
+        #[cfg(feature = "lua")]
+        {
+            let actions = self.script_actions(&event);
+            self.apply_script_actions(actions);
+        }
+        let tab_index = match event {
+            AppEvent::Started { tab_index, .. }
+            | AppEvent::Restarted { tab_index, .. }
+            | AppEvent::Killed { tab_index, .. }
+            | AppEvent::Stats { tab_index, .. } => tab_index,
+            _ => return,
+        };
+        let Some(tab) = self.tab_manager.get_tab(tab_index) else {
+            return;
+        };
+        let reports = self.sinks.lifecycle(Instant::now(), tab.name(), &event);
+        self.report_sinks(reports);
+    }
+
+    /// Publish the summary of a tab's run that just ended
+    fn publish_stats(&mut self, tab_index: usize) {
+        let Some(tab) = self.tab_manager.get_tab(tab_index) else {
+            return;
+        };
+        let event = AppEvent::Stats {
+            tab_index,
+            lines: tab.run_lines(),
+            elapsed: tab.elapsed().unwrap_or_default(),
+        };
+        self.publish(event);
+    }
+
+    /// Forward an event to all subscribers, removing closed ones
+    fn notify_subscribers(&mut self, event: &AppEvent) {
+        self.subscribers
+            .retain(|tx| !matches!(tx.try_send(event.clone()), Err(TrySendError::Closed(_))));
+    }
+
     /// Handle a single app event
     pub fn handle_app_event(&mut self, event: AppEvent) {
+        let event = match event {
+            AppEvent::Searched { id, matches, done } => {
+                if self.search_state.receive(id, matches, done) {
+                    self.dirty = true;
+                }
+                return;
+            }
+            event => event,
+        };
+        if let AppEvent::Exited { tab_index, .. }
+        | AppEvent::Signaled { tab_index, .. }
+        | AppEvent::Failed { tab_index, .. } = event
+            && self.take_stale_exit(tab_index)
+        {
+            return;
+        }
+        self.dirty = true;
+        if !self.subscribers.is_empty() {
+            self.notify_subscribers(&event);
+        }
+        let finished = match event {
+            AppEvent::Exited { tab_index, .. }
+            | AppEvent::Signaled { tab_index, .. }
+            | AppEvent::Failed { tab_index, .. } => Some(tab_index),
+            _ => None,
+        };
+        #[cfg(feature = "lua")]
+        let actions = self.script_actions(&event);
+
         match event {
             AppEvent::Output { tab_index, line } => {
-                if let Some(tab) = self.tab_manager.get_tab_mut(tab_index) {
-                    tab.push_output(line);
+                let unread = tab_index != self.tab_manager.active_index() && line.kind.is_output();
+                if line.kind == OutputKind::Stdout {
+                    self.pipe_line(tab_index, &line);
                 }
+                let Some(tab) = self.tab_manager.get_tab_mut(tab_index) else {
+                    return;
+                };
+                let reports = self.sinks.write_line(
+                    Instant::now(),
+                    &SinkLine {
+                        tab_index,
+                        tab_name: tab.name(),
+                        line: &line,
+                    },
+                );
+                tab.push_output(line);
+                if unread {
+                    tab.mark_unread();
+                }
+                self.report_sinks(reports);
             }
             AppEvent::Exited {
                 tab_index,
                 exit_code,
             } => {
-                if let Some(tab) = self.tab_manager.get_tab_mut(tab_index) {
-                    tab.set_status(CommandStatus::Finished { exit_code });
+                self.children.remove(&tab_index);
+                self.tasks.remove(&tab_index);
+                let Some(tab) = self.tab_manager.get_tab_mut(tab_index) else {
+                    return;
+                };
+                let reports = self
+                    .sinks
+                    .exited(Instant::now(), tab_index, tab.name(), exit_code);
+                tab.end_run(CommandStatus::Finished { exit_code }, Local::now());
+                // `sh -c` exits with 127/126 for a program it can't find or run
+                let stderr = last_stderr(tab.buffer());
+                if let Some(failure) =
+                    SpawnFailure::from_exit(exit_code, tab.spec(), stderr.as_deref())
+                {
+                    tab.set_spawn_failure(failure);
                 }
+                if let Some(delay) = tab.spec().next_run_delay(exit_code) {
+                    self.schedule_run(tab_index, delay);
+                }
+                self.report_sinks(reports);
+                self.run_after_exit(tab_index, exit_code);
+                self.publish_stats(tab_index);
+            }
+            AppEvent::Signaled { tab_index, signal } => {
+                self.children.remove(&tab_index);
+                self.tasks.remove(&tab_index);
+                let Some(tab) = self.tab_manager.get_tab_mut(tab_index) else {
+                    return;
+                };
+                // Report the shell convention (128 + signal) where a code is expected
+                let exit_code = 128 + signal;
+                let reports = self
+                    .sinks
+                    .exited(Instant::now(), tab_index, tab.name(), exit_code);
+                tab.end_run(CommandStatus::Signaled { signal }, Local::now());
+                if let Some(delay) = tab.spec().next_run_delay(exit_code) {
+                    self.schedule_run(tab_index, delay);
+                }
+                self.report_sinks(reports);
+                self.run_after_exit(tab_index, exit_code);
+                self.publish_stats(tab_index);
             }
             AppEvent::Failed { tab_index, reason } => {
+                self.children.remove(&tab_index);
+                self.tasks.remove(&tab_index);
+                if let Some(tab) = self.tab_manager.get_tab_mut(tab_index) {
+                    tab.end_run(CommandStatus::Failed { reason }, Local::now());
+                }
+            }
+            AppEvent::Dropped { tab_index, count } => {
                 if let Some(tab) = self.tab_manager.get_tab_mut(tab_index) {
-                    tab.set_status(CommandStatus::Failed { reason });
+                    tab.record_dropped(count);
+                }
+            }
+            AppEvent::Start { tab_index, run } => {
+                if Self::take_stale(&mut self.stale_starts, tab_index) {
+                    return;
+                }
+                // Ignore if the tab has been started since scheduling
+                let due = self
+                    .tab_manager
+                    .get_tab(tab_index)
+                    .is_some_and(|tab| tab.run_count() == run);
+                if due {
+                    // Sent by the tab's hook as it finishes, if it has one
+                    self.hooks.remove(&tab_index);
+                    self.pending_runs.push(tab_index);
+                }
+            }
+            AppEvent::Probed {
+                tab_index,
+                run,
+                healthy,
+            } => {
+                self.probing.remove(&tab_index);
+                // Ignore probes of a run that has ended since
+                if !self.is_current_run(tab_index, run) {
+                    return;
+                }
+                if healthy {
+                    if let Some(tab) = self.tab_manager.get_tab_mut(tab_index) {
+                        tab.reset_silence();
+                    }
+                } else {
+                    self.mark_hung(tab_index);
+                }
+            }
+            AppEvent::PortChecked {
+                tab_index,
+                run,
+                listening,
+            } => {
+                self.port_checks.remove(&tab_index);
+                if listening && self.is_current_run(tab_index, run) {
+                    self.mark_ready(tab_index);
                 }
             }
+            // Taken by the search state above
+            AppEvent::Searched { .. } => {}
+            // Published by the app itself, never sent through the channel
+            AppEvent::Started { .. }
+            | AppEvent::Restarted { .. }
+            | AppEvent::Killed { .. }
+            | AppEvent::Stats { .. } => {}
+        }
+
+        if let Some(tab_index) = finished {
+            self.close_pipes(tab_index);
+            self.finish_setup(tab_index);
+            self.schedule_exit_if_done();
+        }
+        #[cfg(feature = "lua")]
+        self.apply_script_actions(actions);
+    }
+
+    /// Quit automatically once every command has finished
+    ///
+    /// Starts the `--exit-when-done` countdown, or quits right away when
+    /// there is no delay. Commands scheduled to run again count as unfinished.
+    fn schedule_exit_if_done(&mut self) {
+        let Some(delay) = self.exit_when_done else {
+            return;
+        };
+        if !self.children.is_empty() || !self.tab_manager.all_done() {
+            return;
+        }
+        if delay.is_zero() {
+            self.should_quit = true;
+        } else {
+            self.exit_at = Some(Instant::now() + delay);
+            self.update_exit_countdown();
+        }
+    }
+
+    /// Quit when the automatic exit is due, otherwise show the time left
+    pub fn update_exit_countdown(&mut self) {
+        let Some(exit_at) = self.exit_at else {
+            return;
+        };
+        let remaining = exit_at.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            self.should_quit = true;
+            return;
+        }
+        let message = format!(
+            "All commands finished - exiting in {}s (press any key to stay)",
+            remaining.as_secs_f64().ceil() as u64
+        );
+        if self.message.as_deref() != Some(message.as_str()) {
+            self.set_message(message);
+            self.dirty = true;
+        }
+    }
+
+    /// Quit this long after all commands have finished (`None` to stay open)
+    pub fn set_exit_when_done(&mut self, delay: Option<Duration>) {
+        self.exit_when_done = delay;
+    }
+
+    /// Cancel a pending automatic exit
+    ///
+    /// Returns `true` if an exit was pending.
+    pub fn cancel_exit(&mut self) -> bool {
+        self.exit_at.take().is_some()
+    }
+
+    /// Find running commands that have been silent for their `hang_timeout`
+    ///
+    /// Commands with a `hang_probe` are probed first and only count as hung
+    /// if the probe fails; others are marked hung right away.
+    pub fn check_hung_commands(&mut self) {
+        let now = Instant::now();
+        let silent: Vec<usize> = self
+            .tab_manager
+            .iter()
+            .enumerate()
+            .filter(|(tab_index, tab)| {
+                self.children.contains_key(tab_index)
+                    && !self.probing.contains(tab_index)
+                    && tab
+                        .spec()
+                        .hang_timeout
+                        .zip(tab.silent_for(now))
+                        .is_some_and(|(timeout, silent)| silent >= timeout)
+            })
+            .map(|(tab_index, _)| tab_index)
+            .collect();
+
+        for tab_index in silent {
+            let Some(tab) = self.tab_manager.get_tab(tab_index) else {
+                continue;
+            };
+            if tab.spec().hang_probe.is_some() {
+                let (spec, run) = (tab.spec().clone(), tab.run_count());
+                self.probing.insert(tab_index);
+                self.spawn_probe(tab_index, run, &spec);
+            } else {
+                self.mark_hung(tab_index);
+            }
+        }
+    }
+
+    /// Find running commands that started listening on their first port
+    ///
+    /// The URL is shown in the tab (and its info) and, with `open`, opened
+    /// in the browser the first time. Ports are checked a few times a
+    /// second in the background, reporting `PortChecked`; remote commands
+    /// listen elsewhere and aren't checked.
+    pub fn check_ready_commands(&mut self) {
+        let now = Instant::now();
+        if now < self.next_ready_check {
+            return;
+        }
+        self.next_ready_check = now + READY_CHECK_INTERVAL;
+        let unready: Vec<(usize, usize, u16)> = self
+            .tab_manager
+            .iter()
+            .enumerate()
+            .filter(|(tab_index, tab)| {
+                self.children.contains_key(tab_index)
+                    && !self.port_checks.contains(tab_index)
+                    && tab.ready_url().is_none()
+                    && tab.spec().ssh.is_none()
+            })
+            .filter_map(|(tab_index, tab)| {
+                Some((tab_index, tab.run_count(), *tab.spec().ports.first()?))
+            })
+            .collect();
+
+        for (tab_index, run, port) in unready {
+            self.port_checks.insert(tab_index);
+            let tx = self.event_tx.clone();
+            tokio::spawn(async move {
+                let listening = is_listening(port).await;
+                let _ = tx
+                    .send(AppEvent::PortChecked {
+                        tab_index,
+                        run,
+                        listening,
+                    })
+                    .await;
+            });
+        }
+    }
+
+    /// Show the URL of a command that started listening on its port
+    fn mark_ready(&mut self, tab_index: usize) {
+        let Some(tab) = self.tab_manager.get_tab_mut(tab_index) else {
+            return;
+        };
+        if tab.ready_url().is_some() {
+            return;
+        }
+        let Some(url) = tab.spec().ready_url() else {
+            return;
+        };
+        let message = format!("{} listening on {}", tab.spec().title(), url);
+        if tab.spec().open && self.opened.insert(tab_index) {
+            open_externally(&url);
+        }
+        tab.set_ready(url);
+        self.set_message(message);
+        self.dirty = true;
+    }
+
+    /// Check if a tab's command is running and still on the given run
+    fn is_current_run(&self, tab_index: usize, run: usize) -> bool {
+        self.children.contains_key(&tab_index)
+            && self
+                .tab_manager
+                .get_tab(tab_index)
+                .is_some_and(|tab| tab.run_count() == run)
+    }
+
+    /// Run a tab's hang probe in the background, reporting `Probed`
+    fn spawn_probe(&self, tab_index: usize, run: usize, spec: &CommandSpec) {
+        let Some(probe) = &spec.hang_probe else {
+            return;
+        };
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.arg("-c")
+            .arg(probe)
+            .envs(&spec.env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
+        if let Some(cwd) = &spec.cwd {
+            cmd.current_dir(cwd);
+        }
+
+        let tx = self.event_tx.clone();
+        tokio::spawn(async move {
+            let status = tokio::time::timeout(PROBE_TIMEOUT, cmd.status()).await;
+            let healthy = matches!(status, Ok(Ok(status)) if status.success());
+            let _ = tx
+                .send(AppEvent::Probed {
+                    tab_index,
+                    run,
+                    healthy,
+                })
+                .await;
+        });
+    }
+
+    /// Log that a tab's command hung and queue its restart
+    fn mark_hung(&mut self, tab_index: usize) {
+        let Some(tab) = self.tab_manager.get_tab_mut(tab_index) else {
+            return;
+        };
+        let timeout = humantime::format_duration(tab.spec().hang_timeout.unwrap_or_default());
+        tab.push_output(OutputLine::divider(&format!(
+            "no output for {} - restarting",
+            timeout
+        )));
+        let message = format!(
+            "{} hung (no output for {}) - restarted",
+            tab.spec().title(),
+            timeout
+        );
+        self.set_message(message);
+        self.hung.push(tab_index);
+    }
+
+    /// Take the next tab found hung
+    pub fn take_hung(&mut self) -> Option<usize> {
+        self.hung.pop()
+    }
+
+    /// Kill a hung command and run it again, keeping its output
+    pub async fn restart_hung(&mut self, tab_index: usize) {
+        self.kill_child(tab_index).await;
+        self.kill_detached(tab_index);
+        self.restarting.insert(tab_index);
+        self.start_run(tab_index).await;
+        self.publish_restart(tab_index);
+    }
+
+    /// Consume one ignored exit event for a tab, if any is pending
+    fn take_stale_exit(&mut self, tab_index: usize) -> bool {
+        Self::take_stale(&mut self.stale_exits, tab_index)
+    }
+
+    /// Count down the stale events of a tab, returning whether one was due
+    fn take_stale(stale: &mut HashMap<usize, usize>, tab_index: usize) -> bool {
+        let Some(count) = stale.get_mut(&tab_index) else {
+            return false;
+        };
+        *count -= 1;
+        if *count == 0 {
+            stale.remove(&tab_index);
+        }
+        true
+    }
+
+    /// Kill a tab's running process and wait until it has been reaped
+    ///
+    /// If the process had already exited, its exit event is still queued
+    /// and gets ignored so it can't be mistaken for a later run's exit.
+    /// Otherwise `Killed` and the run's `Stats` are published. The task of
+    /// a `tail` or `source` tab or a scripted run is stopped instead, as is
+    /// a `before_start`/`before_restart` hook the tab is waiting for, which
+    /// then runs again on the next start.
+    async fn kill_child(&mut self, tab_index: usize) {
+        if let Some(hook) = self.hooks.remove(&tab_index) {
+            hook.abort();
+            // A hook that ended on its own has sent its `Start` already
+            if hook.await.is_ok() {
+                *self.stale_starts.entry(tab_index).or_default() += 1;
+            }
+            self.prepared.remove(&tab_index);
+        }
+        if let Some(task) = self.tasks.remove(&tab_index) {
+            task.abort();
+            // A task that ended on its own has sent its last event already
+            if task.await.is_ok() {
+                *self.stale_exits.entry(tab_index).or_default() += 1;
+            }
+        }
+        self.stdin_writers.remove(&tab_index);
+        let Some(waiter) = self.children.remove(&tab_index) else {
+            return;
+        };
+        if waiter.kill().await {
+            *self.stale_exits.entry(tab_index).or_default() += 1;
+        } else {
+            self.publish(AppEvent::Killed {
+                tab_index,
+                signal: Signal::SIGKILL as i32,
+            });
+            self.publish_stats(tab_index);
+        }
+    }
+
+    /// Publish `Restarted` for a tab that was just started again
+    fn publish_restart(&mut self, tab_index: usize) {
+        let attempt = self.restart_counts.entry(tab_index).or_default();
+        *attempt += 1;
+        let attempt = *attempt;
+        self.publish(AppEvent::Restarted { tab_index, attempt });
+    }
+
+    /// Kill all running processes
+    ///
+    /// Sends SIGKILL to all process groups to ensure child processes
+    /// (e.g., servers started by shell commands) are also terminated.
+    /// Waits for each process to terminate before returning, then removes
+    /// the tabs' `{tmpdir}` directories.
+    pub async fn kill_all(&mut self) {
+        let running: Vec<usize> = self
+            .children
+            .keys()
+            .chain(self.tasks.keys())
+            .chain(self.hooks.keys())
+            .copied()
+            .collect();
+        for tab_index in running {
+            self.kill_child(tab_index).await;
+        }
+        for tab_index in 0..self.tab_manager.len() {
+            self.kill_detached(tab_index);
+        }
+        for tab in self.tab_manager.iter() {
+            template::remove_tmpdir(tab.variables());
+        }
+    }
+
+    /// Kill processes that escaped a tab's process group, if configured
+    fn kill_detached(&self, tab_index: usize) {
+        if self
+            .tab_manager
+            .get_tab(tab_index)
+            .is_some_and(|tab| tab.spec().kill_detached)
+        {
+            detached::kill_tagged(tab_index);
+        }
+    }
+
+    /// Mark the screen as needing a redraw
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Take the redraw flag, clearing it
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.dirty)
+    }
+
+    /// Check if a countdown (or the statistics panel's run times) is shown
+    /// that needs regular redraws
+    pub fn has_countdown(&self) -> bool {
+        self.tab_manager.current_tab().next_run().is_some()
+            || self.exit_at.is_some()
+            || self.stats_visible
+    }
+
+    /// Check if mouse events are captured
+    pub fn mouse_capture(&self) -> bool {
+        self.mouse_capture
+    }
+
+    /// Enable or disable mouse capture
+    pub fn set_mouse_capture(&mut self, enabled: bool) {
+        self.mouse_capture = enabled;
+    }
+
+    /// Toggle mouse capture, passing selection through to the terminal when off
+    pub fn toggle_mouse_capture(&mut self) {
+        self.mouse_capture = !self.mouse_capture;
+        self.set_message(if self.mouse_capture {
+            "Mouse capture on"
+        } else {
+            "Mouse capture off - select text with the terminal"
+        });
+    }
+
+    /// Check if the info panel is shown
+    pub fn info_visible(&self) -> bool {
+        self.info_visible
+    }
+
+    /// Show or hide the current tab's info panel
+    pub fn toggle_info(&mut self) {
+        self.info_visible = !self.info_visible;
+        self.stats_visible = false;
+        self.events_visible = false;
+    }
+
+    /// Check if the statistics panel is shown
+    pub fn stats_visible(&self) -> bool {
+        self.stats_visible
+    }
+
+    /// Show or hide the statistics panel of all tabs
+    pub fn toggle_stats(&mut self) {
+        self.stats_visible = !self.stats_visible;
+        self.stats_selected = self.tab_manager.active_index();
+        self.info_visible = false;
+        self.events_visible = false;
+    }
+
+    /// Check if the events panel is shown
+    pub fn events_visible(&self) -> bool {
+        self.events_visible
+    }
+
+    /// Show or hide the panel of sink and export errors
+    pub fn toggle_events(&mut self) {
+        self.events_visible = !self.events_visible;
+        self.info_visible = false;
+        self.stats_visible = false;
+    }
+
+    /// Get the tab highlighted in the statistics panel
+    pub fn stats_selected(&self) -> usize {
+        self.stats_selected
+    }
+
+    /// Highlight the next (or previous) tab in the statistics panel, wrapping around
+    pub fn move_stats_selection(&mut self, forward: bool) {
+        let count = self.tab_manager.len();
+        self.stats_selected = if forward {
+            (self.stats_selected + 1) % count
+        } else {
+            (self.stats_selected + count - 1) % count
+        };
+    }
+
+    /// Switch to the tab highlighted in the statistics panel and close it
+    pub fn open_stats_selection(&mut self) {
+        self.tab_manager.select_tab(self.stats_selected);
+        self.stats_visible = false;
+    }
+
+    /// Get the status bar settings
+    pub fn status_bar(&self) -> &StatusBarConfig {
+        &self.status_bar
+    }
+
+    /// Set the status bar settings
+    pub fn set_status_bar(&mut self, status_bar: StatusBarConfig) {
+        self.status_bar = status_bar;
+    }
+
+    /// Get where the list of commands is shown
+    pub fn layout(&self) -> TabLayout {
+        self.layout
+    }
+
+    /// Set where the list of commands is shown
+    pub fn set_layout(&mut self, layout: TabLayout) {
+        self.layout = layout;
+    }
+
+    /// Get the times shown in front of output lines
+    pub fn timestamps(&self) -> TimestampMode {
+        self.timestamps
+    }
+
+    /// Set the times shown in front of output lines
+    pub fn set_timestamps(&mut self, timestamps: TimestampMode) {
+        self.timestamps = timestamps;
+    }
+
+    /// Set where yanked lines are copied to
+    pub fn set_clipboard(&mut self, clipboard: ClipboardBackend) {
+        self.clipboard = clipboard;
+    }
+
+    /// Set whether `Y` copies a line with its `[stdout]` prefix and times
+    pub fn set_copy_prefix(&mut self, copy_prefix: bool) {
+        self.copy_prefix = copy_prefix;
+    }
+
+    /// Apply the settings of a reloaded config file, whose previous
+    /// contents were `old`, and say so in the status bar
+    ///
+    /// Commands are matched to tabs by title. Changes to how they are
+    /// spawned, and added or removed commands, wait for parallels to be
+    /// started again, and the message names the commands affected.
+    pub fn reload_config(&mut self, old: &Config, new: &Config) {
+        self.copy_prefix = new.copy_prefix;
+        self.status_bar = new.status_bar.clone();
+
+        let mut pending = Vec::new();
+        if old.setup != new.setup {
+            pending.push("setup".to_string());
+        }
+        if old.teardown != new.teardown {
+            pending.push("teardown".to_string());
+        }
+        for old_spec in &old.commands {
+            let title = old_spec.title();
+            if !new.commands.iter().any(|spec| spec.title() == title) {
+                pending.push(title);
+            }
+        }
+        for new_spec in &new.commands {
+            let title = new_spec.title();
+            let Some(old_spec) = old.commands.iter().find(|spec| spec.title() == title) else {
+                pending.push(title);
+                continue;
+            };
+            if old_spec == new_spec {
+                continue;
+            }
+            for tab in self
+                .tab_manager
+                .iter_mut()
+                .filter(|tab| tab.spec().title() == title)
+            {
+                tab.reload_spec(old_spec, new_spec);
+            }
+            if old_spec.needs_restart(new_spec) {
+                pending.push(title);
+            }
+        }
+
+        self.set_message(if pending.is_empty() {
+            "Config reloaded".to_string()
+        } else {
+            format!(
+                "Config reloaded; restart parallels to apply the changes to {}",
+                pending.join(", ")
+            )
+        });
+    }
+
+    /// Get the text of the selected lines, without colors
+    pub fn selected_text(&self) -> Option<String> {
+        let tab = self.tab_manager.current_tab();
+        let selection = tab.selection()?;
+        let lines: Vec<String> = tab
+            .buffer()
+            .range(selection.start()..selection.end() + 1)
+            .map(|line| line.plain())
+            .collect();
+        Some(lines.join("\n"))
+    }
+
+    /// Copy the selected lines to the clipboard and leave Visual mode
+    pub fn yank_selection(&mut self) {
+        let (Some(selection), Some(text)) = (
+            self.tab_manager.current_tab().selection(),
+            self.selected_text(),
+        ) else {
+            return;
+        };
+        let count = selection.end() - selection.start() + 1;
+        let message = match clipboard::copy(&text, self.clipboard) {
+            Ok(backend) => format!("Copied {} ({})", line_count(count), backend),
+            Err(e) => format!("Error: copy failed: {}", e),
+        };
+        self.tab_manager.current_tab_mut().clear_selection();
+        self.set_mode(Mode::Normal);
+        self.set_message(message);
+    }
+
+    /// Get the text of the line `Y` copies, without colors
+    ///
+    /// That's the cursor line in Cursor mode, otherwise the current match's
+    /// line or the top visible line. With `copy_prefix` set, the line keeps
+    /// the prefix and times shown in front of it.
+    pub fn focused_line_text(&self) -> Option<String> {
+        let tab = self.tab_manager.current_tab();
+        let line_idx = match (self.mode, tab.cursor(), self.search_state.current_match()) {
+            (Mode::Cursor, Some(cursor), _) => cursor.line,
+            (_, _, Some(m)) if self.search_state.is_active() => m.line,
+            _ => tab.scroll_offset(),
+        };
+        let line = tab.buffer().get(line_idx)?;
+        if !self.copy_prefix {
+            return Some(line.plain());
+        }
+        let mut text = Renderer::prefix_span(line.kind).content.into_owned();
+        if let Some(stamp) = Renderer::timestamp_span(self.timestamps, tab, line_idx) {
+            text.push_str(&stamp.content);
+        }
+        text.push_str(&line.plain());
+        Some(text)
+    }
+
+    /// Copy the focused line (see [`Self::focused_line_text`]) to the clipboard
+    pub fn yank_line(&mut self) {
+        let Some(text) = self.focused_line_text() else {
+            return;
+        };
+        let message = match clipboard::copy(&text, self.clipboard) {
+            Ok(backend) => format!("Copied line ({})", backend),
+            Err(e) => format!("Error: copy failed: {}", e),
+        };
+        self.set_message(message);
+    }
+
+    /// Copy the text marked in Cursor mode to the clipboard and stop marking
+    pub fn yank_marked(&mut self) {
+        let tab = self.tab_manager.current_tab_mut();
+        let Some(text) = tab.marked_text() else {
+            self.set_message("Nothing marked (v starts marking at the cursor)");
+            return;
+        };
+        tab.toggle_cursor_mark();
+        let message = match clipboard::copy(&text, self.clipboard) {
+            Ok(backend) => format!("Copied {} characters ({})", text.chars().count(), backend),
+            Err(e) => format!("Error: copy failed: {}", e),
+        };
+        self.set_message(message);
+    }
+
+    /// Open the file path under the output cursor with the default application
+    ///
+    /// Relative paths are resolved against the tab's `cwd`.
+    pub fn open_path_under_cursor(&mut self) {
+        let tab = self.tab_manager.current_tab();
+        let path = tab
+            .cursor()
+            .and_then(|cursor| tab.buffer().get(cursor.line))
+            .and_then(|line| path_at(&line.plain(), tab.cursor_byte_offset()).map(PathBuf::from));
+        let Some(path) = path else {
+            self.set_message("No path under cursor");
+            return;
+        };
+        let path = match &tab.spec().cwd {
+            Some(cwd) => cwd.join(path),
+            None => path,
+        };
+        if !path.exists() {
+            self.set_message(format!("No such file: {}", path.display()));
+            return;
+        }
+        open_externally(&path);
+        self.set_message(format!("Opened {}", path.display()));
+    }
+
+    /// Wait for the key of the current tab's pipe command to send lines to
+    pub fn start_pipe(&mut self) {
+        let pipes = &self.tab_manager.current_tab().spec().pipes;
+        if pipes.is_empty() {
+            self.set_message("No pipes for this command - add `pipes` to its config");
+            return;
+        }
+        let choices: Vec<String> = pipes
+            .iter()
+            .map(|(key, command)| format!("{} {}", key, command))
+            .collect();
+        self.set_message(format!("Pipe to: {}", choices.join(" · ")));
+        self.pipe_pending = true;
+    }
+
+    /// Take the pending pipe started with `|`
+    ///
+    /// Returns `true` if the next key was to pick a pipe command.
+    pub fn take_pipe_pending(&mut self) -> bool {
+        std::mem::take(&mut self.pipe_pending)
+    }
+
+    /// Send lines to the current tab's pipe command bound to `key`
+    ///
+    /// Sends the selected lines in Visual mode (and leaves it), otherwise
+    /// the current match's line or the top visible line. The command runs
+    /// locally in the tab's directory, like a hook, with its output shown
+    /// in the tab.
+    pub fn pipe_lines(&mut self, key: char) {
+        let tab_index = self.tab_manager.active_index();
+        let tab = self.tab_manager.current_tab();
+        let Some(command) = tab.spec().pipes.get(&key).cloned() else {
+            self.set_message(format!("No pipe on '{}'", key));
+            return;
+        };
+        let (text, count) = match (tab.selection(), self.selected_text()) {
+            (Some(selection), Some(text)) => (text, selection.end() - selection.start() + 1),
+            _ => {
+                let line = match self.search_state.current_match() {
+                    Some(m) if self.search_state.is_active() => m.line,
+                    _ => tab.scroll_offset(),
+                };
+                let Some(line) = tab.buffer().get(line) else {
+                    return;
+                };
+                (line.plain(), 1)
+            }
+        };
+        // The command's directory and environment are on the remote host
+        let local = tab.spec().ssh.is_none();
+        let spec = CommandSpec {
+            cwd: tab.spec().cwd.clone().filter(|_| local),
+            env: if local {
+                tab.spec().env.clone()
+            } else {
+                Default::default()
+            },
+            ..CommandSpec::new(command.as_str())
+        };
+        self.spawn_hook(tab_index, "pipe", spec, Some(text + "\n"), None);
+
+        if self.mode == Mode::Visual {
+            self.tab_manager.current_tab_mut().clear_selection();
+            self.set_mode(Mode::Normal);
+        }
+        self.set_message(format!("Piped {} to {}", line_count(count), command));
+    }
+
+    /// Carry out the action the current tab's `keys` bind to `key`: write
+    /// text to the command's stdin or send it a signal
+    ///
+    /// Returns `false` if the tab doesn't bind the key, which then keeps
+    /// its usual meaning.
+    pub fn press_tab_key(&mut self, key: char) -> bool {
+        let tab_index = self.tab_manager.active_index();
+        let tab = self.tab_manager.current_tab();
+        let Some(action) = tab.spec().keys.get(&key) else {
+            return false;
+        };
+        let result = match action {
+            KeyAction::Send(text) => match self.stdin_writers.get(&tab_index) {
+                Some(writer) => writer.try_send(text.clone()).map_err(|e| match e {
+                    TrySendError::Full(_) => "its input is full".to_string(),
+                    TrySendError::Closed(_) => "its input is closed".to_string(),
+                }),
+                None => Err("not running".to_string()),
+            },
+            KeyAction::Signal { signal } => match self.children.get(&tab_index) {
+                Some(waiter) => waiter.signal(signal.0).map_err(|e| e.desc().to_string()),
+                None => Err("not running".to_string()),
+            },
+        };
+        let message = match result {
+            Ok(()) => format!("Sent {} to {}", action, tab.name()),
+            Err(e) => format!("Error: {}: {}", tab.name(), e),
+        };
+        self.set_message(message);
+        true
+    }
+
+    /// Switch to the next timestamp mode (off, relative, relative and delta)
+    pub fn cycle_timestamps(&mut self) {
+        self.timestamps = self.timestamps.next();
+        self.set_message(format!("Timestamps: {}", self.timestamps.label()));
+    }
+
+    /// Switch how the current tab shows log prefixes (shown, dimmed, hidden)
+    pub fn cycle_prefix_fold(&mut self) {
+        let tab = self.tab_manager.current_tab_mut();
+        tab.cycle_prefix_fold();
+        let message = format!("Log prefixes: {}", tab.prefix_fold().label());
+        self.set_message(message);
+    }
+
+    /// Align the current tab's visible lines into columns, or stop doing so
+    pub fn toggle_table_view(&mut self) {
+        let tab = self.tab_manager.current_tab_mut();
+        tab.toggle_table_view();
+        let on = tab.table_view();
+        self.set_message(if on {
+            "Table view on - columns of the visible lines are aligned"
+        } else {
+            "Table view off"
+        });
+    }
+
+    /// Hide or show again the tabs that finished successfully
+    pub fn toggle_hide_finished(&mut self) {
+        self.tab_manager.toggle_hide_finished();
+        let message = if self.tab_manager.hides_finished() {
+            format!(
+                "Hiding {} finished tabs - F to show all",
+                self.tab_manager.hidden_count()
+            )
+        } else {
+            "Showing all tabs".to_string()
+        };
+        self.set_message(message);
+    }
+
+    /// Replace the tab bar with a one-line header while there is one command
+    pub fn set_compact(&mut self, compact: bool) {
+        self.compact = compact;
+    }
+
+    /// Check if the tab bar (or sidebar) gives way to a one-line header
+    ///
+    /// Only with compact mode and a single tab; opening another tab
+    /// brings the tab bar back.
+    pub fn compact_layout(&self) -> bool {
+        self.compact && self.tab_manager.len() == 1
+    }
+
+    /// Check if the status bar is shown
+    ///
+    /// A hidden status bar still appears outside Normal mode, for messages
+    /// and once all commands have finished.
+    pub fn status_bar_visible(&self) -> bool {
+        !self.status_bar.hidden
+            || self.mode != Mode::Normal
+            || self.message.is_some()
+            || self.tab_manager.all_done()
+    }
+
+    /// Check if the application should quit
+    pub fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+
+    /// Set the quit flag
+    pub fn quit(&mut self) {
+        self.should_quit = true;
+    }
+
+    /// Get current mode
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Set mode
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+    }
+
+    /// Get the PID of a tab's running process
+    pub fn pid(&self, tab_index: usize) -> Option<u32> {
+        self.children.get(&tab_index)?.pid()
+    }
+
+    /// Get reference to tab manager
+    pub fn tab_manager(&self) -> &TabManager {
+        &self.tab_manager
+    }
+
+    /// Get mutable reference to tab manager
+    pub fn tab_manager_mut(&mut self) -> &mut TabManager {
+        &mut self.tab_manager
+    }
+
+    /// Get reference to search state
+    pub fn search_state(&self) -> &SearchState {
+        &self.search_state
+    }
+
+    /// Get mutable reference to search state
+    pub fn search_state_mut(&mut self) -> &mut SearchState {
+        &mut self.search_state
+    }
+
+    /// Search in current tab's buffer
+    ///
+    /// This method is needed to avoid borrow conflicts when accessing
+    /// both tab_manager and search_state mutably. Large buffers are
+    /// searched in the background, with matches arriving as events.
+    pub fn search_in_current_tab(&mut self, query: &str) {
+        let buffer = self.tab_manager.current_tab().buffer();
+        if let Some(job) = self.search_state.start(query, buffer) {
+            self.spawn_search(job);
+        }
+        self.search_tab = Some(self.tab_manager.active_index());
+    }
+
+    /// Run a background search on a blocking task, streaming its matches
+    /// back as `AppEvent::Searched`
+    fn spawn_search(&self, job: SearchJob) {
+        let tx = self.event_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let id = job.id();
+            job.run(|matches, done| {
+                tx.blocking_send(AppEvent::Searched { id, matches, done })
+                    .is_ok()
+            });
+        });
+    }
+
+    /// Keep the active search's matches in step with the current tab
+    ///
+    /// Searches new output as it arrives, and searches the current tab
+    /// afresh after switching tabs, so a search stays applied everywhere.
+    pub fn refresh_search(&mut self) {
+        if !self.search_state.is_active() {
+            return;
+        }
+        if self.search_tab != Some(self.tab_manager.active_index()) {
+            let query = self.search_state.query().to_string();
+            self.search_in_current_tab(&query);
+            self.dirty = true;
+        } else if self
+            .search_state
+            .update(self.tab_manager.current_tab().buffer())
+        {
+            self.dirty = true;
+        }
+    }
+
+    /// Move to the next (or previous) match of the active search
+    ///
+    /// If the current tab has no matches, switches to the nearest tab in
+    /// that direction that does and jumps to its first (or last) match.
+    pub fn jump_to_match(&mut self, forward: bool) {
+        if !self.search_state.is_active() {
+            return;
+        }
+        let query = self.search_state.query().to_string();
+
+        // Matches are stale if they were found in another tab
+        let jump = if self.search_tab != Some(self.tab_manager.active_index()) {
+            self.search_in_current_tab(&query);
+            self.first_or_last_match(forward).map(|line| MatchJump {
+                line,
+                wrapped: false,
+            })
+        } else if forward {
+            self.search_state.next_match()
+        } else {
+            self.search_state.prev_match()
+        };
+
+        let line = match jump {
+            None if self.search_state.is_searching() => {
+                self.set_message(format!("Still searching for {}", query));
+                return;
+            }
+            Some(jump) => {
+                if jump.wrapped {
+                    self.set_message(if forward {
+                        "search hit BOTTOM, continuing at TOP"
+                    } else {
+                        "search hit TOP, continuing at BOTTOM"
+                    });
+                }
+                jump.line
+            }
+            None => match self.find_tab_with_matches(&query, forward) {
+                Some(line) => line,
+                None => {
+                    self.set_message(format!("Pattern not found in any tab: {}", query));
+                    return;
+                }
+            },
+        };
+        self.tab_manager.current_tab_mut().scroll_to_line(line);
+        if let Some(m) = self.search_state.current_match() {
+            let tab = self.tab_manager.current_tab();
+            // Matches past the cut of a long line aren't shown until it is expanded
+            let shown = tab.buffer().get(m.line).is_some_and(|line| {
+                line.shown_len()
+                    .is_none_or(|shown| m.start + m.len <= shown)
+            });
+            let column = |byte| {
+                Renderer::output_column(tab, self.timestamps, m.line, byte, tab.visible_lines())
+            };
+            if shown && let (Some(first), Some(last)) = (column(m.start), column(m.start + m.len)) {
+                self.tab_manager
+                    .current_tab_mut()
+                    .scroll_to_columns(first, last);
+            }
+        }
+    }
+
+    /// Highlight every occurrence of the word under the cursor (`*`)
+    ///
+    /// The word is taken from the output cursor in Cursor mode, the
+    /// selection cursor line in Visual mode, the current match, or the top
+    /// visible line otherwise. The mode and scroll position are left
+    /// unchanged; `n`/`N` then move between the occurrences.
+    pub fn highlight_word(&mut self) {
+        let tab = self.tab_manager.current_tab();
+        let current_match = self
+            .search_state
+            .current_match()
+            .filter(|_| self.search_tab == Some(self.tab_manager.active_index()));
+        let (line, byte) = match (self.mode, tab.cursor(), tab.selection(), current_match) {
+            (Mode::Cursor, Some(cursor), _, _) => (cursor.line, tab.cursor_byte_offset()),
+            (Mode::Visual, _, Some(selection), _) => (selection.cursor(), 0),
+            (_, _, _, Some(m)) => (m.line, m.start),
+            _ => (tab.scroll_offset(), 0),
+        };
+
+        let word = tab
+            .buffer()
+            .get(line)
+            .filter(|line| line.kind.is_output())
+            .and_then(|line| word_at(&line.plain(), byte).map(str::to_string));
+        match word {
+            Some(word) => self.search_in_current_tab(&word),
+            None => self.set_message("No word under cursor"),
+        }
+    }
+
+    /// Select the nearest other tab with matches for the query
+    ///
+    /// Returns the line of its first (or last) match. The search state is
+    /// left on the current tab if no other tab matches.
+    fn find_tab_with_matches(&mut self, query: &str, forward: bool) -> Option<usize> {
+        let len = self.tab_manager.len();
+        let active = self.tab_manager.active_index();
+
+        for step in 1..len {
+            let tab_index = if forward {
+                (active + step) % len
+            } else {
+                (active + len - step) % len
+            };
+            let buffer = self.tab_manager.get_tab(tab_index)?.buffer();
+            self.search_state.search(query, buffer);
+            if self.search_state.has_matches() {
+                self.tab_manager.select_tab(tab_index);
+                self.search_tab = Some(tab_index);
+                return self.first_or_last_match(forward);
+            }
+        }
+
+        self.search_in_current_tab(query);
+        None
+    }
+
+    /// Move to the first (forward) or last (backward) match of a fresh search
+    fn first_or_last_match(&mut self, forward: bool) -> Option<usize> {
+        if forward {
+            self.search_state.current_match().map(|m| m.line)
+        } else {
+            self.search_state.prev_match().map(|jump| jump.line)
+        }
+    }
+
+    /// Get the command-line input value
+    pub fn command_input(&self) -> &str {
+        self.command_input.value()
+    }
+
+    /// Handle input request for the command line
+    pub fn handle_command_input(&mut self, req: InputRequest) {
+        self.command_input.handle(req);
+        self.completion = None;
+        self.history_browse = None;
+    }
+
+    /// Set the history of commands run with `:new`
+    pub fn set_command_history(&mut self, history: History) {
+        self.command_history = history;
+    }
+
+    /// Offer these commands (oldest first) for `:new` completion
+    ///
+    /// Used to seed completion from the user's shell history.
+    pub fn set_suggested_commands(&mut self, commands: Vec<String>) {
+        self.suggested_commands = commands;
+    }
+
+    /// Show an older (or newer) `:new` command from the history
+    ///
+    /// Moving past the newest entry restores the input typed before browsing.
+    pub fn recall_command(&mut self, older: bool) {
+        let len = self.command_history.entries().len();
+        let current = self.history_browse.as_ref().map(|(index, _)| *index);
+        let index = match (current, older) {
+            (None, true) if len > 0 => len - 1,
+            (None, _) => return,
+            (Some(index), true) => index.saturating_sub(1),
+            (Some(index), false) if index + 1 < len => index + 1,
+            (Some(_), false) => {
+                if let Some((_, draft)) = self.history_browse.take() {
+                    self.command_input = draft.into();
+                }
+                return;
+            }
+        };
+
+        let draft = match self.history_browse.take() {
+            Some((_, draft)) => draft,
+            None => self.command_input.value().to_string(),
+        };
+        let entry = &self.command_history.entries()[index];
+        self.command_input = format!("new {}", entry).into();
+        self.completion = None;
+        self.history_browse = Some((index, draft));
+    }
+
+    /// Complete the command line, or cycle to the next (or previous) candidate
+    ///
+    /// Commands of the open tabs, the command history and suggested
+    /// commands (in that order, newest first), then executables in `$PATH`
+    /// are offered for `:new`.
+    pub fn complete_command(&mut self, forward: bool) {
+        match &mut self.completion {
+            Some(completion) => completion.cycle(forward),
+            None => {
+                let mut history: Vec<String> = self
+                    .suggested_commands
+                    .iter()
+                    .chain(self.command_history.entries())
+                    .cloned()
+                    .chain(
+                        self.tab_manager
+                            .iter()
+                            .filter(|tab| !tab.command().is_empty())
+                            .map(|tab| tab.command().to_string()),
+                    )
+                    .collect();
+                history.reverse();
+                self.completion =
+                    Completion::new(self.command_input.value(), &history, path_executables());
+            }
+        }
+        if let Some(completion) = &self.completion {
+            self.command_input = completion.current().into();
+        }
+    }
+
+    /// Get the completion being cycled through on the command line
+    pub fn completion(&self) -> Option<&Completion> {
+        self.completion.as_ref()
+    }
+
+    /// Enter Command mode with the given initial input
+    pub fn start_command(&mut self, initial: &str) {
+        self.command_input = initial.into();
+        self.completion = None;
+        self.history_browse = None;
+        self.mode = Mode::Command;
+    }
+
+    /// Enter Command mode to edit the current tab's note
+    pub fn edit_note(&mut self) {
+        let note = self.tab_manager.current_tab().note().unwrap_or_default();
+        self.start_command(&format!("note {}", note));
+    }
+
+    /// Leave Command mode without executing
+    pub fn cancel_command(&mut self) {
+        self.command_input.reset();
+        self.completion = None;
+        self.history_browse = None;
+        self.tab_manager.current_tab_mut().clear_selection();
+        self.mode = Mode::Normal;
+    }
+
+    /// Execute the command line and return to Normal mode
+    ///
+    /// The result (or error) is reported via the status bar message.
+    pub fn execute_command(&mut self) {
+        let input = self.command_input.value().to_string();
+        self.cancel_command();
+
+        match CommandLine::parse(&input) {
+            Ok(CommandLine::Write { range, path }) => {
+                let buffer = self.tab_manager.current_tab().buffer();
+                let result = match range {
+                    Some(range) if range.start > buffer.len() => {
+                        self.set_message(format!(
+                            "Error: range out of bounds ({} lines)",
+                            buffer.len()
+                        ));
+                        return;
+                    }
+                    Some(range) => {
+                        export::write_lines(&path, buffer.range(range.start - 1..range.end))
+                    }
+                    None => export::write_lines(&path, buffer.iter()),
+                };
+                match result {
+                    Ok(count) => self.set_message(format!(
+                        "Wrote {} to {}",
+                        line_count(count),
+                        path.display()
+                    )),
+                    Err(e) => self.log_event(format!("Error: {}: {}", path.display(), e)),
+                }
+            }
+            Ok(CommandLine::Mark { label }) => self.insert_marker(label.as_deref()),
+            Ok(CommandLine::Note { text }) => {
+                self.tab_manager.current_tab_mut().set_note(text);
+            }
+            Ok(CommandLine::Since { time }) => {
+                let since = time.map(|time| last_occurrence(time, Local::now()));
+                self.show_since(since);
+            }
+            Ok(CommandLine::Sort { order }) => {
+                self.tab_manager.sort(order);
+                self.set_message(format!("Sorted tabs by {}", order.label()));
+            }
+            Ok(CommandLine::Last { duration }) => {
+                // Durations too long to subtract cover every line
+                let since = TimeDelta::from_std(duration)
+                    .ok()
+                    .and_then(|delta| Local::now().checked_sub_signed(delta));
+                self.show_since(since);
+            }
+            Ok(CommandLine::New { command }) => {
+                if let Err(e) = self.command_history.push(&command) {
+                    self.set_message(format!("Error: command history: {}", e));
+                }
+                self.add_command(CommandSpec::parse(&command));
+            }
+            Ok(CommandLine::ExportAll { path }) => {
+                let names: Vec<String> = self
+                    .tab_manager
+                    .iter()
+                    .map(|tab| tab.display_name())
+                    .collect();
+                let tabs: Vec<TabLog> = self
+                    .tab_manager
+                    .iter()
+                    .zip(&names)
+                    .map(|(tab, name)| TabLog {
+                        name,
+                        command: tab.command(),
+                        status: tab.status().to_string(),
+                        note: tab.note(),
+                        buffer: tab.buffer(),
+                    })
+                    .collect();
+                match export::export_all(&path, &tabs) {
+                    Ok(count) => {
+                        self.set_message(format!("Exported {} tabs to {}", count, path.display()))
+                    }
+                    Err(e) => self.log_event(format!("Error: {}: {}", path.display(), e)),
+                }
+            }
+            Err(e) => self.set_message(format!("Error: {}", e)),
+        }
+    }
+
+    /// Show only the current tab's lines read since a time (all without one)
+    pub fn show_since(&mut self, since: Option<DateTime<Local>>) {
+        self.tab_manager.current_tab_mut().set_since(since);
+        if let Some(since) = since {
+            self.set_message(format!(
+                "Showing lines since {} - :since to show all",
+                since.format("%H:%M:%S")
+            ));
+        }
+    }
+
+    /// Insert a timestamped marker line into the current tab's output
+    pub fn insert_marker(&mut self, label: Option<&str>) {
+        self.tab_manager
+            .current_tab_mut()
+            .insert_marker(label, Local::now());
+    }
+
+    /// Get the status bar message
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// Set the status bar message
+    pub fn set_message(&mut self, message: impl Into<String>) {
+        self.message = Some(message.into());
+    }
+
+    /// Clear the status bar message
+    pub fn clear_message(&mut self) {
+        self.message = None;
+    }
+
+    /// Duplicate a tab's command into a new tab and start it
+    ///
+    /// The new tab becomes active; the original tab and its output are kept.
+    /// Returns the index of the new tab.
+    pub fn duplicate_tab(&mut self, tab_index: usize) -> Option<usize> {
+        let mut spec = self.tab_manager.get_tab(tab_index)?.spec().clone();
+        let base_name = spec.title();
+        let copies = self
+            .tab_manager
+            .iter()
+            .filter(|tab| {
+                let other = tab.spec();
+                other.command == spec.command
+                    && other.tail == spec.tail
+                    && other.source == spec.source
+            })
+            .count();
+        spec.name = Some(format!("{} ({})", base_name, copies + 1));
+
+        Some(self.add_command(spec))
+    }
+
+    /// Add a tab running a new command and start it
+    ///
+    /// The new tab becomes active. Returns its index.
+    pub fn add_command(&mut self, spec: CommandSpec) -> usize {
+        let new_index = self.tab_manager.add_tab(spec);
+        self.tab_manager.select_tab(new_index);
+        // Restarting a tab without a process simply starts it
+        self.request_restart(new_index);
+        new_index
+    }
+
+    /// Request restart for a specific tab
+    pub fn request_restart(&mut self, tab_index: usize) {
+        self.pending_restart = Some(tab_index);
+    }
+
+    /// Take pending restart request
+    ///
+    /// Returns the tab index if a restart was requested, None otherwise.
+    /// Clears the pending restart after taking.
+    pub fn take_pending_restart(&mut self) -> Option<usize> {
+        self.pending_restart.take()
+    }
+
+    /// Restart a specific tab's command
+    ///
+    /// Kills the existing process, resets the tab state, and spawns a new process
+    /// (running `restart_command` if set, once the tab has run before).
+    /// With `before_restart`, the tab waits while that command runs in the
+    /// background and is started once it has finished.
+    pub async fn restart_process(&mut self, tab_index: usize) {
+        // Kill existing process if any
+        self.kill_child(tab_index).await;
+        self.kill_detached(tab_index);
+
+        // Clear previous output (and scroll back) unless configured to keep
+        // it, then start a new run
+        let Some(tab) = self.tab_manager.get_tab_mut(tab_index) else {
+            return;
+        };
+        if tab.run_count() > 0 {
+            self.restarting.insert(tab_index);
+        }
+        match (tab.spec().keep_output, tab.spec().keep_scroll) {
+            (true, _) => {}
+            (false, true) => tab.clear_output(),
+            (false, false) => tab.reset(),
+        }
+        match tab.spec().before_restart.clone() {
+            Some(hook) => {
+                tab.set_status(CommandStatus::Waiting);
+                let start = AppEvent::Start {
+                    tab_index,
+                    run: tab.run_count(),
+                };
+                self.run_hook(tab_index, "before_restart", hook, &[], Some(start));
+            }
+            None => self.start_run(tab_index).await,
+        }
+        self.publish_restart(tab_index);
+    }
+
+    /// Run a tab's `after_exit` command for a run that exited with `exit_code`
+    fn run_after_exit(&mut self, tab_index: usize, exit_code: i32) {
+        let Some(hook) = self
+            .tab_manager
+            .get_tab(tab_index)
+            .and_then(|tab| tab.spec().after_exit.clone())
+        else {
+            return;
+        };
+        let env = [(EXIT_CODE_VAR, exit_code.to_string())];
+        self.run_hook(tab_index, "after_exit", hook, &env, None);
+    }
+
+    /// Run a hook command for a tab in the background
+    ///
+    /// The hook runs with the tab's directory and environment (plus `env`).
+    fn run_hook(
+        &mut self,
+        tab_index: usize,
+        name: &'static str,
+        command: String,
+        env: &[(&str, String)],
+        then: Option<AppEvent>,
+    ) {
+        let Some(tab) = self.tab_manager.get_tab(tab_index) else {
+            return;
+        };
+        // A hook's stdin isn't fed by the tab's `stdin_from` or `keys`
+        let mut spec = CommandSpec {
+            command,
+            stdin_from: None,
+            keys: BTreeMap::new(),
+            ..tab.spec().clone()
+        };
+        for (key, value) in env {
+            spec.env.insert(key.to_string(), value.clone());
+        }
+        let starts = then.is_some();
+        let hook = self.spawn_hook(tab_index, name, spec, None, then);
+        if starts {
+            self.hooks.insert(tab_index, hook);
+        }
+    }
+
+    /// Run a hook-like command for a tab in the background
+    ///
+    /// `input` is written to its stdin, which is empty otherwise. Its output
+    /// goes to the tab as hook lines, followed by a divider if it failed.
+    /// `then` is sent once the command has finished either way. Aborting the
+    /// returned task kills the command's process group.
+    fn spawn_hook(
+        &mut self,
+        tab_index: usize,
+        name: &'static str,
+        mut spec: CommandSpec,
+        input: Option<String>,
+        then: Option<AppEvent>,
+    ) -> JoinHandle<()> {
+        let expanded = self.expand_variables(tab_index, &mut spec);
+
+        let tx = self.event_tx.clone();
+        tokio::spawn(async move {
+            let spawned = match expanded {
+                Ok(()) => {
+                    let stdin = match input {
+                        Some(_) => Stdio::piped(),
+                        None => Stdio::null(),
+                    };
+                    CommandRunner::spawn_hook(tx.clone(), &spec, tab_index, stdin).await
+                }
+                Err(e) => Err(e),
+            };
+            let failure = match spawned {
+                Ok(mut child) => {
+                    let mut group = GroupKiller(child.id());
+                    match (child.stdin.take(), input) {
+                        (Some(mut stdin), Some(input)) => {
+                            // A command that doesn't read all of it just misses the rest
+                            let _ = stdin.write_all(input.as_bytes()).await;
+                        }
+                        // Close an unused pipe so a command reading stdin sees EOF
+                        (stdin, _) => drop(stdin),
+                    }
+                    let status = child.wait().await;
+                    group.0 = None;
+                    status
+                        .ok()
+                        .and_then(|status| status.code())
+                        .filter(|&code| code != 0)
+                        .map(|code| format!("{} exited ({})", name, code))
+                }
+                Err(e) => Some(format!("{} failed: {}", name, e)),
+            };
+            if let Some(label) = failure {
+                let line = OutputLine::divider(&label);
+                let _ = tx.send(AppEvent::Output { tab_index, line }).await;
+            }
+            if let Some(event) = then {
+                let _ = tx.send(event).await;
+            }
+        })
+    }
+}
+
+/// Kills the process group led by a process when dropped, unless the
+/// process was reaped first (cleared to `None`)
+struct GroupKiller(Option<u32>);
+
+impl Drop for GroupKiller {
+    fn drop(&mut self) {
+        if let Some(pid) = self.0 {
+            let _ = killpg(Pid::from_raw(pid as i32), Signal::SIGKILL);
+        }
+    }
+}
+
+/// A number of lines for a message, e.g. "1 line" or "3 lines"
+fn line_count(count: usize) -> String {
+    match count {
+        1 => "1 line".to_string(),
+        count => format!("{} lines", count),
+    }
+}
+
+/// Text of the last stderr line among the last few lines of a buffer
+fn last_stderr(buffer: &OutputBuffer) -> Option<String> {
+    (buffer.len().saturating_sub(5)..buffer.len())
+        .rev()
+        .filter_map(|index| buffer.get(index))
+        .find(|line| line.kind == OutputKind::Stderr)
+        .map(|line| line.plain())
+}
+
+/// Open a URL or file with the default application, without waiting for it
+fn open_externally(target: impl AsRef<std::ffi::OsStr>) {
+    let _ = tokio::process::Command::new(OPENER)
+        .arg(target)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+}
+
+/// Get the latest time at or before `now` with the given time of day
+fn last_occurrence(time: NaiveTime, now: DateTime<Local>) -> DateTime<Local> {
+    let mut date = now.date_naive();
+    if time > now.time() {
+        date = date.pred_opt().unwrap_or(date);
+    }
+    // A time skipped by a DST change has no local date-time; use the hour after
+    date.and_time(time)
+        .and_local_timezone(Local)
+        .earliest()
+        .or_else(|| {
+            (date.and_time(time) + TimeDelta::hours(1))
+                .and_local_timezone(Local)
+                .earliest()
+        })
+        .unwrap_or(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{Script, ScriptedSpawner};
+    use chrono::{Datelike, TimeZone};
+    use nix::sys::signal::kill;
+    use nix::unistd::Pid;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    /// Check if a process exists by sending signal 0
+    fn process_exists(pid: i32) -> bool {
+        kill(Pid::from_raw(pid), None).is_ok()
+    }
+
+    #[test]
+    fn app_new_initializes_correctly() {
+        let app = App::new(vec!["cmd1".into(), "cmd2".into()], 100);
+
+        assert_eq!(app.tab_manager().len(), 2);
+        assert_eq!(app.mode(), Mode::Normal);
+        assert!(!app.should_quit());
+    }
+
+    #[test]
+    fn app_mode_transition_works() {
+        let mut app = App::new(vec!["cmd".into()], 100);
+
+        assert_eq!(app.mode(), Mode::Normal);
+
+        app.set_mode(Mode::Search);
+        assert_eq!(app.mode(), Mode::Search);
+
+        app.set_mode(Mode::Normal);
+        assert_eq!(app.mode(), Mode::Normal);
+    }
+
+    #[test]
+    fn app_quit_sets_flag() {
+        let mut app = App::new(vec!["cmd".into()], 100);
+
+        assert!(!app.should_quit());
+
+        app.quit();
+        assert!(app.should_quit());
+    }
+
+    fn app_with_lines(count: usize) -> App {
+        let mut app = App::new(vec!["cmd".into()], 100);
+        for i in 0..count {
+            app.tab_manager_mut()
+                .current_tab_mut()
+                .push_output(crate::buffer::OutputLine::new(
+                    crate::buffer::OutputKind::Stdout,
+                    format!("line{}", i),
+                ));
+        }
+        app
+    }
+
+    #[test]
+    fn app_execute_command_writes_range_to_file() {
+        let mut app = app_with_lines(10);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("range.txt");
+
+        app.start_command(&format!("write 3,5 {}", path.display()));
+        app.execute_command();
+
+        assert_eq!(app.mode(), Mode::Normal);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "line2\nline3\nline4\n"
+        );
+        assert!(app.message().unwrap().starts_with("Wrote 3 lines"));
+    }
+
+    #[test]
+    fn app_execute_command_writes_whole_buffer_without_range() {
+        let mut app = app_with_lines(4);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("all.txt");
+
+        app.start_command(&format!("w {}", path.display()));
+        app.execute_command();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 4);
+    }
+
+    #[test]
+    fn app_execute_command_reports_errors_as_message() {
+        let mut app = app_with_lines(4);
+
+        app.start_command("write 10,20 out.txt");
+        app.execute_command();
+        assert!(app.message().unwrap().contains("out of bounds"));
+
+        app.start_command("bogus");
+        app.execute_command();
+        assert_eq!(app.message(), Some("Error: unknown command: bogus"));
+    }
+
+    #[test]
+    fn app_edit_note_prefills_and_sets_current_note() {
+        let mut app = App::new(vec!["api".into(), "web".into()], 100);
+
+        app.start_command("note restarted after migration");
+        app.execute_command();
+        app.edit_note();
+        assert_eq!(app.mode(), Mode::Command);
+        assert_eq!(app.command_input(), "note restarted after migration");
+
+        app.start_command("note");
+        app.execute_command();
+        assert_eq!(app.tab_manager().current_tab().note(), None);
+    }
+
+    #[rstest::rstest]
+    #[case(NaiveTime::from_hms_opt(14, 30, 0).unwrap(), 0)]
+    #[case(NaiveTime::from_hms_opt(18, 0, 0).unwrap(), 1)]
+    fn last_occurrence_is_today_or_yesterday(#[case] time: NaiveTime, #[case] days_ago: u32) {
+        let now = Local.with_ymd_and_hms(2026, 3, 2, 16, 0, 0).unwrap();
+
+        let since = last_occurrence(time, now);
+
+        assert_eq!(since.time(), time);
+        assert_eq!(since.date_naive().day(), 2 - days_ago);
+    }
+
+    #[test]
+    fn app_since_hides_older_lines_of_current_tab() {
+        let mut app = App::new(vec!["cmd".into()], 100);
+        let old = Local::now() - TimeDelta::hours(1);
+        let tab = app.tab_manager_mut().current_tab_mut();
+        tab.set_visible_lines(2);
+        for i in 0..5 {
+            tab.push_output(OutputLine::new(OutputKind::Stdout, i.to_string()).at(old));
+        }
+        tab.push_output(OutputLine::new(OutputKind::Stdout, "new".into()));
+
+        app.start_command("last 5m");
+        app.execute_command();
+        assert_eq!(app.tab_manager().current_tab().scroll_offset(), 5);
+        assert!(app.message().unwrap().starts_with("Showing lines since"));
+
+        app.start_command("since");
+        app.execute_command();
+        app.tab_manager_mut().current_tab_mut().scroll_to_top();
+        assert_eq!(app.tab_manager().current_tab().scroll_offset(), 0);
+    }
+
+    #[test]
+    fn app_subscribe_receives_handled_events() {
+        let mut app = App::new(vec!["cmd".into()], 100);
+        let mut rx = app.subscribe();
+
+        app.handle_app_event(AppEvent::Exited {
+            tab_index: 0,
+            exit_code: 3,
+        });
+
+        match rx.try_recv() {
+            Ok(AppEvent::Exited {
+                tab_index: 0,
+                exit_code: 3,
+            }) => {}
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn app_subscribe_drops_closed_subscribers() {
+        let mut app = App::new(vec!["cmd".into()], 100);
+        let rx = app.subscribe();
+        drop(rx);
+
+        app.handle_app_event(AppEvent::Exited {
+            tab_index: 0,
+            exit_code: 0,
+        });
+
+        assert!(app.subscribers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn app_subscribe_receives_lifecycle_events() {
+        let mut app = App::new(vec!["sleep 10".into()], 100);
+        let mut rx = app.subscribe();
+
+        app.spawn_commands().await;
+        app.restart_process(0).await;
+
+        let events: Vec<AppEvent> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        app.kill_all().await;
+
+        assert!(
+            matches!(
+                events.as_slice(),
+                [
+                    AppEvent::Started {
+                        tab_index: 0,
+                        pid: Some(_)
+                    },
+                    AppEvent::Killed {
+                        tab_index: 0,
+                        signal: 9
+                    },
+                    AppEvent::Stats { tab_index: 0, .. },
+                    AppEvent::Started { tab_index: 0, .. },
+                    AppEvent::Restarted {
+                        tab_index: 0,
+                        attempt: 1
+                    },
+                ]
+            ),
+            "unexpected events: {:?}",
+            events
+        );
+    }
+
+    #[test]
+    fn app_duplicate_tab_adds_active_tab_and_requests_start() {
+        let mut app = App::new(vec!["worker".into()], 100);
+
+        let index = app.duplicate_tab(0);
+
+        assert_eq!(index, Some(1));
+        assert_eq!(app.tab_manager().len(), 2);
+        assert_eq!(app.tab_manager().active_index(), 1);
+        let tab = app.tab_manager().get_tab(1).unwrap();
+        assert_eq!(tab.command(), "worker");
+        assert_eq!(tab.display_name(), "worker (2)");
+        assert_eq!(app.take_pending_restart(), Some(1));
+    }
+
+    #[test]
+    fn app_execute_new_command_adds_and_starts_tab() {
+        let mut app = App::new(vec!["worker".into()], 100);
+
+        app.start_command("new npm run dev");
+        app.execute_command();
+
+        assert_eq!(app.tab_manager().len(), 2);
+        assert_eq!(app.tab_manager().active_index(), 1);
+        assert_eq!(app.tab_manager().current_tab().command(), "npm run dev");
+        assert_eq!(app.take_pending_restart(), Some(1));
+    }
+
+    #[test]
+    fn app_complete_command_offers_tab_commands_and_resets_on_input() {
+        let mut app = App::new(vec!["cargo watch -x run".into(), "npm run dev".into()], 100);
+
+        app.start_command("new npm r");
+        app.complete_command(true);
+        assert_eq!(app.command_input(), "new npm run dev");
+        assert_eq!(app.completion().map(Completion::position), Some((1, 1)));
+
+        app.handle_command_input(InputRequest::InsertChar(' '));
+        assert!(app.completion().is_none());
+    }
+
+    #[test]
+    fn app_recall_command_browses_history_and_restores_input() {
+        let mut app = App::new(vec!["worker".into()], 100);
+        for command in ["cargo test", "npm run dev"] {
+            app.start_command(&format!("new {}", command));
+            app.execute_command();
+        }
+
+        app.start_command("new ");
+        app.recall_command(true);
+        assert_eq!(app.command_input(), "new npm run dev");
+        app.recall_command(true);
+        app.recall_command(true);
+        assert_eq!(app.command_input(), "new cargo test");
+
+        app.recall_command(false);
+        assert_eq!(app.command_input(), "new npm run dev");
+        app.recall_command(false);
+        assert_eq!(app.command_input(), "new ");
+    }
+
+    #[test]
+    fn app_complete_command_offers_suggested_commands_last() {
+        let mut app = App::new(vec!["make serve".into()], 100);
+        app.set_suggested_commands(vec!["make build".into(), "make test".into()]);
+
+        app.start_command("new make ");
+        app.complete_command(true);
+        assert_eq!(app.command_input(), "new make serve");
+        app.complete_command(true);
+        assert_eq!(app.command_input(), "new make test");
+        app.complete_command(true);
+        assert_eq!(app.command_input(), "new make build");
+    }
+
+    #[tokio::test]
+    async fn app_hung_command_is_restarted_with_divider() {
+        let spec = CommandSpec {
+            hang_timeout: Some(Duration::ZERO),
+            ..CommandSpec::new("sleep 10")
+        };
+        let mut app = App::new(vec![spec], 100);
+        app.spawn_commands().await;
+
+        app.check_hung_commands();
+        let tab_index = app.take_hung().unwrap();
+        app.restart_hung(tab_index).await;
+
+        let tab = app.tab_manager().current_tab();
+        assert_eq!(tab.run_count(), 2);
+        assert_eq!(
+            tab.buffer().get(0).unwrap().plain(),
+            "── no output for 0s - restarting ──"
+        );
+        assert_eq!(
+            app.message(),
+            Some("sleep hung (no output for 0s) - restarted")
+        );
+        app.kill_all().await;
+    }
+
+    #[tokio::test]
+    async fn app_ready_command_shows_its_url() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let spec = CommandSpec {
+            name: Some("web".into()),
+            ports: vec![port],
+            ..CommandSpec::new("sleep 10")
+        };
+        let mut app = App::new(vec![spec], 100);
+        app.spawn_commands().await;
+        let check = async |app: &mut App| {
+            app.next_ready_check = Instant::now();
+            app.check_ready_commands();
+            loop {
+                let event = tokio::time::timeout(Duration::from_secs(2), app.recv_event())
+                    .await
+                    .unwrap()
+                    .unwrap();
+                let checked = matches!(event, AppEvent::PortChecked { .. });
+                app.handle_app_event(event);
+                if checked {
+                    break;
+                }
+            }
+        };
+        check(&mut app).await;
+        assert_eq!(app.tab_manager().current_tab().ready_url(), None);
+
+        // Stands in for the server the command would start
+        let _server = std::net::TcpListener::bind(("127.0.0.1", port)).unwrap();
+        check(&mut app).await;
+
+        let url = format!("http://localhost:{}", port);
+        let tab = app.tab_manager().current_tab();
+        assert_eq!(tab.ready_url(), Some(url.as_str()));
+        assert_eq!(
+            tab.buffer().get(0).unwrap().plain(),
+            format!("── listening on {} ──", url)
+        );
+        assert_eq!(
+            app.message(),
+            Some(format!("web listening on {}", url).as_str())
+        );
+        app.kill_all().await;
+    }
+
+    #[tokio::test]
+    async fn app_pipe_lines_sends_selection_to_the_command() {
+        let spec = CommandSpec {
+            pipes: [('u', "tr a-z A-Z".into())].into(),
+            ..CommandSpec::new("cmd")
+        };
+        let mut app = App::new(vec![spec], 100);
+        let tab = app.tab_manager_mut().current_tab_mut();
+        for text in ["first", "second", "third"] {
+            tab.push_output(OutputLine::new(OutputKind::Stdout, text.into()));
+        }
+        tab.start_selection(1);
+        tab.extend_selection_down();
+        app.set_mode(Mode::Visual);
+
+        app.pipe_lines('u');
+        assert_eq!(app.message(), Some("Piped 2 lines to tr a-z A-Z"));
+        app.tab_manager_mut().current_tab_mut().start_selection(0);
+        app.set_mode(Mode::Visual);
+        app.pipe_lines('u');
+        assert_eq!(app.message(), Some("Piped 1 line to tr a-z A-Z"));
+        assert_eq!(app.mode(), Mode::Normal);
+        assert!(app.tab_manager().current_tab().selection().is_none());
+
+        let mut piped = Vec::new();
+        while piped.len() < 2 {
+            let event = tokio::time::timeout(Duration::from_secs(2), app.recv_event())
+                .await
+                .unwrap()
+                .unwrap();
+            if let AppEvent::Output { line, .. } = &event {
+                assert_eq!(line.kind, OutputKind::Hook);
+                piped.push(line.plain());
+            }
+        }
+        assert_eq!(piped, ["SECOND", "THIRD"]);
+    }
+
+    #[cfg(feature = "lua")]
+    #[test]
+    fn app_script_actions_mark_restart_and_notify() {
+        let specs = ["api", "worker"].map(|name| CommandSpec {
+            name: Some(name.into()),
+            ..CommandSpec::new("true")
+        });
+        let mut app = App::new(specs.to_vec(), 100);
+        let script = crate::script::Script::from_source(
+            "test.lua",
+            r#"
+            function on_line(tab, line)
+                if line == "panic" then
+                    parallels.mark(tab, "boom")
+                    parallels.restart("worker")
+                end
+            end
+            function on_exit(tab, code)
+                parallels.notify(tab .. " " .. code)
+                parallels.highlight("error")
+            end
+            "#,
+        )
+        .unwrap();
+        app.set_script(script);
+
+        app.handle_app_event(AppEvent::Output {
+            tab_index: 0,
+            line: OutputLine::new(OutputKind::Stdout, "panic".into()),
+        });
+
+        let buffer = app.tab_manager().get_tab(0).unwrap().buffer();
+        assert_eq!(buffer.len(), 2);
+        assert!(
+            buffer
+                .get(1)
+                .unwrap()
+                .plain()
+                .starts_with("── Mark: boom · ")
+        );
+        assert_eq!(app.take_pending_restart(), Some(1));
+
+        app.handle_app_event(AppEvent::Exited {
+            tab_index: 1,
+            exit_code: 3,
+        });
+        assert_eq!(app.message(), Some("worker 3"));
+        assert_eq!(app.search_state().query(), "error");
+    }
+
+    #[tokio::test]
+    async fn app_hang_probe_success_keeps_command_running() {
+        let spec = CommandSpec {
+            hang_timeout: Some(Duration::ZERO),
+            hang_probe: Some("true".into()),
+            ..CommandSpec::new("sleep 10")
+        };
+        let mut app = App::new(vec![spec], 100);
+        app.spawn_commands().await;
+
+        app.check_hung_commands();
+        let event = tokio::time::timeout(Duration::from_millis(1000), app.recv_event())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(event, AppEvent::Probed { healthy: true, .. }));
+        app.handle_app_event(event);
+
+        assert!(app.take_hung().is_none());
+        assert!(app.probing.is_empty());
+        app.kill_all().await;
+    }
+
+    #[test]
+    fn app_duplicate_tab_returns_none_for_unknown_tab() {
+        let mut app = App::new(vec!["worker".into()], 100);
+        assert_eq!(app.duplicate_tab(3), None);
+        assert_eq!(app.tab_manager().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn app_spawn_commands_starts_background_tasks() {
+        let mut app = App::new(vec!["echo hello".into()], 100);
+
+        app.spawn_commands().await;
+
+        // Receive and handle events
+        let timeout = std::time::Duration::from_millis(500);
+        let start = std::time::Instant::now();
+        while start.elapsed() < timeout {
+            tokio::select! {
+                Some(event) = app.recv_event() => {
+                    app.handle_app_event(event);
+                    if !app.tab_manager().current_tab().buffer().is_empty() {
+                        break;
+                    }
+                }
+                _ = tokio::time::sleep(std::time::Duration::from_millis(10)) => {}
+            }
+        }
+
+        // Check if output was received
+        let buffer = app.tab_manager().current_tab().buffer();
+        assert!(!buffer.is_empty(), "Should have received output");
+    }
+
+    #[tokio::test]
+    async fn app_recv_event_handles_output() {
+        let mut app = App::new(vec!["echo test_line".into()], 100);
+
+        app.spawn_commands().await;
+
+        // Receive and handle events
+        let timeout = std::time::Duration::from_millis(500);
+        let start = std::time::Instant::now();
+        while start.elapsed() < timeout {
+            tokio::select! {
+                Some(event) = app.recv_event() => {
+                    app.handle_app_event(event);
+                    if !app.tab_manager().current_tab().buffer().is_empty() {
+                        break;
+                    }
+                }
+                _ = tokio::time::sleep(std::time::Duration::from_millis(10)) => {}
+            }
+        }
+
+        let buffer = app.tab_manager().current_tab().buffer();
+        assert!(!buffer.is_empty(), "Should have received output");
+    }
+
+    #[tokio::test]
+    async fn app_spawn_commands_delays_start() {
+        let spec = CommandSpec {
+            delay: Some(Duration::from_millis(20)),
+            ..CommandSpec::new("echo delayed")
+        };
+        let mut app = App::new(vec![spec], 100);
+
+        app.spawn_commands().await;
+
+        assert_eq!(
+            app.tab_manager().current_tab().status(),
+            &CommandStatus::Waiting
+        );
+        assert!(app.children.is_empty());
+
+        let event = tokio::time::timeout(Duration::from_millis(1000), app.recv_event())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            event,
+            AppEvent::Start {
+                tab_index: 0,
+                run: 0
+            }
+        ));
+
+        app.handle_app_event(event);
+        assert_eq!(app.take_pending_run(), Some(0));
+    }
+
+    #[test]
+    fn app_start_event_ignored_when_started_since() {
+        let mut app = App::new(vec!["cmd".into()], 100);
+        app.tab_manager_mut()
+            .current_tab_mut()
+            .begin_run(Local::now());
+
+        app.handle_app_event(AppEvent::Start {
+            tab_index: 0,
+            run: 0,
+        });
+
+        assert!(app.take_pending_run().is_none());
+    }
+
+    #[tokio::test]
+    async fn app_periodic_command_reruns_with_divider() {
+        let spec = CommandSpec {
+            every: Some(Duration::from_millis(20)),
+            ..CommandSpec::new("echo tick")
+        };
+        let mut app = App::new(vec![spec], 100);
+        app.spawn_commands().await;
+
+        let timeout = Duration::from_millis(2000);
+        let start = Instant::now();
+        while start.elapsed() < timeout && app.tab_manager().current_tab().run_count() < 2 {
+            if let Ok(Some(event)) =
+                tokio::time::timeout(Duration::from_millis(10), app.recv_event()).await
+            {
+                app.handle_app_event(event);
+            }
+            while let Some(tab_index) = app.take_pending_run() {
+                app.start_run(tab_index).await;
+            }
+        }
+
+        let tab = app.tab_manager().current_tab();
+        assert_eq!(tab.run_count(), 2);
+        assert!(
+            tab.buffer()
+                .iter()
+                .any(|line| line.kind == crate::buffer::OutputKind::Divider)
+        );
+    }
+
+    #[tokio::test]
+    async fn app_until_success_stops_after_success() {
+        // Fails on the first attempt, succeeds on the second
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("until_success_marker");
+        let spec = CommandSpec {
+            every: Some(Duration::from_millis(10)),
+            until_success: true,
+            ..CommandSpec::new(format!(
+                "test -e {0} || {{ touch {0}; exit 1; }}",
+                marker.display()
+            ))
+        };
+        let mut app = App::new(vec![spec], 100);
+        app.spawn_commands().await;
+
+        let timeout = Duration::from_millis(2000);
+        let start = Instant::now();
+        while start.elapsed() < timeout
+            && app.tab_manager().current_tab().status()
+                != &(CommandStatus::Finished { exit_code: 0 })
+        {
+            if let Ok(Some(event)) =
+                tokio::time::timeout(Duration::from_millis(10), app.recv_event()).await
+            {
+                app.handle_app_event(event);
+            }
+            while let Some(tab_index) = app.take_pending_run() {
+                app.start_run(tab_index).await;
+            }
+        }
+
+        let tab = app.tab_manager().current_tab();
+        assert_eq!(tab.status(), &CommandStatus::Finished { exit_code: 0 });
+        assert_eq!(tab.run_count(), 2);
+        assert!(tab.next_run().is_none());
+    }
+
+    #[test]
+    fn app_execute_command_export_all_writes_each_tab() {
+        let mut app = App::new(vec!["first".into(), "second".into()], 100);
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("export_all");
+        app.start_command(&format!("export-all {}", dir.display()));
+
+        app.execute_command();
+
+        assert_eq!(
+            app.message(),
+            Some(format!("Exported 2 tabs to {}", dir.display()).as_str())
+        );
+        assert!(dir.join("01-first.log").exists());
+        assert!(dir.join("02-second.log").exists());
+        assert!(dir.join("manifest.toml").exists());
+    }
+
+    #[test]
+    fn app_execute_command_mark_inserts_labeled_marker() {
+        let mut app = app_with_lines(2);
+        app.start_command("mark before click");
+
+        app.execute_command();
+
+        let buffer = app.tab_manager().current_tab().buffer();
+        let marker = buffer.get(2).unwrap();
+        assert_eq!(marker.kind, crate::buffer::OutputKind::Marker);
+        assert!(marker.plain().contains("Mark: before click"));
+    }
+
+    #[test]
+    fn app_selected_text_joins_plain_selected_lines() {
+        let mut app = app_with_lines(3);
+        assert_eq!(app.selected_text(), None);
+        let tab = app.tab_manager_mut().current_tab_mut();
+        tab.push_output(crate::buffer::OutputLine::new(
+            crate::buffer::OutputKind::Stderr,
+            "\x1b[31merror\x1b[0m: boom".into(),
+        ));
+        tab.start_selection(2);
+        tab.extend_selection_down();
+
+        assert_eq!(app.selected_text().as_deref(), Some("line2\nerror: boom"));
+    }
+
+    #[test]
+    fn app_focused_line_text_follows_cursor_and_copy_prefix() {
+        let mut app = app_with_lines(3);
+        app.tab_manager_mut().current_tab_mut().scroll_to_top();
+        assert_eq!(app.focused_line_text().as_deref(), Some("line0"));
+
+        app.tab_manager_mut().current_tab_mut().start_cursor(2);
+        app.set_mode(Mode::Cursor);
+        assert_eq!(app.focused_line_text().as_deref(), Some("line2"));
+
+        app.set_copy_prefix(true);
+        assert_eq!(app.focused_line_text().as_deref(), Some("[stdout] line2"));
+    }
+
+    #[test]
+    fn app_open_path_under_cursor_resolves_against_cwd() {
+        let dir = tempfile::tempdir().unwrap();
+        let spec = CommandSpec {
+            cwd: Some(dir.path().to_path_buf()),
+            ..CommandSpec::new("cargo build")
+        };
+        let mut app = App::new(vec![spec], 100);
+        let tab = app.tab_manager_mut().current_tab_mut();
+        tab.push_output(OutputLine::new(
+            OutputKind::Stderr,
+            "  --> src/missing.rs:3:1".into(),
+        ));
+        tab.start_cursor(0);
+        app.set_mode(Mode::Cursor);
+
+        app.open_path_under_cursor();
+        assert_eq!(app.message(), Some("No path under cursor"));
+
+        app.tab_manager_mut()
+            .current_tab_mut()
+            .move_cursor_word(true);
+        app.open_path_under_cursor();
+        assert_eq!(
+            app.message(),
+            Some(
+                format!(
+                    "No such file: {}",
+                    dir.path().join("src/missing.rs").display()
+                )
+                .as_str()
+            )
+        );
+    }
+
+    #[test]
+    fn app_reload_config_applies_display_settings_and_names_pending_changes() {
+        let web = CommandSpec {
+            name: Some("web".into()),
+            ..CommandSpec::new("npm run dev")
+        };
+        let old = Config {
+            commands: vec![web.clone(), "cargo watch".into()],
+            ..Config::default()
+        };
+        let mut app = App::new(old.commands.clone(), 100);
+
+        let mut new = old.clone();
+        new.copy_prefix = true;
+        new.commands[0].table = true;
+        app.reload_config(&old, &new);
+        assert!(app.copy_prefix);
+        assert!(app.tab_manager().current_tab().table_view());
+        assert_eq!(app.message(), Some("Config reloaded"));
+
+        let mut newer = new.clone();
+        newer.commands[0].cwd = Some("web".into());
+        newer.commands[1] = "cargo watch -x test".into();
+        app.reload_config(&new, &newer);
+        assert_eq!(
+            app.message(),
+            Some("Config reloaded; restart parallels to apply the changes to web, cargo:watch")
+        );
+    }
+
+    /// App with three tabs, where only the given tabs contain "needle"
+    fn app_with_needles(needle_tabs: &[usize]) -> App {
+        let mut app = App::new(vec!["a".into(), "b".into(), "c".into()], 100);
+        for tab_index in 0..3 {
+            let tab = app.tab_manager_mut().get_tab_mut(tab_index).unwrap();
+            tab.push_output(crate::buffer::OutputLine::new(
+                crate::buffer::OutputKind::Stdout,
+                "hay".into(),
+            ));
+            if needle_tabs.contains(&tab_index) {
+                for _ in 0..2 {
+                    tab.push_output(crate::buffer::OutputLine::new(
+                        crate::buffer::OutputKind::Stdout,
+                        "needle".into(),
+                    ));
+                }
+            }
+        }
+        app
+    }
+
+    #[rstest::rstest]
+    #[case(true, 1, 1)]
+    #[case(false, 2, 2)]
+    fn app_jump_to_match_switches_to_nearest_tab_with_matches(
+        #[case] forward: bool,
+        #[case] expected_tab: usize,
+        #[case] expected_line: usize,
+    ) {
+        let mut app = app_with_needles(&[1, 2]);
+        app.search_in_current_tab("needle");
+        assert!(!app.search_state().has_matches());
+
+        app.jump_to_match(forward);
+
+        assert_eq!(app.tab_manager().active_index(), expected_tab);
+        assert_eq!(
+            app.search_state().current_match().map(|m| m.line),
+            Some(expected_line)
+        );
+    }
+
+    #[test]
+    fn app_jump_to_match_researches_after_tab_switch() {
+        let mut app = app_with_needles(&[0, 1]);
+        app.search_in_current_tab("needle");
+        app.tab_manager_mut().next_tab();
+
+        app.jump_to_match(true);
+
+        assert_eq!(app.tab_manager().active_index(), 1);
+        assert_eq!(app.search_state().current_match_display(), Some(1));
+    }
+
+    #[test]
+    fn app_tab_switch_keeps_scroll_and_search() {
+        let mut app = app_with_needles(&[0, 1]);
+        let tab = app.tab_manager_mut().current_tab_mut();
+        tab.set_visible_lines(1);
+        tab.set_auto_scroll(false);
+        tab.scroll_to_line(2);
+        tab.scroll_right();
+        app.search_in_current_tab("needle");
+
+        app.tab_manager_mut().next_tab();
+        app.refresh_search();
+        app.tab_manager_mut().prev_tab();
+        app.refresh_search();
+
+        let tab = app.tab_manager().current_tab();
+        assert_eq!(tab.scroll_offset(), 2);
+        assert_eq!(tab.horizontal_scroll(), 1);
+        assert!(!tab.auto_scroll());
+        assert_eq!(app.search_state().query(), "needle");
+        assert_eq!(app.search_state().match_count(), 2);
+    }
+
+    #[test]
+    fn app_jump_to_match_reports_when_no_tab_matches() {
+        let mut app = app_with_needles(&[]);
+        app.search_in_current_tab("needle");
+
+        app.jump_to_match(true);
+
+        assert_eq!(app.tab_manager().active_index(), 0);
+        assert_eq!(app.message(), Some("Pattern not found in any tab: needle"));
+    }
+
+    #[rstest::rstest]
+    #[case(true, "search hit BOTTOM, continuing at TOP")]
+    #[case(false, "search hit TOP, continuing at BOTTOM")]
+    fn app_jump_to_match_reports_wrap_around(#[case] forward: bool, #[case] expected: &str) {
+        let mut app = app_with_needles(&[0]);
+        app.search_in_current_tab("needle");
+
+        // Two matches: forward wraps on the second jump, backward on the first
+        app.jump_to_match(forward);
+        if forward {
+            assert_eq!(app.message(), None);
+            app.jump_to_match(forward);
+        }
+
+        assert_eq!(app.message(), Some(expected));
+        assert_eq!(app.tab_manager().active_index(), 0);
+    }
+
+    #[test]
+    fn app_jump_to_match_scrolls_horizontally_to_the_match() {
+        let mut app = App::new(vec!["cmd".into()], 100);
+        let tab = app.tab_manager_mut().current_tab_mut();
+        tab.set_visible_columns(20);
+        tab.push_output(crate::buffer::OutputLine::new(
+            crate::buffer::OutputKind::Stdout,
+            format!("needle{}needle", " ".repeat(40)),
+        ));
+        app.search_in_current_tab("needle");
+
+        // First match at column 9 (after the prefix) is visible
+        assert_eq!(app.tab_manager().current_tab().horizontal_scroll(), 0);
+        app.jump_to_match(true);
+        assert_eq!(app.tab_manager().current_tab().horizontal_scroll(), 41);
+        app.jump_to_match(true);
+        assert_eq!(app.tab_manager().current_tab().horizontal_scroll(), 0);
+    }
+
+    #[test]
+    fn app_jump_to_match_counts_timestamp_columns() {
+        let mut app = App::new(vec!["cmd".into()], 100);
+        app.set_timestamps(TimestampMode::Delta);
+        let tab = app.tab_manager_mut().current_tab_mut();
+        tab.set_visible_columns(20);
+        tab.push_output(crate::buffer::OutputLine::new(
+            crate::buffer::OutputKind::Stdout,
+            format!("needle{}needle", " ".repeat(40)),
+        ));
+        let stamp =
+            Renderer::timestamp_span(TimestampMode::Delta, app.tab_manager().current_tab(), 0)
+                .unwrap()
+                .width();
+        app.search_in_current_tab("needle");
+
+        // The second match ends after the prefix, the timestamp and 52 characters
+        app.jump_to_match(true);
+        assert_eq!(
+            app.tab_manager().current_tab().horizontal_scroll(),
+            9 + stamp + 52 - 20
+        );
+    }
+
+    #[tokio::test]
+    async fn app_searches_large_buffers_in_the_background() {
+        let mut app = App::new(vec!["cmd".into()], 0);
+        let tab = app.tab_manager_mut().current_tab_mut();
+        for i in 0..=crate::search::BACKGROUND_SEARCH_LINES {
+            let text = if i % 50_000 == 0 { "needle" } else { "hay" };
+            tab.push_output(OutputLine::new(OutputKind::Stdout, text.into()));
+        }
+
+        app.search_in_current_tab("needle");
+        assert!(app.search_state().is_searching());
+        while app.search_state().is_searching() {
+            let event = tokio::time::timeout(Duration::from_secs(5), app.recv_event())
+                .await
+                .expect("search finishes")
+                .unwrap();
+            app.handle_app_event(event);
+        }
+
+        assert_eq!(app.search_state().match_count(), 3);
+    }
+
+    #[test]
+    fn app_refresh_search_follows_new_output_and_tab_switches() {
+        let mut app = app_with_needles(&[1]);
+        app.search_in_current_tab("needle");
+        assert!(!app.search_state().has_matches());
+
+        app.handle_app_event(AppEvent::Output {
+            tab_index: 0,
+            line: crate::buffer::OutputLine::new(
+                crate::buffer::OutputKind::Stdout,
+                "late needle".into(),
+            ),
+        });
+        app.refresh_search();
+        assert_eq!(app.search_state().match_count(), 1);
+
+        app.tab_manager_mut().next_tab();
+        app.refresh_search();
+        assert_eq!(app.search_state().match_count(), 2);
+    }
+
+    /// Sink recording lines, failing once `fail` is set
+    struct RecordingSink {
+        lines: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+        fail: bool,
+    }
+
+    impl OutputSink for RecordingSink {
+        fn write_line(&mut self, line: &SinkLine) -> std::io::Result<()> {
+            if self.fail {
+                return Err(std::io::Error::other("disk full"));
+            }
+            self.lines
+                .lock()
+                .unwrap()
+                .push(format!("{}:{}", line.tab_name, line.line.plain()));
+            Ok(())
+        }
+    }
+
+    fn output_event(tab_index: usize, content: &str) -> AppEvent {
+        AppEvent::Output {
+            tab_index,
+            line: crate::buffer::OutputLine::new(crate::buffer::OutputKind::Stdout, content.into()),
+        }
+    }
+
+    #[test]
+    fn app_dirty_flag_set_by_events_and_cleared_on_take() {
+        let mut app = App::new(vec!["a".into()], 100);
+        assert!(app.take_dirty(), "new app should draw the first frame");
+        assert!(!app.take_dirty());
+
+        app.handle_app_event(output_event(0, "hello"));
+
+        assert!(app.take_dirty());
+        assert!(!app.take_dirty());
+    }
+
+    #[test]
+    fn app_output_fans_out_to_sinks_and_buffer() {
+        let mut app = App::new(vec!["a".into(), "b".into()], 100);
+        let lines = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        app.add_sink(Box::new(RecordingSink {
+            lines: lines.clone(),
+            fail: false,
+        }));
+
+        app.handle_app_event(output_event(1, "hello"));
+
+        assert_eq!(*lines.lock().unwrap(), vec!["b:hello".to_string()]);
+        assert_eq!(app.tab_manager().get_tab(1).unwrap().buffer().len(), 1);
+    }
+
+    #[test]
+    fn app_failing_sink_is_kept_for_retry_and_reported() {
+        let mut app = App::new(vec!["a".into()], 100);
+        app.add_sink(Box::new(RecordingSink {
+            lines: Default::default(),
+            fail: true,
+        }));
+
+        app.handle_app_event(output_event(0, "hello"));
+
+        assert_eq!(app.sinks.len(), 1);
+        let message = "Error: output sink: disk full - keeping lines, retrying in 1s";
+        assert_eq!(app.message(), Some(message));
+        let events: Vec<&str> = app.events().map(|(_, event)| event.as_str()).collect();
+        assert_eq!(events, [message]);
+        assert_eq!(app.tab_manager().current_tab().buffer().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn app_exit_event_sets_finished_status() {
+        let mut app = App::new(vec!["exit 3".into()], 100);
+        app.spawn_commands().await;
+
+        while app.children.contains_key(&0) {
+            let event = tokio::time::timeout(Duration::from_millis(1000), app.recv_event())
+                .await
+                .unwrap()
+                .unwrap();
+            app.handle_app_event(event);
+        }
+
+        assert_eq!(
+            app.tab_manager().current_tab().status(),
+            &CommandStatus::Finished { exit_code: 3 }
+        );
+    }
+
+    #[test]
+    fn app_exit_when_done_quits_after_last_command() {
+        let mut app = App::new(vec!["a".into(), "b".into()], 100);
+        app.set_exit_when_done(Some(Duration::ZERO));
+
+        app.handle_app_event(AppEvent::Exited {
+            tab_index: 0,
+            exit_code: 0,
+        });
+        assert!(!app.should_quit());
+
+        app.handle_app_event(AppEvent::Failed {
+            tab_index: 1,
+            reason: "not found".into(),
+        });
+        assert!(app.should_quit());
+    }
+
+    #[test]
+    fn app_exit_when_done_with_delay_counts_down_until_cancelled() {
+        let mut app = App::new(vec!["a".into()], 100);
+        app.set_exit_when_done(Some(Duration::from_secs(60)));
+
+        app.handle_app_event(AppEvent::Exited {
+            tab_index: 0,
+            exit_code: 0,
+        });
+
+        assert!(!app.should_quit());
+        assert!(app.has_countdown());
+        assert_eq!(
+            app.message(),
+            Some("All commands finished - exiting in 60s (press any key to stay)")
+        );
+        assert!(app.cancel_exit());
+        app.update_exit_countdown();
+        assert!(!app.should_quit());
+        assert!(!app.has_countdown());
+    }
+
+    #[test]
+    fn app_exit_when_done_quits_after_setup_fails() {
+        let mut app = App::new(vec!["setup".into(), "server".into()], 100);
+        app.set_setup_tab(0);
+        app.set_exit_when_done(Some(Duration::ZERO));
+        app.tab_manager_mut()
+            .get_tab_mut(1)
+            .unwrap()
+            .set_status(CommandStatus::Waiting);
+
+        app.handle_app_event(AppEvent::Exited {
+            tab_index: 0,
+            exit_code: 1,
+        });
+
+        assert!(app.tab_manager().all_done());
+        assert!(app.should_quit());
+    }
+
+    #[tokio::test]
+    async fn app_exit_when_done_waits_for_scheduled_reruns() {
+        let spec = CommandSpec {
+            every: Some(Duration::from_secs(60)),
+            ..CommandSpec::new("a")
+        };
+        let mut app = App::new(vec![spec], 100);
+        app.set_exit_when_done(Some(Duration::ZERO));
+
+        app.handle_app_event(AppEvent::Exited {
+            tab_index: 0,
+            exit_code: 0,
+        });
+
+        assert!(!app.should_quit());
+    }
+
+    #[tokio::test]
+    async fn app_signaled_command_records_signal() {
+        let mut app = App::new(vec!["kill -SEGV $$".into()], 100);
+        app.spawn_commands().await;
+
+        while app.children.contains_key(&0) {
+            let event = tokio::time::timeout(Duration::from_millis(1000), app.recv_event())
+                .await
+                .unwrap()
+                .unwrap();
+            app.handle_app_event(event);
+        }
+
+        assert_eq!(
+            app.tab_manager().current_tab().status(),
+            &CommandStatus::Signaled {
+                signal: nix::sys::signal::Signal::SIGSEGV as i32
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn app_restart_ignores_exit_of_killed_run() {
+        // Fails right away on the first run, keeps running on the second
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("stale_exit_marker");
+        let command = format!(
+            "test -e {0} && exec sleep 10 || {{ touch {0}; exit 1; }}",
+            marker.display()
+        );
+        let mut app = App::new(vec![command.into()], 100);
+        app.spawn_commands().await;
+        // Let the first run exit without handling its event
+        while !marker.exists() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        app.restart_process(0).await;
+        while let Ok(Some(event)) =
+            tokio::time::timeout(Duration::from_millis(100), app.recv_event()).await
+        {
+            app.handle_app_event(event);
+        }
+
+        assert_eq!(
+            app.tab_manager().current_tab().status(),
+            &CommandStatus::Running
+        );
+        app.kill_all().await;
+    }
+
+    #[tokio::test]
+    async fn app_tail_tab_follows_file_until_killed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        std::fs::write(&path, "old\nnew\n").unwrap();
+        let spec = CommandSpec {
+            tail_lines: Some(1),
+            ..CommandSpec::tail(&path)
+        };
+        let mut app = App::new(vec![spec], 100);
+
+        app.spawn_commands().await;
+        let event = tokio::time::timeout(Duration::from_secs(1), app.recv_event())
+            .await
+            .unwrap()
+            .unwrap();
+        app.handle_app_event(event);
+
+        let tab = app.tab_manager().current_tab();
+        assert_eq!(tab.name(), path.to_str().unwrap());
+        assert_eq!(tab.buffer().get(0).unwrap().plain(), "new");
+        assert_eq!(tab.status(), &CommandStatus::Running);
+        assert!(app.tasks.contains_key(&0));
+
+        app.kill_all().await;
+        assert!(app.tasks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn app_pipes_stdout_into_stdin_from_tab() {
+        let producer = CommandSpec {
+            name: Some("words".into()),
+            ..CommandSpec::new("printf 'b\\na\\n'")
+        };
+        let consumer = CommandSpec {
+            stdin_from: Some("words".into()),
+            ..CommandSpec::new("sort")
+        };
+        let mut app = App::new(vec![producer, consumer], 100);
+
+        app.spawn_commands().await;
+        while !app.tab_manager().all_done() {
+            let event = tokio::time::timeout(Duration::from_secs(5), app.recv_event())
+                .await
+                .unwrap()
+                .unwrap();
+            app.handle_app_event(event);
+        }
+
+        let lines = |tab_index| -> Vec<String> {
+            let buffer = app.tab_manager().get_tab(tab_index).unwrap().buffer();
+            (0..buffer.len())
+                .map(|i| buffer.get(i).unwrap().plain())
+                .collect()
+        };
+        assert_eq!(lines(0), ["b", "a"]);
+        assert_eq!(lines(1), ["a", "b"]);
+        assert!(app.stdin_writers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn app_keeps_piped_lines_until_the_reader_starts() {
+        let producer = CommandSpec {
+            name: Some("words".into()),
+            ..CommandSpec::new("printf 'b\\na\\n'")
+        };
+        let consumer = CommandSpec {
+            stdin_from: Some("words".into()),
+            delay: Some(Duration::from_millis(200)),
+            ..CommandSpec::new("sort")
+        };
+        let mut app = App::new(vec![producer, consumer], 100);
+
+        app.spawn_commands().await;
+        while !app.tab_manager().all_done() {
+            while let Some(tab_index) = app.take_pending_run() {
+                app.start_run(tab_index).await;
+            }
+            let event = tokio::time::timeout(Duration::from_secs(5), app.recv_event())
+                .await
+                .unwrap()
+                .unwrap();
+            app.handle_app_event(event);
         }
+
+        let buffer = app.tab_manager().get_tab(1).unwrap().buffer();
+        let lines: Vec<String> = buffer.iter().map(|line| line.plain()).collect();
+        assert_eq!(lines, ["a", "b"]);
     }
 
-    /// Kill all running processes
-    ///
-    /// Sends SIGKILL to all process groups to ensure child processes
-    /// (e.g., servers started by shell commands) are also terminated.
-    /// Waits for each process to terminate before returning.
-    pub async fn kill_all(&mut self) {
-        for child in self.children.values_mut() {
-            if let Some(pid) = child.id() {
-                // Send SIGKILL to the process group (PGID = PID because we used process_group(0))
-                let _ = killpg(Pid::from_raw(pid as i32), Signal::SIGKILL);
+    #[tokio::test]
+    async fn app_keeps_feeding_the_reader_across_reruns() {
+        let producer = CommandSpec {
+            name: Some("ticks".into()),
+            every: Some(Duration::from_millis(50)),
+            ..CommandSpec::new("echo tick")
+        };
+        let consumer = CommandSpec {
+            stdin_from: Some("ticks".into()),
+            ..CommandSpec::new("cat")
+        };
+        let mut app = App::new(vec![producer, consumer], 100);
+
+        app.spawn_commands().await;
+        let ticks = |app: &App| {
+            let buffer = app.tab_manager().get_tab(1).unwrap().buffer();
+            buffer.iter().filter(|line| line.plain() == "tick").count()
+        };
+        while ticks(&app) < 2 {
+            while let Some(tab_index) = app.take_pending_run() {
+                app.start_run(tab_index).await;
             }
-            // Wait for the process to terminate
-            let _ = child.wait().await;
+            let event = tokio::time::timeout(Duration::from_secs(5), app.recv_event())
+                .await
+                .unwrap()
+                .unwrap();
+            app.handle_app_event(event);
         }
+        assert_eq!(
+            app.tab_manager().get_tab(1).unwrap().status(),
+            &CommandStatus::Running
+        );
+        app.kill_all().await;
     }
 
-    /// Check if the application should quit
-    pub fn should_quit(&self) -> bool {
-        self.should_quit
-    }
+    #[test]
+    fn app_reports_lines_dropped_by_a_slow_reader() {
+        let producer = CommandSpec {
+            name: Some("words".into()),
+            ..CommandSpec::new("yes")
+        };
+        let consumer = CommandSpec {
+            name: Some("slow".into()),
+            stdin_from: Some("words".into()),
+            ..CommandSpec::new("sleep 10")
+        };
+        let mut app = App::new(vec![producer, consumer], 100);
+        let (tx, mut rx) = mpsc::channel(PIPE_CAPACITY);
+        app.stdin_writers.insert(1, tx);
+        let output = |app: &mut App| {
+            let line = OutputLine::new(OutputKind::Stdout, "y".into());
+            app.handle_app_event(AppEvent::Output { tab_index: 0, line });
+        };
 
-    /// Set the quit flag
-    pub fn quit(&mut self) {
-        self.should_quit = true;
-    }
+        for _ in 0..PIPE_CAPACITY + 2 {
+            output(&mut app);
+        }
+        assert_eq!(
+            app.message(),
+            Some("Error: slow: not reading its input fast enough - dropping lines")
+        );
 
-    /// Get current mode
-    pub fn mode(&self) -> Mode {
-        self.mode
+        while rx.try_recv().is_ok() {}
+        output(&mut app);
+        assert_eq!(app.message(), Some("slow: 2 piped lines dropped"));
+        assert_eq!(app.events().count(), 2);
     }
 
-    /// Set mode
-    pub fn set_mode(&mut self, mode: Mode) {
-        self.mode = mode;
-    }
+    #[tokio::test]
+    async fn app_tab_keys_send_text_and_signals_to_the_command() {
+        let repl = CommandSpec {
+            name: Some("repl".into()),
+            keys: std::collections::BTreeMap::from([
+                ('t', KeyAction::Send("test\n".into())),
+                (
+                    'x',
+                    KeyAction::Signal {
+                        signal: crate::command::SignalName(Signal::SIGTERM),
+                    },
+                ),
+            ]),
+            ..CommandSpec::new("while read line; do echo \"got $line\"; done")
+        };
+        let mut app = App::new(vec![repl], 100);
+        assert!(!app.press_tab_key('j'));
+        assert!(app.press_tab_key('t'));
+        assert_eq!(app.message(), Some("Error: repl: not running"));
 
-    /// Get reference to tab manager
-    pub fn tab_manager(&self) -> &TabManager {
-        &self.tab_manager
-    }
+        app.spawn_commands().await;
+        assert!(app.press_tab_key('t'));
+        assert_eq!(app.message(), Some("Sent \"test\\n\" to repl"));
+        while app.tab_manager().current_tab().buffer().is_empty() {
+            let event = tokio::time::timeout(Duration::from_secs(5), app.recv_event())
+                .await
+                .unwrap()
+                .unwrap();
+            app.handle_app_event(event);
+        }
+        assert_eq!(
+            app.tab_manager()
+                .current_tab()
+                .buffer()
+                .get(0)
+                .unwrap()
+                .plain(),
+            "got test"
+        );
 
-    /// Get mutable reference to tab manager
-    pub fn tab_manager_mut(&mut self) -> &mut TabManager {
-        &mut self.tab_manager
+        assert!(app.press_tab_key('x'));
+        assert_eq!(app.message(), Some("Sent SIGTERM to repl"));
+        while !app.tab_manager().all_done() {
+            let event = tokio::time::timeout(Duration::from_secs(5), app.recv_event())
+                .await
+                .unwrap()
+                .unwrap();
+            app.handle_app_event(event);
+        }
+        assert_eq!(
+            app.tab_manager().current_tab().status(),
+            &CommandStatus::Signaled {
+                signal: Signal::SIGTERM as i32
+            }
+        );
     }
 
-    /// Get reference to search state
-    pub fn search_state(&self) -> &SearchState {
-        &self.search_state
-    }
+    #[tokio::test]
+    async fn app_runs_commands_with_spawner() {
+        let mut app = App::new(vec!["build".into()], 100);
+        app.set_spawner(
+            ScriptedSpawner::new().script("build", Script::new().stdout("built").exit(2)),
+        );
 
-    /// Get mutable reference to search state
-    pub fn search_state_mut(&mut self) -> &mut SearchState {
-        &mut self.search_state
-    }
+        app.spawn_commands().await;
+        while app.tasks.contains_key(&0) {
+            let event = app.recv_event().await.unwrap();
+            app.handle_app_event(event);
+        }
 
-    /// Search in current tab's buffer
-    ///
-    /// This method is needed to avoid borrow conflicts when accessing
-    /// both tab_manager and search_state mutably.
-    pub fn search_in_current_tab(&mut self, query: &str) {
-        let buffer = self.tab_manager.current_tab().buffer();
-        self.search_state.search(query, buffer);
+        let tab = app.tab_manager().current_tab();
+        assert_eq!(tab.buffer().get(0).unwrap().plain(), "built");
+        assert_eq!(tab.status(), &CommandStatus::Finished { exit_code: 2 });
     }
 
-    /// Request restart for a specific tab
-    pub fn request_restart(&mut self, tab_index: usize) {
-        self.pending_restart = Some(tab_index);
+    #[tokio::test]
+    async fn app_restart_ignores_exit_of_finished_scripted_run() {
+        let mut app = App::new(vec!["serve".into()], 100);
+        app.set_spawner(ScriptedSpawner::new().script("serve", Script::new().exit(1)));
+        app.spawn_commands().await;
+        // Let the first run exit without handling its event
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        app.set_spawner(
+            ScriptedSpawner::new().script("serve", Script::new().sleep(Duration::from_secs(10))),
+        );
+
+        app.restart_process(0).await;
+        while let Ok(Some(event)) =
+            tokio::time::timeout(Duration::from_millis(100), app.recv_event()).await
+        {
+            app.handle_app_event(event);
+        }
+
+        assert_eq!(
+            app.tab_manager().current_tab().status(),
+            &CommandStatus::Running
+        );
+        app.kill_all().await;
     }
 
-    /// Take pending restart request
-    ///
-    /// Returns the tab index if a restart was requested, None otherwise.
-    /// Clears the pending restart after taking.
-    pub fn take_pending_restart(&mut self) -> Option<usize> {
-        self.pending_restart.take()
+    #[rstest::rstest]
+    #[case("true", CommandStatus::Running)]
+    #[case("exit 1", CommandStatus::Failed { reason: "setup failed".into() })]
+    #[tokio::test]
+    async fn app_starts_commands_after_setup_succeeds(
+        #[case] setup: &str,
+        #[case] expected: CommandStatus,
+    ) {
+        let mut app = App::new(vec![setup.into(), "sleep 10".into()], 100);
+        app.set_setup_tab(0);
+        app.spawn_commands().await;
+        assert_eq!(
+            app.tab_manager().get_tab(1).unwrap().status(),
+            &CommandStatus::Waiting
+        );
+
+        while !app.tab_manager().current_tab().status().is_done() {
+            let event = tokio::time::timeout(Duration::from_secs(2), app.recv_event())
+                .await
+                .unwrap()
+                .unwrap();
+            app.handle_app_event(event);
+        }
+        while let Some(tab_index) = app.take_pending_run() {
+            app.start_run(tab_index).await;
+        }
+
+        assert_eq!(app.tab_manager().get_tab(1).unwrap().status(), &expected);
+        app.kill_all().await;
     }
 
-    /// Restart a specific tab's command
-    ///
-    /// Kills the existing process, resets the tab state, and spawns a new process.
-    pub async fn restart_process(&mut self, tab_index: usize) {
-        // Kill existing process if any
-        if let Some(mut child) = self.children.remove(&tab_index) {
-            if let Some(pid) = child.id() {
-                let _ = killpg(Pid::from_raw(pid as i32), Signal::SIGKILL);
+    #[tokio::test]
+    async fn app_runs_hooks_around_the_command() {
+        let spec = CommandSpec {
+            before_start: Some("echo prepare".into()),
+            after_exit: Some("echo \"exit code $PARALLELS_EXIT_CODE\"".into()),
+            ..CommandSpec::new("echo main; exit 4")
+        };
+        let mut app = App::new(vec![spec], 100);
+        app.spawn_commands().await;
+        assert_eq!(
+            app.tab_manager().current_tab().status(),
+            &CommandStatus::Waiting
+        );
+
+        let finished = |app: &App| {
+            let buffer = app.tab_manager().current_tab().buffer();
+            buffer.iter().any(|line| line.plain() == "exit code 4")
+        };
+        while !finished(&app) {
+            while let Some(tab_index) = app.take_pending_run() {
+                app.start_run(tab_index).await;
             }
-            let _ = child.wait().await;
+            let event = tokio::time::timeout(Duration::from_secs(2), app.recv_event())
+                .await
+                .unwrap()
+                .unwrap();
+            app.handle_app_event(event);
         }
 
-        // Reset tab state
-        if let Some(tab) = self.tab_manager.get_tab_mut(tab_index) {
-            tab.reset();
+        // Hook output and exits are read by separate tasks
+        while let Ok(Some(event)) =
+            tokio::time::timeout(Duration::from_millis(100), app.recv_event()).await
+        {
+            app.handle_app_event(event);
         }
 
-        // Get command for this tab
-        let command = self
-            .tab_manager
-            .get_tab(tab_index)
-            .map(|tab| tab.command().to_string());
-
-        // Spawn new process
-        if let Some(command) = command {
-            let tx = self.event_tx.clone();
-            match CommandRunner::spawn(tx.clone(), &command, tab_index).await {
-                Ok(child) => {
-                    self.children.insert(tab_index, child);
-                }
-                Err(e) => {
-                    let _ = tx
-                        .send(AppEvent::Failed {
-                            tab_index,
-                            reason: e.to_string(),
-                        })
-                        .await;
-                }
-            }
+        let lines: Vec<(OutputKind, String)> = app
+            .tab_manager()
+            .current_tab()
+            .buffer()
+            .iter()
+            .map(|line| (line.kind, line.plain()))
+            .collect();
+        for expected in [
+            (OutputKind::Hook, "prepare"),
+            (OutputKind::Stdout, "main"),
+            (OutputKind::Hook, "exit code 4"),
+        ] {
+            let expected = (expected.0, expected.1.to_string());
+            assert!(lines.contains(&expected), "{:?}", lines);
         }
+        assert_eq!(app.tab_manager().current_tab().run_count(), 1);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use nix::sys::signal::kill;
+    #[tokio::test]
+    async fn app_missing_program_shows_failure_banner() {
+        let mut app = App::new(vec![CommandSpec::new("definitely-not-a-binary")], 100);
+        app.spawn_commands().await;
+        while !app.tab_manager().current_tab().status().is_done() {
+            let event = tokio::time::timeout(Duration::from_secs(2), app.recv_event())
+                .await
+                .unwrap()
+                .unwrap();
+            app.handle_app_event(event);
+        }
 
-    /// Check if a process exists by sending signal 0
-    fn process_exists(pid: i32) -> bool {
-        kill(Pid::from_raw(pid), None).is_ok()
+        let mut terminal = Terminal::new(TestBackend::new(100, 10)).unwrap();
+        terminal
+            .draw(|frame| Renderer::render(frame, &app))
+            .unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        for expected in [
+            "Failed to start - Enter to retry",
+            "Command not found (exit code 127)",
+            "`definitely-not-a-binary` wasn't found: install it or add its directory to PATH",
+        ] {
+            assert!(screen.contains(expected), "{}", screen);
+        }
     }
 
-    #[test]
-    fn app_new_initializes_correctly() {
-        let app = App::new(vec!["cmd1".into(), "cmd2".into()], 100);
+    #[tokio::test]
+    async fn app_waits_for_before_start_and_kills_it_on_restart() {
+        let spec = CommandSpec {
+            before_start: Some("echo $$; sleep 10".into()),
+            ..CommandSpec::new("echo main")
+        };
+        let mut app = App::new(vec![spec], 100);
+        app.spawn_commands().await;
+        let event = tokio::time::timeout(Duration::from_secs(2), app.recv_event())
+            .await
+            .unwrap()
+            .unwrap();
+        app.handle_app_event(event);
+        let pid: i32 = app
+            .tab_manager()
+            .current_tab()
+            .buffer()
+            .get(0)
+            .unwrap()
+            .plain()
+            .parse()
+            .unwrap();
 
-        assert_eq!(app.tab_manager().len(), 2);
-        assert_eq!(app.mode(), Mode::Normal);
-        assert!(!app.should_quit());
-    }
+        // Starting again while the hook runs doesn't skip it
+        app.start_run(0).await;
+        assert!(app.children.is_empty());
+        assert_eq!(
+            app.tab_manager().current_tab().status(),
+            &CommandStatus::Waiting
+        );
 
-    #[test]
-    fn app_mode_transition_works() {
-        let mut app = App::new(vec!["cmd".into()], 100);
+        // A restart stops the hook and runs it again
+        app.restart_process(0).await;
+        let running = |pid: i32| {
+            std::fs::read_to_string(format!("/proc/{}/stat", pid))
+                .is_ok_and(|stat| !stat.contains(") Z "))
+        };
+        for _ in 0..20 {
+            if !running(pid) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(!running(pid));
+        assert!(app.children.is_empty());
+        assert!(app.hooks.contains_key(&0));
 
-        assert_eq!(app.mode(), Mode::Normal);
+        app.kill_all().await;
+        assert!(app.hooks.is_empty());
+    }
 
-        app.set_mode(Mode::Search);
-        assert_eq!(app.mode(), Mode::Search);
+    #[tokio::test]
+    async fn app_hook_reading_stdin_sees_eof() {
+        // `keys` pipe the command's stdin, not the hook's
+        let spec = CommandSpec {
+            before_start: Some("cat".into()),
+            keys: [('t', KeyAction::Send("test\n".into()))].into(),
+            ..CommandSpec::new("echo main")
+        };
+        let mut app = App::new(vec![spec], 100);
+        app.spawn_commands().await;
 
-        app.set_mode(Mode::Normal);
-        assert_eq!(app.mode(), Mode::Normal);
+        let started = |app: &App| {
+            let buffer = app.tab_manager().current_tab().buffer();
+            buffer.iter().any(|line| line.plain() == "main")
+        };
+        while !started(&app) {
+            while let Some(tab_index) = app.take_pending_run() {
+                app.start_run(tab_index).await;
+            }
+            let event = tokio::time::timeout(Duration::from_secs(2), app.recv_event())
+                .await
+                .unwrap()
+                .unwrap();
+            app.handle_app_event(event);
+        }
+        app.kill_all().await;
     }
 
-    #[test]
-    fn app_quit_sets_flag() {
-        let mut app = App::new(vec!["cmd".into()], 100);
+    #[tokio::test]
+    async fn app_restart_runs_restart_command() {
+        let spec = CommandSpec {
+            restart_command: Some("echo restarted".into()),
+            ..CommandSpec::new("echo started")
+        };
+        let mut app = App::new(vec![spec], 100);
+        app.spawn_commands().await;
+        app.restart_process(0).await;
 
-        assert!(!app.should_quit());
+        // Output of the first run may still arrive after the restart
+        let restarted = |app: &App| {
+            let buffer = app.tab_manager().current_tab().buffer();
+            buffer.iter().any(|line| line.plain() == "restarted")
+        };
+        while !restarted(&app) {
+            let event = tokio::time::timeout(Duration::from_secs(2), app.recv_event())
+                .await
+                .unwrap()
+                .unwrap();
+            app.handle_app_event(event);
+        }
 
-        app.quit();
-        assert!(app.should_quit());
+        let tab = app.tab_manager().current_tab();
+        assert_eq!(tab.spec().command, "echo started");
+        assert!(app.restarting.is_empty());
+        app.kill_all().await;
     }
 
     #[tokio::test]
-    async fn app_spawn_commands_starts_background_tasks() {
-        let mut app = App::new(vec!["echo hello".into()], 100);
-
+    async fn app_restart_runs_before_restart_first() {
+        let spec = CommandSpec {
+            before_restart: Some("echo hook; exit 3".into()),
+            ..CommandSpec::new("sleep 10")
+        };
+        let mut app = App::new(vec![spec], 100);
         app.spawn_commands().await;
 
-        // Receive and handle events
-        let timeout = std::time::Duration::from_millis(500);
-        let start = std::time::Instant::now();
-        while start.elapsed() < timeout {
-            tokio::select! {
-                Some(event) = app.recv_event() => {
-                    app.handle_app_event(event);
-                    if !app.tab_manager().current_tab().buffer().is_empty() {
-                        break;
-                    }
-                }
-                _ = tokio::time::sleep(std::time::Duration::from_millis(10)) => {}
-            }
+        app.restart_process(0).await;
+        assert_eq!(
+            app.tab_manager().current_tab().status(),
+            &CommandStatus::Waiting
+        );
+        while app.take_pending_run().is_none() {
+            let event = tokio::time::timeout(Duration::from_secs(2), app.recv_event())
+                .await
+                .unwrap()
+                .unwrap();
+            app.handle_app_event(event);
+        }
+        // The hook's output and its exit are read by separate tasks
+        while let Ok(Some(event)) =
+            tokio::time::timeout(Duration::from_millis(100), app.recv_event()).await
+        {
+            app.handle_app_event(event);
         }
 
-        // Check if output was received
-        let buffer = app.tab_manager().current_tab().buffer();
-        assert!(!buffer.is_empty(), "Should have received output");
+        let lines: Vec<String> = app
+            .tab_manager()
+            .current_tab()
+            .buffer()
+            .iter()
+            .map(|line| line.plain())
+            .collect();
+        assert!(lines.contains(&"hook".to_string()), "{:?}", lines);
+        assert!(lines.contains(&"── before_restart exited (3) ──".to_string()));
+        app.kill_all().await;
     }
 
     #[tokio::test]
-    async fn app_recv_event_handles_output() {
-        let mut app = App::new(vec!["echo test_line".into()], 100);
+    async fn app_pid_is_known_while_running() {
+        let mut app = App::new(vec!["sleep 10".into()], 100);
+        assert_eq!(app.pid(0), None);
 
         app.spawn_commands().await;
+        assert!(app.pid(0).is_some());
 
-        // Receive and handle events
-        let timeout = std::time::Duration::from_millis(500);
-        let start = std::time::Instant::now();
-        while start.elapsed() < timeout {
-            tokio::select! {
-                Some(event) = app.recv_event() => {
-                    app.handle_app_event(event);
-                    if !app.tab_manager().current_tab().buffer().is_empty() {
-                        break;
-                    }
-                }
-                _ = tokio::time::sleep(std::time::Duration::from_millis(10)) => {}
-            }
-        }
-
-        let buffer = app.tab_manager().current_tab().buffer();
-        assert!(!buffer.is_empty(), "Should have received output");
+        app.kill_all().await;
+        assert_eq!(app.pid(0), None);
     }
 
     #[tokio::test]
@@ -336,7 +4199,7 @@ mod tests {
             .children
             .get(&0)
             .expect("Should have child at index 0")
-            .id()
+            .pid()
             .expect("Should have PID");
 
         // Verify the process is running
@@ -355,6 +4218,45 @@ mod tests {
         );
     }
 
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn app_kill_all_kills_detached_processes_when_configured() {
+        let spec = CommandSpec {
+            kill_detached: true,
+            ..CommandSpec::new("setsid sh -c 'echo $$; exec sleep 30' & wait")
+        };
+        // Use a tab index other tests don't run processes on, since the tag
+        // is shared by every App in this test process
+        let mut app = App::new(vec!["true".into(), "true".into(), "true".into(), spec], 100);
+        app.spawn_commands().await;
+
+        // The detached shell prints its PID before exec'ing sleep
+        let pid = loop {
+            if let Some(AppEvent::Output { line, .. }) = app.recv_event().await
+                && let Ok(pid) = line.plain().parse::<i32>()
+            {
+                break pid;
+            }
+        };
+        // Wait for the exec so its environment is readable
+        let comm = format!("/proc/{}/comm", pid);
+        while std::fs::read_to_string(&comm).unwrap_or_default().trim() != "sleep" {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        app.kill_all().await;
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        // The detached process was reparented away, so it may linger as a
+        // zombie briefly; a killed process no longer shows up as sleeping
+        let state = std::fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap_or_default();
+        assert!(
+            !state.contains(") S "),
+            "detached process still running: {}",
+            state
+        );
+    }
+
     #[test]
     fn app_request_restart_sets_pending() {
         let mut app = App::new(vec!["cmd".into()], 100);
@@ -387,7 +4289,7 @@ mod tests {
             .children
             .get(&0)
             .expect("Should have child")
-            .id()
+            .pid()
             .expect("Should have PID");
 
         // Verify process is running
@@ -420,7 +4322,7 @@ mod tests {
             .children
             .get(&0)
             .expect("Should have new child")
-            .id()
+            .pid()
             .expect("Should have PID");
         assert_ne!(
             original_pid, new_pid,
@@ -431,8 +4333,13 @@ mod tests {
             "New process should be running"
         );
 
-        // Tab should be reset
-        assert!(app.tab_manager().get_tab(0).unwrap().buffer().is_empty());
+        // Tab should be reset, starting with a divider for the new run
+        let buffer = app.tab_manager().get_tab(0).unwrap().buffer();
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(
+            buffer.get(0).unwrap().kind,
+            crate::buffer::OutputKind::Divider
+        );
         assert_eq!(
             app.tab_manager().get_tab(0).unwrap().status(),
             &CommandStatus::Running
@@ -441,4 +4348,65 @@ mod tests {
         // Cleanup
         app.kill_all().await;
     }
+
+    #[tokio::test]
+    async fn app_restart_process_keeps_output_when_configured() {
+        let spec = CommandSpec {
+            keep_output: true,
+            ..CommandSpec::new("sleep 10")
+        };
+        let mut app = App::new(vec![spec], 100);
+        app.spawn_commands().await;
+        app.tab_manager_mut()
+            .current_tab_mut()
+            .push_output(crate::buffer::OutputLine::new(
+                crate::buffer::OutputKind::Stdout,
+                "previous".into(),
+            ));
+
+        app.restart_process(0).await;
+
+        let buffer = app.tab_manager().current_tab().buffer();
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.get(0).unwrap().plain(), "previous");
+        assert!(buffer.get(1).unwrap().plain().contains("Run #2"));
+
+        app.kill_all().await;
+    }
+
+    #[rstest::rstest]
+    #[case(false, 0, 0)]
+    #[case(true, 7, 2)]
+    #[tokio::test]
+    async fn app_restart_process_keeps_scroll_when_configured(
+        #[case] keep_scroll: bool,
+        #[case] offset: usize,
+        #[case] column: usize,
+    ) {
+        let spec = CommandSpec {
+            keep_scroll,
+            ..CommandSpec::new("sleep 10")
+        };
+        let mut app = App::new(vec![spec], 100);
+        app.spawn_commands().await;
+        let tab = app.tab_manager_mut().current_tab_mut();
+        tab.set_visible_lines(5);
+        for i in 0..20 {
+            tab.push_output(OutputLine::new(OutputKind::Stdout, format!("line{}", i)));
+        }
+        tab.set_auto_scroll(false);
+        tab.scroll_to_line(7);
+        tab.scroll_right();
+        tab.scroll_right();
+
+        app.restart_process(0).await;
+
+        let tab = app.tab_manager().current_tab();
+        assert_eq!(tab.buffer().len(), 1);
+        assert_eq!(tab.scroll_offset(), offset);
+        assert_eq!(tab.horizontal_scroll(), column);
+        assert_eq!(tab.auto_scroll(), !keep_scroll);
+
+        app.kill_all().await;
+    }
 }
@@ -0,0 +1,76 @@
+//! zstd compression of the text of old output chunks (`compress` feature)
+
+use std::io;
+use std::sync::OnceLock;
+
+/// Compression level, favoring speed since chunks are compressed while
+/// output arrives
+const LEVEL: i32 = 1;
+
+/// Text kept zstd-compressed, and decompressed on first access
+#[derive(Debug, Clone)]
+pub(super) struct CompressedText {
+    bytes: Box<[u8]>,
+    /// Length of the text in bytes
+    len: usize,
+    thawed: OnceLock<String>,
+}
+
+impl CompressedText {
+    /// Compress some text
+    pub(super) fn new(text: &str) -> io::Result<Self> {
+        let bytes = zstd::bulk::compress(text.as_bytes(), LEVEL)?;
+        Ok(Self {
+            bytes: bytes.into_boxed_slice(),
+            len: text.len(),
+            thawed: OnceLock::new(),
+        })
+    }
+
+    /// Get the text, decompressing it on first access
+    ///
+    /// Text that fails to decompress reads as spaces, keeping the offsets of
+    /// the lines in it valid.
+    pub(super) fn get(&self) -> &str {
+        self.thawed.get_or_init(|| {
+            zstd::bulk::decompress(&self.bytes, self.len)
+                .ok()
+                .and_then(|text| String::from_utf8(text).ok())
+                .unwrap_or_else(|| " ".repeat(self.len))
+        })
+    }
+
+    /// Check if the text is currently decompressed
+    pub(super) fn is_thawed(&self) -> bool {
+        self.thawed.get().is_some()
+    }
+
+    /// Drop the decompressed text, keeping only the compressed bytes
+    pub(super) fn refreeze(&mut self) {
+        self.thawed.take();
+    }
+
+    /// Get the size of the compressed text in bytes
+    pub(super) fn compressed_len(&self) -> usize {
+        self.bytes.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compressed_text_decompresses_on_demand() {
+        let text = "GET /health 200 1ms\n".repeat(1_000);
+
+        let mut compressed = CompressedText::new(&text).unwrap();
+
+        assert!(compressed.compressed_len() < text.len() / 10);
+        assert!(!compressed.is_thawed());
+        assert_eq!(compressed.get(), text);
+        assert!(compressed.is_thawed());
+        compressed.refreeze();
+        assert!(!compressed.is_thawed());
+    }
+}
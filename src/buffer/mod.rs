@@ -1,3 +1,7 @@
+#[cfg(feature = "compress")]
+mod compress;
 mod output;
 
-pub use output::{OutputBuffer, OutputKind, OutputLine};
+pub use output::{
+    DEFAULT_MAX_LINE_LENGTH, LineRef, OutputBuffer, OutputKind, OutputLine, SharedLines, strip_ansi,
+};
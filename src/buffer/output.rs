@@ -1,61 +1,414 @@
+use std::borrow::Cow;
 use std::collections::VecDeque;
+#[cfg(feature = "compress")]
+use std::ops::Range;
+use std::ops::{Bound, RangeBounds};
+use std::sync::{Arc, LazyLock};
 
 use ansi_to_tui::IntoText;
+use chrono::{DateTime, Local};
+use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::Span;
+use regex::Regex;
+
+#[cfg(feature = "compress")]
+use super::compress::CompressedText;
+use crate::command::classify::severity;
+use crate::command::{Severity, format_count};
+
+/// Characters of a line shown before it is cut, unless configured otherwise
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 10_000;
+
+/// ANSI escape sequences: CSI (colors, cursor movement), OSC (titles,
+/// hyperlinks) and two-character escapes
+static ANSI_ESCAPE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-Z\\-_]")
+        .expect("valid regex")
+});
+
+/// Remove ANSI escape sequences from text
+pub fn strip_ansi(text: &str) -> Cow<'_, str> {
+    ANSI_ESCAPE.replace_all(text, "")
+}
+
+/// Byte index in `text` after `max_chars` visible characters
+///
+/// Escape sequences don't count towards the limit. Returns `None` if the
+/// text is no longer than that, or `max_chars` is zero.
+fn visible_cut(text: &str, max_chars: usize) -> Option<usize> {
+    if max_chars == 0 {
+        return None;
+    }
+    let mut remaining = max_chars;
+    let mut start = 0;
+    let escapes = ANSI_ESCAPE.find_iter(text).map(|m| (m.start(), m.end()));
+    for (end, next) in escapes.chain(std::iter::once((text.len(), text.len()))) {
+        let segment = &text[start..end];
+        match segment.char_indices().nth(remaining) {
+            Some((index, _)) => return Some(start + index),
+            None => remaining -= segment.chars().count(),
+        }
+        start = next;
+    }
+    None
+}
 
 /// Output type enumeration
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OutputKind {
     Stdout,
     Stderr,
+    /// Separator inserted between runs of a command
+    Divider,
+    /// Marker inserted manually by the user
+    Marker,
+    /// Output of a `before_start`, `after_exit` or `before_restart` hook
+    Hook,
+}
+
+impl OutputKind {
+    /// Check if the line was produced by the command (not inserted by parallels)
+    pub fn is_output(self) -> bool {
+        matches!(self, OutputKind::Stdout | OutputKind::Stderr)
+    }
 }
 
+/// Lines per chunk of an [`OutputBuffer`]
+const CHUNK_LINES: usize = 1024;
+
 /// Output line structure
+///
+/// Keeps the text as read, with its ANSI escapes; the styled spans are only
+/// parsed when the line is drawn.
 #[derive(Debug, Clone)]
 pub struct OutputLine {
     /// Output type
     pub kind: OutputKind,
-    /// Pre-parsed spans with styles (for rendering)
-    spans: Vec<Span<'static>>,
+    /// Text as read, or the label of a divider or marker
+    text: String,
+    meta: LineMeta,
+}
+
+/// What is known about a line besides its kind and text
+#[derive(Debug, Clone, Copy)]
+struct LineMeta {
+    /// When the line was read
+    time: DateTime<Local>,
+    /// Whether the line looks like an error or a warning
+    severity: Option<Severity>,
+    /// Byte index in the text after which a line too long to render in
+    /// full is cut
+    cut_at: Option<usize>,
 }
 
 impl OutputLine {
     /// Create a new OutputLine
     ///
-    /// Parses ANSI escape sequences into styled spans.
+    /// Guesses whether the line is an error or a warning; ANSI escape
+    /// sequences are parsed into styled spans once it is drawn.
     pub fn new(kind: OutputKind, content: String) -> Self {
-        // Parse ANSI codes into styled spans
-        let spans = match content.as_str().into_text() {
+        let severity = severity(&strip_ansi(&content));
+        Self::with_meta(kind, content, severity, None)
+    }
+
+    fn with_meta(
+        kind: OutputKind,
+        text: String,
+        severity: Option<Severity>,
+        cut_at: Option<usize>,
+    ) -> Self {
+        Self {
+            kind,
+            text,
+            meta: LineMeta {
+                time: Local::now(),
+                severity,
+                cut_at,
+            },
+        }
+    }
+
+    /// Set when the line was read
+    pub fn at(mut self, time: DateTime<Local>) -> Self {
+        self.meta.time = time;
+        self
+    }
+
+    /// Get when the line was read
+    pub fn time(&self) -> DateTime<Local> {
+        self.meta.time
+    }
+
+    /// Get whether the line looks like an error or a warning
+    pub fn severity(&self) -> Option<Severity> {
+        self.meta.severity
+    }
+
+    /// Create an OutputLine, cutting lines over `max_chars` characters
+    ///
+    /// Only the first `max_chars` characters are parsed into spans, followed
+    /// by a `… (+N chars)` marker; the full text stays available through
+    /// [`OutputLine::plain`] and [`OutputLine::expand`]. Zero means no limit.
+    pub fn with_limit(kind: OutputKind, content: String, max_chars: usize) -> Self {
+        let Some(cut_at) = visible_cut(&content, max_chars) else {
+            return Self::new(kind, content);
+        };
+        let severity = severity(&strip_ansi(&content[..cut_at]));
+        Self::with_meta(kind, content, severity, Some(cut_at))
+    }
+
+    /// Get the length in bytes of the plain text shown for a cut line
+    ///
+    /// Returns `None` if the line is shown in full.
+    pub fn shown_len(&self) -> Option<usize> {
+        self.as_line_ref().shown_len()
+    }
+
+    /// Show the full text of a cut line
+    ///
+    /// Returns `false` if the line was not cut.
+    pub fn expand(&mut self) -> bool {
+        self.meta.cut_at.take().is_some()
+    }
+
+    /// Create a divider line with the given label
+    pub fn divider(label: &str) -> Self {
+        Self::with_meta(OutputKind::Divider, format!("── {} ──", label), None, None)
+    }
+
+    /// Create a marker line with the given label
+    pub fn marker(label: &str) -> Self {
+        Self::with_meta(OutputKind::Marker, format!("── {} ──", label), None, None)
+    }
+
+    /// Parse the line into spans with styles (for rendering)
+    pub fn spans(&self) -> Vec<Span<'static>> {
+        self.as_line_ref().spans()
+    }
+
+    /// Return plain text without ANSI escape sequences
+    ///
+    /// For a cut line, this is the full text rather than the shown part.
+    pub fn plain(&self) -> String {
+        self.as_line_ref().plain()
+    }
+
+    /// Borrow the line, like the lines of an [`OutputBuffer`]
+    pub fn as_line_ref(&self) -> LineRef<'_> {
+        LineRef {
+            kind: self.kind,
+            text: &self.text,
+            meta: self.meta,
+        }
+    }
+}
+
+/// A line of an [`OutputBuffer`], borrowing its text from the buffer
+#[derive(Debug, Clone, Copy)]
+pub struct LineRef<'a> {
+    /// Output type
+    pub kind: OutputKind,
+    text: &'a str,
+    meta: LineMeta,
+}
+
+impl LineRef<'_> {
+    /// Get when the line was read
+    pub fn time(&self) -> DateTime<Local> {
+        self.meta.time
+    }
+
+    /// Get whether the line looks like an error or a warning
+    pub fn severity(&self) -> Option<Severity> {
+        self.meta.severity
+    }
+
+    /// Get the length in bytes of the plain text shown for a cut line
+    ///
+    /// Returns `None` if the line is shown in full.
+    pub fn shown_len(&self) -> Option<usize> {
+        self.meta
+            .cut_at
+            .map(|cut_at| strip_ansi(&self.text[..cut_at]).len())
+    }
+
+    /// Parse the line into spans with styles (for rendering)
+    ///
+    /// A cut line ends with a `… (+N chars)` marker.
+    pub fn spans(&self) -> Vec<Span<'static>> {
+        let style = match self.kind {
+            OutputKind::Divider => Style::default().fg(Color::DarkGray),
+            OutputKind::Marker => Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+            _ => return self.parse_spans(),
+        };
+        vec![Span::styled(self.text.to_string(), style)]
+    }
+
+    /// Parse ANSI escape sequences of the shown text into styled spans
+    fn parse_spans(&self) -> Vec<Span<'static>> {
+        let shown = self
+            .meta
+            .cut_at
+            .map_or(self.text, |cut_at| &self.text[..cut_at]);
+        let mut spans = match shown.into_text() {
             Ok(text) => text
                 .lines
                 .into_iter()
                 .next()
                 .map(|line| line.spans)
                 .unwrap_or_else(Vec::new),
-            Err(_) => vec![Span::raw(content)],
+            Err(_) => vec![Span::raw(shown.to_string())],
         };
+        if let Some(cut_at) = self.meta.cut_at {
+            let hidden = strip_ansi(&self.text[cut_at..]).chars().count() as u64;
+            spans.push(Span::styled(
+                format!("… (+{} chars)", format_count(hidden)),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        spans
+    }
 
-        Self { kind, spans }
+    /// Return plain text without ANSI escape sequences
+    ///
+    /// For a cut line, this is the full text rather than the shown part.
+    pub fn plain(&self) -> String {
+        strip_ansi(self.text).into_owned()
     }
 
-    /// Return pre-parsed spans for rendering
-    pub fn spans(&self) -> &[Span<'static>] {
-        &self.spans
+    /// Copy the line out of its buffer
+    pub fn to_line(&self) -> OutputLine {
+        OutputLine {
+            kind: self.kind,
+            text: self.text.to_string(),
+            meta: self.meta,
+        }
     }
+}
 
-    /// Return plain text without ANSI escape sequences (derived from spans)
-    pub fn plain(&self) -> String {
-        self.spans.iter().map(|s| s.content.as_ref()).collect()
+/// Kind and metadata of a line stored in a [`Chunk`]
+#[derive(Debug, Clone, Copy)]
+struct StoredLine {
+    kind: OutputKind,
+    meta: LineMeta,
+    /// Where the line's text ends in the chunk's text
+    end: usize,
+}
+
+/// Up to [`CHUNK_LINES`] lines, their texts stored one after another
+#[derive(Debug, Clone, Default)]
+struct Chunk {
+    /// Empty while the text is compressed
+    text: String,
+    #[cfg(feature = "compress")]
+    compressed: Option<CompressedText>,
+    lines: Vec<StoredLine>,
+}
+
+impl Chunk {
+    /// Get the text of the chunk's lines
+    fn text(&self) -> &str {
+        #[cfg(feature = "compress")]
+        if let Some(compressed) = &self.compressed {
+            return compressed.get();
+        }
+        &self.text
+    }
+
+    fn push(&mut self, line: OutputLine) {
+        // Only full chunks are compressed, but don't rely on it
+        #[cfg(feature = "compress")]
+        if let Some(compressed) = self.compressed.take() {
+            self.text = compressed.get().to_string();
+        }
+        self.text.push_str(&line.text);
+        self.lines.push(StoredLine {
+            kind: line.kind,
+            meta: line.meta,
+            end: self.text.len(),
+        });
+        // A full chunk never grows again
+        if self.lines.len() == CHUNK_LINES {
+            self.text.shrink_to_fit();
+        }
+    }
+
+    fn get(&self, index: usize) -> Option<LineRef<'_>> {
+        let line = self.lines.get(index)?;
+        let start = index
+            .checked_sub(1)
+            .map_or(0, |previous| self.lines[previous].end);
+        Some(LineRef {
+            kind: line.kind,
+            text: &self.text()[start..line.end],
+            meta: line.meta,
+        })
+    }
+
+    fn iter(&self) -> impl Iterator<Item = LineRef<'_>> {
+        let chunk_text = self.text();
+        let mut start = 0;
+        self.lines.iter().map(move |line| {
+            let text = &chunk_text[start..line.end];
+            start = line.end;
+            LineRef {
+                kind: line.kind,
+                text,
+                meta: line.meta,
+            }
+        })
+    }
+
+    /// Compress the text, or drop it again if it was decompressed
+    ///
+    /// Text that fails to compress is kept as it is.
+    #[cfg(feature = "compress")]
+    fn freeze(&mut self) {
+        match &mut self.compressed {
+            Some(compressed) => compressed.refreeze(),
+            None => {
+                if let Ok(compressed) = CompressedText::new(&self.text) {
+                    self.text = String::new();
+                    self.compressed = Some(compressed);
+                }
+            }
+        }
+    }
+
+    /// Check if the chunk takes no more memory than needed
+    #[cfg(feature = "compress")]
+    fn is_frozen(&self) -> bool {
+        self.compressed
+            .as_ref()
+            .is_some_and(|compressed| !compressed.is_thawed())
     }
 }
 
 /// Ring buffer for output lines
 ///
 /// When max lines is exceeded, old lines are automatically discarded.
-/// Uses VecDeque internally for O(1) removal from the front.
+/// Lines are stored in chunks of [`CHUNK_LINES`], each holding their texts
+/// in one string with the lines' end offsets, instead of allocating for
+/// every line; a chunk is dropped once all of its lines are evicted.
+/// Chunks are reference counted, so a background search can share them.
+///
+/// Every pushed line is assigned a generation number that increases
+/// monotonically, even across evictions and `clear`, so consumers can
+/// track new content with [`OutputBuffer::lines_since`].
 pub struct OutputBuffer {
-    lines: VecDeque<OutputLine>,
+    /// All chunks but the last one are full
+    chunks: VecDeque<Arc<Chunk>>,
+    /// Lines evicted from the first chunk
+    evicted: usize,
+    len: usize,
     max_lines: usize,
+    /// Total number of lines ever pushed (generation of the next line)
+    generation: u64,
+    /// Number of newest lines kept uncompressed, if older ones are compressed
+    #[cfg(feature = "compress")]
+    compress_after: Option<usize>,
 }
 
 impl OutputBuffer {
@@ -65,19 +418,206 @@ impl OutputBuffer {
     /// * `max_lines` - Maximum number of lines to keep (0 for unlimited)
     pub fn new(max_lines: usize) -> Self {
         Self {
-            lines: VecDeque::new(),
+            chunks: VecDeque::new(),
+            evicted: 0,
+            len: 0,
             max_lines,
+            generation: 0,
+            #[cfg(feature = "compress")]
+            compress_after: None,
+        }
+    }
+
+    /// Keep all but the newest `lines` lines zstd-compressed, see `compact`
+    #[cfg(feature = "compress")]
+    pub fn set_compress_after(&mut self, lines: usize) {
+        self.compress_after = Some(lines);
+    }
+
+    /// Compress the chunks older than the newest `compress_after` lines,
+    /// leaving the ones overlapping `keep` (e.g. the visible lines) alone
+    ///
+    /// Compressed chunks are decompressed again when their lines are read,
+    /// for drawing or searching, and compressed (their decompressed text
+    /// dropped) by the next call once out of `keep`. Chunks shared with a
+    /// background search are left until it is done.
+    #[cfg(feature = "compress")]
+    pub fn compact(&mut self, keep: Range<usize>) {
+        let Some(compress_after) = self.compress_after else {
+            return;
+        };
+        let old_lines = self.len.saturating_sub(compress_after);
+        for (index, chunk) in self.chunks.iter_mut().enumerate() {
+            // Indices of the chunk's lines, the first chunk's evicted ones below 0
+            let start = (index * CHUNK_LINES).saturating_sub(self.evicted);
+            let end = ((index + 1) * CHUNK_LINES).saturating_sub(self.evicted);
+            if end > old_lines {
+                break;
+            }
+            if chunk.is_frozen() || (start < keep.end && keep.start < end) {
+                continue;
+            }
+            if let Some(chunk) = Arc::get_mut(chunk) {
+                chunk.freeze();
+            }
         }
     }
 
+    /// Get the number of lines kept compressed, and their compressed size
+    /// in bytes
+    #[cfg(feature = "compress")]
+    pub fn compressed(&self) -> (usize, usize) {
+        self.chunks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, chunk)| {
+                let compressed = chunk.compressed.as_ref()?;
+                let evicted = if index == 0 { self.evicted } else { 0 };
+                Some((chunk.lines.len() - evicted, compressed.compressed_len()))
+            })
+            .fold((0, 0), |(lines, bytes), (chunk_lines, chunk_bytes)| {
+                (lines + chunk_lines, bytes + chunk_bytes)
+            })
+    }
+
+    /// Check if reading all lines would decompress some (`compress` feature)
+    pub fn has_frozen_lines(&self) -> bool {
+        #[cfg(feature = "compress")]
+        return self.chunks.iter().any(|chunk| chunk.is_frozen());
+        #[cfg(not(feature = "compress"))]
+        false
+    }
+
     /// Add an output line
     ///
     /// When max_lines is exceeded, the oldest line is discarded.
     pub fn push(&mut self, line: OutputLine) {
-        if self.max_lines > 0 && self.lines.len() >= self.max_lines {
-            self.lines.pop_front();
+        if self.max_lines > 0 && self.len >= self.max_lines {
+            self.evict_oldest();
+        }
+        match self.chunks.back_mut() {
+            Some(chunk) if chunk.lines.len() < CHUNK_LINES => Arc::make_mut(chunk).push(line),
+            _ => {
+                let mut chunk = Chunk::default();
+                chunk.push(line);
+                self.chunks.push_back(Arc::new(chunk));
+            }
+        }
+        self.len += 1;
+        self.generation += 1;
+    }
+
+    /// Discard the oldest line, and its chunk once all of its lines are gone
+    fn evict_oldest(&mut self) {
+        let Some(first) = self.chunks.front() else {
+            return;
+        };
+        self.evicted += 1;
+        self.len -= 1;
+        if self.evicted == first.lines.len() {
+            self.chunks.pop_front();
+            self.evicted = 0;
+        }
+    }
+
+    /// Chunk and position in it of the line at an index
+    fn locate(&self, index: usize) -> (usize, usize) {
+        let position = self.evicted + index;
+        (position / CHUNK_LINES, position % CHUNK_LINES)
+    }
+
+    /// Get the line at the specified index (0-based, oldest retained line first)
+    pub fn get(&self, index: usize) -> Option<LineRef<'_>> {
+        if index >= self.len {
+            return None;
+        }
+        let (chunk, position) = self.locate(index);
+        self.chunks.get(chunk)?.get(position)
+    }
+
+    /// Show the full text of the cut line at the specified index
+    ///
+    /// Returns `false` if the line was not cut.
+    pub fn expand(&mut self, index: usize) -> bool {
+        if self
+            .get(index)
+            .is_none_or(|line| line.meta.cut_at.is_none())
+        {
+            return false;
+        }
+        let (chunk, position) = self.locate(index);
+        Arc::make_mut(&mut self.chunks[chunk]).lines[position]
+            .meta
+            .cut_at = None;
+        true
+    }
+
+    /// Get the index of the first line read at or after `time`
+    ///
+    /// Returns the number of lines if all of them are older.
+    pub fn first_since(&self, time: DateTime<Local>) -> usize {
+        let (mut low, mut high) = (0, self.len);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if self.get(middle).is_some_and(|line| line.time() < time) {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        low
+    }
+
+    /// Return an iterator over the last `n` lines
+    pub fn last_n(&self, n: usize) -> impl Iterator<Item = LineRef<'_>> {
+        self.range(self.len.saturating_sub(n)..)
+    }
+
+    /// Return the generation that will be assigned to the next pushed line
+    ///
+    /// Store this value as a cursor and pass it to `lines_since` later to
+    /// get only the lines added in between.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Return the generation of the oldest retained line
+    ///
+    /// If a stored cursor is smaller than this value, lines were evicted
+    /// (or cleared) before they could be read.
+    pub fn first_generation(&self) -> u64 {
+        self.generation - self.len as u64
+    }
+
+    /// Index of the line of a generation, clamped to the retained lines
+    fn index_of(&self, generation: u64) -> usize {
+        let skip = generation.saturating_sub(self.first_generation());
+        usize::try_from(skip).unwrap_or(usize::MAX)
+    }
+
+    /// Return an iterator over lines pushed at or after `generation`
+    ///
+    /// Lines that have already been evicted are skipped.
+    pub fn lines_since(&self, generation: u64) -> impl Iterator<Item = LineRef<'_>> {
+        self.range(self.index_of(generation)..)
+    }
+
+    /// Share the lines pushed at or after `generation`, like `lines_since`
+    ///
+    /// Only the chunks' reference counts change, so a background task can
+    /// read the lines while new ones are pushed.
+    pub fn shared_since(&self, generation: u64) -> SharedLines {
+        let start = self.index_of(generation).min(self.len);
+        let (chunk, skip) = self.locate(start);
+        SharedLines {
+            chunks: self
+                .chunks
+                .range(chunk.min(self.chunks.len())..)
+                .cloned()
+                .collect(),
+            skip,
+            len: self.len - start,
         }
-        self.lines.push_back(line);
     }
 
     /// Get lines in specified range
@@ -88,34 +628,93 @@ impl OutputBuffer {
     ///
     /// # Returns
     /// Lines in the specified range. Empty or partial result if out of bounds.
-    pub fn get_range(&self, start: usize, count: usize) -> Vec<&OutputLine> {
-        self.lines.iter().skip(start).take(count).collect()
+    pub fn get_range(&self, start: usize, count: usize) -> Vec<LineRef<'_>> {
+        self.range(start..start.saturating_add(count)).collect()
+    }
+
+    /// Return an iterator over lines in the specified index range
+    ///
+    /// The range is clamped to the buffer bounds, so out-of-range indices
+    /// yield a partial or empty result instead of panicking.
+    pub fn range(&self, range: impl RangeBounds<usize>) -> impl Iterator<Item = LineRef<'_>> {
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n.saturating_add(1),
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => self.len,
+        };
+        let end = end.min(self.len);
+        let start = start.min(end);
+        let (chunk, skip) = self.locate(start);
+        self.chunks
+            .range(chunk.min(self.chunks.len())..)
+            .flat_map(|chunk| chunk.iter())
+            .skip(skip)
+            .take(end - start)
     }
 
     /// Return the number of lines in the buffer
     pub fn len(&self) -> usize {
-        self.lines.len()
+        self.len
     }
 
     /// Check if the buffer is empty
     pub fn is_empty(&self) -> bool {
-        self.lines.is_empty()
+        self.len == 0
     }
 
     /// Return an iterator over all lines (for search)
-    pub fn iter(&self) -> impl Iterator<Item = &OutputLine> {
-        self.lines.iter()
+    pub fn iter(&self) -> impl Iterator<Item = LineRef<'_>> {
+        self.range(..)
     }
 
     /// Clear all lines from the buffer
     pub fn clear(&mut self) {
-        self.lines.clear();
+        self.chunks.clear();
+        self.evicted = 0;
+        self.len = 0;
+    }
+}
+
+/// Lines of an [`OutputBuffer`] shared with a background task
+#[derive(Debug, Clone)]
+pub struct SharedLines {
+    chunks: Vec<Arc<Chunk>>,
+    /// Lines of the first chunk before the shared ones
+    skip: usize,
+    len: usize,
+}
+
+impl SharedLines {
+    /// Return the number of shared lines
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if no lines are shared
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return an iterator over the shared lines
+    pub fn iter(&self) -> impl Iterator<Item = LineRef<'_>> {
+        self.chunks
+            .iter()
+            .flat_map(|chunk| chunk.iter())
+            .skip(self.skip)
+            .take(self.len)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
+    use rstest::rstest;
 
     #[test]
     fn output_buffer_push_adds_line_to_buffer() {
@@ -190,6 +789,110 @@ mod tests {
         assert!(lines.is_empty());
     }
 
+    #[test]
+    fn output_buffer_range_returns_inclusive_range() {
+        let mut buffer = OutputBuffer::new(100);
+        for i in 0..10 {
+            buffer.push(OutputLine::new(OutputKind::Stdout, format!("line{}", i)));
+        }
+
+        let contents: Vec<_> = buffer.range(2..=4).map(|l| l.plain()).collect();
+        assert_eq!(contents, vec!["line2", "line3", "line4"]);
+    }
+
+    #[test]
+    fn output_buffer_range_clamps_out_of_bounds() {
+        let mut buffer = OutputBuffer::new(100);
+        for i in 0..5 {
+            buffer.push(OutputLine::new(OutputKind::Stdout, format!("line{}", i)));
+        }
+
+        assert_eq!(buffer.range(3..100).count(), 2);
+        assert_eq!(buffer.range(10..20).count(), 0);
+        assert_eq!(buffer.range(..).count(), 5);
+    }
+
+    #[test]
+    fn output_buffer_get_returns_line_at_index() {
+        let mut buffer = OutputBuffer::new(100);
+        buffer.push(OutputLine::new(OutputKind::Stdout, "line0".into()));
+        buffer.push(OutputLine::new(OutputKind::Stdout, "line1".into()));
+
+        assert_eq!(buffer.get(1).unwrap().plain(), "line1");
+        assert!(buffer.get(2).is_none());
+    }
+
+    #[test]
+    fn output_buffer_last_n_returns_newest_lines() {
+        let mut buffer = OutputBuffer::new(100);
+        for i in 0..5 {
+            buffer.push(OutputLine::new(OutputKind::Stdout, format!("line{}", i)));
+        }
+
+        let contents: Vec<_> = buffer.last_n(2).map(|l| l.plain()).collect();
+        assert_eq!(contents, vec!["line3", "line4"]);
+        assert_eq!(buffer.last_n(10).count(), 5);
+    }
+
+    #[rstest]
+    #[case(0, 0)]
+    #[case(20, 2)]
+    #[case(21, 3)]
+    #[case(40, 4)]
+    fn output_buffer_first_since_finds_first_newer_line(
+        #[case] minute: u32,
+        #[case] expected: usize,
+    ) {
+        let at = |minute| Local.with_ymd_and_hms(2026, 3, 1, 14, minute, 0).unwrap();
+        let mut buffer = OutputBuffer::new(100);
+        for (i, minute) in [0, 10, 20, 30].into_iter().enumerate() {
+            buffer.push(OutputLine::new(OutputKind::Stdout, format!("line{}", i)).at(at(minute)));
+        }
+
+        assert_eq!(buffer.first_since(at(minute)), expected);
+    }
+
+    #[test]
+    fn output_buffer_generation_increases_across_evictions() {
+        let mut buffer = OutputBuffer::new(3);
+        for i in 0..5 {
+            buffer.push(OutputLine::new(OutputKind::Stdout, format!("line{}", i)));
+        }
+
+        assert_eq!(buffer.generation(), 5);
+        assert_eq!(buffer.first_generation(), 2);
+
+        buffer.clear();
+        assert_eq!(buffer.generation(), 5);
+        assert_eq!(buffer.first_generation(), 5);
+    }
+
+    #[test]
+    fn output_buffer_lines_since_returns_only_new_lines() {
+        let mut buffer = OutputBuffer::new(100);
+        buffer.push(OutputLine::new(OutputKind::Stdout, "old".into()));
+        let cursor = buffer.generation();
+        buffer.push(OutputLine::new(OutputKind::Stdout, "new1".into()));
+        buffer.push(OutputLine::new(OutputKind::Stdout, "new2".into()));
+
+        let contents: Vec<_> = buffer.lines_since(cursor).map(|l| l.plain()).collect();
+        assert_eq!(contents, vec!["new1", "new2"]);
+        assert_eq!(buffer.lines_since(buffer.generation()).count(), 0);
+    }
+
+    #[test]
+    fn output_buffer_lines_since_skips_evicted_lines() {
+        let mut buffer = OutputBuffer::new(2);
+        let cursor = buffer.generation();
+        for i in 0..4 {
+            buffer.push(OutputLine::new(OutputKind::Stdout, format!("line{}", i)));
+        }
+
+        assert!(cursor < buffer.first_generation());
+        let contents: Vec<_> = buffer.lines_since(cursor).map(|l| l.plain()).collect();
+        assert_eq!(contents, vec!["line2", "line3"]);
+    }
+
     #[test]
     fn output_buffer_iter_returns_all_lines() {
         let mut buffer = OutputBuffer::new(100);
@@ -201,6 +904,94 @@ mod tests {
         assert_eq!(contents, vec!["line1", "line2", "line3"]);
     }
 
+    #[test]
+    fn output_buffer_drops_chunks_once_all_their_lines_are_evicted() {
+        let mut buffer = OutputBuffer::new(CHUNK_LINES + 10);
+        for i in 0..3 * CHUNK_LINES {
+            buffer.push(OutputLine::new(OutputKind::Stdout, format!("line{}", i)));
+        }
+
+        assert_eq!(buffer.len(), CHUNK_LINES + 10);
+        assert_eq!(buffer.chunks.len(), 2);
+        let first = 2 * CHUNK_LINES - 10;
+        assert_eq!(buffer.get(0).unwrap().plain(), format!("line{}", first));
+        // Ranges run on across chunks
+        let contents: Vec<_> = buffer.range(9..12).map(|l| l.plain()).collect();
+        assert_eq!(
+            contents,
+            (first + 9..first + 12)
+                .map(|i| format!("line{}", i))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(buffer.iter().count(), buffer.len());
+    }
+
+    #[test]
+    fn output_buffer_expand_shows_full_text_of_cut_line() {
+        let mut buffer = OutputBuffer::new(100);
+        buffer.push(OutputLine::new(OutputKind::Stdout, "short".into()));
+        buffer.push(OutputLine::with_limit(
+            OutputKind::Stdout,
+            "abcdefgh".into(),
+            3,
+        ));
+
+        assert!(!buffer.expand(0));
+        assert!(buffer.expand(1));
+
+        assert_eq!(buffer.get(1).unwrap().shown_len(), None);
+        assert_eq!(buffer.get(1).unwrap().spans()[0].content, "abcdefgh");
+        assert!(!buffer.expand(1));
+    }
+
+    #[test]
+    fn output_buffer_shared_since_keeps_lines_while_pushing() {
+        let mut buffer = OutputBuffer::new(0);
+        for i in 0..CHUNK_LINES + 2 {
+            buffer.push(OutputLine::new(OutputKind::Stdout, format!("line{}", i)));
+        }
+
+        let shared = buffer.shared_since(CHUNK_LINES as u64);
+        buffer.push(OutputLine::new(OutputKind::Stdout, "later".into()));
+        buffer.clear();
+
+        let contents: Vec<_> = shared.iter().map(|l| l.plain()).collect();
+        assert_eq!(
+            contents,
+            [
+                format!("line{}", CHUNK_LINES),
+                format!("line{}", CHUNK_LINES + 1)
+            ]
+        );
+        assert_eq!(shared.len(), 2);
+    }
+
+    #[cfg(feature = "compress")]
+    #[test]
+    fn output_buffer_compact_compresses_old_chunks_out_of_view() {
+        let mut buffer = OutputBuffer::new(0);
+        buffer.set_compress_after(CHUNK_LINES);
+        for i in 0..4 * CHUNK_LINES {
+            buffer.push(OutputLine::new(OutputKind::Stdout, format!("line{}", i)));
+        }
+
+        // The second chunk is in view
+        buffer.compact(CHUNK_LINES + 5..CHUNK_LINES + 10);
+
+        let frozen: Vec<bool> = buffer.chunks.iter().map(|c| c.is_frozen()).collect();
+        assert_eq!(frozen, [true, false, true, false]);
+        assert_eq!(buffer.compressed().0, 2 * CHUNK_LINES);
+        assert!(buffer.has_frozen_lines());
+
+        // Reading decompresses, and the next compaction drops the text again
+        assert_eq!(buffer.get(7).unwrap().plain(), "line7");
+        assert!(!buffer.chunks[0].is_frozen());
+        buffer.compact(CHUNK_LINES + 5..CHUNK_LINES + 10);
+        assert!(buffer.chunks[0].is_frozen());
+        let contents: Vec<_> = buffer.range(..2).map(|l| l.plain()).collect();
+        assert_eq!(contents, ["line0", "line1"]);
+    }
+
     #[test]
     fn output_line_spans_contains_parsed_ansi_styles() {
         use ratatui::style::Color;
@@ -245,6 +1036,48 @@ mod tests {
         assert_eq!(red_span.unwrap().content, "ERROR");
     }
 
+    #[test]
+    fn output_line_with_limit_cuts_long_lines() {
+        let content = format!("\x1b[32m{}\x1b[0m", "x".repeat(12_345));
+
+        let line = OutputLine::with_limit(OutputKind::Stdout, content, 10);
+
+        let shown: String = line.spans().iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(shown, "xxxxxxxxxx… (+12,335 chars)");
+        assert_eq!(line.spans()[0].style.fg, Some(Color::Green));
+        assert_eq!(line.shown_len(), Some(10));
+        assert_eq!(line.plain().len(), 12_345);
+    }
+
+    #[test]
+    fn output_line_with_limit_keeps_short_lines() {
+        let line = OutputLine::with_limit(OutputKind::Stdout, "äöü".into(), 3);
+        assert_eq!(line.shown_len(), None);
+        assert_eq!(line.plain(), "äöü");
+
+        let line = OutputLine::with_limit(OutputKind::Stdout, "abcdef".into(), 0);
+        assert_eq!(line.shown_len(), None);
+    }
+
+    #[test]
+    fn output_line_expand_shows_full_text() {
+        let mut line = OutputLine::with_limit(OutputKind::Stdout, "abcdefgh".into(), 3);
+
+        assert!(line.expand());
+
+        assert_eq!(line.shown_len(), None);
+        assert_eq!(line.spans()[0].content, "abcdefgh");
+        assert!(!line.expand());
+    }
+
+    #[test]
+    fn strip_ansi_removes_escape_sequences() {
+        assert_eq!(
+            strip_ansi("\x1b[1;31mred\x1b[0m \x1b]0;title\x07ok"),
+            "red ok"
+        );
+    }
+
     #[test]
     fn output_buffer_clear_removes_all_lines() {
         let mut buffer = OutputBuffer::new(100);
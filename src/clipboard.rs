@@ -0,0 +1,138 @@
+//! Copying text to the system clipboard
+//!
+//! Locally the text is piped into a clipboard command (`pbcopy`, `wl-copy`,
+//! `xclip`, `xsel`). Over SSH, or without any of them, it is sent to the
+//! terminal as an OSC 52 sequence instead, which most terminal emulators
+//! (and tmux with `set-clipboard on`) put into the local clipboard.
+
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Commands that read text to copy on stdin, with the environment variable
+/// that must be set for them to work (if any)
+const COMMANDS: &[(&str, &[&str], Option<&str>)] = &[
+    ("pbcopy", &[], None),
+    ("wl-copy", &[], Some("WAYLAND_DISPLAY")),
+    ("xclip", &["-selection", "clipboard"], Some("DISPLAY")),
+    ("xsel", &["--clipboard", "--input"], Some("DISPLAY")),
+];
+
+/// How copied text reaches the clipboard
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ClipboardBackend {
+    /// OSC 52 over SSH or without a clipboard command, the command otherwise
+    #[default]
+    Auto,
+    /// A local clipboard command only
+    Command,
+    /// An OSC 52 escape sequence written to the terminal
+    Osc52,
+}
+
+/// Copy `text`, returning the name of the backend that took it
+pub fn copy(text: &str, backend: ClipboardBackend) -> io::Result<&'static str> {
+    let remote =
+        std::env::var_os("SSH_TTY").is_some() || std::env::var_os("SSH_CONNECTION").is_some();
+    let command = match backend {
+        ClipboardBackend::Osc52 => None,
+        ClipboardBackend::Auto if remote => None,
+        ClipboardBackend::Auto | ClipboardBackend::Command => local_command(),
+    };
+    match command {
+        Some((program, args)) => {
+            pipe_to(program, args, text)?;
+            Ok(program)
+        }
+        None if backend == ClipboardBackend::Command => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no clipboard command (pbcopy, wl-copy, xclip or xsel) found",
+        )),
+        None => {
+            let mut stdout = io::stdout();
+            stdout.write_all(osc52(text).as_bytes())?;
+            stdout.flush()?;
+            Ok("OSC 52")
+        }
+    }
+}
+
+/// Find the first clipboard command usable in this session
+fn local_command() -> Option<(&'static str, &'static [&'static str])> {
+    COMMANDS
+        .iter()
+        .filter(|(_, _, var)| var.is_none_or(|var| std::env::var_os(var).is_some()))
+        .find(|(program, _, _)| on_path(program))
+        .map(|&(program, args, _)| (program, args))
+}
+
+/// Check if an executable is in `$PATH`
+fn on_path(program: &str) -> bool {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    std::env::split_paths(&path).any(|dir| Path::new(&dir).join(program).is_file())
+}
+
+/// Run a clipboard command with `text` on its stdin
+fn pipe_to(program: &str, args: &[&str], text: &str) -> io::Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{} failed: {}", program, status)))
+    }
+}
+
+/// Build the OSC 52 sequence setting the clipboard to `text`
+pub fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64(text.as_bytes()))
+}
+
+/// Encode bytes as standard, padded base64
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &byte)| n | ((byte as u32) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("", "")]
+    #[case("f", "Zg==")]
+    #[case("fo", "Zm8=")]
+    #[case("foo", "Zm9v")]
+    #[case("error: ✗\n", "ZXJyb3I6IOKclwo=")]
+    fn base64_encodes_with_padding(#[case] text: &str, #[case] expected: &str) {
+        assert_eq!(base64(text.as_bytes()), expected);
+    }
+
+    #[test]
+    fn osc52_sets_the_clipboard_selection() {
+        assert_eq!(osc52("hi"), "\x1b]52;c;aGk=\x07");
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use super::throttle::{Admit, LineThrottle};
+
+/// Item produced by a [`LineAssembler`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Assembled {
+    /// A complete line, without its line ending
+    Line(String),
+    /// This many lines were dropped by the throttle before the next item
+    Dropped(u64),
+}
+
+/// Turns a stream of bytes into output lines
+///
+/// Bytes are fed in chunks as they are read (lines may be split across
+/// chunks) and lines are taken one at a time, so a reader can stop taking
+/// lines while its queue is full. Handles `\r\n` endings, keeps only the
+/// last state of lines redrawn with `\r` (progress bars), decodes invalid
+/// UTF-8 lossily and applies the optional per-second line limit.
+pub struct LineAssembler {
+    /// Bytes fed but not yet returned as lines
+    pending: Vec<u8>,
+    /// Start of the unreturned bytes in `pending`
+    consumed: usize,
+    /// End of the bytes in `pending` already searched for `\n`
+    scanned: usize,
+    /// Items ready to be taken (a dropped count and the line after it)
+    ready: VecDeque<Assembled>,
+    throttle: Option<LineThrottle>,
+    finished: bool,
+}
+
+impl LineAssembler {
+    /// Create an assembler, limited to `max_lines_per_sec` if given
+    pub fn new(max_lines_per_sec: Option<u32>, now: Instant) -> Self {
+        Self {
+            pending: Vec::new(),
+            consumed: 0,
+            scanned: 0,
+            ready: VecDeque::new(),
+            throttle: max_lines_per_sec.map(|max| LineThrottle::new(max, now)),
+            finished: false,
+        }
+    }
+
+    /// Feed bytes read from the stream
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.pending.drain(..self.consumed);
+        self.scanned -= self.consumed;
+        self.consumed = 0;
+        self.pending.extend_from_slice(bytes);
+    }
+
+    /// Mark the end of the stream
+    ///
+    /// A last line without a line ending becomes a line, and lines dropped
+    /// at the end are reported once all lines have been taken.
+    pub fn finish(&mut self) {
+        if self.pending.len() > self.consumed {
+            self.pending.push(b'\n');
+        }
+        self.finished = true;
+    }
+
+    /// Take the next complete line (or dropped count) arriving at `now`
+    pub fn next(&mut self, now: Instant) -> Option<Assembled> {
+        while self.ready.is_empty() {
+            let Some(line) = self.next_raw_line() else {
+                // Report lines dropped at the end of the stream
+                let dropped = self
+                    .throttle
+                    .as_mut()
+                    .filter(|_| self.finished)
+                    .map_or(0, LineThrottle::take_dropped);
+                return (dropped > 0).then_some(Assembled::Dropped(dropped));
+            };
+            match self.throttle.as_mut().map(|throttle| throttle.admit(now)) {
+                Some(Admit::Drop) => {}
+                Some(Admit::Pass { dropped }) if dropped > 0 => {
+                    self.ready.push_back(Assembled::Dropped(dropped));
+                    self.ready.push_back(Assembled::Line(line));
+                }
+                _ => self.ready.push_back(Assembled::Line(line)),
+            }
+        }
+        self.ready.pop_front()
+    }
+
+    /// Cut the next `\n`-terminated line from the pending bytes
+    ///
+    /// Only bytes not searched before are searched, so a long line fed in
+    /// many chunks is scanned once.
+    fn next_raw_line(&mut self) -> Option<String> {
+        let Some(found) = self.pending[self.scanned..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+        else {
+            self.scanned = self.pending.len();
+            return None;
+        };
+        let end = self.scanned + found;
+        let line = decode_line(&self.pending[self.consumed..end]);
+        self.consumed = end + 1;
+        self.scanned = self.consumed;
+        Some(line)
+    }
+}
+
+/// Decode a line's bytes without the `\n`
+///
+/// Drops a trailing `\r` (CRLF) and keeps what follows the last `\r`
+/// inside the line, which is what a terminal would end up showing.
+fn decode_line(bytes: &[u8]) -> String {
+    let end = bytes
+        .iter()
+        .rposition(|&byte| byte != b'\r')
+        .map_or(0, |last| last + 1);
+    let bytes = &bytes[..end];
+    let start = bytes
+        .iter()
+        .rposition(|&byte| byte == b'\r')
+        .map_or(0, |cr| cr + 1);
+    String::from_utf8_lossy(&bytes[start..]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// Feed chunks, finish and collect everything assembled
+    fn assemble(chunks: &[&[u8]], max_lines_per_sec: Option<u32>) -> Vec<Assembled> {
+        let now = Instant::now();
+        let mut assembler = LineAssembler::new(max_lines_per_sec, now);
+        let mut items = Vec::new();
+        for chunk in chunks {
+            assembler.push(chunk);
+            items.extend(std::iter::from_fn(|| assembler.next(now)));
+        }
+        assembler.finish();
+        items.extend(std::iter::from_fn(|| assembler.next(now)));
+        items
+    }
+
+    fn lines(texts: &[&str]) -> Vec<Assembled> {
+        texts
+            .iter()
+            .map(|text| Assembled::Line(text.to_string()))
+            .collect()
+    }
+
+    #[rstest]
+    #[case(&[b"one\ntwo\n".as_slice()], &["one", "two"])]
+    #[case(&[b"par".as_slice(), b"tial\nlast"], &["partial", "last"])]
+    #[case(&[b"lo".as_slice(), b"ng", b" one\nne", b"xt\n"], &["long one", "next"])]
+    #[case(&[b"crlf\r\n".as_slice(), b"\r\n"], &["crlf", ""])]
+    #[case(&[b" 10%\r 50%\r100%\n".as_slice()], &["100%"])]
+    #[case(&[b"bad \xff byte\n".as_slice()], &["bad \u{fffd} byte"])]
+    #[case(&[b"\xe3\x81".as_slice(), b"\x82\n"], &["あ"])]
+    #[case(&[b"".as_slice()], &[])]
+    fn line_assembler_splits_and_decodes_lines(
+        #[case] chunks: &[&[u8]],
+        #[case] expected: &[&str],
+    ) {
+        assert_eq!(assemble(chunks, None), lines(expected));
+    }
+
+    #[test]
+    fn line_assembler_reports_throttled_lines() {
+        let items = assemble(&[b"a\nb\nc\nd\n"], Some(2));
+
+        assert_eq!(
+            items,
+            vec![
+                Assembled::Line("a".into()),
+                Assembled::Line("b".into()),
+                Assembled::Dropped(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn line_assembler_holds_lines_until_taken() {
+        let now = Instant::now();
+        let mut assembler = LineAssembler::new(None, now);
+        assembler.push(b"a\nb\n");
+        assert_eq!(assembler.next(now), Some(Assembled::Line("a".into())));
+
+        assembler.push(b"c\n");
+        assert_eq!(assembler.next(now), Some(Assembled::Line("b".into())));
+        assert_eq!(assembler.next(now), Some(Assembled::Line("c".into())));
+        assert_eq!(assembler.next(now), None);
+    }
+}
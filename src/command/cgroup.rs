@@ -0,0 +1,96 @@
+use std::process::Command as StdCommand;
+
+use tokio::process::Command;
+
+use super::CommandSpec;
+
+/// Name of the transient systemd scope for a tab's command
+///
+/// Includes the parallels PID so several instances don't collide.
+pub(crate) fn unit_name(tab_index: usize) -> String {
+    format!("parallels-{}-tab{}.scope", std::process::id(), tab_index)
+}
+
+/// Build a command that runs `sh -c` inside a transient cgroup scope
+///
+/// Uses `systemd-run --user --scope`, which applies `MemoryMax`/`CPUQuota`
+/// and then execs the shell in place, so the working directory, environment
+/// and process group of the returned command carry over.
+pub(crate) fn scoped_command(spec: &CommandSpec, tab_index: usize) -> Command {
+    let unit = unit_name(tab_index);
+    // A scope left in the failed state by the previous run blocks the name
+    reset_failed(&unit);
+
+    let mut cmd = Command::new("systemd-run");
+    cmd.args(["--user", "--scope", "--quiet"])
+        .arg(format!("--unit={}", unit));
+    if let Some(memory_max) = &spec.memory_max {
+        cmd.arg("-p").arg(format!("MemoryMax={}", memory_max));
+    }
+    if let Some(cpu_quota) = &spec.cpu_quota {
+        cmd.arg("-p").arg(format!("CPUQuota={}", cpu_quota));
+    }
+    cmd.args(["--", "sh", "-c"]).arg(&spec.command);
+    cmd
+}
+
+/// Check whether the tab's last scope was killed by the OOM killer
+///
+/// Clears the scope's failed state so the next run can reuse the name.
+pub(crate) fn take_oom_kill(tab_index: usize) -> bool {
+    let unit = unit_name(tab_index);
+    let result = StdCommand::new("systemctl")
+        .args(["--user", "show", "--property=Result", "--value", &unit])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "oom-kill")
+        .unwrap_or(false);
+    reset_failed(&unit);
+    result
+}
+
+/// Forget a failed scope (ignoring errors, e.g. when it doesn't exist)
+fn reset_failed(unit: &str) {
+    let _ = StdCommand::new("systemctl")
+        .args(["--user", "reset-failed", unit])
+        .output();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cgroup_scoped_command_passes_limits_to_systemd_run() {
+        let spec = CommandSpec {
+            memory_max: Some("512M".into()),
+            cpu_quota: Some("50%".into()),
+            ..CommandSpec::new("make -j8")
+        };
+
+        let cmd = scoped_command(&spec, 3);
+        let std_cmd = cmd.as_std();
+
+        assert_eq!(std_cmd.get_program(), "systemd-run");
+        let args: Vec<_> = std_cmd
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            args,
+            vec![
+                "--user".to_string(),
+                "--scope".into(),
+                "--quiet".into(),
+                format!("--unit={}", unit_name(3)),
+                "-p".into(),
+                "MemoryMax=512M".into(),
+                "-p".into(),
+                "CPUQuota=50%".into(),
+                "--".into(),
+                "sh".into(),
+                "-c".into(),
+                "make -j8".into(),
+            ]
+        );
+    }
+}
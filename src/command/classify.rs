@@ -0,0 +1,81 @@
+/// Line starts that mark errors (`error: ...`, `[ERROR] ...`), ignoring case
+const ERROR_PREFIXES: &[&str] = &[
+    "error",
+    "err:",
+    "[error",
+    "fatal",
+    "panic",
+    "traceback",
+    "exception",
+];
+
+/// Line starts that mark warnings (`warning: ...`, `WARN ...`), ignoring case
+const WARNING_PREFIXES: &[&str] = &["warning", "warn:", "warn ", "[warn"];
+
+/// Parts of compiler-style errors (`src/main.c:3:5: error: ...`)
+const ERROR_MARKERS: &[&str] = &[": error", ": fatal", "' panicked at "];
+
+/// Parts of compiler-style warnings (`src/main.c:3:5: warning: ...`)
+const WARNING_MARKERS: &[&str] = &[": warning"];
+
+/// How bad a diagnostic line is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Guess whether a line is an error or a warning
+///
+/// Merged streams lose which pipe a line came from, so lines that look like
+/// either are tagged as stderr to keep them highlighted; tabs also count
+/// them for the statistics panel.
+pub(crate) fn severity(text: &str) -> Option<Severity> {
+    let text = text.trim_start();
+    if starts_with_any(text, ERROR_PREFIXES) || contains_any(text, ERROR_MARKERS) {
+        Some(Severity::Error)
+    } else if starts_with_any(text, WARNING_PREFIXES) || contains_any(text, WARNING_MARKERS) {
+        Some(Severity::Warning)
+    } else {
+        None
+    }
+}
+
+/// Check if the text starts with one of the (lowercase ASCII) prefixes, ignoring case
+fn starts_with_any(text: &str, prefixes: &[&str]) -> bool {
+    prefixes.iter().any(|prefix| {
+        text.as_bytes()
+            .get(..prefix.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(prefix.as_bytes()))
+    })
+}
+
+/// Check if the text contains one of the (lowercase ASCII) markers, ignoring case
+fn contains_any(text: &str, markers: &[&str]) -> bool {
+    markers.iter().any(|marker| {
+        text.as_bytes()
+            .windows(marker.len())
+            .any(|window| window.eq_ignore_ascii_case(marker.as_bytes()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("error[E0308]: mismatched types", Some(Severity::Error))]
+    #[case("  WARNING: deprecated option", Some(Severity::Warning))]
+    #[case("src/main.c:3:5: error: expected ';'", Some(Severity::Error))]
+    #[case("src/main.c:7:1: Warning: unused", Some(Severity::Warning))]
+    #[case("thread 'main' panicked at src/main.rs:2:5:", Some(Severity::Error))]
+    #[case("Traceback (most recent call last):", Some(Severity::Error))]
+    #[case("[WARN] disk almost full", Some(Severity::Warning))]
+    #[case("   Compiling parallels v0.1.0", None)]
+    #[case("0 errors, 0 warnings", None)]
+    #[case("", None)]
+    fn classify_severity(#[case] text: &str, #[case] expected: Option<Severity>) {
+        assert_eq!(severity(text), expected);
+    }
+}
@@ -0,0 +1,77 @@
+use nix::sys::signal::{Signal, kill};
+use nix::unistd::Pid;
+
+/// Environment variable tagging every process started for a tab
+///
+/// Descendants inherit it even after double-forking or calling `setsid`,
+/// which takes them out of the command's process group.
+pub(crate) const TAG_VAR: &str = "PARALLELS_TAB";
+
+/// Tag value for a tab (includes the parallels PID so instances don't clash)
+pub(crate) fn tag(tab_index: usize) -> String {
+    format!("{}:{}", std::process::id(), tab_index)
+}
+
+/// Send SIGKILL to every process still carrying the tab's tag
+///
+/// Returns the number of processes signalled.
+pub(crate) fn kill_tagged(tab_index: usize) -> usize {
+    let pids = tagged_pids(&tag(tab_index));
+    for &pid in &pids {
+        let _ = kill(Pid::from_raw(pid), Signal::SIGKILL);
+    }
+    pids.len()
+}
+
+/// Find processes whose environment contains the tag (Linux, via `/proc`)
+///
+/// Only processes of the same user are readable, which covers everything
+/// parallels started.
+#[cfg(target_os = "linux")]
+fn tagged_pids(tag: &str) -> Vec<i32> {
+    let needle = format!("{}={}", TAG_VAR, tag);
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<i32>().ok())
+        .filter(|&pid| {
+            std::fs::read(format!("/proc/{}/environ", pid)).is_ok_and(|environ| {
+                environ
+                    .split(|&byte| byte == 0)
+                    .any(|var| var == needle.as_bytes())
+            })
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn tagged_pids(_tag: &str) -> Vec<i32> {
+    Vec::new()
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detached_kill_tagged_kills_processes_outside_the_group() {
+        // Tab index unlikely to clash with other tests in this process
+        let tab_index = 9_001;
+        let mut child = std::process::Command::new("setsid")
+            .args(["sleep", "30"])
+            .env(TAG_VAR, tag(tab_index))
+            .spawn()
+            .unwrap();
+
+        // Wait for the exec so its environment is readable
+        let comm = format!("/proc/{}/comm", child.id());
+        while std::fs::read_to_string(&comm).unwrap_or_default().trim() != "sleep" {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(kill_tagged(tab_index), 1);
+
+        let status = child.wait().unwrap();
+        assert!(!status.success());
+    }
+}
@@ -0,0 +1,223 @@
+use std::fmt;
+use std::io;
+
+use nix::errno::Errno;
+
+use super::naming::main_program;
+use super::{CommandRunner, CommandSpec};
+
+/// Exit code of `sh` for a command it couldn't find
+const NOT_FOUND: i32 = 127;
+
+/// Exit code of `sh` for a command it found but couldn't run
+const NOT_EXECUTABLE: i32 = 126;
+
+/// Why a command couldn't be started, with the OS error and a suggested fix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpawnFailure {
+    /// What went wrong, e.g. `No such file or directory`
+    pub reason: String,
+    /// Error number reported by the OS, if any (e.g. `ENOENT`)
+    pub errno: Option<Errno>,
+    /// What to check or change before retrying
+    pub fix: Option<String>,
+}
+
+impl SpawnFailure {
+    /// Describe an error returned while starting a spec's command
+    ///
+    /// The fix is guessed from the error number and the spec: a missing
+    /// `cwd` and a missing program both fail with `ENOENT`, for example.
+    pub fn diagnose(error: &io::Error, spec: &CommandSpec) -> Self {
+        let Some(errno) = error.raw_os_error().map(Errno::from_raw) else {
+            let fix = (error.kind() == io::ErrorKind::AddrInUse)
+                .then(|| "Stop the process holding the port, or change `ports`".to_string());
+            return Self {
+                reason: error.to_string(),
+                errno: None,
+                fix,
+            };
+        };
+
+        let program = CommandRunner::program(spec);
+        // The directory only matters locally; a remote one is ssh's business
+        let cwd = spec.cwd.as_ref().filter(|_| spec.ssh.is_none());
+        let fix = match errno {
+            Errno::ENOENT | Errno::ENOTDIR => match cwd {
+                Some(cwd) if !cwd.is_dir() => Some(format!(
+                    "The directory {} doesn't exist: create it or fix `cwd`",
+                    cwd.display()
+                )),
+                _ => Some(not_found_fix(program)),
+            },
+            Errno::EACCES => Some(match cwd {
+                Some(cwd) => format!(
+                    "Check that you may enter {} and run `{}`",
+                    cwd.display(),
+                    program
+                ),
+                None => not_executable_fix(program),
+            }),
+            Errno::EAGAIN => Some(
+                "Too many processes for this user (see `ulimit -u`): stop some, then retry"
+                    .to_string(),
+            ),
+            Errno::EMFILE | Errno::ENFILE => Some(
+                "Too many open files (see `ulimit -n`): raise the limit or close some tabs"
+                    .to_string(),
+            ),
+            Errno::ENOMEM => Some("Not enough memory: free some, then retry".to_string()),
+            Errno::E2BIG => {
+                Some("The command line and environment are too long: shorten `env`".to_string())
+            }
+            _ => None,
+        };
+        Self {
+            reason: errno.desc().to_string(),
+            errno: Some(errno),
+            fix,
+        }
+    }
+
+    /// Describe a local command that `sh -c` couldn't run, from its exit code
+    ///
+    /// A missing or non-executable program doesn't fail the spawn: `sh`
+    /// prints why (`sh: 1: npm: not found`) and exits with 127 or 126.
+    /// `stderr` is the last error line of the run, which names the program;
+    /// while it hasn't been read yet, the program is taken from the command.
+    pub fn from_exit(exit_code: i32, spec: &CommandSpec, stderr: Option<&str>) -> Option<Self> {
+        let (reason, message, fix): (_, _, fn(&str) -> String) = match exit_code {
+            NOT_FOUND => ("Command not found", "not found", not_found_fix),
+            NOT_EXECUTABLE => ("Permission denied", "permission denied", not_executable_fix),
+            _ => return None,
+        };
+        if spec.ssh.is_some() {
+            return None;
+        }
+        let program = stderr
+            .and_then(|line| shell_error_program(line, message))
+            .or_else(|| main_program(&spec.command))?;
+        Some(Self {
+            reason: format!("{} (exit code {})", reason, exit_code),
+            errno: None,
+            fix: Some(fix(program)),
+        })
+    }
+}
+
+fn not_found_fix(program: &str) -> String {
+    format!(
+        "`{}` wasn't found: install it or add its directory to PATH",
+        program
+    )
+}
+
+fn not_executable_fix(program: &str) -> String {
+    format!("Check that `{}` is executable", program)
+}
+
+/// Program named by a shell error line ending in `message`, like
+/// `sh: 1: npm: not found` or `bash: line 1: npm: command not found`
+fn shell_error_program<'a>(line: &'a str, message: &str) -> Option<&'a str> {
+    let mut fields = line.rsplit(": ");
+    let last = fields.next()?.to_ascii_lowercase();
+    last.ends_with(message).then(|| fields.next()).flatten()
+}
+
+/// The reason and error number, like `No such file or directory (ENOENT, os error 2)`
+impl fmt::Display for SpawnFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.reason)?;
+        if let Some(errno) = self.errno {
+            write!(f, " ({:?}, os error {})", errno, errno as i32)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Errno::ENOENT, None, "`sh` wasn't found")]
+    #[case(
+        Errno::ENOENT,
+        Some("/nonexistent/web"),
+        "/nonexistent/web doesn't exist"
+    )]
+    #[case(Errno::EAGAIN, None, "ulimit -u")]
+    #[case(Errno::EMFILE, None, "ulimit -n")]
+    fn spawn_failure_suggests_fixes(
+        #[case] errno: Errno,
+        #[case] cwd: Option<&str>,
+        #[case] fix: &str,
+    ) {
+        let spec = CommandSpec {
+            cwd: cwd.map(Into::into),
+            ..CommandSpec::new("npm run dev")
+        };
+
+        let failure = SpawnFailure::diagnose(&io::Error::from_raw_os_error(errno as i32), &spec);
+
+        assert_eq!(failure.errno, Some(errno));
+        assert!(
+            failure.fix.as_deref().unwrap().contains(fix),
+            "{:?}",
+            failure
+        );
+    }
+
+    #[rstest]
+    #[case(127, Some("sh: 1: npm: not found"), "`npm` wasn't found")]
+    #[case(
+        127,
+        Some("bash: line 1: npm: command not found"),
+        "`npm` wasn't found"
+    )]
+    #[case(127, None, "`./dev.sh` wasn't found")]
+    #[case(
+        126,
+        Some("sh: 1: ./dev.sh: Permission denied"),
+        "`./dev.sh` is executable"
+    )]
+    fn spawn_failure_explains_shell_exit_codes(
+        #[case] exit_code: i32,
+        #[case] stderr: Option<&str>,
+        #[case] fix: &str,
+    ) {
+        let spec = CommandSpec::new("cd web && PORT=3000 ./dev.sh");
+
+        let failure = SpawnFailure::from_exit(exit_code, &spec, stderr).unwrap();
+
+        assert!(
+            failure.fix.as_deref().unwrap().contains(fix),
+            "{:?}",
+            failure
+        );
+    }
+
+    #[test]
+    fn spawn_failure_ignores_other_exit_codes() {
+        let spec = CommandSpec::new("npm test");
+        assert_eq!(SpawnFailure::from_exit(1, &spec, None), None);
+        let remote = CommandSpec::parse("ssh://web1:npm test");
+        assert_eq!(SpawnFailure::from_exit(127, &remote, None), None);
+    }
+
+    #[test]
+    fn spawn_failure_shows_the_error_number() {
+        let error = io::Error::from_raw_os_error(Errno::ENOENT as i32);
+        let failure = SpawnFailure::diagnose(&error, &CommandSpec::parse("ssh://web1:uptime"));
+
+        assert_eq!(
+            failure.to_string(),
+            "No such file or directory (ENOENT, os error 2)"
+        );
+        assert_eq!(
+            failure.fix.as_deref(),
+            Some("`ssh` wasn't found: install it or add its directory to PATH")
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use std::fmt;
+
+use nix::sys::signal::Signal;
+use serde::Deserialize;
+
+/// What a key of a command's `keys` in the config does when pressed in
+/// Normal mode on its tab
+///
+/// ```toml
+/// keys = { t = "test\n", r = { signal = "HUP" } }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum KeyAction {
+    /// Write the text to the command's stdin
+    Send(String),
+    /// Send a signal to the command's process group
+    Signal { signal: SignalName },
+}
+
+impl fmt::Display for KeyAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyAction::Send(text) => write!(f, "{:?}", text),
+            KeyAction::Signal { signal } => f.write_str(signal.0.as_str()),
+        }
+    }
+}
+
+/// A signal given by name, with or without the `SIG` prefix (`INT`, `SIGHUP`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct SignalName(pub Signal);
+
+impl TryFrom<String> for SignalName {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        let upper = name.to_ascii_uppercase();
+        let full = match upper.strip_prefix("SIG") {
+            Some(_) => upper,
+            None => format!("SIG{}", upper),
+        };
+        full.parse()
+            .map(Self)
+            .map_err(|_| format!("unknown signal '{}'", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::collections::BTreeMap;
+
+    fn parse(toml: &str) -> Result<BTreeMap<char, KeyAction>, toml::de::Error> {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            keys: BTreeMap<char, KeyAction>,
+        }
+        toml::from_str::<Wrapper>(&format!("keys = {}", toml)).map(|w| w.keys)
+    }
+
+    #[rstest]
+    #[case(r#"{ t = "test\n" }"#, KeyAction::Send("test\n".into()))]
+    #[case(r#"{ t = { signal = "INT" } }"#, KeyAction::Signal { signal: SignalName(Signal::SIGINT) })]
+    #[case(r#"{ t = { signal = "sighup" } }"#, KeyAction::Signal { signal: SignalName(Signal::SIGHUP) })]
+    fn key_action_deserializes_text_and_signals(#[case] toml: &str, #[case] expected: KeyAction) {
+        assert_eq!(parse(toml).unwrap()[&'t'], expected);
+    }
+
+    #[test]
+    fn key_action_rejects_unknown_signals() {
+        assert!(parse(r#"{ t = { signal = "NOPE" } }"#).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use std::borrow::Cow;
+
+use regex::Regex;
+
+use crate::command::{CommandSpec, Transformer};
+
+/// Text shown in place of a masked secret
+pub const MASK: &str = "****";
+
+/// Secret values shorter than this are not masked, since they would hide
+/// unrelated text (e.g. every `1` for `DEBUG=1`)
+const MIN_SECRET_LEN: usize = 4;
+
+/// Replaces secrets in output lines with [`MASK`] as they are read
+#[derive(Debug)]
+pub struct SecretMask {
+    /// Values of the `mask_env` variables, longest first
+    values: Vec<String>,
+    patterns: Vec<Regex>,
+}
+
+impl SecretMask {
+    /// Build the mask for a spec's `mask` patterns and `mask_env` variables
+    ///
+    /// Variables are looked up in the spec's `env`, then in the environment
+    /// parallels runs in. Returns `None` when there is nothing to mask.
+    pub fn new(spec: &CommandSpec) -> Option<Self> {
+        let mut values: Vec<String> = spec
+            .mask_env
+            .iter()
+            .filter_map(|name| {
+                spec.env
+                    .get(name)
+                    .cloned()
+                    .or_else(|| std::env::var(name).ok())
+            })
+            .filter(|value| value.len() >= MIN_SECRET_LEN)
+            .collect();
+        // Mask the longest value first, in case one contains another
+        values.sort_by_key(|value| std::cmp::Reverse(value.len()));
+        let patterns: Vec<Regex> = spec.mask.iter().map(|p| p.regex().clone()).collect();
+
+        (!values.is_empty() || !patterns.is_empty()).then_some(Self { values, patterns })
+    }
+}
+
+impl Transformer for SecretMask {
+    fn transform<'a>(&self, mut line: Cow<'a, str>) -> Cow<'a, str> {
+        for value in &self.values {
+            if line.contains(value.as_str()) {
+                line = Cow::Owned(line.replace(value.as_str(), MASK));
+            }
+        }
+        for pattern in &self.patterns {
+            if let Cow::Owned(masked) = pattern.replace_all(&line, MASK) {
+                line = Cow::Owned(masked);
+            }
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn spec(mask: &[&str], env: &[(&str, &str)]) -> CommandSpec {
+        CommandSpec {
+            mask: mask
+                .iter()
+                .map(|pattern| pattern.parse().unwrap())
+                .collect(),
+            mask_env: env.iter().map(|(name, _)| name.to_string()).collect(),
+            env: env
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            ..CommandSpec::new("deploy")
+        }
+    }
+
+    #[rstest]
+    #[case(&["ghp_[A-Za-z0-9]+"], &[], "token ghp_abc123 ok", "token **** ok")]
+    #[case(&[], &[("API_KEY", "s3cr3t-key")], "key=s3cr3t-key", "key=****")]
+    #[case(&[], &[("TOKEN", "abcd"), ("LONG", "abcdef")], "abcdef abcd", "**** ****")]
+    #[case(&["x+"], &[("DEBUG", "1")], "retry 1 of 3", "retry 1 of 3")]
+    #[case(&["password=\\S+"], &[], "nothing here", "nothing here")]
+    fn secret_mask_replaces_secrets(
+        #[case] mask: &[&str],
+        #[case] env: &[(&str, &str)],
+        #[case] line: &str,
+        #[case] expected: &str,
+    ) {
+        let mask = SecretMask::new(&spec(mask, env)).unwrap();
+
+        assert_eq!(mask.transform(Cow::Borrowed(line)), expected);
+    }
+
+    #[test]
+    fn secret_mask_is_none_without_secrets() {
+        assert!(SecretMask::new(&spec(&[], &[])).is_none());
+        assert!(SecretMask::new(&spec(&[], &[("DEBUG", "1")])).is_none());
+    }
+
+    #[test]
+    fn mask_pattern_rejects_invalid_regex() {
+        assert!("ghp_[".parse::<crate::command::Pattern>().is_err());
+    }
+}
@@ -1,3 +1,44 @@
+mod assembler;
+#[cfg(all(feature = "cgroups", target_os = "linux"))]
+pub(crate) mod cgroup;
+pub(crate) mod classify;
+pub(crate) mod detached;
+mod failure;
+mod keys;
+mod mask;
+mod naming;
+mod ports;
+mod queue;
+mod quote;
 mod runner;
+mod socket;
+mod source;
+mod spawner;
+mod spec;
+mod ssh;
+mod tail;
+pub(crate) mod template;
+mod throttle;
+mod transform;
+mod waiter;
 
+pub use assembler::{Assembled, LineAssembler};
+pub use classify::Severity;
+pub use failure::SpawnFailure;
+pub use keys::{KeyAction, SignalName};
+pub use mask::{MASK, SecretMask};
+pub use naming::auto_name;
+pub use ports::{check_free, is_listening};
+pub use queue::OverflowPolicy;
+pub use quote::{shell_join, shell_quote};
 pub use runner::CommandRunner;
+pub use socket::SocketSource;
+pub use source::{Endpoint, InvalidSource, Running, Source, SourceAddr};
+pub use spawner::{Script, ScriptedSpawner, Spawner, Step};
+pub use spec::{CommandSpec, PrefixFold, StartAt};
+pub use ssh::{SSH_CONNECTION_FAILED, parse_ssh_url};
+pub use tail::{DEFAULT_TAIL_LINES, FileTailer};
+pub use template::Variable;
+pub(crate) use throttle::format_count;
+pub use transform::{Pattern, Pipeline, TransformSpec, Transformer};
+pub use waiter::{ChildWaiter, signal_name};
@@ -0,0 +1,105 @@
+/// Task runners whose first argument names the task (`npm run dev` → `npm:dev`)
+const TASK_RUNNERS: &[&str] = &[
+    "npm", "pnpm", "yarn", "bun", "make", "just", "cargo", "go", "rake", "mix", "task",
+];
+
+/// Interpreters whose script names the tab (`python3 app.py` → `app.py`)
+const INTERPRETERS: &[&str] = &[
+    "python", "python3", "node", "ruby", "perl", "sh", "bash", "zsh",
+];
+
+/// Words that only prefix the real program (`exec`, `sudo`, ...)
+const WRAPPERS: &[&str] = &["exec", "env", "sudo", "time", "nohup"];
+
+/// Subcommands skipped when looking for a task name (`npm run dev`)
+const RUN_WORDS: &[&str] = &["run", "run-script"];
+
+/// Derive a short tab name from a shell command line
+///
+/// Takes the basename of the program, plus the task for task runners:
+/// `npm run dev` → `npm:dev`, `./scripts/serve.sh --port 3000` → `serve.sh`,
+/// `cd web && npm start` → `npm:start`. Falls back to the command itself.
+pub fn auto_name(command: &str) -> String {
+    let mut words = main_words(command);
+    let Some(program) = words.next() else {
+        return command.to_string();
+    };
+    let program = basename(program);
+
+    let mut args = words.filter(|word| !word.starts_with('-'));
+    if TASK_RUNNERS.contains(&program) {
+        match args.find(|arg| !RUN_WORDS.contains(arg)) {
+            Some(task) => format!("{}:{}", program, basename(task)),
+            None => program.to_string(),
+        }
+    } else if INTERPRETERS.contains(&program) {
+        args.next().map_or(program, basename).to_string()
+    } else {
+        program.to_string()
+    }
+}
+
+/// Program of the command doing the work in a command line, as written
+/// (`cd web && ./serve.sh` → `./serve.sh`)
+pub(super) fn main_program(command: &str) -> Option<&str> {
+    main_words(command).next()
+}
+
+/// Words of the command doing the work, from its program on
+fn main_words(command: &str) -> impl Iterator<Item = &str> {
+    // The last command of a `&&`/`;` chain does the work (`cd dir && ...`),
+    // the first of a pipeline produces the output
+    let segment = command
+        .rsplit(['&', ';'])
+        .map(str::trim)
+        .find(|segment| !segment.is_empty())
+        .and_then(|segment| segment.split('|').next())
+        .unwrap_or(command);
+
+    segment
+        .split_whitespace()
+        .map(|word| word.trim_matches(['"', '\'']))
+        .skip_while(|word| is_env_assignment(word) || WRAPPERS.contains(word))
+}
+
+/// Check if a word is a `NAME=value` prefix setting an environment variable
+fn is_env_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+/// Last component of a path (`./scripts/serve.sh` → `serve.sh`)
+fn basename(path: &str) -> &str {
+    let path = path.trim_end_matches('/');
+    path.rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())
+        .unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("npm run dev", "npm:dev")]
+    #[case("npm start", "npm:start")]
+    #[case("./scripts/serve.sh --port 3000", "serve.sh")]
+    #[case("cargo watch -x test", "cargo:watch")]
+    #[case("make -j8 build", "make:build")]
+    #[case("make", "make")]
+    #[case("go run ./cmd/server", "go:server")]
+    #[case("python3 -u app/main.py", "main.py")]
+    #[case("RUST_LOG=debug /usr/local/bin/api --verbose", "api")]
+    #[case("cd web && npm run build", "npm:build")]
+    #[case("tail -f /var/log/syslog | grep error", "tail")]
+    #[case("exec 'docker' compose up", "docker")]
+    #[case("   ", "   ")]
+    fn auto_name_derives_short_name(#[case] command: &str, #[case] expected: &str) {
+        assert_eq!(auto_name(command), expected);
+    }
+}
@@ -0,0 +1,166 @@
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, TcpListener};
+use std::time::Duration;
+
+use tokio::net::TcpStream;
+
+/// How long a readiness check waits for a local connection
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Check that the ports a command listens on are free, before starting it
+///
+/// A port is taken if it can't be bound on all IPv4 and all IPv6
+/// interfaces, which also catches listeners on a single address like
+/// `[::1]`. The error names the process holding the port when it can be
+/// found (Linux, same user).
+pub fn check_free(ports: &[u16]) -> io::Result<()> {
+    let any = [
+        IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+    ];
+    for &port in ports {
+        for ip in any {
+            match TcpListener::bind((ip, port)) {
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::AddrInUse => {
+                    let message = match holder(port) {
+                        Some((pid, name)) => {
+                            format!("port {} is already in use by PID {} ({})", port, pid, name)
+                        }
+                        None => format!("port {} is already in use", port),
+                    };
+                    return Err(io::Error::new(io::ErrorKind::AddrInUse, message));
+                }
+                // A system without IPv6 can only have IPv4 listeners
+                Err(_) if ip.is_ipv6() => {}
+                Err(e) => return Err(io::Error::new(e.kind(), format!("port {}: {}", port, e))),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check if something accepts TCP connections on a local port
+///
+/// Both loopback addresses are tried: servers started for `localhost`
+/// often listen on `[::1]` only (Node 17+, Vite).
+pub async fn is_listening(port: u16) -> bool {
+    for ip in [
+        IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(Ipv6Addr::LOCALHOST),
+    ] {
+        let connect = TcpStream::connect((ip, port));
+        if let Ok(Ok(_)) = tokio::time::timeout(CONNECT_TIMEOUT, connect).await {
+            return true;
+        }
+    }
+    false
+}
+
+/// Find the process listening on a TCP port (Linux, via `/proc`)
+///
+/// The socket's inode is looked up in `/proc/net/tcp{,6}`, then among the
+/// open files of the processes readable by this user.
+#[cfg(target_os = "linux")]
+fn holder(port: u16) -> Option<(u32, String)> {
+    /// Socket state of a listening socket in `/proc/net/tcp`
+    const LISTEN: &str = "0A";
+
+    let tables: String = ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|table| std::fs::read_to_string(table).ok())
+        .collect();
+    // Header lines have `st` in the state column, so they never match
+    let inode = tables.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (_, local_port) = fields.get(1)?.rsplit_once(':')?;
+        let listening = fields.get(3) == Some(&LISTEN)
+            && u16::from_str_radix(local_port, 16).ok() == Some(port);
+        listening.then(|| fields.get(9).map(|inode| inode.to_string()))?
+    })?;
+    let socket = format!("socket:[{}]", inode);
+    std::fs::read_dir("/proc")
+        .ok()?
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u32>().ok())
+        .find(|pid| {
+            std::fs::read_dir(format!("/proc/{}/fd", pid)).is_ok_and(|fds| {
+                fds.filter_map(Result::ok).any(|fd| {
+                    std::fs::read_link(fd.path())
+                        .is_ok_and(|link| link.as_os_str() == socket.as_str())
+                })
+            })
+        })
+        .map(|pid| {
+            let name = std::fs::read_to_string(format!("/proc/{}/comm", pid)).unwrap_or_default();
+            (pid, name.trim().to_string())
+        })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn holder(_port: u16) -> Option<(u32, String)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn check_free_accepts_unused_ports() {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        assert!(check_free(&[port]).is_ok());
+        assert!(check_free(&[]).is_ok());
+    }
+
+    #[rstest]
+    #[case("127.0.0.1:0")]
+    #[case("[::1]:0")]
+    #[tokio::test]
+    async fn is_listening_sees_open_ports_only(#[case] addr: &str) {
+        // Without IPv6 there is nothing to check on `[::1]`
+        let Ok(listener) = TcpListener::bind(addr) else {
+            return;
+        };
+        let port = listener.local_addr().unwrap().port();
+
+        assert!(is_listening(port).await);
+        drop(listener);
+        assert!(!is_listening(port).await);
+    }
+
+    #[test]
+    fn check_free_sees_ipv6_only_listeners() {
+        let Ok(listener) = TcpListener::bind("[::1]:0") else {
+            return;
+        };
+        let port = listener.local_addr().unwrap().port();
+
+        let error = check_free(&[port]).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+    }
+
+    #[test]
+    fn check_free_reports_port_in_use() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let error = check_free(&[port]).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+        let message = error.to_string();
+        assert!(message.starts_with(&format!("port {} is already in use", port)));
+        #[cfg(target_os = "linux")]
+        assert!(
+            message.contains(&format!("PID {}", std::process::id())),
+            "{}",
+            message
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::VecDeque;
+
+use serde::Deserialize;
+
+use crate::event::AppEvent;
+
+/// Number of events a reader task queues while the event channel is full
+pub const QUEUE_CAPACITY: usize = 1000;
+
+/// What a reader task does when the UI can't keep up with its output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverflowPolicy {
+    /// Stop reading until the UI catches up (the process may block on write)
+    #[default]
+    Block,
+    /// Keep reading and discard the oldest queued lines, counting them
+    DropOldest,
+}
+
+/// Bounded queue of events waiting for room in the event channel
+pub struct OutputQueue {
+    events: VecDeque<AppEvent>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    dropped: u64,
+}
+
+impl OutputQueue {
+    /// Create an empty queue
+    pub fn new(policy: OverflowPolicy, capacity: usize) -> Self {
+        Self {
+            events: VecDeque::new(),
+            capacity,
+            policy,
+            dropped: 0,
+        }
+    }
+
+    /// Check if more events can be read into the queue
+    ///
+    /// Always true for `DropOldest`, which makes room by dropping.
+    pub fn accepts_more(&self) -> bool {
+        self.policy == OverflowPolicy::DropOldest || self.events.len() < self.capacity
+    }
+
+    /// Check if there is nothing left to send
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty() && self.dropped == 0
+    }
+
+    /// Queue an event, dropping the oldest one if the queue is full
+    pub fn push(&mut self, event: AppEvent) {
+        if self.events.len() >= self.capacity {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(event);
+    }
+
+    /// Take the next event to send
+    ///
+    /// Pending drops are reported first as `AppEvent::Dropped`, since the
+    /// dropped lines preceded everything still queued.
+    pub fn pop(&mut self, tab_index: usize) -> Option<AppEvent> {
+        if self.dropped > 0 {
+            let count = std::mem::take(&mut self.dropped);
+            return Some(AppEvent::Dropped { tab_index, count });
+        }
+        self.events.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::{OutputKind, OutputLine};
+
+    fn output(content: &str) -> AppEvent {
+        AppEvent::Output {
+            tab_index: 0,
+            line: OutputLine::new(OutputKind::Stdout, content.into()),
+        }
+    }
+
+    fn plain(event: Option<AppEvent>) -> String {
+        match event {
+            Some(AppEvent::Output { line, .. }) => line.plain(),
+            other => panic!("expected output, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn output_queue_block_stops_accepting_when_full() {
+        let mut queue = OutputQueue::new(OverflowPolicy::Block, 2);
+        queue.push(output("a"));
+        assert!(queue.accepts_more());
+        queue.push(output("b"));
+        assert!(!queue.accepts_more());
+    }
+
+    #[test]
+    fn output_queue_drop_oldest_reports_drops_before_remaining_lines() {
+        let mut queue = OutputQueue::new(OverflowPolicy::DropOldest, 2);
+        for content in ["a", "b", "c", "d"] {
+            assert!(queue.accepts_more());
+            queue.push(output(content));
+        }
+
+        assert!(matches!(
+            queue.pop(0),
+            Some(AppEvent::Dropped {
+                tab_index: 0,
+                count: 2
+            })
+        ));
+        assert_eq!(plain(queue.pop(0)), "c");
+        assert_eq!(plain(queue.pop(0)), "d");
+        assert!(queue.is_empty());
+    }
+}
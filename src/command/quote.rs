@@ -0,0 +1,66 @@
+/// Quote a string for a POSIX shell (`it's` → `'it'\''s'`)
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Join arguments into one shell command line, quoting only those that need it
+///
+/// `["npm", "run", "dev", "--", "--title", "my app"]` becomes
+/// `npm run dev -- --title 'my app'`.
+pub fn shell_join<S: AsRef<str>>(args: &[S]) -> String {
+    args.iter()
+        .map(|arg| {
+            let arg = arg.as_ref();
+            if !arg.is_empty() && arg.chars().all(is_shell_safe) {
+                arg.to_string()
+            } else {
+                shell_quote(arg)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Characters that never need quoting in a POSIX shell word
+fn is_shell_safe(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("npm run dev", "'npm run dev'")]
+    #[case("echo it's", "'echo it'\\''s'")]
+    fn shell_quote_escapes_single_quotes(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(shell_quote(input), expected);
+    }
+
+    #[rstest]
+    #[case(&["npm", "run", "dev"], "npm run dev")]
+    #[case(&["grep", "-r", "it's", "./src"], "grep -r 'it'\\''s' ./src")]
+    #[case(&["echo", "", "$HOME", "a b"], "echo '' '$HOME' 'a b'")]
+    #[case(&["curl", "-H", "Authorization: x", "http://h:80/?q=1"], "curl -H 'Authorization: x' 'http://h:80/?q=1'")]
+    fn shell_join_quotes_only_unsafe_args(#[case] args: &[&str], #[case] expected: &str) {
+        assert_eq!(shell_join(args), expected);
+    }
+
+    #[tokio::test]
+    async fn shell_join_round_trips_through_sh() {
+        let args = ["printf", "%s|", "it's", "a  b", "$HOME", "*", ""];
+
+        let output = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(shell_join(&args))
+            .output()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "it's|a  b|$HOME|*||"
+        );
+    }
+}
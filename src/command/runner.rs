@@ -1,12 +1,31 @@
+use std::io;
+use std::os::fd::OwnedFd;
 use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Instant;
 
-use tokio::io::{AsyncBufReadExt, BufReader};
+use nix::errno::Errno;
+#[cfg(target_os = "linux")]
+use nix::unistd::Pid;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::net::unix::pipe;
 use tokio::process::{Child, Command};
 use tokio::sync::mpsc;
 
 use crate::buffer::{OutputKind, OutputLine};
+use crate::command::CommandSpec;
+use crate::command::OverflowPolicy;
+use crate::command::detached;
+use crate::command::queue::{OutputQueue, QUEUE_CAPACITY};
+use crate::command::ssh::ssh_command;
+use crate::command::throttle::format_count;
+use crate::command::{Assembled, LineAssembler, Pipeline, Running, Source};
 use crate::event::AppEvent;
 
+/// Bytes read from a stream at a time
+const READ_CHUNK_SIZE: usize = 8192;
+
 /// Command execution manager
 pub struct CommandRunner;
 
@@ -14,7 +33,9 @@ impl CommandRunner {
     /// Spawn a shell command
     ///
     /// Executes the command using `sh -c "command"` format,
-    /// capturing stdout/stderr asynchronously.
+    /// capturing stdout/stderr asynchronously. With `merge_streams`, both
+    /// share one pipe and are read by a single reader task, which tags
+    /// lines that look like errors as stderr.
     ///
     /// The command is spawned in a new process group so that
     /// all child processes can be killed together.
@@ -22,57 +43,345 @@ impl CommandRunner {
     /// Events are sent directly to the provided channel.
     pub async fn spawn(
         event_tx: mpsc::Sender<AppEvent>,
-        command: &str,
+        spec: &CommandSpec,
         tab_index: usize,
     ) -> std::io::Result<Child> {
-        let mut cmd = Command::new("sh");
-        cmd.arg("-c")
-            .arg(command)
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            // Create a new process group with PGID = child PID
-            .process_group(0);
+        let cmd = Self::build_command(spec, tab_index)?;
+        Self::spawn_reading(event_tx, cmd, spec, ReaderOptions::new(spec, tab_index)).await
+    }
+
+    /// Spawn a hook command like [`CommandRunner::spawn`], tagging its
+    /// lines as hook output
+    ///
+    /// The hook's stdin is `stdin` rather than what the spec configures for
+    /// the tab's command.
+    pub async fn spawn_hook(
+        event_tx: mpsc::Sender<AppEvent>,
+        spec: &CommandSpec,
+        tab_index: usize,
+        stdin: Stdio,
+    ) -> std::io::Result<Child> {
+        let mut cmd = Self::build_command(spec, tab_index)?;
+        cmd.stdin(stdin);
+        let options = ReaderOptions {
+            hook: true,
+            ..ReaderOptions::new(spec, tab_index)
+        };
+        Self::spawn_reading(event_tx, cmd, spec, options).await
+    }
 
+    /// Spawn a built command and start the reader tasks for its output
+    async fn spawn_reading(
+        event_tx: mpsc::Sender<AppEvent>,
+        mut cmd: Command,
+        spec: &CommandSpec,
+        options: ReaderOptions,
+    ) -> std::io::Result<Child> {
+        let merged = if spec.merge_streams {
+            let (reader, writer) = io::pipe()?;
+            cmd.stdout(writer.try_clone()?).stderr(writer);
+            Some(pipe::Receiver::from_owned_fd(OwnedFd::from(reader))?)
+        } else {
+            None
+        };
         let mut child = cmd.spawn()?;
+        // Close our copies of the write end so the reader sees EOF on exit
+        drop(cmd);
+
+        // Capture the merged stream
+        if let Some(merged) = merged {
+            tokio::spawn(Self::read_lines(
+                merged,
+                OutputKind::Stdout,
+                options,
+                event_tx,
+            ));
+            return Ok(child);
+        }
 
         // Capture stdout
         if let Some(stdout) = child.stdout.take() {
             let tx = event_tx.clone();
-            tokio::spawn(async move {
-                let reader = BufReader::new(stdout);
-                let mut lines = reader.lines();
-                while let Ok(Some(line)) = lines.next_line().await {
-                    let event = AppEvent::Output {
-                        tab_index,
-                        line: OutputLine::new(OutputKind::Stdout, line),
-                    };
-                    if tx.send(event).await.is_err() {
-                        break;
-                    }
-                }
-            });
+            tokio::spawn(Self::read_lines(
+                stdout,
+                OutputKind::Stdout,
+                options.clone(),
+                tx,
+            ));
         }
 
         // Capture stderr
         if let Some(stderr) = child.stderr.take() {
             let tx = event_tx.clone();
-            tokio::spawn(async move {
-                let reader = BufReader::new(stderr);
-                let mut lines = reader.lines();
-                while let Ok(Some(line)) = lines.next_line().await {
-                    let event = AppEvent::Output {
-                        tab_index,
-                        line: OutputLine::new(OutputKind::Stderr, line),
+            tokio::spawn(Self::read_lines(stderr, OutputKind::Stderr, options, tx));
+        }
+
+        Ok(child)
+    }
+
+    /// Read lines from a stream and send them as output events
+    ///
+    /// Bytes are split into lines by a [`LineAssembler`], which also applies
+    /// the throttle: lines over the per-second limit are dropped and reported
+    /// as a "dropped N lines" divider once lines pass again. Lines are queued
+    /// while the event channel is full; the overflow policy decides whether
+    /// reading pauses or the oldest queued lines are dropped.
+    pub(super) async fn read_lines(
+        mut stream: impl AsyncRead + Unpin,
+        kind: OutputKind,
+        options: ReaderOptions,
+        tx: mpsc::Sender<AppEvent>,
+    ) {
+        let tab_index = options.tab_index;
+        let mut assembler = LineAssembler::new(options.max_lines_per_sec, Instant::now());
+        let mut queue = OutputQueue::new(options.overflow, QUEUE_CAPACITY);
+        let mut chunk = vec![0; READ_CHUNK_SIZE];
+
+        loop {
+            // Move complete lines into the queue while it has room
+            while queue.accepts_more()
+                && let Some(item) = assembler.next(Instant::now())
+            {
+                queue.push(output_event(item, kind, &options));
+            }
+
+            tokio::select! {
+                // Prefer delivering queued events over reading new lines
+                biased;
+
+                permit = tx.reserve(), if !queue.is_empty() => {
+                    let Ok(permit) = permit else {
+                        return;
                     };
-                    if tx.send(event).await.is_err() {
-                        break;
+                    if let Some(event) = queue.pop(tab_index) {
+                        permit.send(event);
                     }
                 }
-            });
+
+                read = stream.read(&mut chunk), if queue.accepts_more() => match read {
+                    Ok(0) | Err(_) => break,
+                    Ok(len) => assembler.push(&chunk[..len]),
+                },
+            }
         }
 
-        Ok(child)
+        // The last line may lack a line ending
+        assembler.finish();
+        while let Some(item) = assembler.next(Instant::now()) {
+            queue.push(output_event(item, kind, &options));
+        }
+
+        // Deliver everything still queued
+        while let Some(event) = queue.pop(tab_index) {
+            if tx.send(event).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Name of the program [`build_command`](Self::build_command) runs for a spec
+    pub fn program(spec: &CommandSpec) -> &'static str {
+        if spec.ssh.is_some() {
+            "ssh"
+        } else if spec.has_resource_limits() {
+            "systemd-run"
+        } else {
+            "sh"
+        }
+    }
+
+    /// Build the process command for a spec
+    ///
+    /// Applies the working directory, environment variables, niceness,
+    /// CPU affinity and cgroup limits from the spec. With `ssh`, the
+    /// command runs through `ssh -tt` on the remote host instead.
+    fn build_command(spec: &CommandSpec, tab_index: usize) -> io::Result<Command> {
+        let mut cmd = if let Some(destination) = &spec.ssh {
+            ssh_command(spec, destination)
+        } else if spec.has_resource_limits() {
+            Self::limited_command(spec, tab_index)?
+        } else {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c").arg(&spec.command);
+            cmd
+        };
+        // A remote command gets its directory and environment on the remote host
+        if spec.ssh.is_none() {
+            cmd.envs(&spec.env);
+            if let Some(cwd) = &spec.cwd {
+                cmd.current_dir(cwd);
+            }
+        }
+        // Commands fed by another tab or keys get their stdin written by the App
+        let stdin = if spec.pipes_stdin() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        };
+        cmd.env(detached::TAG_VAR, detached::tag(tab_index))
+            .stdin(stdin)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            // Create a new process group with PGID = child PID
+            .process_group(0);
+
+        let nice = spec.nice;
+        let cpus = spec.cpu_affinity.as_deref().map(cpu_set).transpose()?;
+        if nice.is_some() || cpus.is_some() {
+            // SAFETY: the hook runs between fork and exec and only calls
+            // async-signal-safe functions (nice, sched_setaffinity).
+            unsafe {
+                cmd.pre_exec(move || {
+                    if let Some(increment) = nice {
+                        set_nice(increment)?;
+                    }
+                    if let Some(cpus) = &cpus {
+                        set_affinity(cpus)?;
+                    }
+                    Ok(())
+                });
+            }
+        }
+        Ok(cmd)
+    }
+
+    /// Build a command that runs inside a cgroup with the spec's limits
+    #[cfg(all(feature = "cgroups", target_os = "linux"))]
+    fn limited_command(spec: &CommandSpec, tab_index: usize) -> io::Result<Command> {
+        Ok(super::cgroup::scoped_command(spec, tab_index))
+    }
+
+    #[cfg(not(all(feature = "cgroups", target_os = "linux")))]
+    fn limited_command(_spec: &CommandSpec, _tab_index: usize) -> io::Result<Command> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "memory_max/cpu_quota need the cgroups feature (Linux only)",
+        ))
+    }
+}
+
+impl Source for CommandRunner {
+    async fn start(
+        &self,
+        event_tx: mpsc::Sender<AppEvent>,
+        spec: &CommandSpec,
+        tab_index: usize,
+    ) -> io::Result<Running> {
+        Self::spawn(event_tx, spec, tab_index)
+            .await
+            .map(Running::Process)
+    }
+}
+
+/// Per-command options for reader tasks
+#[derive(Clone)]
+pub(super) struct ReaderOptions {
+    pub(super) tab_index: usize,
+    max_lines_per_sec: Option<u32>,
+    overflow: OverflowPolicy,
+    /// Tag lines that look like errors as stderr (for merged streams)
+    guess_stderr: bool,
+    /// Tag every line as hook output
+    hook: bool,
+    /// Transformers applied to every line
+    pipeline: Option<Arc<Pipeline>>,
+    /// Characters shown before a line is cut (`0` for no limit)
+    max_line_length: usize,
+}
+
+impl ReaderOptions {
+    /// Options for reading a tab's streams as configured in its spec
+    pub(super) fn new(spec: &CommandSpec, tab_index: usize) -> Self {
+        Self {
+            tab_index,
+            max_lines_per_sec: spec.max_lines_per_sec,
+            overflow: spec.overflow.unwrap_or_default(),
+            guess_stderr: spec.merge_streams,
+            hook: false,
+            pipeline: Pipeline::new(spec).map(Arc::new),
+            max_line_length: spec.line_limit(),
+        }
+    }
+}
+
+/// Raise (or, with privileges, lower) the niceness of the calling process
+fn set_nice(increment: i32) -> io::Result<()> {
+    // nice() may legitimately return -1, so errors are told apart via errno
+    Errno::clear();
+    // SAFETY: nice() has no memory-safety preconditions
+    if unsafe { nix::libc::nice(increment) } == -1 && Errno::last_raw() != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+type CpuSet = nix::sched::CpuSet;
+
+#[cfg(not(target_os = "linux"))]
+type CpuSet = ();
+
+/// Build the CPU set for `cpu_affinity`, rejecting CPUs out of range
+#[cfg(target_os = "linux")]
+fn cpu_set(cpus: &[usize]) -> io::Result<CpuSet> {
+    let mut set = CpuSet::new();
+    for &cpu in cpus {
+        set.set(cpu).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid CPU in cpu_affinity: {}", cpu),
+            )
+        })?;
+    }
+    Ok(set)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cpu_set(_cpus: &[usize]) -> io::Result<CpuSet> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "cpu_affinity is only supported on Linux",
+    ))
+}
+
+/// Restrict the calling process to the given CPUs
+#[cfg(target_os = "linux")]
+fn set_affinity(cpus: &CpuSet) -> io::Result<()> {
+    nix::sched::sched_setaffinity(Pid::from_raw(0), cpus).map_err(io::Error::from)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_affinity(_cpus: &CpuSet) -> io::Result<()> {
+    Ok(())
+}
+
+/// Build the event for a line (or dropped count) from a reader's assembler
+fn output_event(item: Assembled, kind: OutputKind, options: &ReaderOptions) -> AppEvent {
+    let tab_index = options.tab_index;
+    match item {
+        Assembled::Line(text) => {
+            let text = match &options.pipeline {
+                Some(pipeline) => pipeline.apply(text),
+                None => text,
+            };
+            let mut line = OutputLine::with_limit(kind, text, options.max_line_length);
+            if options.hook {
+                line.kind = OutputKind::Hook;
+            } else if options.guess_stderr && line.severity().is_some() {
+                line.kind = OutputKind::Stderr;
+            }
+            AppEvent::Output { tab_index, line }
+        }
+        Assembled::Dropped(dropped) => dropped_divider(tab_index, dropped),
+    }
+}
+
+/// Build a "dropped N lines" divider event for throttled lines
+pub(super) fn dropped_divider(tab_index: usize, dropped: u64) -> AppEvent {
+    let label = format!("dropped {} lines", format_count(dropped));
+    AppEvent::Output {
+        tab_index,
+        line: OutputLine::divider(&label),
     }
 }
 
@@ -85,7 +394,7 @@ mod tests {
         let (tx, _rx) = mpsc::channel(100);
         // sh -c will still succeed even with invalid command
         // but the command itself will fail
-        let result = CommandRunner::spawn(tx, "/nonexistent/command", 0).await;
+        let result = CommandRunner::spawn(tx, &CommandSpec::new("/nonexistent/command"), 0).await;
         // spawn succeeds because sh exists
         assert!(result.is_ok());
     }
@@ -93,7 +402,9 @@ mod tests {
     #[tokio::test]
     async fn command_runner_captures_stdout() {
         let (tx, mut rx) = mpsc::channel(100);
-        let _child = CommandRunner::spawn(tx, "echo hello", 0).await.unwrap();
+        let _child = CommandRunner::spawn(tx, &CommandSpec::new("echo hello"), 0)
+            .await
+            .unwrap();
 
         let mut found_hello = false;
         while let Some(event) = rx.recv().await {
@@ -108,10 +419,29 @@ mod tests {
         assert!(found_hello, "Expected to find 'hello' in stdout");
     }
 
+    #[tokio::test]
+    async fn command_runner_masks_secrets() {
+        let spec = CommandSpec {
+            env: [("API_TOKEN".to_string(), "tok-12345".to_string())].into(),
+            mask_env: vec!["API_TOKEN".to_string()],
+            mask: vec!["ghp_\\w+".parse().unwrap()],
+            ..CommandSpec::new("echo \"auth $API_TOKEN ghp_abc\" >&2")
+        };
+        let (tx, mut rx) = mpsc::channel(100);
+        let _child = CommandRunner::spawn(tx, &spec, 0).await.unwrap();
+
+        let Some(AppEvent::Output { line, .. }) = rx.recv().await else {
+            panic!("expected an output line");
+        };
+        assert_eq!(line.plain(), "auth **** ****");
+    }
+
     #[tokio::test]
     async fn command_runner_captures_stderr() {
         let (tx, mut rx) = mpsc::channel(100);
-        let _child = CommandRunner::spawn(tx, "echo error >&2", 0).await.unwrap();
+        let _child = CommandRunner::spawn(tx, &CommandSpec::new("echo error >&2"), 0)
+            .await
+            .unwrap();
 
         let mut found_error = false;
         while let Some(event) = rx.recv().await {
@@ -126,12 +456,42 @@ mod tests {
         assert!(found_error, "Expected to find 'error' in stderr");
     }
 
+    #[tokio::test]
+    async fn command_runner_merge_streams_keeps_write_order() {
+        let spec = CommandSpec {
+            merge_streams: true,
+            ..CommandSpec::new("echo out1; echo err >&2; echo out2; echo 'error: bad' >&2")
+        };
+        let (tx, mut rx) = mpsc::channel(100);
+        let _child = CommandRunner::spawn(tx, &spec, 0).await.unwrap();
+
+        let mut lines = Vec::new();
+        while let Some(event) = rx.recv().await {
+            if let AppEvent::Output { line, .. } = event {
+                lines.push((line.plain(), line.kind));
+            }
+        }
+        assert_eq!(
+            lines,
+            vec![
+                ("out1".to_string(), OutputKind::Stdout),
+                ("err".into(), OutputKind::Stdout),
+                ("out2".into(), OutputKind::Stdout),
+                ("error: bad".into(), OutputKind::Stderr),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn command_runner_captures_multiple_lines() {
         let (tx, mut rx) = mpsc::channel(100);
-        let _child = CommandRunner::spawn(tx, "echo line1; echo line2; echo line3", 0)
-            .await
-            .unwrap();
+        let _child = CommandRunner::spawn(
+            tx,
+            &CommandSpec::new("echo line1; echo line2; echo line3"),
+            0,
+        )
+        .await
+        .unwrap();
 
         let mut lines = Vec::new();
         while let Some(event) = rx.recv().await {
@@ -145,17 +505,115 @@ mod tests {
         assert_eq!(lines, vec!["line1", "line2", "line3"]);
     }
 
+    #[test]
+    fn command_runner_build_command_applies_cwd_and_env() {
+        let spec = CommandSpec {
+            cwd: Some("/tmp".into()),
+            env: [("FOO".to_string(), "bar".to_string())].into(),
+            ..CommandSpec::new("echo $FOO")
+        };
+
+        let cmd = CommandRunner::build_command(&spec, 0).unwrap();
+        let std_cmd = cmd.as_std();
+
+        assert_eq!(std_cmd.get_program(), "sh");
+        assert_eq!(
+            std_cmd.get_args().collect::<Vec<_>>(),
+            vec!["-c", "echo $FOO"]
+        );
+        assert_eq!(
+            std_cmd.get_current_dir(),
+            Some(std::path::Path::new("/tmp"))
+        );
+        assert!(
+            std_cmd
+                .get_envs()
+                .any(|(k, v)| k == "FOO" && v == Some("bar".as_ref()))
+        );
+    }
+
+    #[tokio::test]
+    async fn command_runner_spawn_applies_env() {
+        let (tx, mut rx) = mpsc::channel(100);
+        let spec = CommandSpec {
+            env: [("PARALLELS_TEST".to_string(), "value".to_string())].into(),
+            ..CommandSpec::new("echo $PARALLELS_TEST")
+        };
+        let _child = CommandRunner::spawn(tx, &spec, 0).await.unwrap();
+
+        let mut found = false;
+        while let Some(event) = rx.recv().await {
+            if let AppEvent::Output { line, .. } = event
+                && line.plain() == "value"
+            {
+                found = true;
+                break;
+            }
+        }
+        assert!(found, "Expected env var to be visible to the command");
+    }
+
+    #[tokio::test]
+    async fn command_runner_spawn_applies_nice() {
+        let (tx, mut rx) = mpsc::channel(100);
+        let spec = CommandSpec {
+            nice: Some(5),
+            ..CommandSpec::new("nice")
+        };
+        let _child = CommandRunner::spawn(tx, &spec, 0).await.unwrap();
+
+        let base = String::from_utf8(std::process::Command::new("nice").output().unwrap().stdout)
+            .unwrap()
+            .trim()
+            .parse::<i32>()
+            .unwrap();
+        let mut niceness = None;
+        while let Some(event) = rx.recv().await {
+            if let AppEvent::Output { line, .. } = event {
+                niceness = line.plain().parse::<i32>().ok();
+                break;
+            }
+        }
+        assert_eq!(niceness, Some((base + 5).min(19)));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn command_runner_build_command_rejects_invalid_cpu() {
+        let spec = CommandSpec {
+            cpu_affinity: Some(vec![usize::MAX]),
+            ..CommandSpec::new("true")
+        };
+        let err = CommandRunner::build_command(&spec, 0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[cfg(not(feature = "cgroups"))]
+    #[test]
+    fn command_runner_build_command_rejects_limits_without_cgroups_feature() {
+        let spec = CommandSpec {
+            memory_max: Some("512M".into()),
+            ..CommandSpec::new("true")
+        };
+        let err = CommandRunner::build_command(&spec, 0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+
     #[tokio::test]
     async fn command_runner_child_has_pid() {
         let (tx, _rx) = mpsc::channel(100);
-        let child = CommandRunner::spawn(tx, "sleep 0.1", 0).await.unwrap();
+        let child = CommandRunner::spawn(tx, &CommandSpec::new("sleep 0.1"), 0)
+            .await
+            .unwrap();
         assert!(child.id().is_some());
     }
 
     #[tokio::test]
     async fn command_runner_child_can_be_killed() {
         let (tx, _rx) = mpsc::channel(100);
-        let mut child = CommandRunner::spawn(tx, "sleep 10", 0).await.unwrap();
+        let mut child = CommandRunner::spawn(tx, &CommandSpec::new("sleep 10"), 0)
+            .await
+            .unwrap();
         let pid = child.id();
         assert!(pid.is_some());
 
@@ -166,7 +624,9 @@ mod tests {
     #[tokio::test]
     async fn command_runner_child_wait_returns_exit_code_success() {
         let (tx, _rx) = mpsc::channel(100);
-        let mut child = CommandRunner::spawn(tx, "exit 0", 0).await.unwrap();
+        let mut child = CommandRunner::spawn(tx, &CommandSpec::new("exit 0"), 0)
+            .await
+            .unwrap();
         let status = child.wait().await.unwrap();
         assert_eq!(status.code(), Some(0));
     }
@@ -174,8 +634,96 @@ mod tests {
     #[tokio::test]
     async fn command_runner_child_wait_returns_exit_code_failure() {
         let (tx, _rx) = mpsc::channel(100);
-        let mut child = CommandRunner::spawn(tx, "exit 42", 0).await.unwrap();
+        let mut child = CommandRunner::spawn(tx, &CommandSpec::new("exit 42"), 0)
+            .await
+            .unwrap();
         let status = child.wait().await.unwrap();
         assert_eq!(status.code(), Some(42));
     }
+
+    #[tokio::test]
+    async fn command_runner_throttles_lines_and_reports_dropped() {
+        let (tx, mut rx) = mpsc::channel(2000);
+        let spec = CommandSpec {
+            max_lines_per_sec: Some(10),
+            ..CommandSpec::new("seq 1 1000")
+        };
+        let _child = CommandRunner::spawn(tx, &spec, 0).await.unwrap();
+
+        let mut lines = Vec::new();
+        while let Some(event) = rx.recv().await {
+            if let AppEvent::Output { line, .. } = event {
+                lines.push(line);
+            }
+        }
+
+        let passed = lines
+            .iter()
+            .filter(|l| l.kind == OutputKind::Stdout)
+            .count();
+        let dropped: Vec<String> = lines
+            .iter()
+            .filter(|l| l.kind == OutputKind::Divider)
+            .map(|l| l.plain())
+            .collect();
+        assert_eq!(passed, 10);
+        assert_eq!(dropped, vec!["── dropped 990 lines ──".to_string()]);
+    }
+
+    /// Spawn `seq 1 5000` against a one-slot channel and drain it after exit
+    async fn collect_with_slow_consumer(overflow: OverflowPolicy) -> Vec<AppEvent> {
+        let (tx, mut rx) = mpsc::channel(1);
+        let spec = CommandSpec {
+            overflow: Some(overflow),
+            ..CommandSpec::new("seq 1 5000")
+        };
+        let mut child = CommandRunner::spawn(tx, &spec, 0).await.unwrap();
+        if overflow == OverflowPolicy::DropOldest {
+            // The reader keeps consuming while nobody receives
+            child.wait().await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+
+        let mut events = Vec::new();
+        while let Some(event) = rx.recv().await {
+            events.push(event);
+        }
+        events
+    }
+
+    fn output_lines(events: &[AppEvent]) -> Vec<String> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                AppEvent::Output { line, .. } => Some(line.plain()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn command_runner_block_policy_delivers_every_line() {
+        let events = collect_with_slow_consumer(OverflowPolicy::Block).await;
+
+        let lines = output_lines(&events);
+        assert_eq!(lines.len(), 5000);
+        assert_eq!(lines.last().map(String::as_str), Some("5000"));
+    }
+
+    #[tokio::test]
+    async fn command_runner_drop_oldest_policy_reports_dropped_lines() {
+        let events = collect_with_slow_consumer(OverflowPolicy::DropOldest).await;
+
+        let dropped: u64 = events
+            .iter()
+            .map(|event| match event {
+                AppEvent::Dropped { count, .. } => *count,
+                _ => 0,
+            })
+            .sum();
+        let lines = output_lines(&events);
+        assert!(dropped > 0);
+        assert_eq!(lines.len() as u64 + dropped, 5000);
+        assert_eq!(lines.last().map(String::as_str), Some("5000"));
+    }
 }
@@ -0,0 +1,358 @@
+use std::io;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use nix::errno::Errno;
+use tokio::io::AsyncRead;
+use tokio::net::{TcpListener, UnixListener};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+
+use crate::buffer::{OutputKind, OutputLine};
+use crate::command::runner::ReaderOptions;
+use crate::command::{CommandRunner, CommandSpec, Endpoint, Running, Source};
+use crate::event::AppEvent;
+
+/// Source for tabs with a `source` address: a FIFO, or a Unix or TCP
+/// socket accepting any number of writers
+///
+/// Each connection is read like a process's stdout and announced with a
+/// divider line when it opens and closes.
+pub struct SocketSource;
+
+impl Source for SocketSource {
+    async fn start(
+        &self,
+        event_tx: mpsc::Sender<AppEvent>,
+        spec: &CommandSpec,
+        tab_index: usize,
+    ) -> io::Result<Running> {
+        let endpoint = spec
+            .source
+            .as_ref()
+            .map(|source| source.endpoint().clone())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no source address"))?;
+        let options = ReaderOptions::new(spec, tab_index);
+
+        let listener = match endpoint {
+            Endpoint::Fifo(path) => {
+                let fifo = open_fifo(&path)?;
+                let task = CommandRunner::read_lines(fifo, OutputKind::Stdout, options, event_tx);
+                return Ok(Running::Task(tokio::spawn(task)));
+            }
+            Endpoint::Unix(path) => {
+                remove_stale_socket(&path)?;
+                let listener = UnixListener::bind(&path)?;
+                Listener::Unix {
+                    listener,
+                    _file: SocketFile(path),
+                }
+            }
+            Endpoint::Tcp(addr) => Listener::Tcp(TcpListener::bind(addr).await?),
+        };
+        Ok(Running::Task(tokio::spawn(serve(
+            listener, options, event_tx,
+        ))))
+    }
+}
+
+/// Pause before accepting again after running out of file descriptors or memory
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// A bound socket accepting writers
+enum Listener {
+    Tcp(TcpListener),
+    Unix {
+        listener: UnixListener,
+        /// Removes the socket file when the listener stops
+        _file: SocketFile,
+    },
+}
+
+impl Listener {
+    /// Wait for the next writer, returning its stream and a description
+    async fn accept(&self) -> io::Result<(Box<dyn AsyncRead + Unpin + Send>, String)> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, peer) = listener.accept().await?;
+                Ok((Box::new(stream), peer.to_string()))
+            }
+            Listener::Unix { listener, .. } => {
+                let (stream, _) = listener.accept().await?;
+                Ok((Box::new(stream), "unix socket".to_string()))
+            }
+        }
+    }
+}
+
+/// Removes a Unix socket's file on drop
+struct SocketFile(PathBuf);
+
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// How `serve` goes on after a failed accept
+#[derive(Debug, PartialEq, Eq)]
+enum AcceptFailure {
+    /// Only the connection being accepted failed: accept the next one
+    Connection,
+    /// Out of file descriptors or memory: wait for some to be freed
+    Resources,
+    /// The listener itself is broken: stop
+    Listener,
+}
+
+impl AcceptFailure {
+    fn of(error: &io::Error) -> Self {
+        match error.raw_os_error().map(Errno::from_raw) {
+            Some(Errno::EMFILE | Errno::ENFILE | Errno::ENOBUFS | Errno::ENOMEM) => {
+                AcceptFailure::Resources
+            }
+            // Network errors of the pending connection (see accept(2))
+            Some(
+                Errno::ECONNABORTED
+                | Errno::ECONNRESET
+                | Errno::EINTR
+                | Errno::EPROTO
+                | Errno::EPERM
+                | Errno::ENETDOWN
+                | Errno::ENETUNREACH
+                | Errno::EHOSTDOWN
+                | Errno::EHOSTUNREACH
+                | Errno::ENOPROTOOPT
+                | Errno::ENONET
+                | Errno::ETIMEDOUT,
+            ) => AcceptFailure::Connection,
+            _ => AcceptFailure::Listener,
+        }
+    }
+}
+
+/// Accept writers until aborted, reading each one's lines into the tab
+///
+/// Connection readers live in a `JoinSet`, so aborting this task also
+/// stops them. While accepting fails for lack of resources, a divider says
+/// so and accepting is retried after [`ACCEPT_BACKOFF`]; if the listener
+/// itself fails, the tab's run fails.
+async fn serve(listener: Listener, options: ReaderOptions, tx: mpsc::Sender<AppEvent>) {
+    let tab_index = options.tab_index;
+    let mut connections = JoinSet::new();
+    let mut backing_off = false;
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, peer) = match accepted {
+                    Ok(accepted) => accepted,
+                    Err(e) => match AcceptFailure::of(&e) {
+                        AcceptFailure::Connection => continue,
+                        AcceptFailure::Resources => {
+                            if !backing_off {
+                                backing_off = true;
+                                let label = format!("accept failed: {} - retrying", e);
+                                let _ = tx.send(divider(tab_index, &label)).await;
+                            }
+                            tokio::time::sleep(ACCEPT_BACKOFF).await;
+                            continue;
+                        }
+                        AcceptFailure::Listener => {
+                            let reason = format!("accept failed: {}", e);
+                            let _ = tx.send(AppEvent::Failed { tab_index, reason }).await;
+                            return;
+                        }
+                    },
+                };
+                if backing_off {
+                    backing_off = false;
+                    let _ = tx.send(divider(tab_index, "accepting connections again")).await;
+                }
+                let tx = tx.clone();
+                let options = options.clone();
+                connections.spawn(async move {
+                    let _ = tx.send(divider(tab_index, &format!("{} connected", peer))).await;
+                    CommandRunner::read_lines(stream, OutputKind::Stdout, options, tx.clone()).await;
+                    let _ = tx.send(divider(tab_index, &format!("{} disconnected", peer))).await;
+                });
+            }
+            // Reap finished connections
+            Some(_) = connections.join_next() => {}
+        }
+    }
+}
+
+/// Build a divider line event
+fn divider(tab_index: usize, label: &str) -> AppEvent {
+    AppEvent::Output {
+        tab_index,
+        line: OutputLine::divider(label),
+    }
+}
+
+/// Remove a socket file left behind by an earlier run, refusing other files
+fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path),
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and is not a socket", path.display()),
+        )),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Open a FIFO for reading, creating it if missing
+///
+/// It is opened read-write so that it never reports end of file, and
+/// writers can come and go.
+#[cfg(target_os = "linux")]
+fn open_fifo(path: &Path) -> io::Result<tokio::net::unix::pipe::Receiver> {
+    use nix::sys::stat::Mode;
+
+    if !path.exists() {
+        nix::unistd::mkfifo(path, Mode::S_IRUSR | Mode::S_IWUSR)?;
+    }
+    tokio::net::unix::pipe::OpenOptions::new()
+        .read_write(true)
+        .open_receiver(path)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_fifo(_path: &Path) -> io::Result<tokio::net::unix::pipe::Receiver> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "fifo sources are only supported on Linux",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::{TcpStream, UnixStream};
+
+    use super::*;
+
+    /// Receive the next output line's text, waiting at most a second
+    async fn next_line(rx: &mut mpsc::Receiver<AppEvent>) -> Option<String> {
+        let event = tokio::time::timeout(Duration::from_secs(1), rx.recv()).await;
+        match event {
+            Ok(Some(AppEvent::Output { line, .. })) => Some(line.plain()),
+            _ => None,
+        }
+    }
+
+    #[rstest::rstest]
+    #[case(Errno::EMFILE, AcceptFailure::Resources)]
+    #[case(Errno::ECONNABORTED, AcceptFailure::Connection)]
+    #[case(Errno::EBADF, AcceptFailure::Listener)]
+    fn accept_failure_tells_retries_from_broken_listeners(
+        #[case] errno: Errno,
+        #[case] expected: AcceptFailure,
+    ) {
+        let error = io::Error::from_raw_os_error(errno as i32);
+        assert_eq!(AcceptFailure::of(&error), expected);
+    }
+
+    fn spec(source: &str) -> CommandSpec {
+        CommandSpec {
+            source: Some(source.parse().unwrap()),
+            ..CommandSpec::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn socket_source_reads_tcp_connections() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        let (tx, mut rx) = mpsc::channel(100);
+
+        let Running::Task(task) = SocketSource
+            .start(tx, &spec(&format!("listen://{}", addr)), 0)
+            .await
+            .unwrap()
+        else {
+            panic!("expected a task");
+        };
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(b"hello\n").await.unwrap();
+        drop(stream);
+
+        let connected = next_line(&mut rx).await.unwrap();
+        assert!(connected.contains("connected"), "{}", connected);
+        assert_eq!(next_line(&mut rx).await.as_deref(), Some("hello"));
+        let disconnected = next_line(&mut rx).await.unwrap();
+        assert!(disconnected.contains("disconnected"), "{}", disconnected);
+        task.abort();
+    }
+
+    #[tokio::test]
+    async fn socket_source_reads_unix_socket_and_removes_it_when_stopped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.sock");
+        let (tx, mut rx) = mpsc::channel(100);
+
+        let Running::Task(task) = SocketSource
+            .start(tx, &spec(&format!("unix://{}", path.display())), 0)
+            .await
+            .unwrap()
+        else {
+            panic!("expected a task");
+        };
+        let mut stream = UnixStream::connect(&path).await.unwrap();
+        stream.write_all(b"from unix\n").await.unwrap();
+
+        assert_eq!(
+            next_line(&mut rx).await.as_deref(),
+            Some("── unix socket connected ──")
+        );
+        assert_eq!(next_line(&mut rx).await.as_deref(), Some("from unix"));
+
+        task.abort();
+        let _ = task.await;
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn remove_stale_socket_refuses_regular_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("not-a-socket");
+        std::fs::write(&path, "data").unwrap();
+
+        let err = remove_stale_socket(&path).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert!(path.exists());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn socket_source_reads_fifo_across_writers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.pipe");
+        let (tx, mut rx) = mpsc::channel(100);
+
+        let Running::Task(task) = SocketSource
+            .start(tx, &spec(&format!("fifo://{}", path.display())), 0)
+            .await
+            .unwrap()
+        else {
+            panic!("expected a task");
+        };
+        for text in ["first writer\n", "second writer\n"] {
+            let mut writer = tokio::net::unix::pipe::OpenOptions::new()
+                .open_sender(&path)
+                .unwrap();
+            writer.write_all(text.as_bytes()).await.unwrap();
+        }
+
+        assert_eq!(next_line(&mut rx).await.as_deref(), Some("first writer"));
+        assert_eq!(next_line(&mut rx).await.as_deref(), Some("second writer"));
+        task.abort();
+    }
+}
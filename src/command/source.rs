@@ -0,0 +1,152 @@
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use thiserror::Error;
+use tokio::process::Child;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::command::CommandSpec;
+use crate::event::AppEvent;
+
+/// Where a tab's output comes from
+///
+/// Implemented by `CommandRunner` (a process), `FileTailer` (a followed
+/// file) and `SocketSource` (a FIFO or socket). All of them send lines as
+/// `AppEvent::Output` for the tab.
+pub trait Source {
+    /// Start producing output for a tab
+    fn start(
+        &self,
+        event_tx: mpsc::Sender<AppEvent>,
+        spec: &CommandSpec,
+        tab_index: usize,
+    ) -> impl Future<Output = io::Result<Running>> + Send;
+}
+
+/// A started source
+pub enum Running {
+    /// A child process, reaped by a `ChildWaiter`
+    Process(Child),
+    /// A task reading the source, which runs until it is aborted
+    Task(JoinHandle<()>),
+}
+
+/// Error for a `source` that isn't a supported address
+#[derive(Debug, Error)]
+#[error("invalid source {0:?}: expected fifo://PATH, unix://PATH or listen://HOST:PORT")]
+pub struct InvalidSource(String);
+
+/// Endpoint a tab reads from instead of running a command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    /// A named pipe, created if missing (`fifo://PATH`)
+    Fifo(PathBuf),
+    /// A Unix socket to listen on (`unix://PATH`)
+    Unix(PathBuf),
+    /// A TCP address to listen on (`listen://HOST:PORT`)
+    Tcp(String),
+}
+
+/// Address of a `source`, e.g. `listen://0.0.0.0:5000`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct SourceAddr {
+    /// The address as written, used as the tab's name
+    url: String,
+    endpoint: Endpoint,
+}
+
+impl SourceAddr {
+    /// Get the address as written
+    pub fn as_str(&self) -> &str {
+        &self.url
+    }
+
+    /// Get the endpoint to read from
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
+    }
+
+    /// Resolve a relative FIFO or socket path against a directory
+    pub fn resolve_against(&mut self, base_dir: &Path) {
+        let (scheme, path) = match &mut self.endpoint {
+            Endpoint::Fifo(path) => ("fifo", path),
+            Endpoint::Unix(path) => ("unix", path),
+            Endpoint::Tcp(_) => return,
+        };
+        if path.is_relative() {
+            *path = base_dir.join(&*path);
+            self.url = format!("{}://{}", scheme, path.display());
+        }
+    }
+}
+
+impl TryFrom<String> for SourceAddr {
+    type Error = InvalidSource;
+
+    fn try_from(url: String) -> Result<Self, Self::Error> {
+        let endpoint = match url.split_once("://") {
+            Some(("fifo", path)) if !path.is_empty() => Endpoint::Fifo(path.into()),
+            Some(("unix", path)) if !path.is_empty() => Endpoint::Unix(path.into()),
+            Some(("listen", addr)) if addr.contains(':') => Endpoint::Tcp(addr.to_string()),
+            _ => return Err(InvalidSource(url)),
+        };
+        Ok(Self { url, endpoint })
+    }
+}
+
+impl std::str::FromStr for SourceAddr {
+    type Err = InvalidSource;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s.to_string())
+    }
+}
+
+impl fmt::Display for SourceAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("fifo:///tmp/app.pipe", Endpoint::Fifo("/tmp/app.pipe".into()))]
+    #[case("unix://app.sock", Endpoint::Unix("app.sock".into()))]
+    #[case("listen://0.0.0.0:5000", Endpoint::Tcp("0.0.0.0:5000".into()))]
+    fn source_addr_parses_endpoints(#[case] url: &str, #[case] expected: Endpoint) {
+        let addr: SourceAddr = url.parse().unwrap();
+
+        assert_eq!(addr.endpoint(), &expected);
+        assert_eq!(addr.as_str(), url);
+    }
+
+    #[rstest]
+    #[case("/tmp/app.pipe")]
+    #[case("fifo://")]
+    #[case("listen://5000")]
+    #[case("http://localhost:5000")]
+    fn source_addr_rejects_unknown_addresses(#[case] url: &str) {
+        let err = url.parse::<SourceAddr>().unwrap_err();
+
+        assert!(err.to_string().contains(url));
+    }
+
+    #[test]
+    fn source_addr_resolves_relative_paths() {
+        let mut addr: SourceAddr = "unix://run/app.sock".parse().unwrap();
+
+        addr.resolve_against(Path::new("/srv"));
+
+        assert_eq!(addr.endpoint(), &Endpoint::Unix("/srv/run/app.sock".into()));
+        assert_eq!(addr.as_str(), "unix:///srv/run/app.sock");
+    }
+}
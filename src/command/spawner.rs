@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+use std::io;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use tokio::sync::mpsc;
+
+use crate::buffer::{OutputKind, OutputLine};
+use crate::command::{CommandRunner, CommandSpec, Pipeline, Running, Source};
+use crate::event::AppEvent;
+
+/// Starts the commands of an `App`'s tabs
+///
+/// `CommandRunner` runs real processes; `ScriptedSpawner` plays back
+/// predefined output instead, for tests and `--demo`. Tabs with `tail` or
+/// `source` don't run a command and aren't started by a spawner.
+pub trait Spawner: Send + Sync {
+    /// Start a tab's command
+    fn spawn<'a>(
+        &'a self,
+        event_tx: mpsc::Sender<AppEvent>,
+        spec: &'a CommandSpec,
+        tab_index: usize,
+    ) -> BoxFuture<'a, io::Result<Running>>;
+}
+
+impl Spawner for CommandRunner {
+    fn spawn<'a>(
+        &'a self,
+        event_tx: mpsc::Sender<AppEvent>,
+        spec: &'a CommandSpec,
+        tab_index: usize,
+    ) -> BoxFuture<'a, io::Result<Running>> {
+        Box::pin(self.start(event_tx, spec, tab_index))
+    }
+}
+
+/// One step of a [`Script`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// Print a line
+    Line(OutputKind, String),
+    /// Wait before the next step
+    Sleep(Duration),
+}
+
+/// Output and exit code a scripted command plays back
+///
+/// ```
+/// use std::time::Duration;
+/// use parallels::command::Script;
+///
+/// let script = Script::new()
+///     .stdout("Compiling app v0.1.0")
+///     .sleep(Duration::from_millis(500))
+///     .stderr("error: could not compile `app`")
+///     .exit(101);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Script {
+    steps: Vec<Step>,
+    exit_code: i32,
+    /// Play the steps again and again, until the command is killed
+    repeat: bool,
+}
+
+impl Script {
+    /// Create a script that exits successfully without output
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Print a line on stdout
+    pub fn stdout(mut self, line: impl Into<String>) -> Self {
+        self.steps.push(Step::Line(OutputKind::Stdout, line.into()));
+        self
+    }
+
+    /// Print a line on stderr
+    pub fn stderr(mut self, line: impl Into<String>) -> Self {
+        self.steps.push(Step::Line(OutputKind::Stderr, line.into()));
+        self
+    }
+
+    /// Wait before the next step
+    pub fn sleep(mut self, duration: Duration) -> Self {
+        self.steps.push(Step::Sleep(duration));
+        self
+    }
+
+    /// Exit with this code after the last step
+    pub fn exit(mut self, exit_code: i32) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
+
+    /// Play the steps in a loop instead of exiting, like a server
+    pub fn repeat(mut self) -> Self {
+        self.repeat = true;
+        self
+    }
+
+    /// Get the steps, in order
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+}
+
+/// Deterministic stand-in for `CommandRunner`
+///
+/// Each command line is mapped to a [`Script`]. Lines go through the
+/// spec's transform pipeline and line length limit like real output, and
+/// the run ends with `AppEvent::Exited`. Commands without a script fail to
+/// start.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedSpawner {
+    scripts: HashMap<String, Script>,
+}
+
+impl ScriptedSpawner {
+    /// Create a spawner without any scripts
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Play back `script` whenever `command` is started
+    pub fn script(mut self, command: impl Into<String>, script: Script) -> Self {
+        self.scripts.insert(command.into(), script);
+        self
+    }
+
+    /// Check if `command` has a script
+    pub fn has_script(&self, command: &str) -> bool {
+        self.scripts.contains_key(command)
+    }
+}
+
+impl Spawner for ScriptedSpawner {
+    fn spawn<'a>(
+        &'a self,
+        event_tx: mpsc::Sender<AppEvent>,
+        spec: &'a CommandSpec,
+        tab_index: usize,
+    ) -> BoxFuture<'a, io::Result<Running>> {
+        let started = match self.scripts.get(&spec.command) {
+            Some(script) => {
+                let playback = Playback {
+                    script: script.clone(),
+                    tab_index,
+                    pipeline: Pipeline::new(spec),
+                    max_line_length: spec.line_limit(),
+                };
+                Ok(Running::Task(tokio::spawn(playback.run(event_tx))))
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no script for {:?}", spec.command),
+            )),
+        };
+        Box::pin(async move { started })
+    }
+}
+
+/// A script being played back for a tab
+struct Playback {
+    script: Script,
+    tab_index: usize,
+    pipeline: Option<Pipeline>,
+    /// Characters shown before a line is cut (`0` for no limit)
+    max_line_length: usize,
+}
+
+impl Playback {
+    /// Send the script's lines, then its exit
+    async fn run(self, tx: mpsc::Sender<AppEvent>) {
+        let tab_index = self.tab_index;
+        loop {
+            for step in self.script.steps() {
+                match step {
+                    Step::Line(kind, text) => {
+                        let text = match &self.pipeline {
+                            Some(pipeline) => pipeline.apply(text.clone()),
+                            None => text.clone(),
+                        };
+                        let line = OutputLine::with_limit(*kind, text, self.max_line_length);
+                        if tx.send(AppEvent::Output { tab_index, line }).await.is_err() {
+                            return;
+                        }
+                    }
+                    Step::Sleep(duration) => tokio::time::sleep(*duration).await,
+                }
+            }
+            // A script without steps would otherwise spin
+            if !self.script.repeat || self.script.steps.is_empty() {
+                break;
+            }
+        }
+        let exit_code = self.script.exit_code;
+        let _ = tx
+            .send(AppEvent::Exited {
+                tab_index,
+                exit_code,
+            })
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Collect the events of a run until its exit
+    async fn play(spawner: &ScriptedSpawner, spec: &CommandSpec) -> io::Result<Vec<AppEvent>> {
+        let (tx, mut rx) = mpsc::channel(16);
+        spawner.spawn(tx, spec, 2).await?;
+        let mut events = Vec::new();
+        while let Some(event) = rx.recv().await {
+            let exited = matches!(event, AppEvent::Exited { .. });
+            events.push(event);
+            if exited {
+                break;
+            }
+        }
+        Ok(events)
+    }
+
+    fn describe(events: &[AppEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| match event {
+                AppEvent::Output { tab_index, line } => {
+                    format!("{} {:?} {}", tab_index, line.kind, line.plain())
+                }
+                AppEvent::Exited {
+                    tab_index,
+                    exit_code,
+                } => format!("{} exited {}", tab_index, exit_code),
+                other => format!("{:?}", other),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn scripted_spawner_plays_back_script() {
+        let spawner = ScriptedSpawner::new().script(
+            "cargo build",
+            Script::new()
+                .stdout("Compiling")
+                .sleep(Duration::from_millis(10))
+                .stderr("error: oops")
+                .exit(101),
+        );
+
+        let events = play(&spawner, &CommandSpec::new("cargo build"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            describe(&events),
+            vec!["2 Stdout Compiling", "2 Stderr error: oops", "2 exited 101"]
+        );
+    }
+
+    #[tokio::test]
+    async fn scripted_spawner_applies_spec_transforms() {
+        let spawner =
+            ScriptedSpawner::new().script("deploy", Script::new().stdout("token=abc123456"));
+        let spec = CommandSpec {
+            mask: vec!["abc\\d+".parse().unwrap()],
+            ..CommandSpec::new("deploy")
+        };
+
+        let events = play(&spawner, &spec).await.unwrap();
+
+        assert_eq!(describe(&events), vec!["2 Stdout token=****", "2 exited 0"]);
+    }
+
+    #[tokio::test]
+    async fn scripted_spawner_fails_without_script() {
+        let spawner = ScriptedSpawner::new();
+
+        let error = play(&spawner, &CommandSpec::new("make"))
+            .await
+            .err()
+            .unwrap();
+
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[tokio::test]
+    async fn scripted_spawner_repeats_until_killed() {
+        let spawner = ScriptedSpawner::new().script(
+            "serve",
+            Script::new()
+                .stdout("GET /")
+                .sleep(Duration::from_millis(1))
+                .repeat(),
+        );
+        let (tx, mut rx) = mpsc::channel(16);
+
+        let Ok(Running::Task(task)) = spawner.spawn(tx, &CommandSpec::new("serve"), 0).await else {
+            panic!("expected a task");
+        };
+        for _ in 0..3 {
+            assert!(matches!(rx.recv().await, Some(AppEvent::Output { .. })));
+        }
+        task.abort();
+
+        assert!(rx.recv().await.is_none());
+    }
+}
@@ -0,0 +1,644 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::buffer::DEFAULT_MAX_LINE_LENGTH;
+
+use super::{
+    KeyAction, OverflowPolicy, Pattern, SourceAddr, TransformSpec, auto_name, parse_ssh_url,
+};
+
+/// Delay between attempts of an `until_success` command without `every`
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Where a tab's view starts when the command starts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum StartAt {
+    /// Follow new output at the bottom
+    #[default]
+    Bottom,
+    /// Stay at the first line until scrolled, to read the output from the top
+    Top,
+}
+
+/// How the log prefixes of a tab's lines (timestamps, tags, module names)
+/// are shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum PrefixFold {
+    /// As printed
+    #[default]
+    Off,
+    /// Dimmed, to draw the eye to the message
+    Dim,
+    /// Not shown (search and exports still see them)
+    Hide,
+}
+
+impl PrefixFold {
+    /// Get the mode after this one, wrapping around
+    pub fn next(self) -> Self {
+        match self {
+            PrefixFold::Off => PrefixFold::Dim,
+            PrefixFold::Dim => PrefixFold::Hide,
+            PrefixFold::Hide => PrefixFold::Off,
+        }
+    }
+
+    /// Get the name shown when the mode is switched
+    pub fn label(self) -> &'static str {
+        match self {
+            PrefixFold::Off => "shown",
+            PrefixFold::Dim => "dimmed",
+            PrefixFold::Hide => "hidden",
+        }
+    }
+}
+
+/// Specification of a command to run in a tab
+///
+/// Built from a positional CLI argument (command line only) or from an
+/// entry in the config file (with per-command options).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CommandSpec {
+    /// Display name for the tab (defaults to one derived from the command)
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Shell command line, executed via `sh -c` (empty for `tail` and `source` tabs)
+    #[serde(default)]
+    pub command: String,
+    /// Working directory (defaults to the current directory)
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    /// Additional environment variables
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Delay before the command is first started (e.g. `"2s"`)
+    #[serde(default, with = "humantime_serde")]
+    pub delay: Option<Duration>,
+    /// Re-run the command this long after each run finishes (e.g. `"30s"`)
+    #[serde(default, with = "humantime_serde")]
+    pub every: Option<Duration>,
+    /// Keep re-running the command until it exits with code 0
+    ///
+    /// Retries are spaced by `every`, or one second if unset.
+    #[serde(default)]
+    pub until_success: bool,
+    /// Keep the previous run's output when the command is restarted
+    #[serde(default)]
+    pub keep_output: bool,
+    /// Keep the scroll position when the command is restarted, instead of
+    /// following the new run's output from the top
+    #[serde(default)]
+    pub keep_scroll: bool,
+    /// Show a summary (`142 lines, exit 0`) instead of the output unless
+    /// the command fails or the output is expanded
+    #[serde(default)]
+    pub quiet: bool,
+    /// TCP ports the command listens on, checked to be free before each
+    /// local start so a taken port fails the tab instead of crash-looping it
+    #[serde(default)]
+    pub ports: Vec<u16>,
+    /// Address shown once the command accepts connections on its first
+    /// port (defaults to `http://localhost:PORT`)
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Open `url` in the browser the first time the command is ready
+    #[serde(default)]
+    pub open: bool,
+    /// Whether the view starts at the bottom following new output
+    /// (`bottom`, the default) or at the top (`top`), also after restarts
+    #[serde(default)]
+    pub start_at: Option<StartAt>,
+    /// Dim (`dim`) or hide (`hide`) the log prefixes of lines, like
+    /// timestamps and module names (defaults to `off`)
+    #[serde(default)]
+    pub fold_prefix: Option<PrefixFold>,
+    /// Start in table view, aligning the columns (tab- or multi-space-separated)
+    /// of the visible lines
+    #[serde(default)]
+    pub table: bool,
+    /// Keep at most this many output lines per second per stream, dropping the rest
+    #[serde(default)]
+    pub max_lines_per_sec: Option<u32>,
+    /// What to do when the UI can't keep up (defaults to `block`)
+    #[serde(default)]
+    pub overflow: Option<OverflowPolicy>,
+    /// Niceness increment applied to the command, like `nice -n` (e.g. `10`)
+    #[serde(default)]
+    pub nice: Option<i32>,
+    /// CPUs the command may run on (Linux only, e.g. `[0, 1]`)
+    #[serde(default)]
+    pub cpu_affinity: Option<Vec<usize>>,
+    /// Memory limit in systemd syntax (e.g. `"512M"`; needs the `cgroups` feature)
+    #[serde(default)]
+    pub memory_max: Option<String>,
+    /// CPU limit in systemd syntax (e.g. `"50%"`; needs the `cgroups` feature)
+    #[serde(default)]
+    pub cpu_quota: Option<String>,
+    /// On restart and quit, also kill processes that left the command's
+    /// process group (double-forked daemons, `setsid`; Linux only)
+    #[serde(default)]
+    pub kill_detached: bool,
+    /// Send stderr into the same pipe as stdout (like `2>&1`), so lines keep
+    /// the order the program wrote them in; lines that look like errors or
+    /// warnings are still shown as stderr
+    #[serde(default)]
+    pub merge_streams: bool,
+    /// Restart the command when it prints nothing for this long (e.g. `"5m"`)
+    #[serde(default, with = "humantime_serde")]
+    pub hang_timeout: Option<Duration>,
+    /// Shell command run once `hang_timeout` has passed; the command is only
+    /// restarted if the probe fails (e.g. `"curl -sf localhost:3000/health"`)
+    #[serde(default)]
+    pub hang_probe: Option<String>,
+    /// Regular expressions of secrets to replace with `****` in the output
+    #[serde(default)]
+    pub mask: Vec<Pattern>,
+    /// Environment variables whose values are replaced with `****` in the
+    /// output (looked up in `env`, then in parallels' own environment)
+    #[serde(default)]
+    pub mask_env: Vec<String>,
+    /// Transforms applied in order to every output line (after masking)
+    #[serde(default)]
+    pub transform: Vec<TransformSpec>,
+    /// Characters of a line shown before it is cut with `… (+N chars)`
+    /// (defaults to 10000; `0` shows every line in full)
+    #[serde(default)]
+    pub max_line_length: Option<usize>,
+    /// Run the command on this host over ssh (`[user@]host`, as for `ssh`)
+    #[serde(default)]
+    pub ssh: Option<String>,
+    /// Shell command run to completion before each run of the command
+    /// (e.g. `"npm install"`); its output is shown dimmed in the tab
+    #[serde(default)]
+    pub before_start: Option<String>,
+    /// Shell command run after the command exits, with the exit code in
+    /// `PARALLELS_EXIT_CODE`; its output is shown dimmed in the tab
+    #[serde(default)]
+    pub after_exit: Option<String>,
+    /// Shell command run instead of `command` when the tab is restarted
+    /// (e.g. `"npm run dev -- --force"`)
+    #[serde(default)]
+    pub restart_command: Option<String>,
+    /// Shell command run to completion before the command is restarted
+    /// (e.g. `"docker compose restart web"`); its output goes to the tab
+    #[serde(default)]
+    pub before_restart: Option<String>,
+    /// Shell commands that `|` followed by their key sends the current
+    /// match's line (or the selected lines) to on stdin (e.g.
+    /// `{ j = "jq ." }`); their output is shown dimmed in the tab
+    #[serde(default)]
+    pub pipes: BTreeMap<char, String>,
+    /// Keys that, pressed in Normal mode on the tab, write text to the
+    /// command's stdin or send it a signal instead of their usual action
+    /// (e.g. `{ t = "test\n", r = { signal = "HUP" } }`)
+    #[serde(default)]
+    pub keys: BTreeMap<char, KeyAction>,
+    /// Follow this file instead of running a command, like `tail -F`
+    #[serde(default)]
+    pub tail: Option<PathBuf>,
+    /// Lines shown from the end of the `tail` file at start (defaults to 10)
+    #[serde(default)]
+    pub tail_lines: Option<usize>,
+    /// Read lines written to a FIFO or socket instead of running a command
+    /// (`fifo://PATH`, `unix://PATH` or `listen://HOST:PORT`)
+    #[serde(default)]
+    pub source: Option<SourceAddr>,
+    /// Feed the stdout of another tab (by name, command or 1-based
+    /// position) into the command's stdin, like `a | b`; both tabs still
+    /// show their output, and stdin is closed when the other command exits
+    #[serde(default)]
+    pub stdin_from: Option<String>,
+}
+
+impl CommandSpec {
+    /// Create a spec from a shell command line
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Characters of a line shown before it is cut (`0` for no limit)
+    pub fn line_limit(&self) -> usize {
+        self.max_line_length.unwrap_or(DEFAULT_MAX_LINE_LENGTH)
+    }
+
+    /// Create a spec from a command-line argument
+    ///
+    /// `ssh://[user@]host:command` runs the command on a remote host; any
+    /// other argument is a local shell command line.
+    pub fn parse(arg: &str) -> Self {
+        match parse_ssh_url(arg) {
+            Some((destination, command)) => Self {
+                ssh: Some(destination.to_string()),
+                ..Self::new(command)
+            },
+            None => Self::new(arg),
+        }
+    }
+
+    /// Create a spec following a file instead of running a command
+    pub fn tail(path: impl Into<PathBuf>) -> Self {
+        Self {
+            tail: Some(path.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Create a spec reading from a FIFO or socket instead of running a command
+    pub fn source(source: SourceAddr) -> Self {
+        Self {
+            source: Some(source),
+            ..Self::default()
+        }
+    }
+
+    /// Tab title: the explicit name, or a short one derived from the
+    /// command (or the followed file's name, or the source address)
+    pub fn title(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        match (&self.tail, &self.source) {
+            (Some(path), _) => path
+                .file_name()
+                .unwrap_or(path.as_os_str())
+                .to_string_lossy()
+                .into_owned(),
+            (None, Some(source)) => source.to_string(),
+            (None, None) => match &self.ssh {
+                Some(destination) => {
+                    let host = destination.rsplit('@').next().unwrap_or(destination);
+                    format!("{}:{}", host, auto_name(&self.command))
+                }
+                None => auto_name(&self.command),
+            },
+        }
+    }
+
+    /// Check if the tab follows new output when the command starts
+    pub fn follows_output(&self) -> bool {
+        self.start_at.unwrap_or_default() == StartAt::Bottom
+    }
+
+    /// Address of the command once it listens on its first port
+    pub fn ready_url(&self) -> Option<String> {
+        let port = self.ports.first()?;
+        Some(
+            self.url
+                .clone()
+                .unwrap_or_else(|| format!("http://localhost:{}", port)),
+        )
+    }
+
+    /// Check if the command's stdin is written by the app, with the output
+    /// of `stdin_from` or the text of its `keys`
+    pub fn pipes_stdin(&self) -> bool {
+        self.stdin_from.is_some()
+            || self
+                .keys
+                .values()
+                .any(|action| matches!(action, KeyAction::Send(_)))
+    }
+
+    /// Check if the command runs with cgroup memory/CPU limits
+    pub fn has_resource_limits(&self) -> bool {
+        self.memory_max.is_some() || self.cpu_quota.is_some()
+    }
+
+    /// Delay before the next run after the command exited with `exit_code`
+    ///
+    /// Returns `None` if the command should not be run again.
+    pub fn next_run_delay(&self, exit_code: i32) -> Option<Duration> {
+        if self.until_success {
+            (exit_code != 0).then(|| self.every.unwrap_or(DEFAULT_RETRY_DELAY))
+        } else {
+            self.every
+        }
+    }
+
+    /// Spec to spawn when the tab is restarted, using `restart_command`
+    /// in place of the command if one is set
+    pub fn for_restart(&self) -> CommandSpec {
+        match &self.restart_command {
+            Some(command) => CommandSpec {
+                command: command.clone(),
+                ..self.clone()
+            },
+            None => self.clone(),
+        }
+    }
+
+    /// Take over the settings that changed from `old` to `new` (a command's
+    /// entry in a reloaded config) and apply without restarting it: how the
+    /// output is shown, what restarts keep, the pipes and the keys (unless
+    /// they change whether stdin is piped)
+    ///
+    /// Settings left unchanged keep their value, e.g. one set on the command line.
+    pub fn reload(&mut self, old: &CommandSpec, new: &CommandSpec) {
+        macro_rules! reload {
+            ($($field:ident),*) => {$(
+                if old.$field != new.$field {
+                    self.$field = new.$field.clone();
+                }
+            )*};
+        }
+        reload!(
+            quiet,
+            keep_output,
+            keep_scroll,
+            open,
+            start_at,
+            fold_prefix,
+            table,
+            pipes
+        );
+        if old.keys != new.keys {
+            let piped = self.pipes_stdin();
+            let keys = std::mem::replace(&mut self.keys, new.keys.clone());
+            if self.pipes_stdin() != piped {
+                self.keys = keys;
+            }
+        }
+    }
+
+    /// Check if going from `self` to `new` changes settings only read when
+    /// the command is spawned, which [`reload`](Self::reload) can't apply
+    pub fn needs_restart(&self, new: &CommandSpec) -> bool {
+        let mut reloaded = self.clone();
+        reloaded.reload(self, new);
+        reloaded != *new
+    }
+}
+
+impl From<String> for CommandSpec {
+    fn from(command: String) -> Self {
+        Self::new(command)
+    }
+}
+
+impl From<&str> for CommandSpec {
+    fn from(command: &str) -> Self {
+        Self::new(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::SignalName;
+    use nix::sys::signal::Signal;
+    use rstest::rstest;
+
+    #[test]
+    fn command_spec_from_str_sets_only_command() {
+        let spec = CommandSpec::from("echo hello");
+        assert_eq!(spec.command, "echo hello");
+        assert!(spec.name.is_none());
+        assert!(spec.cwd.is_none());
+        assert!(spec.env.is_empty());
+        assert!(spec.delay.is_none());
+        assert!(!spec.until_success);
+    }
+
+    #[test]
+    fn command_spec_deserializes_all_fields() {
+        let spec: CommandSpec = toml::from_str(
+            r#"
+            name = "web"
+            command = "npm run dev"
+            cwd = "web"
+            env = { PORT = "3000" }
+            delay = "1500ms"
+            every = "30s"
+            until_success = true
+            keep_output = true
+            keep_scroll = true
+            quiet = true
+            start_at = "top"
+            ports = [3000, 9229]
+            url = "http://localhost:3000/admin"
+            open = true
+            max_lines_per_sec = 100
+            overflow = "drop-oldest"
+            nice = 10
+            cpu_affinity = [0, 2]
+            memory_max = "512M"
+            cpu_quota = "50%"
+            kill_detached = true
+            merge_streams = true
+            hang_timeout = "5m"
+            hang_probe = "curl -sf localhost:3000"
+            tail_lines = 50
+            mask = ["ghp_[A-Za-z0-9]+"]
+            mask_env = ["API_TOKEN"]
+            transform = [{ type = "strip-ansi" }, { type = "truncate", max_chars = 200 }]
+            before_start = "npm install"
+            after_exit = "notify-send \"exited $PARALLELS_EXIT_CODE\""
+            restart_command = "npm run dev -- --force"
+            before_restart = "make migrate"
+            pipes = { j = "jq .", "|" = "notify-send parallels" }
+            keys = { t = "test\n" }
+            stdin_from = "api"
+            fold_prefix = "dim"
+            table = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(spec.name.as_deref(), Some("web"));
+        assert_eq!(spec.command, "npm run dev");
+        assert_eq!(spec.cwd, Some(PathBuf::from("web")));
+        assert_eq!(spec.env.get("PORT").map(String::as_str), Some("3000"));
+        assert_eq!(spec.delay, Some(Duration::from_millis(1500)));
+        assert_eq!(spec.every, Some(Duration::from_secs(30)));
+        assert!(spec.until_success);
+        assert!(spec.keep_output);
+        assert!(spec.keep_scroll);
+        assert!(spec.quiet);
+        assert_eq!(spec.start_at, Some(StartAt::Top));
+        assert_eq!(spec.ports, vec![3000, 9229]);
+        assert_eq!(
+            spec.ready_url().as_deref(),
+            Some("http://localhost:3000/admin")
+        );
+        assert!(spec.open);
+        assert!(!spec.follows_output());
+        assert_eq!(spec.max_lines_per_sec, Some(100));
+        assert_eq!(spec.overflow, Some(OverflowPolicy::DropOldest));
+        assert_eq!(spec.nice, Some(10));
+        assert_eq!(spec.cpu_affinity, Some(vec![0, 2]));
+        assert_eq!(spec.memory_max.as_deref(), Some("512M"));
+        assert_eq!(spec.cpu_quota.as_deref(), Some("50%"));
+        assert!(spec.has_resource_limits());
+        assert!(spec.kill_detached);
+        assert!(spec.merge_streams);
+        assert_eq!(spec.hang_timeout, Some(Duration::from_secs(300)));
+        assert_eq!(spec.hang_probe.as_deref(), Some("curl -sf localhost:3000"));
+        assert_eq!(spec.tail_lines, Some(50));
+        assert_eq!(spec.mask[0].as_str(), "ghp_[A-Za-z0-9]+");
+        assert_eq!(spec.mask_env, vec!["API_TOKEN"]);
+        assert_eq!(
+            spec.transform,
+            vec![
+                TransformSpec::StripAnsi,
+                TransformSpec::Truncate { max_chars: 200 }
+            ]
+        );
+        assert_eq!(spec.stdin_from.as_deref(), Some("api"));
+        assert_eq!(spec.fold_prefix, Some(PrefixFold::Dim));
+        assert!(spec.table);
+        assert_eq!(spec.before_start.as_deref(), Some("npm install"));
+        assert_eq!(
+            spec.after_exit.as_deref(),
+            Some("notify-send \"exited $PARALLELS_EXIT_CODE\"")
+        );
+        assert_eq!(
+            spec.restart_command.as_deref(),
+            Some("npm run dev -- --force")
+        );
+        assert_eq!(spec.before_restart.as_deref(), Some("make migrate"));
+        assert_eq!(spec.pipes.get(&'j').map(String::as_str), Some("jq ."));
+        assert_eq!(spec.keys.get(&'t'), Some(&KeyAction::Send("test\n".into())));
+        assert!(spec.pipes_stdin());
+        assert_eq!(
+            spec.pipes.get(&'|').map(String::as_str),
+            Some("notify-send parallels")
+        );
+    }
+
+    #[rstest]
+    #[case("npm run dev", None, "npm run dev", "npm:dev")]
+    #[case(
+        "ssh://deploy@web1:npm run dev",
+        Some("deploy@web1"),
+        "npm run dev",
+        "web1:npm:dev"
+    )]
+    fn command_spec_parse_reads_ssh_urls(
+        #[case] arg: &str,
+        #[case] ssh: Option<&str>,
+        #[case] command: &str,
+        #[case] title: &str,
+    ) {
+        let spec = CommandSpec::parse(arg);
+
+        assert_eq!(spec.ssh.as_deref(), ssh);
+        assert_eq!(spec.command, command);
+        assert_eq!(spec.title(), title);
+    }
+
+    #[test]
+    fn command_spec_source_is_titled_after_the_address() {
+        let spec: CommandSpec = toml::from_str(r#"source = "listen://0.0.0.0:5000""#).unwrap();
+
+        assert_eq!(spec.title(), "listen://0.0.0.0:5000");
+        assert!(toml::from_str::<CommandSpec>(r#"source = "5000""#).is_err());
+    }
+
+    #[test]
+    fn command_spec_tail_is_titled_after_the_file() {
+        let spec: CommandSpec = toml::from_str(r#"tail = "logs/app.log""#).unwrap();
+
+        assert_eq!(spec, CommandSpec::tail("logs/app.log"));
+        assert_eq!(spec.title(), "app.log");
+    }
+
+    #[rstest]
+    #[case(None, false, 1, None)]
+    #[case(Some(5), false, 0, Some(5))]
+    #[case(Some(5), false, 1, Some(5))]
+    #[case(None, true, 1, Some(1))]
+    #[case(Some(5), true, 1, Some(5))]
+    #[case(Some(5), true, 0, None)]
+    fn command_spec_next_run_delay(
+        #[case] every_secs: Option<u64>,
+        #[case] until_success: bool,
+        #[case] exit_code: i32,
+        #[case] expected_secs: Option<u64>,
+    ) {
+        let spec = CommandSpec {
+            every: every_secs.map(Duration::from_secs),
+            until_success,
+            ..CommandSpec::new("cmd")
+        };
+        assert_eq!(
+            spec.next_run_delay(exit_code),
+            expected_secs.map(Duration::from_secs)
+        );
+    }
+
+    #[test]
+    fn command_spec_reload_applies_only_changed_settings() {
+        let old = CommandSpec {
+            pipes: BTreeMap::from([('j', "jq .".to_string())]),
+            ..CommandSpec::new("npm run dev")
+        };
+        let new = CommandSpec {
+            table: true,
+            ..old.clone()
+        };
+        // Set on the command line, and not changed in the config
+        let mut running = CommandSpec {
+            fold_prefix: Some(PrefixFold::Hide),
+            ..old.clone()
+        };
+
+        running.reload(&old, &new);
+
+        assert!(running.table);
+        assert_eq!(running.fold_prefix, Some(PrefixFold::Hide));
+        assert!(!old.needs_restart(&new));
+        assert!(old.needs_restart(&CommandSpec {
+            env: BTreeMap::from([("PORT".to_string(), "3001".to_string())]),
+            ..new
+        }));
+    }
+
+    #[test]
+    fn command_spec_reload_applies_keys_unless_stdin_changes() {
+        let signal = |signal| KeyAction::Signal {
+            signal: SignalName(signal),
+        };
+        let old = CommandSpec {
+            keys: BTreeMap::from([('r', signal(Signal::SIGHUP))]),
+            ..CommandSpec::new("server")
+        };
+        let new = CommandSpec {
+            keys: BTreeMap::from([('r', signal(Signal::SIGUSR1))]),
+            ..old.clone()
+        };
+        let sends = CommandSpec {
+            keys: BTreeMap::from([('r', KeyAction::Send("r\n".into()))]),
+            ..old.clone()
+        };
+
+        let mut running = old.clone();
+        running.reload(&old, &new);
+        assert_eq!(running.keys, new.keys);
+        assert!(!old.needs_restart(&new));
+
+        let mut running = old.clone();
+        running.reload(&old, &sends);
+        assert_eq!(running.keys, old.keys);
+        assert!(old.needs_restart(&sends));
+    }
+
+    #[test]
+    fn command_spec_rejects_unknown_fields() {
+        let result: Result<CommandSpec, _> = toml::from_str(
+            r#"
+            command = "echo"
+            comand = "typo"
+            "#,
+        );
+        assert!(result.is_err());
+    }
+}
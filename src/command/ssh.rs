@@ -0,0 +1,104 @@
+use tokio::process::Command;
+
+use super::{CommandSpec, shell_quote};
+
+/// Prefix of a command-line argument that runs a command over ssh
+const SSH_PREFIX: &str = "ssh://";
+
+/// Exit code ssh reports when the connection fails (or the remote command
+/// itself exits with 255)
+pub const SSH_CONNECTION_FAILED: i32 = 255;
+
+/// Split an `ssh://[user@]host:command` argument into destination and command
+pub fn parse_ssh_url(arg: &str) -> Option<(&str, &str)> {
+    let (destination, command) = arg.strip_prefix(SSH_PREFIX)?.split_once(':')?;
+    (!destination.is_empty() && !command.trim().is_empty()).then_some((destination, command))
+}
+
+/// Build the `ssh -tt` command running a spec on its remote host
+///
+/// A remote pseudo-terminal makes the remote command exit when the
+/// connection is closed on restart or quit (stderr then arrives on stdout).
+/// `BatchMode` fails instead of prompting for a password, since there is
+/// no terminal to answer on.
+pub(super) fn ssh_command(spec: &CommandSpec, destination: &str) -> Command {
+    let mut cmd = Command::new("ssh");
+    cmd.args(["-tt", "-o", "BatchMode=yes", "--", destination])
+        .arg(remote_command(spec));
+    cmd
+}
+
+/// Remote shell line applying the spec's directory and environment
+///
+/// ssh joins its arguments into one line for the remote login shell, so
+/// everything is quoted here and the command runs through `sh -c` like a
+/// local one.
+fn remote_command(spec: &CommandSpec) -> String {
+    let mut line = String::new();
+    if let Some(cwd) = &spec.cwd {
+        line.push_str(&format!("cd {} && ", shell_quote(&cwd.to_string_lossy())));
+    }
+    line.push_str("exec ");
+    if !spec.env.is_empty() {
+        line.push_str("env ");
+        for (key, value) in &spec.env {
+            line.push_str(&shell_quote(&format!("{}={}", key, value)));
+            line.push(' ');
+        }
+    }
+    line.push_str("sh -c ");
+    line.push_str(&shell_quote(&spec.command));
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("ssh://deploy@web1:tail -f /var/log/app.log", Some(("deploy@web1", "tail -f /var/log/app.log")))]
+    #[case("ssh://web1:npm run dev", Some(("web1", "npm run dev")))]
+    #[case("ssh://web1:", None)]
+    #[case("ssh://:uptime", None)]
+    #[case("ssh://web1", None)]
+    #[case("npm run dev", None)]
+    fn parse_ssh_url_splits_destination_and_command(
+        #[case] arg: &str,
+        #[case] expected: Option<(&str, &str)>,
+    ) {
+        assert_eq!(parse_ssh_url(arg), expected);
+    }
+
+    #[test]
+    fn remote_command_quotes_directory_environment_and_command() {
+        let spec = CommandSpec {
+            cwd: Some("/srv/my app".into()),
+            env: [("GREETING".to_string(), "it's me".to_string())].into(),
+            ..CommandSpec::new("echo \"$GREETING\"")
+        };
+
+        assert_eq!(
+            remote_command(&spec),
+            r#"cd '/srv/my app' && exec env 'GREETING=it'\''s me' sh -c 'echo "$GREETING"'"#
+        );
+    }
+
+    #[tokio::test]
+    async fn remote_command_runs_in_a_local_shell() {
+        // The remote login shell parses the line like this local one does
+        let spec = CommandSpec {
+            env: [("A".to_string(), "x y'z".to_string())].into(),
+            ..CommandSpec::new("printf '%s\\n' \"$A\"")
+        };
+
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(remote_command(&spec))
+            .output()
+            .await
+            .unwrap();
+
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "x y'z\n");
+    }
+}
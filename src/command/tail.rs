@@ -0,0 +1,288 @@
+use std::io::{self, SeekFrom};
+use std::os::unix::fs::MetadataExt;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::mpsc;
+
+use crate::buffer::{OutputKind, OutputLine};
+use crate::command::runner::dropped_divider;
+use crate::command::{Assembled, CommandSpec, LineAssembler, Pipeline, Running, Source};
+use crate::event::AppEvent;
+
+/// Lines shown from the end of a file when `tail_lines` is unset
+pub const DEFAULT_TAIL_LINES: usize = 10;
+
+/// How often a followed file is checked for new data
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Bytes read from the file at a time
+const READ_CHUNK_SIZE: usize = 8192;
+
+/// File-following source for tabs with `tail`, like `tail -n N -F`
+pub struct FileTailer;
+
+/// Follows the spec's `tail` file
+///
+/// The last `tail_lines` lines are sent first, then every line appended
+/// later. A file that is truncated or replaced (log rotation) is read again
+/// from its start.
+impl Source for FileTailer {
+    async fn start(
+        &self,
+        event_tx: mpsc::Sender<AppEvent>,
+        spec: &CommandSpec,
+        tab_index: usize,
+    ) -> io::Result<Running> {
+        let path = spec
+            .tail
+            .clone()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no file to tail"))?;
+        let mut file = File::open(&path).await?;
+        let lines = spec.tail_lines.unwrap_or(DEFAULT_TAIL_LINES);
+        let position = last_lines_offset(&mut file, lines).await?;
+        file.seek(SeekFrom::Start(position)).await?;
+
+        let follower = Follower {
+            path,
+            file,
+            position,
+            tab_index,
+            max_lines_per_sec: spec.max_lines_per_sec,
+            pipeline: Pipeline::new(spec),
+            max_line_length: spec.line_limit(),
+        };
+        Ok(Running::Task(tokio::spawn(follower.run(event_tx))))
+    }
+}
+
+/// State of a followed file
+struct Follower {
+    path: PathBuf,
+    file: File,
+    /// Offset of the next byte to read
+    position: u64,
+    tab_index: usize,
+    max_lines_per_sec: Option<u32>,
+    pipeline: Option<Pipeline>,
+    /// Characters shown before a line is cut (`0` for no limit)
+    max_line_length: usize,
+}
+
+impl Follower {
+    /// Send the file's lines as they are appended
+    async fn run(mut self, tx: mpsc::Sender<AppEvent>) {
+        let mut assembler = LineAssembler::new(self.max_lines_per_sec, Instant::now());
+        let mut inode = match self.file.metadata().await {
+            Ok(metadata) => metadata.ino(),
+            Err(_) => 0,
+        };
+        let mut chunk = vec![0; READ_CHUNK_SIZE];
+
+        loop {
+            match self.file.read(&mut chunk).await {
+                Ok(0) => {}
+                Ok(len) => {
+                    self.position += len as u64;
+                    assembler.push(&chunk[..len]);
+                }
+                Err(e) => {
+                    let reason = format!("failed to read {}: {}", self.path.display(), e);
+                    let tab_index = self.tab_index;
+                    let _ = tx.send(AppEvent::Failed { tab_index, reason }).await;
+                    return;
+                }
+            }
+            while let Some(item) = assembler.next(Instant::now()) {
+                if tx.send(self.output_event(item)).await.is_err() {
+                    return;
+                }
+            }
+
+            // Caught up: wait for the file to grow, or to be replaced
+            if self.position < self.file_len().await.unwrap_or(0) {
+                continue;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let Ok(metadata) = tokio::fs::metadata(&self.path).await else {
+                // Missing while a rotation is in progress
+                continue;
+            };
+            if metadata.ino() == inode && metadata.len() >= self.position {
+                continue;
+            }
+            let Ok(file) = File::open(&self.path).await else {
+                continue;
+            };
+            self.file = file;
+            self.position = 0;
+            inode = metadata.ino();
+            assembler = LineAssembler::new(self.max_lines_per_sec, Instant::now());
+            let line = OutputLine::divider("file truncated or replaced - reading from the start");
+            let tab_index = self.tab_index;
+            if tx.send(AppEvent::Output { tab_index, line }).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Current length of the open file
+    async fn file_len(&self) -> io::Result<u64> {
+        Ok(self.file.metadata().await?.len())
+    }
+
+    /// Build the event for a line (or dropped count) of the file
+    fn output_event(&self, item: Assembled) -> AppEvent {
+        let tab_index = self.tab_index;
+        match item {
+            Assembled::Line(text) => {
+                let text = match &self.pipeline {
+                    Some(pipeline) => pipeline.apply(text),
+                    None => text,
+                };
+                AppEvent::Output {
+                    tab_index,
+                    line: OutputLine::with_limit(OutputKind::Stdout, text, self.max_line_length),
+                }
+            }
+            Assembled::Dropped(dropped) => dropped_divider(tab_index, dropped),
+        }
+    }
+}
+
+/// Find the offset where the last `lines` lines of a file start
+///
+/// Reads backwards from the end, so only the tail of a large file is read.
+async fn last_lines_offset(file: &mut File, lines: usize) -> io::Result<u64> {
+    let len = file.metadata().await?.len();
+    if lines == 0 {
+        return Ok(len);
+    }
+
+    let mut chunk = vec![0; READ_CHUNK_SIZE];
+    let mut end = len;
+    let mut newlines = 0;
+    while end > 0 {
+        let start = end.saturating_sub(READ_CHUNK_SIZE as u64);
+        let buf = &mut chunk[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start)).await?;
+        file.read_exact(buf).await?;
+        for (index, &byte) in buf.iter().enumerate().rev() {
+            // A trailing newline ends the last line rather than starting one
+            if byte != b'\n' || start + index as u64 == len - 1 {
+                continue;
+            }
+            newlines += 1;
+            if newlines == lines {
+                return Ok(start + index as u64 + 1);
+            }
+        }
+        end = start;
+    }
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::path::Path;
+
+    /// Write a file in a temporary directory
+    fn write_file(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    /// Receive the next output line's text, waiting at most a second
+    async fn next_line(rx: &mut mpsc::Receiver<AppEvent>) -> Option<String> {
+        let event = tokio::time::timeout(Duration::from_secs(1), rx.recv()).await;
+        match event {
+            Ok(Some(AppEvent::Output { line, .. })) => Some(line.plain()),
+            _ => None,
+        }
+    }
+
+    #[rstest]
+    #[case("a\nb\nc\n", 2, "b\nc\n")]
+    #[case("a\nb\nc", 2, "b\nc")]
+    #[case("a\nb\n", 5, "a\nb\n")]
+    #[case("a\nb\n", 0, "")]
+    #[case("", 3, "")]
+    #[case("a\n\n\n", 2, "\n\n")]
+    #[tokio::test]
+    async fn last_lines_offset_finds_start_of_last_lines(
+        #[case] content: &str,
+        #[case] lines: usize,
+        #[case] expected: &str,
+    ) {
+        let name = format!("offset-{}-{}", lines, content.len());
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_file(dir.path(), &name, content);
+        let mut file = File::open(&path).await.unwrap();
+
+        let offset = last_lines_offset(&mut file, lines).await.unwrap();
+
+        assert_eq!(&content[offset as usize..], expected);
+    }
+
+    #[tokio::test]
+    async fn last_lines_offset_reads_across_chunks() {
+        let content: String = (0..5000).map(|i| format!("line {}\n", i)).collect();
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_file(dir.path(), "large.log", &content);
+        let mut file = File::open(&path).await.unwrap();
+
+        let offset = last_lines_offset(&mut file, 3000).await.unwrap();
+
+        assert!(content[offset as usize..].starts_with("line 2000\n"));
+    }
+
+    #[tokio::test]
+    async fn file_tailer_sends_last_lines_then_appended_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_file(dir.path(), "follow.log", "one\ntwo\nthree\n");
+        let spec = CommandSpec {
+            tail: Some(path.clone()),
+            tail_lines: Some(2),
+            ..CommandSpec::default()
+        };
+        let (tx, mut rx) = mpsc::channel(100);
+
+        let Running::Task(task) = FileTailer.start(tx, &spec, 0).await.unwrap() else {
+            panic!("expected a task");
+        };
+        assert_eq!(next_line(&mut rx).await.as_deref(), Some("two"));
+        assert_eq!(next_line(&mut rx).await.as_deref(), Some("three"));
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        std::io::Write::write_all(&mut file, b"four\n").unwrap();
+        assert_eq!(next_line(&mut rx).await.as_deref(), Some("four"));
+
+        // Truncated: read again from the start
+        std::fs::write(&path, "fresh\n").unwrap();
+        assert_eq!(
+            next_line(&mut rx).await.as_deref(),
+            Some("── file truncated or replaced - reading from the start ──")
+        );
+        assert_eq!(next_line(&mut rx).await.as_deref(), Some("fresh"));
+        task.abort();
+    }
+
+    #[tokio::test]
+    async fn file_tailer_fails_for_missing_file() {
+        let spec = CommandSpec {
+            tail: Some(PathBuf::from("/nonexistent/app.log")),
+            ..CommandSpec::default()
+        };
+        let (tx, _rx) = mpsc::channel(100);
+
+        assert!(FileTailer.start(tx, &spec, 0).await.is_err());
+    }
+}
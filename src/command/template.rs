@@ -0,0 +1,208 @@
+use std::io;
+use std::net::TcpListener;
+use std::path::PathBuf;
+
+use crate::command::CommandSpec;
+
+/// A value that can be used as `{name}` in a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variable {
+    /// A free TCP port on localhost
+    Port,
+    /// A temporary directory for the tab
+    Tmpdir,
+}
+
+impl Variable {
+    /// All variables, in display order
+    pub const ALL: [Variable; 2] = [Variable::Port, Variable::Tmpdir];
+
+    /// Name used in the `{name}` placeholder
+    pub fn name(self) -> &'static str {
+        match self {
+            Variable::Port => "port",
+            Variable::Tmpdir => "tmpdir",
+        }
+    }
+
+    /// Environment variable the value is exported as
+    pub fn env_var(self) -> &'static str {
+        match self {
+            Variable::Port => "PARALLELS_PORT",
+            Variable::Tmpdir => "PARALLELS_TMPDIR",
+        }
+    }
+
+    fn placeholder(self) -> String {
+        format!("{{{}}}", self.name())
+    }
+
+    /// Allocate a fresh value for a tab
+    fn allocate(self, tab_index: usize) -> io::Result<String> {
+        match self {
+            // The port is free once the probe listener is dropped
+            Variable::Port => Ok(TcpListener::bind("127.0.0.1:0")?
+                .local_addr()?
+                .port()
+                .to_string()),
+            Variable::Tmpdir => {
+                let dir = tmpdir(tab_index);
+                std::fs::create_dir_all(&dir)?;
+                Ok(dir.to_string_lossy().into_owned())
+            }
+        }
+    }
+}
+
+/// Values of a tab's variables
+pub type Values = Vec<(Variable, String)>;
+
+/// Allocate the variables a spec uses that have no value yet
+///
+/// Values are kept per tab, so a restarted command gets the same port and
+/// directory again.
+pub fn allocate_missing(
+    spec: &CommandSpec,
+    tab_index: usize,
+    values: &mut Values,
+) -> io::Result<()> {
+    let texts = templated_texts(spec);
+    for variable in Variable::ALL {
+        let used = texts
+            .iter()
+            .any(|text| text.contains(&variable.placeholder()));
+        if used && !values.iter().any(|(v, _)| *v == variable) {
+            values.push((variable, variable.allocate(tab_index)?));
+        }
+    }
+    Ok(())
+}
+
+/// Substitute variables in a spec's commands and environment, and export
+/// them as environment variables
+pub fn expand(spec: &mut CommandSpec, values: &[(Variable, String)]) {
+    if values.is_empty() {
+        return;
+    }
+    let substitute = |text: &mut String| {
+        for (variable, value) in values {
+            *text = text.replace(&variable.placeholder(), value);
+        }
+    };
+    substitute(&mut spec.command);
+    for hook in [
+        &mut spec.restart_command,
+        &mut spec.before_start,
+        &mut spec.after_exit,
+        &mut spec.before_restart,
+        &mut spec.hang_probe,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        substitute(hook);
+    }
+    for value in spec.env.values_mut() {
+        substitute(value);
+    }
+    for (variable, value) in values {
+        spec.env
+            .insert(variable.env_var().to_string(), value.clone());
+    }
+}
+
+/// Shell command lines and environment values of a spec that may use variables
+fn templated_texts(spec: &CommandSpec) -> Vec<&str> {
+    let hooks = [
+        &spec.restart_command,
+        &spec.before_start,
+        &spec.after_exit,
+        &spec.before_restart,
+        &spec.hang_probe,
+    ];
+    std::iter::once(spec.command.as_str())
+        .chain(hooks.into_iter().flatten().map(String::as_str))
+        .chain(spec.env.values().map(String::as_str))
+        .collect()
+}
+
+/// Remove the temporary directory allocated for a tab, if any
+pub fn remove_tmpdir(values: &[(Variable, String)]) {
+    for (variable, value) in values {
+        if *variable == Variable::Tmpdir {
+            let _ = std::fs::remove_dir_all(value);
+        }
+    }
+}
+
+/// Temporary directory of a tab, unique to this process
+fn tmpdir(tab_index: usize) -> PathBuf {
+    std::env::temp_dir().join(format!("parallels-{}-tab{}", std::process::id(), tab_index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocate_missing_allocates_only_used_variables() {
+        let spec = CommandSpec::new("npm run dev -- --port {port}");
+        let mut values = Values::new();
+
+        allocate_missing(&spec, 0, &mut values).unwrap();
+
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].0, Variable::Port);
+        assert!(values[0].1.parse::<u16>().unwrap() > 0);
+    }
+
+    #[test]
+    fn allocate_missing_keeps_existing_values() {
+        let spec = CommandSpec {
+            env: [("DATA".to_string(), "{tmpdir}/data".to_string())].into(),
+            ..CommandSpec::new("serve --port {port}")
+        };
+        let mut values = vec![(Variable::Port, "4000".to_string())];
+
+        allocate_missing(&spec, 7, &mut values).unwrap();
+
+        assert_eq!(values[0], (Variable::Port, "4000".to_string()));
+        assert_eq!(values[1].0, Variable::Tmpdir);
+        assert!(PathBuf::from(&values[1].1).is_dir());
+        assert!(values[1].1.ends_with("tab7"));
+    }
+
+    #[test]
+    fn remove_tmpdir_removes_the_allocated_directory() {
+        let spec = CommandSpec::new("build --out {tmpdir}/out");
+        let mut values = Values::new();
+        allocate_missing(&spec, 9, &mut values).unwrap();
+        let dir = PathBuf::from(&values[0].1);
+        std::fs::write(dir.join("file"), "data").unwrap();
+
+        remove_tmpdir(&values);
+
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn expand_substitutes_and_exports_values() {
+        let mut spec = CommandSpec {
+            env: [("URL".to_string(), "http://localhost:{port}".to_string())].into(),
+            before_start: Some("mkdir -p {tmpdir}/cache".into()),
+            ..CommandSpec::new("serve --port {port} --dir {tmpdir} {other}")
+        };
+        let values = vec![
+            (Variable::Port, "4000".to_string()),
+            (Variable::Tmpdir, "/tmp/t".to_string()),
+        ];
+
+        expand(&mut spec, &values);
+
+        assert_eq!(spec.command, "serve --port 4000 --dir /tmp/t {other}");
+        assert_eq!(spec.before_start.as_deref(), Some("mkdir -p /tmp/t/cache"));
+        assert_eq!(spec.env["URL"], "http://localhost:4000");
+        assert_eq!(spec.env["PARALLELS_PORT"], "4000");
+        assert_eq!(spec.env["PARALLELS_TMPDIR"], "/tmp/t");
+    }
+}
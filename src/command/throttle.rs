@@ -0,0 +1,103 @@
+use std::time::{Duration, Instant};
+
+/// Length of a throttling window
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Decision for a single line passing through a [`LineThrottle`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admit {
+    /// Forward the line; `dropped` lines were discarded since the last one
+    Pass { dropped: u64 },
+    /// Discard the line
+    Drop,
+}
+
+/// Limits the number of lines forwarded per second
+///
+/// Lines beyond the limit within a one-second window are dropped and
+/// counted, so the count can be reported once lines pass again.
+pub struct LineThrottle {
+    max_per_window: u32,
+    window_start: Instant,
+    passed: u32,
+    dropped: u64,
+}
+
+impl LineThrottle {
+    /// Create a throttle allowing `max_per_sec` lines per second
+    pub fn new(max_per_sec: u32, now: Instant) -> Self {
+        Self {
+            max_per_window: max_per_sec,
+            window_start: now,
+            passed: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Decide whether a line arriving at `now` is forwarded
+    pub fn admit(&mut self, now: Instant) -> Admit {
+        if now.duration_since(self.window_start) >= WINDOW {
+            self.window_start = now;
+            self.passed = 0;
+        }
+
+        if self.passed < self.max_per_window {
+            self.passed += 1;
+            Admit::Pass {
+                dropped: self.take_dropped(),
+            }
+        } else {
+            self.dropped += 1;
+            Admit::Drop
+        }
+    }
+
+    /// Take the number of lines dropped since the last report
+    pub fn take_dropped(&mut self) -> u64 {
+        std::mem::take(&mut self.dropped)
+    }
+}
+
+/// Format a count with thousands separators (e.g. `1,234`)
+pub fn format_count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(c);
+    }
+    formatted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn line_throttle_drops_lines_over_limit_and_reports_count() {
+        let start = Instant::now();
+        let mut throttle = LineThrottle::new(2, start);
+
+        assert_eq!(throttle.admit(start), Admit::Pass { dropped: 0 });
+        assert_eq!(throttle.admit(start), Admit::Pass { dropped: 0 });
+        assert_eq!(throttle.admit(start), Admit::Drop);
+        assert_eq!(throttle.admit(start), Admit::Drop);
+
+        // Next window reports the dropped lines
+        let later = start + WINDOW;
+        assert_eq!(throttle.admit(later), Admit::Pass { dropped: 2 });
+        assert_eq!(throttle.admit(later), Admit::Pass { dropped: 0 });
+    }
+
+    #[rstest]
+    #[case(0, "0")]
+    #[case(999, "999")]
+    #[case(1234, "1,234")]
+    #[case(1234567, "1,234,567")]
+    fn format_count_adds_thousands_separators(#[case] count: u64, #[case] expected: &str) {
+        assert_eq!(format_count(count), expected);
+    }
+}
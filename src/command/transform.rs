@@ -0,0 +1,224 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::buffer::strip_ansi;
+use crate::command::{CommandSpec, MASK, SecretMask};
+
+/// Marker appended to lines cut by a `truncate` transform
+const TRUNCATED: &str = "…";
+
+/// Regular expression in the config (e.g. `"ghp_[A-Za-z0-9]+"`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Pattern(Regex);
+
+impl Pattern {
+    /// Get the pattern as written
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Get the compiled expression
+    pub fn regex(&self) -> &Regex {
+        &self.0
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Pattern {}
+
+impl TryFrom<String> for Pattern {
+    type Error = regex::Error;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        Regex::new(&pattern).map(Self)
+    }
+}
+
+impl std::str::FromStr for Pattern {
+    type Err = regex::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Regex::new(s).map(Self)
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One step of a command's `transform` list in the config
+///
+/// ```toml
+/// transform = [
+///   { type = "strip-ansi" },
+///   { type = "replace", pattern = "^\\S+Z ", with = "" },
+///   { type = "mask", pattern = "ghp_\\w+" },
+///   { type = "truncate", max_chars = 500 },
+/// ]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum TransformSpec {
+    /// Remove ANSI escape sequences (colors, cursor movement, titles)
+    StripAnsi,
+    /// Replace matches with `****`
+    Mask { pattern: Pattern },
+    /// Replace matches, with `$1`-style references to capture groups
+    Replace { pattern: Pattern, with: String },
+    /// Cut lines longer than `max_chars` characters, marking them with `…`
+    Truncate { max_chars: usize },
+}
+
+/// Rewrites output lines before they reach the buffer, sinks and exports
+pub trait Transformer: fmt::Debug + Send + Sync {
+    /// Transform a line, borrowing it unchanged where possible
+    fn transform<'a>(&self, line: Cow<'a, str>) -> Cow<'a, str>;
+}
+
+impl Transformer for TransformSpec {
+    fn transform<'a>(&self, line: Cow<'a, str>) -> Cow<'a, str> {
+        match self {
+            TransformSpec::StripAnsi => match strip_ansi(&line) {
+                Cow::Owned(stripped) => Cow::Owned(stripped),
+                Cow::Borrowed(_) => line,
+            },
+            TransformSpec::Mask { pattern } => replace_all(pattern.regex(), line, MASK),
+            TransformSpec::Replace { pattern, with } => replace_all(pattern.regex(), line, with),
+            TransformSpec::Truncate { max_chars } => match line.char_indices().nth(*max_chars) {
+                Some((end, _)) => Cow::Owned(format!("{}{}", &line[..end], TRUNCATED)),
+                None => line,
+            },
+        }
+    }
+}
+
+/// Replace all matches in a line, keeping it borrowed if nothing matched
+fn replace_all<'a>(regex: &Regex, line: Cow<'a, str>, with: &str) -> Cow<'a, str> {
+    match regex.replace_all(&line, with) {
+        Cow::Owned(replaced) => Cow::Owned(replaced),
+        Cow::Borrowed(_) => line,
+    }
+}
+
+/// A command's transformers, applied in order to every line read
+///
+/// Secret masking (`mask`, `mask_env`) runs first, followed by the
+/// `transform` list.
+#[derive(Debug)]
+pub struct Pipeline {
+    stages: Vec<Box<dyn Transformer>>,
+}
+
+impl Pipeline {
+    /// Assemble the pipeline for a spec, or `None` if it transforms nothing
+    pub fn new(spec: &CommandSpec) -> Option<Self> {
+        let mut stages: Vec<Box<dyn Transformer>> = Vec::new();
+        if let Some(mask) = SecretMask::new(spec) {
+            stages.push(Box::new(mask));
+        }
+        for transform in &spec.transform {
+            stages.push(Box::new(transform.clone()));
+        }
+        (!stages.is_empty()).then_some(Self { stages })
+    }
+
+    /// Run a line through every stage
+    pub fn apply(&self, line: String) -> String {
+        let transformed = self
+            .stages
+            .iter()
+            .fold(Cow::Borrowed(line.as_str()), |line, stage| {
+                stage.transform(line)
+            });
+        match transformed {
+            Cow::Owned(transformed) => transformed,
+            Cow::Borrowed(_) => line,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// Parse a transform written as an inline table
+    fn parse(toml: &str) -> Result<TransformSpec, toml::de::Error> {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            transform: TransformSpec,
+        }
+        toml::from_str::<Wrapper>(&format!("transform = {}", toml)).map(|w| w.transform)
+    }
+
+    fn transform(toml: &str) -> TransformSpec {
+        parse(toml).unwrap()
+    }
+
+    #[rstest]
+    #[case(
+        r#"{ type = "strip-ansi" }"#,
+        "\x1b[31merror\x1b[0m \x1b]0;title\x07done",
+        "error done"
+    )]
+    #[case(r#"{ type = "mask", pattern = "\\d{4}" }"#, "pin 1234", "pin ****")]
+    #[case(
+        r#"{ type = "replace", pattern = "^(\\S+)Z (.*)", with = "$2 @$1" }"#,
+        "2024-01-01T00:00:00Z started",
+        "started @2024-01-01T00:00:00"
+    )]
+    #[case(r#"{ type = "truncate", max_chars = 5 }"#, "abcdefgh", "abcde…")]
+    #[case(r#"{ type = "truncate", max_chars = 5 }"#, "abcde", "abcde")]
+    #[case(r#"{ type = "truncate", max_chars = 2 }"#, "äöü", "äö…")]
+    fn transform_spec_transforms_lines(
+        #[case] toml: &str,
+        #[case] line: &str,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(transform(toml).transform(Cow::Borrowed(line)), expected);
+    }
+
+    #[rstest]
+    #[case(r#"{ type = "upcase" }"#)]
+    #[case(r#"{ type = "mask", pattern = "[" }"#)]
+    #[case(r#"{ type = "truncate" }"#)]
+    fn transform_spec_rejects_invalid_transforms(#[case] toml: &str) {
+        assert!(parse(toml).is_err());
+    }
+
+    #[test]
+    fn pipeline_applies_stages_in_order() {
+        let spec = CommandSpec {
+            mask_env: vec!["TOKEN".to_string()],
+            env: [("TOKEN".to_string(), "secret-value".to_string())].into(),
+            transform: vec![
+                transform(r#"{ type = "strip-ansi" }"#),
+                transform(r#"{ type = "truncate", max_chars = 12 }"#),
+            ],
+            ..CommandSpec::new("deploy")
+        };
+
+        let pipeline = Pipeline::new(&spec).unwrap();
+
+        assert_eq!(
+            pipeline.apply("\x1b[1mtoken secret-value sent\x1b[0m".to_string()),
+            "token **** s…"
+        );
+    }
+
+    #[test]
+    fn pipeline_is_none_without_transforms() {
+        assert!(Pipeline::new(&CommandSpec::new("ls")).is_none());
+    }
+}
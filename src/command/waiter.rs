@@ -0,0 +1,226 @@
+use std::os::unix::process::ExitStatusExt;
+
+use nix::sys::signal::{Signal, killpg};
+use nix::unistd::Pid;
+use tokio::process::Child;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use crate::command::{CommandSpec, SSH_CONNECTION_FAILED};
+use crate::event::AppEvent;
+
+/// Handle to a child process owned by a waiter task
+///
+/// The task waits for the child (so it never lingers as a zombie) and
+/// reports how it ended: `AppEvent::Exited` with the exit code,
+/// `AppEvent::Signaled` when it was terminated by a signal, or
+/// `AppEvent::Failed` if waiting failed, the OOM killer stopped it or an
+/// ssh command couldn't connect.
+pub struct ChildWaiter {
+    pid: Option<u32>,
+    kill_tx: oneshot::Sender<()>,
+    /// Resolves to whether an exit event was sent
+    task: JoinHandle<bool>,
+}
+
+impl ChildWaiter {
+    /// Spawn a waiter task for a tab's child process
+    pub fn spawn(
+        mut child: Child,
+        spec: &CommandSpec,
+        tab_index: usize,
+        event_tx: mpsc::Sender<AppEvent>,
+    ) -> Self {
+        let pid = child.id();
+        let (kill_tx, mut kill_rx) = oneshot::channel();
+        #[cfg(all(feature = "cgroups", target_os = "linux"))]
+        let memory_max = spec.has_resource_limits().then(|| spec.memory_max.clone());
+        let ssh = spec.ssh.clone();
+
+        let task = tokio::spawn(async move {
+            let status = tokio::select! {
+                status = child.wait() => status,
+                // Killed on request: reap without reporting
+                Ok(()) = &mut kill_rx => {
+                    if let Some(pid) = pid {
+                        let _ = killpg(Pid::from_raw(pid as i32), Signal::SIGKILL);
+                    }
+                    let _ = child.wait().await;
+                    return false;
+                }
+            };
+
+            let event = match status {
+                Ok(status) => match (status.code(), status.signal()) {
+                    (Some(exit_code), _) => AppEvent::Exited {
+                        tab_index,
+                        exit_code,
+                    },
+                    (None, Some(signal)) => AppEvent::Signaled { tab_index, signal },
+                    (None, None) => AppEvent::Exited {
+                        tab_index,
+                        exit_code: -1,
+                    },
+                },
+                Err(e) => AppEvent::Failed {
+                    tab_index,
+                    reason: e.to_string(),
+                },
+            };
+
+            // ssh exits with 255 when it can't connect
+            let event = match (event, ssh) {
+                (
+                    AppEvent::Exited {
+                        exit_code: SSH_CONNECTION_FAILED,
+                        ..
+                    },
+                    Some(destination),
+                ) => AppEvent::Failed {
+                    tab_index,
+                    reason: format!("ssh connection to {} failed", destination),
+                },
+                (event, _) => event,
+            };
+
+            #[cfg(all(feature = "cgroups", target_os = "linux"))]
+            let event = match memory_max {
+                Some(memory_max) => oom_checked(event, tab_index, memory_max).await,
+                None => event,
+            };
+
+            let _ = event_tx.send(event).await;
+            true
+        });
+
+        Self { pid, kill_tx, task }
+    }
+
+    /// Get the process ID (`None` if the child had already been reaped)
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// Send a signal to the child's process group
+    pub fn signal(&self, signal: Signal) -> nix::Result<()> {
+        match self.pid {
+            Some(pid) => killpg(Pid::from_raw(pid as i32), signal),
+            None => Err(nix::errno::Errno::ESRCH),
+        }
+    }
+
+    /// Kill the child's process group and wait until it has been reaped
+    ///
+    /// No event is sent for a child killed this way. Returns `true` if the
+    /// child had already exited and its exit event was sent (the event is
+    /// then still in flight and refers to the killed run).
+    pub async fn kill(self) -> bool {
+        let _ = self.kill_tx.send(());
+        self.task.await.unwrap_or(false)
+    }
+}
+
+/// Name of a signal number (`SIGSEGV`), or `signal N` if it's unknown
+pub fn signal_name(signal: i32) -> String {
+    Signal::try_from(signal)
+        .map(|signal| signal.as_str().to_string())
+        .unwrap_or_else(|_| format!("signal {}", signal))
+}
+
+/// Replace the exit event with an OOM failure if the cgroup reports one
+#[cfg(all(feature = "cgroups", target_os = "linux"))]
+async fn oom_checked(event: AppEvent, tab_index: usize, memory_max: Option<String>) -> AppEvent {
+    let oom_killed = tokio::task::spawn_blocking(move || super::cgroup::take_oom_kill(tab_index))
+        .await
+        .unwrap_or(false);
+    if !oom_killed {
+        return event;
+    }
+    let reason = match memory_max {
+        Some(limit) => format!("out of memory (memory_max = {})", limit),
+        None => "out of memory".to_string(),
+    };
+    AppEvent::Failed { tab_index, reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::CommandRunner;
+
+    /// Spawn a command with a waiter reporting to its own channel
+    async fn spawn_waiter(command: &str) -> (ChildWaiter, mpsc::Receiver<AppEvent>) {
+        let spec = CommandSpec::new(command);
+        let (output_tx, _output_rx) = mpsc::channel(100);
+        let child = CommandRunner::spawn(output_tx, &spec, 0).await.unwrap();
+        let (tx, rx) = mpsc::channel(100);
+        (ChildWaiter::spawn(child, &spec, 0, tx), rx)
+    }
+
+    #[tokio::test]
+    async fn child_waiter_reports_exit_code() {
+        let (_waiter, mut rx) = spawn_waiter("exit 3").await;
+
+        let event = rx.recv().await.unwrap();
+        assert!(matches!(
+            event,
+            AppEvent::Exited {
+                tab_index: 0,
+                exit_code: 3
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn child_waiter_reports_ssh_connection_failure() {
+        // Spawned locally; the waiter only looks at the spec and exit code
+        let local = CommandSpec::new("exit 255");
+        let (output_tx, _output_rx) = mpsc::channel(100);
+        let child = CommandRunner::spawn(output_tx, &local, 0).await.unwrap();
+        let remote = CommandSpec {
+            ssh: Some("deploy@web1".into()),
+            ..local
+        };
+        let (tx, mut rx) = mpsc::channel(100);
+        let _waiter = ChildWaiter::spawn(child, &remote, 0, tx);
+
+        match rx.recv().await.unwrap() {
+            AppEvent::Failed { reason, .. } => {
+                assert_eq!(reason, "ssh connection to deploy@web1 failed")
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn child_waiter_reports_termination_signal() {
+        let (_waiter, mut rx) = spawn_waiter("kill -SEGV $$").await;
+
+        let event = rx.recv().await.unwrap();
+        assert!(matches!(
+            event,
+            AppEvent::Signaled {
+                tab_index: 0,
+                signal
+            } if signal == Signal::SIGSEGV as i32
+        ));
+    }
+
+    #[test]
+    fn signal_name_falls_back_to_number() {
+        assert_eq!(signal_name(Signal::SIGKILL as i32), "SIGKILL");
+        assert_eq!(signal_name(200), "signal 200");
+    }
+
+    #[tokio::test]
+    async fn child_waiter_kill_reaps_without_event() {
+        let (waiter, mut rx) = spawn_waiter("sleep 10").await;
+        let pid = waiter.pid().unwrap();
+
+        assert!(!waiter.kill().await);
+
+        // Reaped: the PID no longer refers to a process (not even a zombie)
+        assert!(nix::sys::signal::kill(Pid::from_raw(pid as i32), None).is_err());
+        assert!(rx.try_recv().is_err());
+    }
+}
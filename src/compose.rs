@@ -0,0 +1,136 @@
+//! Tabs for the services of a Docker Compose project (`--compose`)
+//!
+//! Each service gets a tab following `docker compose logs -f <service>`;
+//! restarting the tab runs `docker compose restart <service>` first.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::command::{CommandSpec, shell_quote};
+
+/// File names `docker compose` looks for without `-f`, in its order
+const DEFAULT_FILES: [&str; 4] = [
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yaml",
+    "docker-compose.yml",
+];
+
+/// List the services defined by a compose file (or the default one)
+///
+/// Runs `docker compose config --services`, which also validates the file.
+pub fn services(file: Option<&Path>) -> io::Result<Vec<String>> {
+    let mut command = Command::new("docker");
+    command.arg("compose");
+    if let Some(file) = file {
+        command.arg("-f").arg(file);
+    }
+    let output = command.args(["config", "--services"]).output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!(
+            "docker compose config failed: {}",
+            stderr.trim()
+        )));
+    }
+    Ok(parse_services(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// The file identifying a compose project, for its session lock
+///
+/// Without an explicit file, this is the default file of the current
+/// directory (or the directory itself if it has none).
+pub fn project_path(file: Option<&Path>) -> PathBuf {
+    if let Some(file) = file {
+        return file.to_path_buf();
+    }
+    let dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    default_file(&dir).unwrap_or(dir)
+}
+
+/// The compose file `docker compose` would use in `dir`
+fn default_file(dir: &Path) -> Option<PathBuf> {
+    DEFAULT_FILES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Build the tab for a service: its logs, restarted through compose
+pub fn service_spec(file: Option<&Path>, service: &str) -> CommandSpec {
+    let compose = compose_command(file);
+    let service_arg = shell_quote(service);
+    CommandSpec {
+        name: Some(service.to_string()),
+        before_restart: Some(format!("{} restart {}", compose, service_arg)),
+        ..CommandSpec::new(format!(
+            "{} logs -f --no-log-prefix {}",
+            compose, service_arg
+        ))
+    }
+}
+
+/// `docker compose`, with `-f` for an explicit file
+fn compose_command(file: Option<&Path>) -> String {
+    match file {
+        Some(file) => format!("docker compose -f {}", shell_quote(&file.to_string_lossy())),
+        None => "docker compose".to_string(),
+    }
+}
+
+/// Parse the one-service-per-line output of `config --services`
+fn parse_services(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_services_skips_blank_lines() {
+        assert_eq!(parse_services("web\n\ndb\n"), vec!["web", "db"]);
+    }
+
+    #[test]
+    fn default_file_prefers_compose_yaml() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(default_file(dir.path()), None);
+
+        std::fs::write(dir.path().join("docker-compose.yml"), "").unwrap();
+        std::fs::write(dir.path().join("compose.yaml"), "").unwrap();
+
+        assert_eq!(
+            default_file(dir.path()),
+            Some(dir.path().join("compose.yaml"))
+        );
+    }
+
+    #[test]
+    fn service_spec_follows_logs_and_restarts_through_compose() {
+        let spec = service_spec(Some(Path::new("deploy/compose.yml")), "web");
+
+        assert_eq!(spec.name.as_deref(), Some("web"));
+        assert_eq!(
+            spec.command,
+            "docker compose -f 'deploy/compose.yml' logs -f --no-log-prefix 'web'"
+        );
+        assert_eq!(
+            spec.before_restart.as_deref(),
+            Some("docker compose -f 'deploy/compose.yml' restart 'web'")
+        );
+    }
+
+    #[test]
+    fn service_spec_uses_default_compose_file() {
+        let spec = service_spec(None, "db");
+
+        assert_eq!(spec.command, "docker compose logs -f --no-log-prefix 'db'");
+    }
+}
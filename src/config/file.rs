@@ -0,0 +1,257 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::command::CommandSpec;
+use crate::tui::StatusBarConfig;
+
+/// Error while loading a config file
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("failed to read {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("failed to parse {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("{path}: commands[{index}] needs exactly one of `command`, `tail` and `source`")]
+    CommandOrSource { path: PathBuf, index: usize },
+}
+
+/// Config file contents
+///
+/// ```toml
+/// setup = "docker compose up -d db"
+/// teardown = "docker compose down"
+///
+/// [[commands]]
+/// name = "web"
+/// command = "npm run dev"
+/// cwd = "web"
+/// env = { PORT = "3000" }
+///
+/// [[commands]]
+/// tail = "logs/app.log"
+///
+/// [[commands]]
+/// source = "listen://0.0.0.0:5000"
+///
+/// [status_bar]
+/// format = "{mode} | {tab}: {tab_status} {elapsed}"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Shell command run in its own tab before the commands, which only
+    /// start once it has succeeded
+    #[serde(default)]
+    pub setup: Option<String>,
+    /// Shell command run once after all commands have stopped
+    #[serde(default)]
+    pub teardown: Option<String>,
+    /// Commands to run, one tab each
+    #[serde(default)]
+    pub commands: Vec<CommandSpec>,
+    /// Copy lines with `Y` as shown, with their `[stdout]` prefix and times
+    #[serde(default)]
+    pub copy_prefix: bool,
+    /// Status bar settings
+    #[serde(default)]
+    pub status_bar: StatusBarConfig,
+}
+
+impl Config {
+    /// Load a config file
+    ///
+    /// Relative `cwd`, `tail` and `source` paths are resolved against the
+    /// directory containing the config file. Each entry must have exactly
+    /// one of a `command`, a `tail` file or a `source` address.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let mut config: Config = toml::from_str(&content).map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        for (index, spec) in config.commands.iter_mut().enumerate() {
+            let sources = [
+                !spec.command.is_empty(),
+                spec.tail.is_some(),
+                spec.source.is_some(),
+            ];
+            if sources.into_iter().filter(|&set| set).count() != 1 {
+                return Err(ConfigError::CommandOrSource {
+                    path: path.to_path_buf(),
+                    index,
+                });
+            }
+            if let Some(cwd) = &spec.cwd
+                && cwd.is_relative()
+            {
+                spec.cwd = Some(base_dir.join(cwd));
+            }
+            if let Some(tail) = &spec.tail
+                && tail.is_relative()
+            {
+                spec.tail = Some(base_dir.join(tail));
+            }
+            if let Some(source) = &mut spec.source {
+                source.resolve_against(base_dir);
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_config(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn config_load_reads_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_config(
+            dir.path(),
+            "commands.toml",
+            r#"
+            [[commands]]
+            name = "web"
+            command = "npm run dev"
+
+            [[commands]]
+            command = "cargo watch"
+            "#,
+        );
+
+        let config = Config::load(&path).unwrap();
+
+        assert_eq!(config.commands.len(), 2);
+        assert_eq!(config.commands[0].name.as_deref(), Some("web"));
+        assert_eq!(config.commands[1].command, "cargo watch");
+    }
+
+    #[test]
+    fn config_load_reads_setup_and_teardown() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_config(
+            dir.path(),
+            "setup.toml",
+            r#"
+            setup = "docker compose up -d db"
+            teardown = "docker compose down"
+
+            [[commands]]
+            command = "npm run dev"
+            "#,
+        );
+
+        let config = Config::load(&path).unwrap();
+
+        assert_eq!(config.setup.as_deref(), Some("docker compose up -d db"));
+        assert_eq!(config.teardown.as_deref(), Some("docker compose down"));
+    }
+
+    #[test]
+    fn config_load_reports_invalid_status_bar_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_config(
+            dir.path(),
+            "status_bar.toml",
+            "[status_bar]\nformat = \"{mode} {nope}\"\n",
+        );
+        let err = Config::load(&path).unwrap_err();
+        assert!(err.to_string().contains("unknown placeholder: {nope}"));
+    }
+
+    #[test]
+    fn config_load_resolves_relative_cwd_against_config_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_config(
+            dir.path(),
+            "cwd.toml",
+            r#"
+            [[commands]]
+            command = "ls"
+            cwd = "sub"
+
+            [[commands]]
+            command = "ls"
+            cwd = "/tmp"
+            "#,
+        );
+
+        let config = Config::load(&path).unwrap();
+
+        assert_eq!(
+            config.commands[0].cwd,
+            Some(path.parent().unwrap().join("sub"))
+        );
+        assert_eq!(config.commands[1].cwd, Some(PathBuf::from("/tmp")));
+    }
+
+    #[test]
+    fn config_load_resolves_relative_tail_against_config_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_config(
+            dir.path(),
+            "tail.toml",
+            "[[commands]]\ntail = \"logs/app.log\"\n",
+        );
+
+        let config = Config::load(&path).unwrap();
+
+        assert_eq!(
+            config.commands[0].tail,
+            Some(path.parent().unwrap().join("logs/app.log"))
+        );
+    }
+
+    #[rstest::rstest]
+    #[case("neither.toml", "[[commands]]\nname = \"empty\"\n")]
+    #[case("both.toml", "[[commands]]\ncommand = \"ls\"\ntail = \"app.log\"\n")]
+    #[case(
+        "tail_and_source.toml",
+        "[[commands]]\ntail = \"app.log\"\nsource = \"fifo://app.pipe\"\n"
+    )]
+    fn config_load_requires_one_source(#[case] name: &str, #[case] content: &str) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_config(dir.path(), name, content);
+
+        let result = Config::load(&path);
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::CommandOrSource { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn config_load_returns_read_error_for_missing_file() {
+        let result = Config::load(Path::new("/nonexistent/parallels.toml"));
+        assert!(matches!(result, Err(ConfigError::Read { .. })));
+    }
+
+    #[test]
+    fn config_load_returns_parse_error_for_invalid_toml() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_config(dir.path(), "invalid.toml", "[[commands]]\nname = 1\n");
+        let result = Config::load(&path);
+        assert!(matches!(result, Err(ConfigError::Parse { .. })));
+    }
+}
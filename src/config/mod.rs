@@ -0,0 +1,5 @@
+mod file;
+mod watch;
+
+pub use file::{Config, ConfigError};
+pub use watch::ConfigWatcher;
@@ -0,0 +1,104 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use super::{Config, ConfigError};
+
+/// How often the config file is checked for changes
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Watches a config file for changes by polling its modification time
+pub struct ConfigWatcher {
+    path: PathBuf,
+    /// Modification time of the last version read
+    modified: Option<SystemTime>,
+    /// Contents of the last version that loaded
+    config: Config,
+    last_check: Option<Instant>,
+}
+
+impl ConfigWatcher {
+    /// Watch a config file, whose current contents are `config`
+    pub fn new(path: impl Into<PathBuf>, config: Config) -> Self {
+        let path = path.into();
+        Self {
+            modified: modified(&path),
+            path,
+            config,
+            last_check: None,
+        }
+    }
+
+    /// Get the contents of the last version that loaded
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Check the file if the last check was long enough ago (see [`check`](Self::check))
+    pub fn poll(&mut self) -> Option<Result<Config, ConfigError>> {
+        if self
+            .last_check
+            .is_some_and(|checked| checked.elapsed() < CHECK_INTERVAL)
+        {
+            return None;
+        }
+        self.last_check = Some(Instant::now());
+        self.check()
+    }
+
+    /// Load the file again if it changed since it was last read
+    ///
+    /// Returns the previous contents once the new ones are loaded, or the
+    /// error of a version that doesn't load, which is reported only once.
+    pub fn check(&mut self) -> Option<Result<Config, ConfigError>> {
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        Some(Config::load(&self.path).map(|config| std::mem::replace(&mut self.config, config)))
+    }
+}
+
+/// Modification time of a file, or `None` while it is missing
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    /// Write a config file and give it a distinct modification time
+    fn write(path: &Path, content: &str, age: u64) {
+        std::fs::write(path, content).unwrap();
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(age))
+            .unwrap();
+    }
+
+    #[test]
+    fn config_watcher_reloads_changed_file_and_reports_errors_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("parallels.toml");
+        write(&path, "copy_prefix = false", 30);
+        let mut watcher = ConfigWatcher::new(&path, Config::load(&path).unwrap());
+        assert!(watcher.check().is_none());
+
+        write(&path, "copy_prefix = true", 20);
+        let old = watcher.check().unwrap().unwrap();
+        assert!(!old.copy_prefix);
+        assert!(watcher.config().copy_prefix);
+
+        write(&path, "copy_prefix = ", 10);
+        assert!(matches!(
+            watcher.check(),
+            Some(Err(ConfigError::Parse { .. }))
+        ));
+        assert!(watcher.check().is_none());
+        assert!(watcher.config().copy_prefix);
+    }
+}
@@ -0,0 +1,145 @@
+//! Scripted commands for `--demo`
+//!
+//! `parallels --demo` opens a few tabs that look like a typical dev session
+//! (a server, a test run, a build) without running anything, so the UI can
+//! be tried out, recorded or screenshotted anywhere.
+
+use std::time::Duration;
+
+use crate::command::{CommandSpec, Script, ScriptedSpawner};
+
+/// Dev server logging requests until it is killed
+const SERVER: &str = "npm run dev";
+
+/// Test run with a failing test
+const TESTS: &str = "cargo test";
+
+/// Release build with a warning
+const BUILD: &str = "cargo build --release";
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+/// Get the tabs of the demo
+pub fn commands() -> Vec<CommandSpec> {
+    [SERVER, TESTS, BUILD]
+        .into_iter()
+        .map(CommandSpec::new)
+        .collect()
+}
+
+/// Get the spawner playing back the demo's commands
+pub fn spawner() -> ScriptedSpawner {
+    ScriptedSpawner::new()
+        .script(SERVER, server())
+        .script(TESTS, tests())
+        .script(BUILD, build())
+}
+
+fn server() -> Script {
+    let mut script = Script::new()
+        .stdout("> app@1.0.0 dev")
+        .stdout("> vite")
+        .sleep(ms(600))
+        .stdout("\x1b[32m  VITE v5.2.0\x1b[0m  ready in \x1b[1m412\x1b[0m ms")
+        .stdout("  ➜  Local:   \x1b[36mhttp://localhost:5173/\x1b[0m")
+        .sleep(ms(1000));
+    let requests = [
+        ("GET", "/", 200, 12),
+        ("GET", "/api/items", 200, 48),
+        ("POST", "/api/items", 201, 95),
+        ("GET", "/api/items/42", 404, 3),
+        ("GET", "/assets/app.js", 200, 7),
+    ];
+    for (method, path, status, took) in requests {
+        let color = if status >= 400 { 33 } else { 32 };
+        script = script
+            .stdout(format!(
+                "{} {} \x1b[{}m{}\x1b[0m {}ms",
+                method, path, color, status, took
+            ))
+            .sleep(ms(700));
+    }
+    script
+        .stderr("\x1b[31m[ERROR]\x1b[0m database connection reset, retrying")
+        .sleep(ms(1500))
+        .repeat()
+}
+
+fn tests() -> Script {
+    let passing = [
+        "config::tests::parses_defaults",
+        "config::tests::rejects_unknown_keys",
+        "buffer::tests::keeps_last_lines",
+        "search::tests::smartcase_matches",
+        "api::tests::lists_items",
+    ];
+    let mut script = Script::new()
+        .stderr("   Compiling app v1.0.0 (/src/app)")
+        .sleep(ms(1500))
+        .stderr("    Finished `test` profile [unoptimized + debuginfo] target(s) in 3.21s")
+        .stderr("     Running unittests src/lib.rs")
+        .stdout("")
+        .stdout(format!("running {} tests", passing.len() + 1));
+    for name in passing {
+        script = script
+            .stdout(format!("test {} ... \x1b[32mok\x1b[0m", name))
+            .sleep(ms(250));
+    }
+    script
+        .stdout("test api::tests::creates_item ... \x1b[31mFAILED\x1b[0m")
+        .stdout("")
+        .stdout("failures:")
+        .stdout("")
+        .stdout("---- api::tests::creates_item stdout ----")
+        .stdout("thread 'api::tests::creates_item' panicked at src/api.rs:88:9:")
+        .stdout("assertion `left == right` failed")
+        .stdout("  left: 500")
+        .stdout(" right: 201")
+        .stdout("")
+        .stdout(
+            "test result: \x1b[31mFAILED\x1b[0m. 5 passed; 1 failed; 0 ignored; finished in 0.84s",
+        )
+        .stderr("error: test failed, to rerun pass `--lib`")
+        .exit(101)
+}
+
+fn build() -> Script {
+    let crates = [
+        "libc v0.2.155",
+        "serde v1.0.204",
+        "tokio v1.39.2",
+        "hyper v1.4.1",
+        "app v1.0.0 (/src/app)",
+    ];
+    let mut script = Script::new();
+    for name in crates {
+        script = script
+            .stderr(format!("   \x1b[32;1mCompiling\x1b[0m {}", name))
+            .sleep(ms(900));
+    }
+    script
+        .stderr("\x1b[33;1mwarning\x1b[0m: unused variable: `retries`")
+        .stderr("  --> src/db.rs:17:9")
+        .sleep(ms(1200))
+        .stderr("    \x1b[32;1mFinished\x1b[0m `release` profile [optimized] target(s) in 6.02s")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_demo_command_has_a_script() {
+        let spawner = spawner();
+
+        for spec in commands() {
+            assert!(
+                spawner.has_script(&spec.command),
+                "no script for {}",
+                spec.command
+            );
+        }
+    }
+}
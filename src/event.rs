@@ -1,11 +1,65 @@
+use std::time::Duration;
+
 use crate::buffer::OutputLine;
+use crate::search::Match;
 
-/// Event from background command tasks
+/// Event from background command tasks, or a lifecycle event of the app
+///
+/// `Started`, `Restarted`, `Killed` and `Stats` are published by the app
+/// itself to subscribers and sinks; they never arrive through the channel.
+#[derive(Debug, Clone)]
 pub enum AppEvent {
     /// Output line for a specific tab
     Output { tab_index: usize, line: OutputLine },
     /// Command exited
     Exited { tab_index: usize, exit_code: i32 },
+    /// Command was terminated by a signal (e.g. SIGSEGV, SIGKILL)
+    Signaled { tab_index: usize, signal: i32 },
     /// Command failed to start
     Failed { tab_index: usize, reason: String },
+    /// Output lines were dropped because the UI could not keep up
+    Dropped { tab_index: usize, count: u64 },
+    /// Scheduled start of a command is due
+    ///
+    /// `run` is the tab's run count when the start was scheduled; the event
+    /// is ignored if the tab has been started since.
+    Start { tab_index: usize, run: usize },
+    /// Hang probe of a silent command finished (`healthy` if it succeeded)
+    ///
+    /// `run` is the tab's run count when the probe was started.
+    Probed {
+        tab_index: usize,
+        run: usize,
+        healthy: bool,
+    },
+    /// Readiness check of a command's first port finished (`listening` if
+    /// something accepted a connection)
+    ///
+    /// `run` is the tab's run count when the check was started.
+    PortChecked {
+        tab_index: usize,
+        run: usize,
+        listening: bool,
+    },
+    /// Matches found by a background search of a large buffer, the last
+    /// batch `done`
+    Searched {
+        id: u64,
+        matches: Vec<Match>,
+        done: bool,
+    },
+    /// A run of a command was spawned
+    Started { tab_index: usize, pid: Option<u32> },
+    /// A command was killed and started again (by the user or its watchdog);
+    /// `attempt` counts the restarts of the tab
+    Restarted { tab_index: usize, attempt: usize },
+    /// A running command was killed by parallels (restart or quit)
+    Killed { tab_index: usize, signal: i32 },
+    /// Summary of a run that ended
+    Stats {
+        tab_index: usize,
+        /// Output lines printed during the run
+        lines: u64,
+        elapsed: Duration,
+    },
 }
@@ -0,0 +1,219 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use serde::Serialize;
+
+use super::write_lines_to;
+use crate::buffer::OutputBuffer;
+
+/// Name of the manifest file inside an export
+const MANIFEST_FILE: &str = "manifest.toml";
+
+/// A single tab's log to include in an export
+pub struct TabLog<'a> {
+    /// Tab display name
+    pub name: &'a str,
+    /// Shell command line
+    pub command: &'a str,
+    /// Human-readable command status
+    pub status: String,
+    /// Note attached to the tab
+    pub note: Option<&'a str>,
+    /// Output buffer of the tab
+    pub buffer: &'a OutputBuffer,
+}
+
+/// Manifest describing the exported logs
+#[derive(Serialize)]
+struct Manifest<'a> {
+    tabs: Vec<ManifestEntry<'a>>,
+}
+
+#[derive(Serialize)]
+struct ManifestEntry<'a> {
+    file: String,
+    name: &'a str,
+    command: &'a str,
+    status: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<&'a str>,
+    lines: usize,
+}
+
+/// Export every tab's log to its own file plus a manifest
+///
+/// If `path` ends with `.tar.gz` or `.tgz` the files are bundled into a
+/// gzipped tarball, otherwise they are written into the directory at `path`
+/// (created if missing).
+///
+/// # Returns
+/// The number of tabs exported.
+pub fn export_all(path: &Path, tabs: &[TabLog]) -> io::Result<usize> {
+    let files = render_files(tabs)?;
+    if is_tarball(path) {
+        write_tarball(path, &files)?;
+    } else {
+        write_directory(path, &files)?;
+    }
+    Ok(tabs.len())
+}
+
+/// Render the log files and the manifest in memory as `(file name, content)`
+fn render_files(tabs: &[TabLog]) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let mut files = Vec::with_capacity(tabs.len() + 1);
+    let mut manifest = Manifest { tabs: Vec::new() };
+
+    for (index, tab) in tabs.iter().enumerate() {
+        let file = format!("{:02}-{}.log", index + 1, sanitize_file_name(tab.name));
+        let mut content = Vec::new();
+        let lines = write_lines_to(&mut content, tab.buffer.iter())?;
+        manifest.tabs.push(ManifestEntry {
+            file: file.clone(),
+            name: tab.name,
+            command: tab.command,
+            status: &tab.status,
+            note: tab.note,
+            lines,
+        });
+        files.push((file, content));
+    }
+
+    let manifest = toml::to_string(&manifest).map_err(io::Error::other)?;
+    files.push((MANIFEST_FILE.to_string(), manifest.into_bytes()));
+    Ok(files)
+}
+
+/// Check if the path names a gzipped tarball
+fn is_tarball(path: &Path) -> bool {
+    let name = path.to_string_lossy();
+    name.ends_with(".tar.gz") || name.ends_with(".tgz")
+}
+
+/// Write the files into a directory
+fn write_directory(dir: &Path, files: &[(String, Vec<u8>)]) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    for (name, content) in files {
+        fs::write(dir.join(name), content)?;
+    }
+    Ok(())
+}
+
+/// Write the files into a gzipped tarball
+fn write_tarball(path: &Path, files: &[(String, Vec<u8>)]) -> io::Result<()> {
+    let encoder = GzEncoder::new(File::create(path)?, Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    for (name, content) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, name, content.as_slice())?;
+    }
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// Replace characters that are unsafe in file names
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::{OutputKind, OutputLine};
+    use std::io::Read;
+
+    fn buffer_with(lines: &[&str]) -> OutputBuffer {
+        let mut buffer = OutputBuffer::new(100);
+        for line in lines {
+            buffer.push(OutputLine::new(OutputKind::Stdout, (*line).to_string()));
+        }
+        buffer
+    }
+
+    #[test]
+    fn export_all_writes_directory_with_manifest() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("logs");
+        let web = buffer_with(&["listening", "GET /"]);
+        let db = buffer_with(&["ready"]);
+        let tabs = [
+            TabLog {
+                name: "web server",
+                command: "npm run dev",
+                status: "running".into(),
+                note: Some("restarted after migration"),
+                buffer: &web,
+            },
+            TabLog {
+                name: "db",
+                command: "postgres",
+                status: "exited (1)".into(),
+                note: None,
+                buffer: &db,
+            },
+        ];
+
+        let count = export_all(&dir, &tabs).unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(
+            fs::read_to_string(dir.join("01-web_server.log")).unwrap(),
+            "listening\nGET /\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("02-db.log")).unwrap(),
+            "ready\n"
+        );
+        let manifest = fs::read_to_string(dir.join(MANIFEST_FILE)).unwrap();
+        assert!(manifest.contains(r#"status = "exited (1)""#));
+        assert!(manifest.contains(r#"file = "01-web_server.log""#));
+        assert!(manifest.contains(r#"note = "restarted after migration""#));
+        assert_eq!(manifest.matches("note =").count(), 1);
+    }
+
+    #[test]
+    fn export_all_writes_tarball() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs.tar.gz");
+        let buffer = buffer_with(&["hello"]);
+        let tabs = [TabLog {
+            name: "echo",
+            command: "echo hello",
+            status: "exited (0)".into(),
+            note: None,
+            buffer: &buffer,
+        }];
+
+        export_all(&path, &tabs).unwrap();
+
+        let decoder = flate2::read::GzDecoder::new(File::open(&path).unwrap());
+        let mut archive = tar::Archive::new(decoder);
+        let mut entries = Vec::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().into_owned();
+            let mut content = String::new();
+            entry.read_to_string(&mut content).unwrap();
+            entries.push((name, content));
+        }
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0],
+            ("01-echo.log".to_string(), "hello\n".to_string())
+        );
+        assert_eq!(entries[1].0, MANIFEST_FILE);
+    }
+}
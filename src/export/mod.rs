@@ -0,0 +1,5 @@
+mod archive;
+mod writer;
+
+pub use archive::{TabLog, export_all};
+pub use writer::{write_lines, write_lines_to};
@@ -0,0 +1,65 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::buffer::LineRef;
+
+/// Write output lines to a file as plain text
+///
+/// ANSI styling is dropped; each line is written followed by a newline.
+/// An existing file at `path` is overwritten.
+///
+/// # Returns
+/// The number of lines written.
+pub fn write_lines<'a>(
+    path: &Path,
+    lines: impl IntoIterator<Item = LineRef<'a>>,
+) -> io::Result<usize> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    let count = write_lines_to(&mut writer, lines)?;
+    writer.flush()?;
+    Ok(count)
+}
+
+/// Write output lines as plain text to any writer
+///
+/// # Returns
+/// The number of lines written.
+pub fn write_lines_to<'a>(
+    writer: &mut impl Write,
+    lines: impl IntoIterator<Item = LineRef<'a>>,
+) -> io::Result<usize> {
+    let mut count = 0;
+    for line in lines {
+        writeln!(writer, "{}", line.plain())?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::{OutputKind, OutputLine};
+
+    #[test]
+    fn write_lines_writes_plain_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.txt");
+
+        let lines = [
+            OutputLine::new(OutputKind::Stdout, "\x1b[31mred\x1b[0m".into()),
+            OutputLine::new(OutputKind::Stderr, "plain".into()),
+        ];
+        let count = write_lines(&path, lines.iter().map(OutputLine::as_line_ref)).unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "red\nplain\n");
+    }
+
+    #[test]
+    fn write_lines_returns_error_for_missing_directory() {
+        let path = Path::new("/nonexistent/parallels/out.txt");
+        assert!(write_lines(path, []).is_err());
+    }
+}
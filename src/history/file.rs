@@ -0,0 +1,125 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of entries kept in a history file
+const MAX_ENTRIES: usize = 1000;
+
+/// Entries entered earlier, optionally backed by a file
+#[derive(Debug, Default)]
+pub struct History {
+    /// Entries, oldest first, without duplicates
+    entries: Vec<String>,
+    path: Option<PathBuf>,
+}
+
+impl History {
+    /// Load a history file (a missing file is an empty history)
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(content) => content.lines().map(String::from).collect(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            entries,
+            path: Some(path),
+        })
+    }
+
+    /// Start an empty history saved to `path`, e.g. in place of an
+    /// unreadable file
+    pub fn empty(path: impl Into<PathBuf>) -> Self {
+        Self {
+            entries: Vec::new(),
+            path: Some(path.into()),
+        }
+    }
+
+    /// Get the entries, oldest first
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Add an entry as the newest, dropping an earlier copy, and save
+    ///
+    /// The file is replaced atomically so a concurrent session never reads
+    /// a partial history.
+    pub fn push(&mut self, entry: &str) -> io::Result<()> {
+        self.entries.retain(|existing| existing != entry);
+        self.entries.push(entry.to_string());
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.drain(..self.entries.len() - MAX_ENTRIES);
+        }
+
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+        let mut content = self.entries.join("\n");
+        content.push('\n');
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, path)
+    }
+}
+
+/// Default location of the history file with the given name
+///
+/// Returns `None` if neither `XDG_STATE_HOME` nor `HOME` is set.
+pub fn default_path(name: &str) -> Option<PathBuf> {
+    let state_dir = std::env::var_os("XDG_STATE_HOME")
+        .filter(|dir| Path::new(dir).is_absolute())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/state")))?;
+    Some(state_dir.join("parallels").join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_push_dedupes_and_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("commands");
+
+        let mut history = History::load(&path).unwrap();
+        assert!(history.entries().is_empty());
+        history.push("npm run dev").unwrap();
+        history.push("cargo test").unwrap();
+        history.push("npm run dev").unwrap();
+
+        let reloaded = History::load(&path).unwrap();
+        assert_eq!(reloaded.entries(), ["cargo test", "npm run dev"]);
+    }
+
+    #[test]
+    fn history_empty_replaces_unreadable_file() {
+        let dir = tempfile::tempdir().unwrap();
+        // A directory can't be read as a history file
+        let path = dir.path().join("commands");
+        std::fs::create_dir(&path).unwrap();
+        assert!(History::load(&path).is_err());
+
+        let file = dir.path().join("replaced");
+        let mut history = History::empty(&file);
+        history.push("cargo test").unwrap();
+
+        assert_eq!(History::load(&file).unwrap().entries(), ["cargo test"]);
+    }
+
+    #[test]
+    fn history_without_file_keeps_entries_in_memory() {
+        let mut history = History::default();
+        for i in 0..MAX_ENTRIES + 5 {
+            history.push(&i.to_string()).unwrap();
+        }
+
+        assert_eq!(history.entries().len(), MAX_ENTRIES);
+        assert_eq!(history.entries()[0], "5");
+    }
+}
@@ -0,0 +1,11 @@
+//! Input history persisted across sessions
+//!
+//! Each history is a plain-text file with one entry per line (oldest
+//! first), kept under `$XDG_STATE_HOME/parallels` (or
+//! `~/.local/state/parallels`).
+
+mod file;
+#[cfg(feature = "shell-history")]
+pub mod shell;
+
+pub use file::{History, default_path};
@@ -0,0 +1,216 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Maximum number of imported commands (the most recent ones are kept)
+const MAX_IMPORTED: usize = 500;
+
+/// Source of commands the user ran in their shell
+pub trait HistorySource {
+    /// Name used in messages (e.g. `zsh`)
+    fn name(&self) -> &str;
+
+    /// Read the commands, oldest first
+    fn commands(&self) -> io::Result<Vec<String>>;
+}
+
+/// Shell whose history file format is understood
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    /// Shell name
+    pub fn name(self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+        }
+    }
+
+    /// Default history file below the home directory
+    pub fn default_file(self, home: &Path) -> PathBuf {
+        match self {
+            Shell::Bash => home.join(".bash_history"),
+            Shell::Zsh => home.join(".zsh_history"),
+            Shell::Fish => home.join(".local/share/fish/fish_history"),
+        }
+    }
+
+    /// Parse a history file, oldest first
+    ///
+    /// Multi-line commands are skipped, since the command line holds one line.
+    pub fn parse(self, content: &str) -> Vec<String> {
+        let commands: Vec<&str> = match self {
+            Shell::Bash => content
+                .lines()
+                // `HISTTIMEFORMAT` adds `#1700000000` lines before each command
+                .filter(|line| !is_timestamp(line))
+                .collect(),
+            Shell::Zsh => parse_zsh(content),
+            Shell::Fish => content
+                .lines()
+                .filter_map(|line| line.strip_prefix("- cmd: "))
+                .filter(|command| !command.contains("\\n"))
+                .collect(),
+        };
+        commands
+            .into_iter()
+            .map(|command| match self {
+                Shell::Fish => command.replace("\\\\", "\\"),
+                _ => command.to_string(),
+            })
+            .map(|command| command.trim().to_string())
+            .filter(|command| !command.is_empty())
+            .collect()
+    }
+}
+
+/// Check for a bash history timestamp line (`#1700000000`)
+fn is_timestamp(line: &str) -> bool {
+    line.strip_prefix('#')
+        .is_some_and(|rest| !rest.is_empty() && rest.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Parse zsh history, plain or with `EXTENDED_HISTORY` (`: START:ELAPSED;command`)
+fn parse_zsh(content: &str) -> Vec<&str> {
+    let mut commands = Vec::new();
+    let mut continued = false;
+    for line in content.lines() {
+        // Lines ending in a backslash continue on the next line
+        let multi_line = line.ends_with('\\');
+        if std::mem::replace(&mut continued, multi_line) || multi_line {
+            continue;
+        }
+        let command = line
+            .strip_prefix(": ")
+            .and_then(|rest| rest.split_once(';'))
+            .map_or(line, |(_, command)| command);
+        commands.push(command);
+    }
+    commands
+}
+
+/// History file of a shell
+pub struct ShellHistoryFile {
+    shell: Shell,
+    path: PathBuf,
+}
+
+impl ShellHistoryFile {
+    /// Read the given shell's history from a file
+    pub fn new(shell: Shell, path: impl Into<PathBuf>) -> Self {
+        Self {
+            shell,
+            path: path.into(),
+        }
+    }
+}
+
+impl HistorySource for ShellHistoryFile {
+    fn name(&self) -> &str {
+        self.shell.name()
+    }
+
+    fn commands(&self) -> io::Result<Vec<String>> {
+        // zsh "metafies" non-ASCII bytes, so the file isn't always UTF-8
+        let content = std::fs::read(&self.path)?;
+        Ok(self.shell.parse(&String::from_utf8_lossy(&content)))
+    }
+}
+
+/// History recorded by atuin, read with `atuin history list`
+pub struct Atuin;
+
+impl HistorySource for Atuin {
+    fn name(&self) -> &str {
+        "atuin"
+    }
+
+    fn commands(&self) -> io::Result<Vec<String>> {
+        let output = Command::new("atuin")
+            .args(["history", "list", "--cmd-only"])
+            .output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|command| !command.is_empty())
+            .map(String::from)
+            .collect())
+    }
+}
+
+/// History sources available for the current user
+///
+/// Includes the bash, zsh and fish history files that exist, and atuin if
+/// it is installed.
+pub fn default_sources() -> Vec<Box<dyn HistorySource>> {
+    let mut sources: Vec<Box<dyn HistorySource>> = Vec::new();
+    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let path = shell.default_file(&home);
+            if path.is_file() {
+                sources.push(Box::new(ShellHistoryFile::new(shell, path)));
+            }
+        }
+    }
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    if std::env::split_paths(&path).any(|dir| dir.join("atuin").is_file()) {
+        sources.push(Box::new(Atuin));
+    }
+    sources
+}
+
+/// Keep the most recent unique commands, oldest first
+pub fn most_recent(commands: Vec<String>) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    let mut recent: Vec<String> = commands
+        .into_iter()
+        .rev()
+        .filter(|command| seen.insert(command.clone()))
+        .take(MAX_IMPORTED)
+        .collect();
+    recent.reverse();
+    recent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Shell::Bash, "#1700000000\nnpm run dev\n\n#notatime\n", &["npm run dev", "#notatime"])]
+    #[case(
+        Shell::Zsh,
+        ": 1700000000:0;cargo watch -x run\n: 1700000001:0;for f in *; do\\\n  echo $f\\\ndone\nls -la\n",
+        &["cargo watch -x run", "ls -la"]
+    )]
+    #[case(
+        Shell::Fish,
+        "- cmd: tail -f a\\\\b.log\n  when: 1700000000\n- cmd: echo a\\nb\n  when: 1700000001\n",
+        &["tail -f a\\b.log"]
+    )]
+    fn shell_parse_history_formats(
+        #[case] shell: Shell,
+        #[case] content: &str,
+        #[case] expected: &[&str],
+    ) {
+        assert_eq!(shell.parse(content), expected);
+    }
+
+    #[test]
+    fn shell_most_recent_keeps_latest_unique_commands() {
+        let commands = ["a", "b", "a", "c"].map(String::from).to_vec();
+        assert_eq!(most_recent(commands), ["b", "a", "c"]);
+    }
+}
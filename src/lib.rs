@@ -1,6 +1,21 @@
 pub mod app;
 pub mod buffer;
+pub mod clipboard;
 pub mod command;
+pub mod compose;
+pub mod config;
+pub mod demo;
 pub mod event;
+pub mod export;
+pub mod history;
+pub mod lock;
+#[cfg(feature = "lua")]
+pub mod script;
 pub mod search;
+pub mod session;
+pub mod sink;
+pub mod status_file;
+pub mod stress;
+#[cfg(feature = "test-driver")]
+pub mod testing;
 pub mod tui;
@@ -0,0 +1,125 @@
+//! One session per config file (or compose project)
+//!
+//! Starting the same config twice would run two copies of every dev server,
+//! fighting over the same ports. A session holds an exclusive `flock` on a
+//! file named after the config's path; the lock goes away with the process,
+//! even if it crashes, so there is never a stale lock to clean up.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+use nix::errno::Errno;
+use nix::fcntl::{Flock, FlockArg};
+use thiserror::Error;
+
+/// Error while locking a config file's session
+#[derive(Debug, Error)]
+pub enum LockError {
+    #[error("{} is already running in another parallels (PID {pid}); quit it first or pass --no-lock", config.display())]
+    Held { config: PathBuf, pid: String },
+    #[error("{}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+}
+
+/// Exclusive lock on the session of a config file, held until dropped
+#[derive(Debug)]
+pub struct SessionLock {
+    _lock: Flock<File>,
+}
+
+impl SessionLock {
+    /// Lock the session of `config`, failing if another process holds it
+    pub fn acquire(config: &Path) -> Result<Self, LockError> {
+        let config = config
+            .canonicalize()
+            .unwrap_or_else(|_| config.to_path_buf());
+        Self::acquire_at(&lock_path(&config), &config)
+    }
+
+    /// Lock the session of `config` with the lock file at `path`
+    pub fn acquire_at(path: &Path, config: &Path) -> Result<Self, LockError> {
+        let io_error = |source| LockError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(io_error)?;
+        let mut lock = match Flock::lock(file, FlockArg::LockExclusiveNonblock) {
+            Ok(lock) => lock,
+            Err((mut file, Errno::EWOULDBLOCK)) => {
+                let mut pid = String::new();
+                let _ = file.read_to_string(&mut pid);
+                return Err(LockError::Held {
+                    config: config.to_path_buf(),
+                    pid: pid.trim().to_string(),
+                });
+            }
+            Err((_, errno)) => return Err(io_error(errno.into())),
+        };
+        // Leave the PID for the error shown to a second session
+        lock.set_len(0).map_err(io_error)?;
+        lock.rewind().map_err(io_error)?;
+        writeln!(lock, "{}", std::process::id()).map_err(io_error)?;
+        Ok(Self { _lock: lock })
+    }
+}
+
+/// Lock file of a config: `parallels-<hash>.lock` in `$XDG_RUNTIME_DIR`
+/// (or the temp directory)
+fn lock_path(config: &Path) -> PathBuf {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .filter(|dir| Path::new(dir).is_absolute())
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+    dir.join(format!("parallels-{:016x}.lock", fnv1a(config)))
+}
+
+/// Hash a path with FNV-1a, which is the same in every build
+fn fnv1a(path: &Path) -> u64 {
+    path.as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_lock_refuses_second_session_until_released() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.lock");
+        let config = Path::new("/srv/app/parallels.toml");
+
+        let first = SessionLock::acquire_at(&path, config).unwrap();
+        let error = SessionLock::acquire_at(&path, config).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "/srv/app/parallels.toml is already running in another parallels (PID {}); quit it first or pass --no-lock",
+                std::process::id()
+            )
+        );
+        drop(first);
+        assert!(SessionLock::acquire_at(&path, config).is_ok());
+    }
+
+    #[test]
+    fn lock_path_differs_per_config() {
+        let a = lock_path(Path::new("/srv/a/parallels.toml"));
+        let b = lock_path(Path::new("/srv/b/parallels.toml"));
+
+        assert_ne!(a, b);
+        assert_eq!(a, lock_path(Path::new("/srv/a/parallels.toml")));
+    }
+}
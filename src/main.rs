@@ -1,24 +1,47 @@
-use std::io;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use crossterm::{
-    event::{Event, EventStream, KeyEventKind},
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, Event, EventStream,
+        KeyEventKind,
+    },
     execute,
+    style::Print,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
 use futures::StreamExt;
 use ratatui::{Terminal, backend::CrosstermBackend};
-use tokio::time::interval;
 
 use parallels::app::App;
-use parallels::tui::{Renderer, handle_key};
+use parallels::clipboard::ClipboardBackend;
+use parallels::command::{
+    CommandSpec, OverflowPolicy, PrefixFold, SourceAddr, StartAt, shell_join, shell_quote,
+};
+use parallels::compose;
+use parallels::config::{Config, ConfigWatcher};
+use parallels::demo;
+use parallels::history::{self, History};
+use parallels::lock::SessionLock;
+use parallels::session::Session;
+use parallels::sink::{FileSink, JsonSink, LostRecords, OutputSink};
+use parallels::status_file::StatusFile;
+use parallels::stress;
+use parallels::tui::{
+    Renderer, TabLayout, TimestampMode, Wizard, WizardAction, handle_wizard_key,
+    handle_wizard_paste,
+};
 
 /// Default maximum buffer lines per command
 const DEFAULT_MAX_BUFFER_LINES: usize = 10000;
 
-/// Render interval (milliseconds)
-const RENDER_INTERVAL_MS: u64 = 16; // ~60fps
+/// Config file whose commands the startup wizard offers when `--config` is not given
+const WIZARD_CONFIG: &str = "parallels.toml";
+
+/// Default maximum frames per second
+const DEFAULT_FPS: u64 = 60;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -26,23 +49,197 @@ const RENDER_INTERVAL_MS: u64 = 16; // ~60fps
     author,
     version,
     about = "Run multiple commands in parallel with TUI",
-    long_about = None
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true,
+    disable_help_subcommand = true
 )]
 struct Args {
-    /// Commands to run in parallel
-    #[arg(required = true)]
+    #[command(subcommand)]
+    subcommand: Option<Command>,
+
+    /// Commands to run in parallel (ssh://[user@]host:command runs one remotely;
+    /// without any, a wizard asks for them)
     commands: Vec<String>,
 
+    /// One more command, given as separate arguments after `--` (quoted for you)
+    #[arg(last = true, value_name = "ARGS")]
+    argv: Vec<String>,
+
+    /// Follow a log file in its own tab, starting with its last lines (repeatable)
+    #[arg(long, value_name = "FILE")]
+    tail: Vec<PathBuf>,
+
+    /// One tab per Docker Compose service following its logs (optionally =FILE)
+    #[arg(
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        require_equals = true
+    )]
+    compose: Option<Option<PathBuf>>,
+
+    /// Read lines written to fifo://PATH, unix://PATH or listen://HOST:PORT in its own tab (repeatable)
+    #[arg(long, value_name = "URL")]
+    source: Vec<SourceAddr>,
+
+    /// Config file with command definitions (TOML)
+    #[arg(short = 'c', long)]
+    config: Option<PathBuf>,
+
+    /// Allow running the same config or compose file more than once at a time
+    #[arg(long)]
+    no_lock: bool,
+
+    /// Delay between command launches (e.g. 500ms, 2s)
+    #[arg(long, value_parser = humantime::parse_duration)]
+    stagger: Option<Duration>,
+
+    /// What to do when output arrives faster than it can be displayed
+    #[arg(long, value_enum)]
+    overflow: Option<OverflowPolicy>,
+
+    /// Cut displayed lines after this many characters (0 for no limit, default 10000)
+    #[arg(long, value_name = "CHARS")]
+    max_line_length: Option<usize>,
+
+    /// Start tabs following the output at the bottom, or at the top to read it from the start
+    #[arg(long, value_enum)]
+    start_at: Option<StartAt>,
+
+    /// Dim or hide log prefixes (timestamps, tags, module names) of lines; z cycles per tab
+    #[arg(long, value_enum)]
+    fold_prefix: Option<PrefixFold>,
+
+    /// On restart and quit, also kill processes that detached from a command (Linux)
+    #[arg(long)]
+    kill_detached: bool,
+
+    /// Read stderr through the same pipe as stdout to keep their relative order
+    #[arg(long)]
+    merge_streams: bool,
+
+    /// Append the output of all commands to a plain-text log file
+    #[arg(long, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+
+    /// Append the output of all commands to a JSON Lines file
+    #[arg(long, value_name = "PATH")]
+    json_log: Option<PathBuf>,
+
+    /// Keep a one-line status summary in this file (e.g. for a tmux status line)
+    #[arg(long, value_name = "PATH")]
+    status_file: Option<PathBuf>,
+
+    /// Show the UI with a few scripted commands instead of running any
+    #[arg(
+        long,
+        conflicts_with_all = ["commands", "argv", "tail", "compose", "source", "config"]
+    )]
+    demo: bool,
+
+    /// Print synthetic log lines as fast as possible (for stress testing)
+    #[arg(long, hide = true)]
+    stress: bool,
+
     /// Maximum buffer lines per command
     #[arg(short = 'b', long, default_value_t = DEFAULT_MAX_BUFFER_LINES)]
     max_buffer_lines: usize,
+
+    /// Start with this tab active (name, command, or 1-based position)
+    #[arg(long, value_name = "NAME|INDEX")]
+    focus: Option<String>,
+
+    /// Start with this search active in every tab (smartcase, like `/`)
+    #[arg(long, value_name = "PATTERN")]
+    search: Option<String>,
+
+    /// Show commands in a tab bar or, for many commands, a sidebar list with unread counts
+    #[arg(long, value_enum, default_value_t)]
+    layout: TabLayout,
+
+    /// Show each line's time since its run started (`relative`), and since the previous line (`delta`)
+    #[arg(long, value_enum, default_value_t)]
+    timestamps: TimestampMode,
+
+    /// With a single command, show a one-line header instead of the tab bar
+    #[arg(long)]
+    compact: bool,
+
+    /// Start with the statistics panel open, as a live overview of a batch of jobs
+    #[arg(long)]
+    overview: bool,
+
+    /// Where `y` in Visual mode copies lines: a clipboard command, or OSC 52 (auto: OSC 52 over SSH)
+    #[arg(long, value_enum, default_value_t)]
+    clipboard: ClipboardBackend,
+
+    /// Capture the mouse for wheel scrolling (toggle with `M` to select text)
+    #[arg(long)]
+    mouse: bool,
+
+    /// Quit once all commands have finished, after an optional delay (e.g. =5s; any key cancels)
+    #[arg(
+        long,
+        value_name = "DELAY",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "0s",
+        value_parser = humantime::parse_duration
+    )]
+    exit_when_done: Option<Duration>,
+
+    /// Offer commands from bash/zsh/fish/atuin history in `:new` completion
+    #[cfg(feature = "shell-history")]
+    #[arg(long)]
+    shell_history: bool,
+
+    /// Keep all but the newest LINES output lines of each command zstd-compressed in memory (e.g. with -b 0);
+    /// scrolling back decompresses the shown lines' chunk of 1024 while drawing, searches do it in the background
+    #[cfg(feature = "compress")]
+    #[arg(long, value_name = "LINES")]
+    compress_after: Option<usize>,
+
+    /// Lua script whose on_line/on_exit/on_restart functions can notify, mark tabs and restart them
+    #[cfg(feature = "lua")]
+    #[arg(long, value_name = "FILE")]
+    script: Option<PathBuf>,
+
+    /// Maximum frames per second (frames are only drawn when something changed)
+    #[arg(long, default_value_t = DEFAULT_FPS, value_parser = clap::value_parser!(u64).range(1..=1000))]
+    fps: u64,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print a command, given as separate arguments, quoted as one argument
+    ///
+    /// `parallels quote -- npm run dev --title "my app"` prints the command
+    /// as one single-quoted string, ready to paste into a parallels
+    /// invocation or script.
+    Quote {
+        /// The command and its arguments
+        #[arg(last = true, required = true, value_name = "ARGS")]
+        args: Vec<String>,
+    },
 }
 
+/// Save the terminal title on the terminal's title stack (xterm `CSI 22 t`)
+const PUSH_TITLE: &str = "\x1b[22;0t";
+
+/// Restore the terminal title saved by `PUSH_TITLE` (xterm `CSI 23 t`)
+const POP_TITLE: &str = "\x1b[23;0t";
+
 /// Initialize the terminal for TUI
 fn init_terminal() -> io::Result<Terminal<CrosstermBackend<io::Stdout>>> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(
+        stdout,
+        Print(PUSH_TITLE),
+        EnterAlternateScreen,
+        EnableBracketedPaste
+    )?;
     let backend = CrosstermBackend::new(stdout);
     Terminal::new(backend)
 }
@@ -50,85 +247,372 @@ fn init_terminal() -> io::Result<Terminal<CrosstermBackend<io::Stdout>>> {
 /// Restore the terminal to its original state
 fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()> {
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        LeaveAlternateScreen,
+        Print(POP_TITLE)
+    )?;
     terminal.show_cursor()
 }
 
-/// Run the application
-async fn run_app(
+/// Let the user choose the commands to run
+///
+/// Returns `false` if the user quit instead of starting.
+async fn run_wizard(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
-    mut app: App,
-) -> io::Result<()> {
-    // Spawn all commands (starts background tasks)
-    app.spawn_commands().await;
-
+    wizard: &mut Wizard,
+) -> io::Result<bool> {
     let mut event_stream = EventStream::new();
-    let mut render_interval = interval(Duration::from_millis(RENDER_INTERVAL_MS));
-
     loop {
-        // Update visible lines for all tabs based on terminal size
-        let size = terminal.size()?;
-        let visible_lines = size.height.saturating_sub(5) as usize;
-        for tab in app.tab_manager_mut().iter_mut() {
-            tab.set_visible_lines(visible_lines);
-        }
-
-        tokio::select! {
-            // Handle app events from background command tasks
-            Some(event) = app.recv_event() => {
-                app.handle_app_event(event);
-            }
-            // Handle key events
-            Some(Ok(Event::Key(key))) = event_stream.next() => {
-                if key.kind == KeyEventKind::Press {
-                    handle_key(&mut app, key);
-
-                    // Handle pending restart request
-                    if let Some(tab_index) = app.take_pending_restart() {
-                        app.restart_process(tab_index).await;
-                    }
+        terminal.draw(|frame| Renderer::render_wizard(frame, wizard))?;
+        match event_stream.next().await {
+            Some(Ok(Event::Key(key))) if key.kind == KeyEventKind::Press => {
+                match handle_wizard_key(wizard, key) {
+                    WizardAction::Start => return Ok(true),
+                    WizardAction::Quit => return Ok(false),
+                    WizardAction::None => {}
                 }
             }
-            // Render at fixed interval
-            _ = render_interval.tick() => {
-                terminal.draw(|frame| {
-                    Renderer::render(frame, &app);
-                })?;
-            }
+            Some(Ok(Event::Paste(text))) => handle_wizard_paste(wizard, &text),
+            Some(Ok(_)) => {}
+            Some(Err(e)) => return Err(e),
+            None => return Ok(false),
         }
+    }
+}
 
-        // Check if we should quit
-        if app.should_quit() {
-            // Kill all child processes before exiting
-            app.kill_all().await;
-            break;
-        }
+/// Open an output sink, exiting with an error message on failure
+fn open_sink<S>(path: &Path, create: impl FnOnce(&Path) -> io::Result<S>) -> S {
+    create(path).unwrap_or_else(|e| {
+        eprintln!("Error: {}: {}", path.display(), e);
+        std::process::exit(1);
+    })
+}
+
+/// Run the teardown command in the restored terminal, reporting failures
+fn run_teardown(command: &str) {
+    eprintln!("Running teardown: {}", command);
+    match std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .status()
+    {
+        Ok(status) if !status.success() => eprintln!("Error: teardown failed ({})", status),
+        Ok(_) => {}
+        Err(e) => eprintln!("Error: teardown failed: {}", e),
     }
+}
 
-    Ok(())
+/// Read commands from the user's shell histories, warning about unreadable ones
+#[cfg(feature = "shell-history")]
+fn import_shell_history() -> Vec<String> {
+    use parallels::history::shell;
+
+    let mut commands = Vec::new();
+    for source in shell::default_sources() {
+        match source.commands() {
+            Ok(source_commands) => commands.extend(source_commands),
+            Err(e) => eprintln!("Warning: {} history: {}", source.name(), e),
+        }
+    }
+    shell::most_recent(commands)
 }
 
 #[tokio::main]
 async fn main() -> io::Result<()> {
     let args = Args::parse();
 
-    // Validate commands
-    if args.commands.is_empty() {
-        eprintln!("Error: At least one command is required");
-        std::process::exit(1);
+    if let Some(Command::Quote { args }) = &args.subcommand {
+        println!("{}", shell_quote(&shell_join(args)));
+        return Ok(());
+    }
+
+    // Act as an output generator instead of running the TUI
+    if args.stress {
+        return match stress::generate(&mut io::stdout().lock(), None) {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(e),
+            _ => Ok(()),
+        };
+    }
+
+    // Commands from the config file come first, followed by positional ones
+    // and followed files, sources and compose services
+    let config = match &args.config {
+        Some(path) => Config::load(path).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }),
+        None => Config::default(),
+    };
+    // Refuse to start a config's (or compose project's) dev servers twice;
+    // the locks are held until exit
+    let mut locked = Vec::new();
+    if !args.no_lock {
+        locked.extend(args.config.clone());
+        if let Some(file) = &args.compose {
+            locked.push(compose::project_path(file.as_deref()));
+        }
+    }
+    let _session_locks: Vec<SessionLock> = locked
+        .iter()
+        .map(|path| {
+            SessionLock::acquire(path).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            })
+        })
+        .collect();
+    // The setup command gets the first tab, ahead of the commands it prepares
+    let mut commands: Vec<CommandSpec> = Vec::new();
+    if let Some(setup) = &config.setup {
+        commands.push(CommandSpec {
+            name: Some("setup".to_string()),
+            ..CommandSpec::new(setup.clone())
+        });
+    }
+    commands.extend(config.commands.iter().cloned());
+    if args.demo {
+        commands.extend(demo::commands());
+    }
+    commands.extend(args.commands.iter().map(|arg| CommandSpec::parse(arg)));
+    if !args.argv.is_empty() {
+        commands.push(CommandSpec::new(shell_join(&args.argv)));
+    }
+    commands.extend(args.tail.into_iter().map(CommandSpec::tail));
+    commands.extend(args.source.into_iter().map(CommandSpec::source));
+    if let Some(file) = &args.compose {
+        let file = file.as_deref();
+        let services = compose::services(file).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+        commands.extend(
+            services
+                .iter()
+                .map(|service| compose::service_spec(file, service)),
+        );
+    }
+
+    // Open output sinks before taking over the terminal
+    let mut sinks: Vec<Box<dyn OutputSink>> = Vec::new();
+    if let Some(path) = &args.log_file {
+        sinks.push(Box::new(open_sink(path, FileSink::create)));
+    }
+    if let Some(path) = &args.json_log {
+        sinks.push(Box::new(open_sink(path, JsonSink::create)));
+    }
+    #[cfg(feature = "lua")]
+    let script = args.script.as_deref().map(|path| {
+        parallels::script::Script::load(path).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        })
+    });
+
+    // History is a convenience: an unreadable file just starts a new one,
+    // reported once the session runs
+    let mut history_error = None;
+    let mut command_history = match history::default_path("commands") {
+        Some(path) => History::load(&path).unwrap_or_else(|e| {
+            history_error = Some(format!(
+                "Warning: command history {}: {}; starting a new one",
+                path.display(),
+                e
+            ));
+            History::empty(path)
+        }),
+        None => History::default(),
+    };
+
+    // Without commands, ask for them in a wizard that hands its terminal on
+    // to the session
+    let mut terminal = None;
+    if commands.is_empty() {
+        if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+            eprintln!("Error: At least one command is required");
+            std::process::exit(1);
+        }
+        let offered = match &args.config {
+            Some(_) => Vec::new(),
+            None => Config::load(Path::new(WIZARD_CONFIG))
+                .map(|config| config.commands)
+                .unwrap_or_default(),
+        };
+        let mut wizard = Wizard::new(offered, command_history.entries());
+        let mut wizard_terminal = init_terminal()?;
+        match run_wizard(&mut wizard_terminal, &mut wizard).await {
+            Ok(true) => {}
+            result => {
+                restore_terminal(&mut wizard_terminal)?;
+                return result.map(|_| ());
+            }
+        }
+        for line in wizard.typed() {
+            // Losing the history is not worth failing the session for
+            let _ = command_history.push(line);
+        }
+        commands = wizard.into_commands();
+        terminal = Some(wizard_terminal);
+    }
+
+    // Offset launches unless a command has its own delay
+    if let Some(stagger) = args.stagger {
+        for (index, spec) in commands.iter_mut().enumerate() {
+            if spec.delay.is_none() {
+                spec.delay = Some(stagger * index as u32);
+            }
+        }
+    }
+
+    // Apply the overflow policy unless a command has its own
+    if let Some(overflow) = args.overflow {
+        for spec in commands.iter_mut().filter(|spec| spec.overflow.is_none()) {
+            spec.overflow = Some(overflow);
+        }
+    }
+
+    if let Some(max) = args.max_line_length {
+        for spec in commands
+            .iter_mut()
+            .filter(|spec| spec.max_line_length.is_none())
+        {
+            spec.max_line_length = Some(max);
+        }
+    }
+
+    if let Some(start_at) = args.start_at {
+        for spec in commands.iter_mut().filter(|spec| spec.start_at.is_none()) {
+            spec.start_at = Some(start_at);
+        }
+    }
+
+    if let Some(fold) = args.fold_prefix {
+        for spec in commands
+            .iter_mut()
+            .filter(|spec| spec.fold_prefix.is_none())
+        {
+            spec.fold_prefix = Some(fold);
+        }
+    }
+
+    if args.kill_detached {
+        for spec in &mut commands {
+            spec.kill_detached = true;
+        }
+    }
+
+    if args.merge_streams {
+        for spec in &mut commands {
+            spec.merge_streams = true;
+        }
     }
 
     // Create app
-    let app = App::new(args.commands, args.max_buffer_lines);
+    let mut app = App::new(commands, args.max_buffer_lines);
+    if config.setup.is_some() {
+        app.set_setup_tab(0);
+    }
+    if args.demo {
+        app.set_spawner(demo::spawner());
+    }
+    app.set_layout(args.layout);
+    app.set_compact(args.compact);
+    app.set_timestamps(args.timestamps);
+    app.set_clipboard(args.clipboard);
+    app.set_copy_prefix(config.copy_prefix);
+    if args.overview {
+        app.toggle_stats();
+    }
+    app.set_mouse_capture(args.mouse);
+    app.set_status_bar(config.status_bar.clone());
+    app.set_exit_when_done(args.exit_when_done);
+    app.set_command_history(command_history);
+    if let Some(warning) = history_error {
+        app.log_event(warning);
+    }
+    #[cfg(feature = "compress")]
+    if let Some(lines) = args.compress_after {
+        app.tab_manager_mut().set_compress_after(lines);
+    }
+    #[cfg(feature = "shell-history")]
+    if args.shell_history {
+        app.set_suggested_commands(import_shell_history());
+    }
+    for (tab_index, tab) in app.tab_manager().iter().enumerate() {
+        let Some(from) = &tab.spec().stdin_from else {
+            continue;
+        };
+        let problem = match app.tab_manager().find_tab(from) {
+            None => "no such tab",
+            Some(source) if source == tab_index => "a tab can't read its own output",
+            Some(_) => continue,
+        };
+        if let Some(terminal) = &mut terminal {
+            restore_terminal(terminal)?;
+        }
+        eprintln!(
+            "Error: {}: stdin_from = '{}': {}",
+            tab.name(),
+            from,
+            problem
+        );
+        std::process::exit(1);
+    }
+    if let Some(focus) = &args.focus {
+        let Some(index) = app.tab_manager().find_tab(focus) else {
+            if let Some(terminal) = &mut terminal {
+                restore_terminal(terminal)?;
+            }
+            eprintln!(
+                "Error: --focus: no tab named '{}' (and not a position from 1 to {})",
+                focus,
+                app.tab_manager().len()
+            );
+            std::process::exit(1);
+        };
+        app.tab_manager_mut().select_tab(index);
+    }
+    if let Some(pattern) = &args.search {
+        app.search_in_current_tab(pattern);
+    }
+    for sink in sinks {
+        app.add_sink(sink);
+    }
+    #[cfg(feature = "lua")]
+    if let Some(script) = script {
+        app.set_script(script);
+    }
 
-    // Initialize terminal
-    let mut terminal = init_terminal()?;
+    // Initialize terminal, unless the wizard already did
+    let terminal = match terminal {
+        Some(terminal) => terminal,
+        None => init_terminal()?,
+    };
 
     // Run application
-    let result = run_app(&mut terminal, app).await;
+    let mut session = Session::new(terminal, app, args.fps);
+    if let Some(path) = args.status_file {
+        session.set_status_file(StatusFile::new(path));
+    }
+    if let Some(path) = &args.config {
+        session.set_config_watcher(ConfigWatcher::new(path, config.clone()));
+    }
+    let result = session.run(EventStream::new()).await;
+    let lost = session.app_mut().finish_sinks();
 
     // Restore terminal
+    let mut terminal = session.into_terminal();
     restore_terminal(&mut terminal)?;
 
+    for LostRecords { name, count } in lost {
+        eprintln!("Warning: {}: {} records were not written", name, count);
+    }
+
+    if let Some(teardown) = &config.teardown {
+        run_teardown(teardown);
+    }
+
     result
 }
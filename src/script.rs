@@ -0,0 +1,251 @@
+//! Lua scripts reacting to commands' events (`--script`, `lua` feature)
+//!
+//! A script defines any of these global functions, called as things happen:
+//!
+//! - `on_line(tab, line)` for every output line (plain text)
+//! - `on_exit(tab, code)` when a command ends (128 + signal if killed)
+//! - `on_restart(tab, attempt)` when a command is restarted
+//!
+//! They act through the `parallels` table: `parallels.notify(message)`
+//! shows a status bar message, `parallels.mark(tab, label)` inserts a marker
+//! into a tab, `parallels.restart(tab)` restarts one and
+//! `parallels.highlight(pattern)` highlights a pattern in every tab, like
+//! `--search`. Tabs are given by name or 1-based position, like `--focus`.
+//!
+//! Callbacks run on the event loop, so each call (and the script's first
+//! run) is stopped with an error once it takes longer than [`TIME_LIMIT`].
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use mlua::{Function, HookTriggers, IntoLuaMulti, Lua, Table};
+use thiserror::Error;
+
+/// Callbacks a script can define
+const CALLBACKS: [&str; 3] = ["on_line", "on_exit", "on_restart"];
+
+/// Time a callback may run before it is stopped
+const TIME_LIMIT: Duration = Duration::from_millis(100);
+
+/// Lua instructions run between checks of the time limit
+const CHECK_EVERY: u32 = 10_000;
+
+/// When the running call exceeds [`TIME_LIMIT`]
+struct Deadline(Instant);
+
+/// Error while loading or running a script
+#[derive(Debug, Error)]
+pub enum ScriptError {
+    #[error("{}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("{0}")]
+    Lua(#[from] mlua::Error),
+}
+
+/// What a script asked parallels to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Show a message in the status bar
+    Notify(String),
+    /// Insert a marker line into a tab
+    Mark { tab: String, label: String },
+    /// Restart a tab's command
+    Restart(String),
+    /// Highlight a pattern in every tab, like a search
+    Highlight(String),
+}
+
+/// A loaded script
+#[derive(Debug)]
+pub struct Script {
+    lua: Lua,
+    /// Which of [`CALLBACKS`] the script defines, to skip calling the others
+    defined: [bool; 3],
+}
+
+impl Script {
+    /// Load and run a script file
+    pub fn load(path: &Path) -> Result<Self, ScriptError> {
+        let source = std::fs::read_to_string(path).map_err(|source| ScriptError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_source(&path.display().to_string(), &source)
+    }
+
+    /// Run a script's source, named `name` in error messages
+    pub fn from_source(name: &str, source: &str) -> Result<Self, ScriptError> {
+        let lua = Lua::new();
+        lua.set_app_data(Vec::<Action>::new());
+        lua.set_app_data(Deadline(Instant::now() + TIME_LIMIT));
+        lua.set_hook(
+            HookTriggers::new().every_nth_instruction(CHECK_EVERY),
+            |lua, _| match lua.app_data_ref::<Deadline>() {
+                Some(deadline) if Instant::now() > deadline.0 => Err(mlua::Error::runtime(
+                    format!("took longer than {}ms", TIME_LIMIT.as_millis()),
+                )),
+                _ => Ok(()),
+            },
+        );
+        lua.globals().set("parallels", api(&lua)?)?;
+        lua.load(source).set_name(format!("@{}", name)).exec()?;
+
+        let globals = lua.globals();
+        let defined = CALLBACKS.map(|callback| globals.get::<_, Function>(callback).is_ok());
+        drop(globals);
+        Ok(Self { lua, defined })
+    }
+
+    /// Call `on_line` for an output line of a tab
+    pub fn on_line(&self, tab: &str, line: &str) -> Result<Vec<Action>, ScriptError> {
+        self.call(0, (tab, line))
+    }
+
+    /// Call `on_exit` for a command of a tab that ended
+    pub fn on_exit(&self, tab: &str, exit_code: i32) -> Result<Vec<Action>, ScriptError> {
+        self.call(1, (tab, exit_code))
+    }
+
+    /// Call `on_restart` for a command of a tab that was restarted
+    pub fn on_restart(&self, tab: &str, attempt: usize) -> Result<Vec<Action>, ScriptError> {
+        self.call(2, (tab, attempt))
+    }
+
+    /// Call a callback if defined, returning the actions it asked for
+    fn call<'lua>(
+        &'lua self,
+        callback: usize,
+        args: impl IntoLuaMulti<'lua>,
+    ) -> Result<Vec<Action>, ScriptError> {
+        if !self.defined[callback] {
+            return Ok(Vec::new());
+        }
+        let function: Function = self.lua.globals().get(CALLBACKS[callback])?;
+        self.lua.set_app_data(Deadline(Instant::now() + TIME_LIMIT));
+        let result = function.call::<_, ()>(args);
+        // Keep the actions queued before an error out of the next call
+        let actions = self
+            .lua
+            .app_data_mut::<Vec<Action>>()
+            .map(|mut actions| std::mem::take(&mut *actions))
+            .unwrap_or_default();
+        result?;
+        Ok(actions)
+    }
+}
+
+/// Build the `parallels` table, whose functions queue actions
+fn api(lua: &Lua) -> mlua::Result<Table<'_>> {
+    fn queue(lua: &Lua, action: Action) {
+        if let Some(mut actions) = lua.app_data_mut::<Vec<Action>>() {
+            actions.push(action);
+        }
+    }
+
+    let api = lua.create_table()?;
+    api.set(
+        "notify",
+        lua.create_function(|lua, message: String| {
+            queue(lua, Action::Notify(message));
+            Ok(())
+        })?,
+    )?;
+    api.set(
+        "mark",
+        lua.create_function(|lua, (tab, label): (String, String)| {
+            queue(lua, Action::Mark { tab, label });
+            Ok(())
+        })?,
+    )?;
+    api.set(
+        "restart",
+        lua.create_function(|lua, tab: String| {
+            queue(lua, Action::Restart(tab));
+            Ok(())
+        })?,
+    )?;
+    api.set(
+        "highlight",
+        lua.create_function(|lua, pattern: String| {
+            queue(lua, Action::Highlight(pattern));
+            Ok(())
+        })?,
+    )?;
+    Ok(api)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = r#"
+        function on_line(tab, line)
+            if line:find("panic") then
+                parallels.mark(tab, "panic")
+                parallels.restart("worker")
+            end
+        end
+
+        function on_exit(tab, code)
+            parallels.notify(tab .. " exited with " .. code)
+            parallels.highlight("panicked")
+        end
+    "#;
+
+    #[test]
+    fn script_callbacks_return_queued_actions() {
+        let script = Script::from_source("test.lua", SCRIPT).unwrap();
+
+        assert_eq!(script.on_line("api", "listening").unwrap(), vec![]);
+        assert_eq!(
+            script.on_line("api", "thread 'main' panicked").unwrap(),
+            vec![
+                Action::Mark {
+                    tab: "api".into(),
+                    label: "panic".into()
+                },
+                Action::Restart("worker".into()),
+            ]
+        );
+        assert_eq!(
+            script.on_exit("api", 101).unwrap(),
+            vec![
+                Action::Notify("api exited with 101".into()),
+                Action::Highlight("panicked".into()),
+            ]
+        );
+        // Undefined callbacks do nothing
+        assert_eq!(script.on_restart("api", 1).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn script_errors_name_the_script() {
+        let error = Script::from_source("broken.lua", "function on_exit(").unwrap_err();
+        assert!(error.to_string().contains("broken.lua"), "{}", error);
+
+        let script =
+            Script::from_source("test.lua", "function on_exit() error('boom') end").unwrap();
+        let error = script.on_exit("api", 0).unwrap_err();
+        assert!(error.to_string().contains("boom"), "{}", error);
+    }
+
+    #[test]
+    fn script_endless_callbacks_are_stopped() {
+        let script = Script::from_source(
+            "test.lua",
+            "function on_line(tab, line) while true do end end\nfunction on_exit() end",
+        )
+        .unwrap();
+
+        let error = script.on_line("api", "x").unwrap_err();
+        assert!(error.to_string().contains("took longer than"), "{}", error);
+        // Each call gets the full time again
+        assert_eq!(script.on_exit("api", 0).unwrap(), vec![]);
+
+        let error = Script::from_source("loop.lua", "while true do end").unwrap_err();
+        assert!(error.to_string().contains("took longer than"), "{}", error);
+    }
+}
@@ -1,3 +1,5 @@
 mod searcher;
+mod word;
 
-pub use searcher::{Match, SearchState};
+pub use searcher::{BACKGROUND_SEARCH_LINES, Match, MatchJump, SearchJob, SearchState};
+pub use word::{next_word_start, path_at, prev_word_start, word_at};
@@ -1,6 +1,17 @@
+use std::ops::Range;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use tui_input::{Input, InputRequest};
 
-use crate::buffer::OutputBuffer;
+use crate::buffer::{LineRef, OutputBuffer, SharedLines};
+
+/// Buffers with more lines than this are searched on a background task
+pub const BACKGROUND_SEARCH_LINES: usize = 100_000;
+
+/// Lines a background search goes through between checking whether it was
+/// cancelled and sending the matches found so far
+const SEARCH_CHUNK_LINES: usize = 10_000;
 
 /// Search match information
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -13,11 +24,123 @@ pub struct Match {
     pub len: usize,
 }
 
+/// Match moved to by `next_match`/`prev_match`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchJump {
+    /// Line number of the match (0-based)
+    pub line: usize,
+    /// Whether the move wrapped around the end (or start) of the matches
+    pub wrapped: bool,
+}
+
+/// Finds the occurrences of a query in lines, with smartcase
+///
+/// If the query contains no uppercase letters, search is case-insensitive.
+/// If it contains uppercase, search is case-sensitive.
+#[derive(Debug, Clone)]
+struct Matcher {
+    query: String,
+    /// The query in lowercase, for a case-insensitive search
+    query_lower: Option<String>,
+}
+
+impl Matcher {
+    fn new(query: &str) -> Self {
+        let case_sensitive = query.chars().any(|c| c.is_uppercase());
+        Self {
+            query: query.to_string(),
+            query_lower: (!case_sensitive).then(|| query.to_lowercase()),
+        }
+    }
+
+    /// Add the matches in a line to `matches`
+    fn find(&self, line_idx: usize, line: LineRef<'_>, matches: &mut Vec<Match>) {
+        // Skip dividers and markers inserted by parallels
+        if !line.kind.is_output() {
+            return;
+        }
+        // Use pre-stripped content for searching
+        let content = line.plain();
+        let (content, query) = match &self.query_lower {
+            Some(query_lower) => (content.to_lowercase(), query_lower),
+            None => (content, &self.query),
+        };
+        let mut start = 0;
+        while let Some(pos) = content[start..].find(query.as_str()) {
+            let absolute_pos = start + pos;
+            matches.push(Match {
+                line: line_idx,
+                start: absolute_pos,
+                len: self.query.len(),
+            });
+            start = absolute_pos + self.query.len();
+        }
+    }
+}
+
+/// A search of a large buffer, run on a background task
+///
+/// Created by [`SearchState::start`], which takes the matches it streams
+/// back through [`SearchState::receive`].
+pub struct SearchJob {
+    id: u64,
+    matcher: Matcher,
+    lines: SharedLines,
+    cancel: Arc<AtomicBool>,
+}
+
+impl SearchJob {
+    /// Identifies the job's matches to `SearchState::receive`
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Search the lines, passing the matches to `send` a chunk of lines at a
+    /// time along with whether the search is done
+    ///
+    /// Stops early when the search is cancelled or `send` returns `false`.
+    pub fn run(self, mut send: impl FnMut(Vec<Match>, bool) -> bool) {
+        let mut matches = Vec::new();
+        for (line_idx, line) in self.lines.iter().enumerate() {
+            if line_idx % SEARCH_CHUNK_LINES == 0 && line_idx > 0 {
+                if self.cancel.load(Ordering::Relaxed) {
+                    return;
+                }
+                if !send(std::mem::take(&mut matches), false) {
+                    return;
+                }
+            }
+            self.matcher.find(line_idx, line, &mut matches);
+        }
+        if !self.cancel.load(Ordering::Relaxed) {
+            send(matches, true);
+        }
+    }
+}
+
+/// A background search whose matches are still arriving
+struct PendingSearch {
+    id: u64,
+    cancel: Arc<AtomicBool>,
+    /// Buffer generation of the line the job's `Match::line` 0 refers to
+    first_generation: u64,
+}
+
 /// Search state management structure
 pub struct SearchState {
     input: Input,
     matches: Vec<Match>,
     current_index: Option<usize>,
+    /// Incremented whenever matches or the current match change
+    generation: u64,
+    /// Buffer generation of the line `Match::line` 0 refers to
+    first_generation: u64,
+    /// Buffer generation up to which lines have been searched
+    scanned_generation: u64,
+    /// Background search of the lines before `scanned_generation`
+    pending: Option<PendingSearch>,
+    /// Id of the last background search started
+    last_job: u64,
 }
 
 impl SearchState {
@@ -27,6 +150,11 @@ impl SearchState {
             input: Input::default(),
             matches: Vec::new(),
             current_index: None,
+            generation: 0,
+            first_generation: 0,
+            scanned_generation: 0,
+            pending: None,
+            last_job: 0,
         }
     }
 
@@ -35,6 +163,11 @@ impl SearchState {
         self.input.value()
     }
 
+    /// Get the query input (value and cursor)
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+
     /// Handle input request from tui-input
     pub fn handle_input(&mut self, req: InputRequest) {
         self.input.handle(req);
@@ -44,57 +177,158 @@ impl SearchState {
     ///
     /// Uses smartcase: if query contains no uppercase letters, search is
     /// case-insensitive. If query contains uppercase, search is case-sensitive.
-    ///
-    /// TODO: Consider using more efficient search algorithms (e.g., Boyer-Moore,
-    /// Aho-Corasick, or regex-based search) for better performance with large buffers.
     pub fn search(&mut self, query: &str, buffer: &OutputBuffer) {
-        self.input = query.into();
+        self.reset(query, buffer);
+        self.scanned_generation = self.first_generation;
+
+        self.scan_new_lines(buffer);
+        if !self.matches.is_empty() {
+            self.current_index = Some(0);
+        }
+    }
+
+    /// Set search query and start searching the buffer
+    ///
+    /// Buffers of up to [`BACKGROUND_SEARCH_LINES`] lines are searched right
+    /// away, like with `search`. For larger ones, and ones with compressed
+    /// lines to decompress, the returned job is to be
+    /// run on a background task, and its matches passed to `receive`; a
+    /// new search cancels it.
+    pub fn start(&mut self, query: &str, buffer: &OutputBuffer) -> Option<SearchJob> {
+        if query.is_empty()
+            || (buffer.len() <= BACKGROUND_SEARCH_LINES && !buffer.has_frozen_lines())
+        {
+            self.search(query, buffer);
+            return None;
+        }
+        self.reset(query, buffer);
+        // Lines pushed from now on are searched by `update`, once the job is done
+        self.scanned_generation = buffer.generation();
+
+        self.last_job += 1;
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.pending = Some(PendingSearch {
+            id: self.last_job,
+            cancel: Arc::clone(&cancel),
+            first_generation: self.first_generation,
+        });
+        Some(SearchJob {
+            id: self.last_job,
+            matcher: Matcher::new(query),
+            lines: buffer.shared_since(self.first_generation),
+            cancel,
+        })
+    }
+
+    /// Take matches found by a background search
+    ///
+    /// Matches of a search that has been replaced are ignored. Returns
+    /// whether anything changed.
+    pub fn receive(&mut self, id: u64, matches: Vec<Match>, done: bool) -> bool {
+        let Some(pending) = self.pending.as_ref().filter(|pending| pending.id == id) else {
+            return false;
+        };
+        // Lines evicted since the job started shift the line numbers
+        let shift =
+            usize::try_from(self.first_generation - pending.first_generation).unwrap_or(usize::MAX);
+        self.matches.extend(
+            matches
+                .into_iter()
+                .filter(|m| m.line >= shift)
+                .map(|m| Match {
+                    line: m.line - shift,
+                    ..m
+                }),
+        );
+        if done {
+            self.pending = None;
+        }
+        if self.current_index.is_none() && !self.matches.is_empty() {
+            self.current_index = Some(0);
+        }
+        self.generation += 1;
+        true
+    }
+
+    /// Check if a background search is still looking for matches
+    pub fn is_searching(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Stop any background search and start over with a new query
+    fn reset(&mut self, query: &str, buffer: &OutputBuffer) {
+        self.cancel();
+        // Keep the cursor where it is while the query is being edited
+        if self.input.value() != query {
+            self.input = query.into();
+        }
         self.matches.clear();
         self.current_index = None;
+        self.generation += 1;
+        self.first_generation = buffer.first_generation();
+    }
 
-        if query.is_empty() {
-            return;
+    /// Stop the background search, if any
+    fn cancel(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            pending.cancel.store(true, Ordering::Relaxed);
         }
+    }
 
-        // Smartcase: case-insensitive if query has no uppercase letters
-        let case_sensitive = query.chars().any(|c| c.is_uppercase());
+    /// Bring the matches up to date with lines pushed or evicted since the
+    /// last search or update, searching only the new lines
+    ///
+    /// Returns whether anything changed.
+    pub fn update(&mut self, buffer: &OutputBuffer) -> bool {
+        if !self.is_active() || buffer.generation() == self.scanned_generation {
+            return false;
+        }
 
-        for (line_idx, line) in buffer.iter().enumerate() {
-            // Use pre-stripped content for searching
-            let content = line.plain();
-
-            if case_sensitive {
-                // Case-sensitive search
-                let mut start = 0;
-                while let Some(pos) = content[start..].find(query) {
-                    let absolute_pos = start + pos;
-                    self.matches.push(Match {
-                        line: line_idx,
-                        start: absolute_pos,
-                        len: query.len(),
-                    });
-                    start = absolute_pos + query.len();
-                }
-            } else {
-                // Case-insensitive search
-                let query_lower = query.to_lowercase();
-                let content_lower = content.to_lowercase();
-                let mut start = 0;
-                while let Some(pos) = content_lower[start..].find(&query_lower) {
-                    let absolute_pos = start + pos;
-                    self.matches.push(Match {
-                        line: line_idx,
-                        start: absolute_pos,
-                        len: query.len(),
-                    });
-                    start = absolute_pos + query.len();
-                }
+        // New lines wait for a background search, to keep the matches in order
+        let first_generation = buffer.first_generation();
+        let evicted_lines = first_generation > self.first_generation;
+        if self.pending.is_some() && !evicted_lines {
+            return false;
+        }
+
+        // Drop matches on evicted (or cleared) lines and renumber the rest
+        if evicted_lines {
+            let shift =
+                usize::try_from(first_generation - self.first_generation).unwrap_or(usize::MAX);
+            let evicted = self.matches.partition_point(|m| m.line < shift);
+            self.matches.drain(..evicted);
+            for m in &mut self.matches {
+                m.line -= shift;
             }
+            self.current_index = self.current_index.and_then(|i| i.checked_sub(evicted));
+            self.first_generation = first_generation;
         }
 
-        if !self.matches.is_empty() {
+        if self.pending.is_none() {
+            self.scan_new_lines(buffer);
+        }
+        if self.current_index.is_none() && !self.matches.is_empty() {
             self.current_index = Some(0);
         }
+        self.generation += 1;
+        true
+    }
+
+    /// Search the lines added to the buffer since the last scan
+    ///
+    /// Uses smartcase like `search`.
+    fn scan_new_lines(&mut self, buffer: &OutputBuffer) {
+        let start = self.scanned_generation.max(buffer.first_generation());
+        self.scanned_generation = buffer.generation();
+        if self.query().is_empty() {
+            return;
+        }
+
+        let matcher = Matcher::new(self.query());
+        let first_index = (start - buffer.first_generation()) as usize;
+        for (offset, line) in buffer.lines_since(start).enumerate() {
+            matcher.find(first_index + offset, line, &mut self.matches);
+        }
     }
 
     /// Get match results
@@ -102,6 +336,13 @@ impl SearchState {
         &self.matches
     }
 
+    /// Get the matches on a range of lines
+    pub fn matches_in(&self, lines: Range<usize>) -> &[Match] {
+        let start = self.matches.partition_point(|m| m.line < lines.start);
+        let end = self.matches.partition_point(|m| m.line < lines.end);
+        &self.matches[start..end.max(start)]
+    }
+
     /// Get match count
     pub fn match_count(&self) -> usize {
         self.matches.len()
@@ -117,45 +358,58 @@ impl SearchState {
         self.current_index.and_then(|i| self.matches.get(i))
     }
 
-    /// Move to next match and return its line number
-    pub fn next_match(&mut self) -> Option<usize> {
+    /// Move to next match, wrapping from the last to the first
+    pub fn next_match(&mut self) -> Option<MatchJump> {
         if self.matches.is_empty() {
             return None;
         }
 
-        let new_index = match self.current_index {
-            Some(i) => (i + 1) % self.matches.len(),
-            None => 0,
+        let last = self.matches.len() - 1;
+        let (new_index, wrapped) = match self.current_index {
+            Some(i) if i == last => (0, true),
+            Some(i) => (i + 1, false),
+            None => (0, false),
         };
-        self.current_index = Some(new_index);
-        self.matches.get(new_index).map(|m| m.line)
+        self.jump_to(new_index, wrapped)
     }
 
-    /// Move to previous match and return its line number
-    pub fn prev_match(&mut self) -> Option<usize> {
+    /// Move to previous match, wrapping from the first to the last
+    pub fn prev_match(&mut self) -> Option<MatchJump> {
         if self.matches.is_empty() {
             return None;
         }
 
-        let new_index = match self.current_index {
-            Some(i) => {
-                if i == 0 {
-                    self.matches.len() - 1
-                } else {
-                    i - 1
-                }
-            }
-            None => self.matches.len() - 1,
+        let last = self.matches.len() - 1;
+        let (new_index, wrapped) = match self.current_index {
+            Some(0) => (last, true),
+            Some(i) => (i - 1, false),
+            None => (last, false),
         };
-        self.current_index = Some(new_index);
-        self.matches.get(new_index).map(|m| m.line)
+        self.jump_to(new_index, wrapped)
+    }
+
+    /// Make a match current
+    fn jump_to(&mut self, index: usize, wrapped: bool) -> Option<MatchJump> {
+        self.current_index = Some(index);
+        self.generation += 1;
+        self.matches.get(index).map(|m| MatchJump {
+            line: m.line,
+            wrapped,
+        })
     }
 
     /// Clear search state
     pub fn clear(&mut self) {
+        self.cancel();
         self.input.reset();
         self.matches.clear();
         self.current_index = None;
+        self.generation += 1;
+    }
+
+    /// Get the generation, which changes whenever highlights need redrawing
+    pub fn generation(&self) -> u64 {
+        self.generation
     }
 
     /// Clear only the input field (preserves matches)
@@ -193,6 +447,23 @@ mod tests {
         buffer
     }
 
+    #[cfg(feature = "compress")]
+    #[test]
+    fn search_state_start_decompresses_in_the_background() {
+        let mut buffer = OutputBuffer::new(0);
+        buffer.set_compress_after(0);
+        for i in 0..5_000 {
+            buffer.push(OutputLine::new(OutputKind::Stdout, format!("line{}", i)));
+        }
+        buffer.compact(0..0);
+        let mut state = SearchState::new();
+
+        let job = state.start("line42", &buffer);
+
+        assert!(job.is_some());
+        assert!(state.matches().is_empty());
+    }
+
     #[test]
     fn search_state_new_returns_empty_state() {
         let state = SearchState::new();
@@ -201,6 +472,41 @@ mod tests {
         assert!(!state.is_active());
     }
 
+    #[test]
+    fn search_state_generation_changes_on_search_and_navigation() {
+        let buffer = create_buffer_with_lines(&["foo", "foo"]);
+        let mut state = SearchState::new();
+        let mut last = state.generation();
+
+        state.search("foo", &buffer);
+        assert_ne!(state.generation(), last);
+        last = state.generation();
+
+        state.next_match();
+        assert_ne!(state.generation(), last);
+        last = state.generation();
+
+        state.prev_match();
+        assert_ne!(state.generation(), last);
+        last = state.generation();
+
+        state.clear();
+        assert_ne!(state.generation(), last);
+    }
+
+    #[test]
+    fn search_state_search_skips_markers() {
+        let mut buffer = create_buffer_with_lines(&["before click"]);
+        buffer.push(OutputLine::marker("click here"));
+        buffer.push(OutputLine::new(OutputKind::Stdout, "after click".into()));
+        let mut state = SearchState::new();
+
+        state.search("click", &buffer);
+
+        let lines: Vec<usize> = state.matches().iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![0, 2]);
+    }
+
     #[test]
     fn search_state_search_finds_simple_match() {
         let buffer = create_buffer_with_lines(&["hello world", "goodbye world"]);
@@ -259,6 +565,39 @@ mod tests {
         assert!(!state.is_active());
     }
 
+    #[test]
+    fn search_state_update_searches_only_new_lines() {
+        let mut buffer = create_buffer_with_lines(&["foo", "bar"]);
+        let mut state = SearchState::new();
+        state.search("foo", &buffer);
+        assert!(!state.update(&buffer));
+
+        buffer.push(OutputLine::new(OutputKind::Stdout, "foo again".into()));
+
+        assert!(state.update(&buffer));
+        let lines: Vec<_> = state.matches().iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![0, 2]);
+        assert_eq!(state.current_match_display(), Some(1));
+    }
+
+    #[test]
+    fn search_state_update_renumbers_after_eviction() {
+        let mut buffer = OutputBuffer::new(3);
+        let mut state = SearchState::new();
+        state.search("foo", &buffer);
+        assert_eq!(state.current_match(), None);
+
+        for line in ["foo 1", "bar", "foo 2", "foo 3"] {
+            buffer.push(OutputLine::new(OutputKind::Stdout, line.into()));
+            state.update(&buffer);
+        }
+
+        // "foo 1" was evicted; the rest moved up by one line
+        let lines: Vec<_> = state.matches().iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![1, 2]);
+        assert_eq!(state.current_match().unwrap().line, 1);
+    }
+
     #[test]
     fn search_state_next_match_cycles_through_matches() {
         let buffer = create_buffer_with_lines(&["line1 foo", "line2", "line3 foo"]);
@@ -268,12 +607,12 @@ mod tests {
         assert_eq!(state.current_match_display(), Some(1));
         assert_eq!(state.current_match().unwrap().line, 0);
 
-        let line = state.next_match();
-        assert_eq!(line, Some(2));
+        let jump = state.next_match().unwrap();
+        assert_eq!((jump.line, jump.wrapped), (2, false));
         assert_eq!(state.current_match_display(), Some(2));
 
-        let line = state.next_match();
-        assert_eq!(line, Some(0)); // cycles back
+        let jump = state.next_match().unwrap();
+        assert_eq!((jump.line, jump.wrapped), (0, true)); // cycles back
         assert_eq!(state.current_match_display(), Some(1));
     }
 
@@ -285,12 +624,12 @@ mod tests {
 
         assert_eq!(state.current_match_display(), Some(1));
 
-        let line = state.prev_match();
-        assert_eq!(line, Some(2)); // cycles to last
+        let jump = state.prev_match().unwrap();
+        assert_eq!((jump.line, jump.wrapped), (2, true)); // cycles to last
         assert_eq!(state.current_match_display(), Some(2));
 
-        let line = state.prev_match();
-        assert_eq!(line, Some(0));
+        let jump = state.prev_match().unwrap();
+        assert_eq!((jump.line, jump.wrapped), (0, false));
         assert_eq!(state.current_match_display(), Some(1));
     }
 
@@ -454,6 +793,21 @@ mod tests {
         assert_eq!(state.matches()[1].line, 2);
     }
 
+    #[test]
+    fn search_keeps_cursor_when_query_is_unchanged() {
+        let buffer = create_buffer_with_lines(&["日本語"]);
+        let mut state = SearchState::new();
+        state.handle_input(InputRequest::InsertChar('日'));
+        state.handle_input(InputRequest::InsertChar('語'));
+        state.handle_input(InputRequest::GoToPrevChar);
+        state.handle_input(InputRequest::InsertChar('本'));
+
+        state.search("日本語", &buffer);
+
+        assert_eq!(state.query(), "日本語");
+        assert_eq!(state.input().cursor(), 2);
+    }
+
     #[test]
     fn clear_input_should_clear_query_but_preserve_matches() {
         let buffer = create_buffer_with_lines(&["hello world", "hello rust"]);
@@ -516,4 +870,106 @@ mod tests {
         assert_eq!(state.matches().len(), 1);
         assert_eq!(state.matches()[0].line, 1); // "foo bar" の行のみ
     }
+
+    /// Buffer just large enough for a background search, with "needle" on
+    /// every 25,000th line
+    fn create_large_buffer(max_lines: usize) -> OutputBuffer {
+        let mut buffer = OutputBuffer::new(max_lines);
+        for i in 0..=BACKGROUND_SEARCH_LINES {
+            let text = if i % 25_000 == 0 { "needle" } else { "hay" };
+            buffer.push(OutputLine::new(OutputKind::Stdout, text.into()));
+        }
+        buffer
+    }
+
+    #[test]
+    fn search_state_start_searches_small_buffers_right_away() {
+        let buffer = create_buffer_with_lines(&["hello", "world"]);
+        let mut state = SearchState::new();
+
+        assert!(state.start("world", &buffer).is_none());
+        assert!(!state.is_searching());
+        assert_eq!(state.match_count(), 1);
+    }
+
+    #[test]
+    fn search_state_start_streams_matches_of_large_buffers() {
+        let mut buffer = create_large_buffer(0);
+        let mut state = SearchState::new();
+
+        let job = state.start("needle", &buffer).unwrap();
+        let id = job.id();
+        assert!(state.is_searching());
+        assert!(!state.has_matches());
+
+        // Lines arriving meanwhile are searched once the job is done
+        buffer.push(OutputLine::new(OutputKind::Stdout, "needle".into()));
+        assert!(!state.update(&buffer));
+
+        let mut batches = Vec::new();
+        job.run(|matches, done| {
+            batches.push((matches, done));
+            true
+        });
+        assert!(batches.len() > 1);
+        for (matches, done) in batches {
+            assert!(state.receive(id, matches, done));
+        }
+
+        assert!(!state.is_searching());
+        assert_eq!(state.match_count(), 5);
+        assert_eq!(state.current_match_display(), Some(1));
+        assert!(state.update(&buffer));
+        let lines: Vec<usize> = state.matches().iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![0, 25_000, 50_000, 75_000, 100_000, 100_001]);
+    }
+
+    #[test]
+    fn search_state_start_cancels_the_previous_search() {
+        let buffer = create_large_buffer(0);
+        let mut state = SearchState::new();
+        let old = state.start("needle", &buffer).unwrap();
+        let old_id = old.id();
+
+        let new = state.start("hay", &buffer).unwrap();
+
+        let mut sent = 0;
+        old.run(|_, _| {
+            sent += 1;
+            true
+        });
+        assert_eq!(sent, 0);
+        assert!(!state.receive(old_id, vec![], true));
+        assert!(state.is_searching());
+        assert_ne!(new.id(), old_id);
+    }
+
+    #[test]
+    fn search_state_receive_renumbers_matches_after_eviction() {
+        let mut buffer = create_large_buffer(BACKGROUND_SEARCH_LINES + 1);
+        let mut state = SearchState::new();
+        let job = state.start("needle", &buffer).unwrap();
+        let id = job.id();
+
+        // Evict the first needle and the line after it
+        for _ in 0..2 {
+            buffer.push(OutputLine::new(OutputKind::Stdout, "hay".into()));
+        }
+        assert!(state.update(&buffer));
+        job.run(|matches, done| state.receive(id, matches, done));
+
+        let lines: Vec<usize> = state.matches().iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![24_998, 49_998, 74_998, 99_998]);
+    }
+
+    #[test]
+    fn search_state_matches_in_returns_matches_on_lines() {
+        let buffer = create_buffer_with_lines(&["a", "b a", "a a", "b"]);
+        let mut state = SearchState::new();
+        state.search("a", &buffer);
+
+        let lines: Vec<usize> = state.matches_in(1..3).iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![1, 2, 2]);
+        assert!(state.matches_in(3..10).is_empty());
+    }
 }
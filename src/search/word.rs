@@ -0,0 +1,165 @@
+/// Check if a character is part of a word (letters, digits and `_`, as in vim)
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Find the word at a byte position, or the next word after it
+///
+/// Returns `None` if there is no word at or after the position. A position
+/// inside a character is treated as that character's start.
+pub fn word_at(text: &str, byte: usize) -> Option<&str> {
+    let mut pos = byte.min(text.len());
+    while !text.is_char_boundary(pos) {
+        pos -= 1;
+    }
+
+    // Back up to the start of the word the position is in
+    let start = if text[pos..].starts_with(is_word_char) {
+        text[..pos]
+            .char_indices()
+            .rev()
+            .take_while(|&(_, c)| is_word_char(c))
+            .last()
+            .map_or(pos, |(i, _)| i)
+    } else {
+        pos + text[pos..].find(is_word_char)?
+    };
+    let end = text[start..]
+        .find(|c| !is_word_char(c))
+        .map_or(text.len(), |len| start + len);
+
+    Some(&text[start..end])
+}
+
+/// Check if a character can be part of a file path in output text
+fn is_path_char(c: char) -> bool {
+    !c.is_whitespace()
+        && !matches!(
+            c,
+            '"' | '\'' | '`' | '(' | ')' | '[' | ']' | '{' | '}' | '<' | '>' | ',' | ';'
+        )
+}
+
+/// Find the file path at a byte position
+///
+/// A `:line:column` location after the path (as in compiler errors) and
+/// trailing punctuation are left out. Returns `None` if the position is
+/// not on a path.
+pub fn path_at(text: &str, byte: usize) -> Option<&str> {
+    let mut pos = byte.min(text.len());
+    while !text.is_char_boundary(pos) {
+        pos -= 1;
+    }
+    if !text[pos..].starts_with(is_path_char) {
+        return None;
+    }
+    let start = text[..pos]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_path_char(c))
+        .last()
+        .map_or(pos, |(i, _)| i);
+    let end = text[pos..]
+        .find(|c| !is_path_char(c))
+        .map_or(text.len(), |len| pos + len);
+
+    let mut path = &text[start..end];
+    while let Some((head, location)) = path.rsplit_once(':') {
+        if !location.chars().all(|c| c.is_ascii_digit()) {
+            break;
+        }
+        path = head;
+    }
+    let path = path.trim_end_matches(['.', ':']);
+    (!path.is_empty()).then_some(path)
+}
+
+/// Find the start (char index) of the next word after a char column
+pub fn next_word_start(text: &str, column: usize) -> Option<usize> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut pos = column;
+    // Skip the rest of the current word, then the gap after it
+    while pos < chars.len() && is_word_char(chars[pos]) {
+        pos += 1;
+    }
+    while pos < chars.len() && !is_word_char(chars[pos]) {
+        pos += 1;
+    }
+    (pos < chars.len()).then_some(pos)
+}
+
+/// Find the start (char index) of the word before a char column
+pub fn prev_word_start(text: &str, column: usize) -> Option<usize> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut pos = column.min(chars.len());
+    while pos > 0 && !is_word_char(chars[pos - 1]) {
+        pos -= 1;
+    }
+    if pos == 0 {
+        return None;
+    }
+    while pos > 0 && is_word_char(chars[pos - 1]) {
+        pos -= 1;
+    }
+    Some(pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("connection refused", 0, Some("connection"))]
+    #[case("connection refused", 5, Some("connection"))]
+    #[case("connection refused", 10, Some("refused"))]
+    #[case("  [error] disk_full", 0, Some("error"))]
+    #[case("[error] disk_full", 12, Some("disk_full"))]
+    #[case("ünïcode wörds", 9, Some("wörds"))]
+    #[case("ünïcode wörds", 1, Some("ünïcode"))]
+    #[case("--- ", 0, None)]
+    #[case("", 0, None)]
+    fn word_at_finds_word_at_or_after_position(
+        #[case] text: &str,
+        #[case] byte: usize,
+        #[case] expected: Option<&str>,
+    ) {
+        assert_eq!(word_at(text, byte), expected);
+    }
+
+    #[rstest]
+    #[case("error: src/main.rs:42:7: expected `;`", 10, Some("src/main.rs"))]
+    #[case("see (docs/setup.md).", 6, Some("docs/setup.md"))]
+    #[case("wrote ./out/ログ.txt", 14, Some("./out/ログ.txt"))]
+    #[case("path ~/.config/app.toml", 5, Some("~/.config/app.toml"))]
+    #[case("done (ok)", 5, None)]
+    #[case("a  b", 2, None)]
+    fn path_at_finds_path_without_location(
+        #[case] text: &str,
+        #[case] byte: usize,
+        #[case] expected: Option<&str>,
+    ) {
+        assert_eq!(path_at(text, byte), expected);
+    }
+
+    #[rstest]
+    #[case(0, Some(6))]
+    #[case(3, Some(6))]
+    #[case(6, Some(11))]
+    #[case(11, None)]
+    fn next_word_start_skips_word_and_gap(#[case] column: usize, #[case] expected: Option<usize>) {
+        assert_eq!(next_word_start("open (file.rs", column), expected);
+    }
+
+    #[rstest]
+    #[case(13, Some(11))]
+    #[case(11, Some(6))]
+    #[case(6, Some(0))]
+    #[case(0, None)]
+    fn prev_word_start_moves_to_previous_word(
+        #[case] column: usize,
+        #[case] expected: Option<usize>,
+    ) {
+        assert_eq!(prev_word_start("open (file.rs", column), expected);
+    }
+}
@@ -0,0 +1,241 @@
+//! The event loop of a running session
+//!
+//! `Session` owns the terminal and the `App`, and turns app events,
+//! terminal events and frame ticks into state changes and redraws. The
+//! binary drives it with crossterm's `EventStream`; tests can drive it with
+//! any stream of events and a `TestBackend` (see `testing`, behind the
+//! `test-driver` feature).
+
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture, Event, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::SetTitle;
+use futures::{Stream, StreamExt};
+use ratatui::Terminal;
+use ratatui::backend::{Backend, CrosstermBackend};
+use tokio::time::{Interval, interval};
+
+use crate::app::App;
+use crate::config::ConfigWatcher;
+use crate::status_file::StatusFile;
+use crate::tui::{Renderer, handle_key, handle_mouse, handle_paste};
+
+/// Terminal features the event loop controls besides drawing
+pub trait TerminalControl: Backend {
+    /// Capture the mouse (wheel scrolling) or leave it to the terminal
+    fn set_mouse_capture(&mut self, enabled: bool) -> io::Result<()>;
+
+    /// Set the terminal window title
+    fn set_title(&mut self, title: &str) -> io::Result<()>;
+
+    /// Convert a drawing error of the backend
+    fn io_error(error: Self::Error) -> io::Error;
+}
+
+impl<W: Write> TerminalControl for CrosstermBackend<W> {
+    fn set_mouse_capture(&mut self, enabled: bool) -> io::Result<()> {
+        if enabled {
+            execute!(self, EnableMouseCapture)
+        } else {
+            execute!(self, DisableMouseCapture)
+        }
+    }
+
+    fn set_title(&mut self, title: &str) -> io::Result<()> {
+        execute!(self, SetTitle(title))
+    }
+
+    fn io_error(error: io::Error) -> io::Error {
+        error
+    }
+}
+
+/// A running session: the app, its terminal and the loop state
+pub struct Session<B: TerminalControl> {
+    terminal: Terminal<B>,
+    app: App,
+    render_interval: Interval,
+    mouse_captured: bool,
+    title: String,
+    status_file: Option<StatusFile>,
+    config_watcher: Option<ConfigWatcher>,
+}
+
+impl<B: TerminalControl> Session<B> {
+    /// Create a session drawing at most `fps` frames per second
+    pub fn new(terminal: Terminal<B>, app: App, fps: u64) -> Self {
+        Self {
+            terminal,
+            app,
+            render_interval: interval(Duration::from_micros(1_000_000 / fps)),
+            mouse_captured: false,
+            title: String::new(),
+            status_file: None,
+            config_watcher: None,
+        }
+    }
+
+    /// Keep a status summary in this file while the session runs
+    pub fn set_status_file(&mut self, status_file: StatusFile) {
+        self.status_file = Some(status_file);
+    }
+
+    /// Apply changes to this config file while the session runs
+    pub fn set_config_watcher(&mut self, config_watcher: ConfigWatcher) {
+        self.config_watcher = Some(config_watcher);
+    }
+
+    /// Get the app
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    /// Get the app mutably
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// Get the terminal
+    pub fn terminal(&self) -> &Terminal<B> {
+        &self.terminal
+    }
+
+    /// Get the terminal mutably
+    pub fn terminal_mut(&mut self) -> &mut Terminal<B> {
+        &mut self.terminal
+    }
+
+    /// Take the terminal back, e.g. to restore it
+    pub fn into_terminal(self) -> Terminal<B> {
+        self.terminal
+    }
+
+    /// Spawn all commands and handle events until the user quits
+    pub async fn run<S>(&mut self, mut events: S) -> io::Result<()>
+    where
+        S: Stream<Item = io::Result<Event>> + Unpin,
+    {
+        self.app.spawn_commands().await;
+        while !self.turn(&mut events).await? {}
+        Ok(())
+    }
+
+    /// Handle one event (or frame tick) and the work it made due
+    ///
+    /// Returns `true` once the app has quit and all commands were killed.
+    pub async fn turn<S>(&mut self, events: &mut S) -> io::Result<bool>
+    where
+        S: Stream<Item = io::Result<Event>> + Unpin,
+    {
+        let app = &mut self.app;
+        let terminal = &mut self.terminal;
+
+        // Follow mouse capture toggles (off lets the terminal select text)
+        if app.mouse_capture() != self.mouse_captured {
+            self.mouse_captured = app.mouse_capture();
+            terminal
+                .backend_mut()
+                .set_mouse_capture(self.mouse_captured)?;
+        }
+
+        // Update visible lines for all tabs based on terminal size
+        let size = terminal.size().map_err(B::io_error)?;
+        let (visible_lines, visible_columns) = Renderer::output_size(app, size.width, size.height);
+        for tab in app.tab_manager_mut().iter_mut() {
+            let pinned_height = tab.pinned_area_height();
+            tab.set_visible_lines(visible_lines.saturating_sub(pinned_height));
+            tab.set_visible_columns(visible_columns);
+        }
+
+        tokio::select! {
+            // Handle app events from background command tasks
+            Some(event) = app.recv_event() => {
+                app.handle_app_event(event);
+            }
+            // Handle terminal events
+            Some(Ok(event)) = events.next() => match event {
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    handle_key(app, key);
+                    app.mark_dirty();
+                }
+                Event::Paste(text) => {
+                    handle_paste(app, &text);
+                    app.mark_dirty();
+                }
+                Event::Mouse(mouse) => {
+                    handle_mouse(app, mouse);
+                    app.mark_dirty();
+                }
+                Event::Resize(..) => app.mark_dirty(),
+                _ => {}
+            },
+            // Render at most once per frame, and only if something changed
+            _ = self.render_interval.tick() => {
+                app.refresh_search();
+                app.retry_sinks();
+                #[cfg(feature = "compress")]
+                app.tab_manager_mut().compact_buffers();
+                app.update_exit_countdown();
+                app.check_hung_commands();
+                app.check_ready_commands();
+                if let Some(watcher) = &mut self.config_watcher
+                    && let Some(reloaded) = watcher.poll()
+                {
+                    match reloaded {
+                        Ok(old) => app.reload_config(&old, watcher.config()),
+                        Err(e) => app.set_message(format!("Error: config not reloaded: {}", e)),
+                    }
+                    app.mark_dirty();
+                }
+                if app.take_dirty() || app.has_countdown() {
+                    terminal
+                        .draw(|frame| {
+                            Renderer::render(frame, app);
+                        })
+                        .map_err(B::io_error)?;
+                }
+            }
+        }
+
+        // Keep the terminal title in sync with the aggregate status
+        let summary = format!("parallels: {}", app.tab_manager().summary());
+        if summary != self.title {
+            terminal.backend_mut().set_title(&summary)?;
+            self.title = summary;
+        }
+        if let Some(file) = &mut self.status_file
+            && let Err(e) = file.update(&app.tab_manager().summary())
+        {
+            app.log_event(format!("Error: status file disabled: {}", e));
+            self.status_file = None;
+        }
+
+        // Handle pending restart request
+        if let Some(tab_index) = app.take_pending_restart() {
+            app.restart_process(tab_index).await;
+        }
+
+        // Start scheduled runs that are due
+        while let Some(tab_index) = app.take_pending_run() {
+            app.start_run(tab_index).await;
+        }
+
+        // Restart commands found hung by their watchdog
+        while let Some(tab_index) = app.take_hung() {
+            app.restart_hung(tab_index).await;
+        }
+
+        // Check if we should quit
+        if app.should_quit() {
+            // Kill all child processes before exiting
+            app.kill_all().await;
+            if let Some(file) = &self.status_file {
+                let _ = file.remove();
+            }
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
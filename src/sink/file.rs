@@ -0,0 +1,65 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, LineWriter, Write};
+use std::path::{Path, PathBuf};
+
+use super::{OutputSink, SinkLine};
+
+/// Sink writing plain-text output of all tabs to a single log file
+///
+/// Each line is prefixed with the tab name, e.g. `[web] listening on :3000`.
+pub struct FileSink {
+    path: PathBuf,
+    writer: LineWriter<File>,
+}
+
+impl FileSink {
+    /// Open (or create) a log file, appending to existing content
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: LineWriter::new(file),
+        })
+    }
+}
+
+impl OutputSink for FileSink {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn write_line(&mut self, line: &SinkLine) -> io::Result<()> {
+        writeln!(self.writer, "[{}] {}", line.tab_name, line.line.plain())
+    }
+
+    fn exited(&mut self, _tab_index: usize, tab_name: &str, exit_code: i32) -> io::Result<()> {
+        writeln!(self.writer, "[{}] exited ({})", tab_name, exit_code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::{OutputKind, OutputLine};
+
+    #[test]
+    fn file_sink_writes_prefixed_plain_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file_sink.log");
+
+        let mut sink = FileSink::create(&path).unwrap();
+        let line = OutputLine::new(OutputKind::Stdout, "\x1b[32mready\x1b[0m".into());
+        sink.write_line(&SinkLine {
+            tab_index: 0,
+            tab_name: "web",
+            line: &line,
+        })
+        .unwrap();
+        sink.exited(0, "web", 1).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "[web] ready\n[web] exited (1)\n"
+        );
+    }
+}
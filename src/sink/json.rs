@@ -0,0 +1,190 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, LineWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use super::{OutputSink, SinkLine};
+use crate::buffer::OutputKind;
+use crate::command::signal_name;
+use crate::event::AppEvent;
+
+/// Sink writing output of all tabs as a JSON Lines stream
+///
+/// Every output line, exit and lifecycle event becomes one JSON object per
+/// line, e.g.
+/// `{"event":"output","tab":0,"name":"web","stream":"stdout","line":"ready"}`.
+pub struct JsonSink {
+    path: PathBuf,
+    writer: LineWriter<File>,
+}
+
+/// A single record of the JSON stream
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+enum Record<'a> {
+    Output {
+        tab: usize,
+        name: &'a str,
+        stream: &'a str,
+        line: String,
+    },
+    Exit {
+        tab: usize,
+        name: &'a str,
+        exit_code: i32,
+    },
+    Started {
+        tab: usize,
+        name: &'a str,
+        pid: Option<u32>,
+    },
+    Restarted {
+        tab: usize,
+        name: &'a str,
+        attempt: usize,
+    },
+    Killed {
+        tab: usize,
+        name: &'a str,
+        signal: String,
+    },
+    Stats {
+        tab: usize,
+        name: &'a str,
+        lines: u64,
+        elapsed_ms: u128,
+    },
+}
+
+impl JsonSink {
+    /// Open (or create) a JSON Lines file, appending to existing content
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: LineWriter::new(file),
+        })
+    }
+
+    fn write_record(&mut self, record: &Record) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, record)?;
+        self.writer.write_all(b"\n")
+    }
+}
+
+impl OutputSink for JsonSink {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn write_line(&mut self, line: &SinkLine) -> io::Result<()> {
+        let stream = match line.line.kind {
+            OutputKind::Stdout => "stdout",
+            OutputKind::Stderr => "stderr",
+            OutputKind::Divider => "divider",
+            OutputKind::Marker => "marker",
+            OutputKind::Hook => "hook",
+        };
+        self.write_record(&Record::Output {
+            tab: line.tab_index,
+            name: line.tab_name,
+            stream,
+            line: line.line.plain(),
+        })
+    }
+
+    fn exited(&mut self, tab_index: usize, tab_name: &str, exit_code: i32) -> io::Result<()> {
+        self.write_record(&Record::Exit {
+            tab: tab_index,
+            name: tab_name,
+            exit_code,
+        })
+    }
+
+    fn lifecycle(&mut self, name: &str, event: &AppEvent) -> io::Result<()> {
+        let record = match *event {
+            AppEvent::Started { tab_index, pid } => Record::Started {
+                tab: tab_index,
+                name,
+                pid,
+            },
+            AppEvent::Restarted { tab_index, attempt } => Record::Restarted {
+                tab: tab_index,
+                name,
+                attempt,
+            },
+            AppEvent::Killed { tab_index, signal } => Record::Killed {
+                tab: tab_index,
+                name,
+                signal: signal_name(signal),
+            },
+            AppEvent::Stats {
+                tab_index,
+                lines,
+                elapsed,
+            } => Record::Stats {
+                tab: tab_index,
+                name,
+                lines,
+                elapsed_ms: elapsed.as_millis(),
+            },
+            _ => return Ok(()),
+        };
+        self.write_record(&record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::OutputLine;
+
+    #[test]
+    fn json_sink_writes_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("json_sink.jsonl");
+
+        let mut sink = JsonSink::create(&path).unwrap();
+        let line = OutputLine::new(OutputKind::Stderr, "oops \"quoted\"".into());
+        sink.write_line(&SinkLine {
+            tab_index: 1,
+            tab_name: "db",
+            line: &line,
+        })
+        .unwrap();
+        sink.exited(1, "db", 2).unwrap();
+        sink.lifecycle(
+            "db",
+            &AppEvent::Killed {
+                tab_index: 1,
+                signal: 9,
+            },
+        )
+        .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let records: Vec<serde_json::Value> = content
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(
+            records[0],
+            serde_json::json!({
+                "event": "output",
+                "tab": 1,
+                "name": "db",
+                "stream": "stderr",
+                "line": "oops \"quoted\"",
+            })
+        );
+        assert_eq!(
+            records[1],
+            serde_json::json!({"event": "exit", "tab": 1, "name": "db", "exit_code": 2})
+        );
+        assert_eq!(
+            records[2],
+            serde_json::json!({"event": "killed", "tab": 1, "name": "db", "signal": "SIGKILL"})
+        );
+    }
+}
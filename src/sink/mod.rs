@@ -0,0 +1,46 @@
+mod file;
+mod json;
+mod set;
+
+use std::io;
+
+use crate::buffer::OutputLine;
+use crate::event::AppEvent;
+
+pub use file::FileSink;
+pub use json::JsonSink;
+pub use set::{LostRecords, MAX_FAILURES, SinkReport, Sinks};
+
+/// Output line routed to a sink, with the tab it came from
+#[derive(Clone, Copy)]
+pub struct SinkLine<'a> {
+    pub tab_index: usize,
+    /// Tab name (or command line if unnamed)
+    pub tab_name: &'a str,
+    pub line: &'a OutputLine,
+}
+
+/// Destination for command output besides the TUI buffer
+///
+/// Sinks are registered on the `App` at startup and receive every output
+/// line and exit before the line is stored in the tab's buffer. A sink
+/// whose writes fail is retried and eventually disabled (see [`Sinks`]).
+pub trait OutputSink: Send {
+    /// Name shown when writing fails (e.g. the file's path)
+    fn name(&self) -> String {
+        "output sink".to_string()
+    }
+
+    /// Write a single output line
+    fn write_line(&mut self, line: &SinkLine) -> io::Result<()>;
+
+    /// Record that a tab's command exited
+    fn exited(&mut self, _tab_index: usize, _tab_name: &str, _exit_code: i32) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Record a lifecycle event (`Started`, `Restarted`, `Killed`, `Stats`)
+    fn lifecycle(&mut self, _tab_name: &str, _event: &AppEvent) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,456 @@
+use std::collections::VecDeque;
+use std::io;
+use std::time::{Duration, Instant};
+
+use super::{OutputSink, SinkLine};
+use crate::buffer::OutputLine;
+use crate::event::AppEvent;
+
+/// Records kept for a failing sink before the oldest are dropped
+const MAX_QUEUED: usize = 10_000;
+
+/// Failed writes in a row after which a sink is disabled
+pub const MAX_FAILURES: u32 = 5;
+
+/// Delay before a failing sink is retried, doubled after each further failure
+const FIRST_RETRY: Duration = Duration::from_secs(1);
+
+/// Something written to the sinks
+#[derive(Clone, Copy)]
+enum Record<'a> {
+    Line(SinkLine<'a>),
+    Exited {
+        tab_index: usize,
+        tab_name: &'a str,
+        exit_code: i32,
+    },
+    Lifecycle {
+        tab_name: &'a str,
+        event: &'a AppEvent,
+    },
+}
+
+impl Record<'_> {
+    fn write_to(self, sink: &mut dyn OutputSink) -> io::Result<()> {
+        match self {
+            Record::Line(line) => sink.write_line(&line),
+            Record::Exited {
+                tab_index,
+                tab_name,
+                exit_code,
+            } => sink.exited(tab_index, tab_name, exit_code),
+            Record::Lifecycle { tab_name, event } => sink.lifecycle(tab_name, event),
+        }
+    }
+
+    fn to_owned(self) -> QueuedRecord {
+        match self {
+            Record::Line(line) => QueuedRecord::Line {
+                tab_index: line.tab_index,
+                tab_name: line.tab_name.to_string(),
+                line: line.line.clone(),
+            },
+            Record::Exited {
+                tab_index,
+                tab_name,
+                exit_code,
+            } => QueuedRecord::Exited {
+                tab_index,
+                tab_name: tab_name.to_string(),
+                exit_code,
+            },
+            Record::Lifecycle { tab_name, event } => QueuedRecord::Lifecycle {
+                tab_name: tab_name.to_string(),
+                event: event.clone(),
+            },
+        }
+    }
+}
+
+/// A record kept until a failing sink is retried
+enum QueuedRecord {
+    Line {
+        tab_index: usize,
+        tab_name: String,
+        line: OutputLine,
+    },
+    Exited {
+        tab_index: usize,
+        tab_name: String,
+        exit_code: i32,
+    },
+    Lifecycle {
+        tab_name: String,
+        event: AppEvent,
+    },
+}
+
+impl QueuedRecord {
+    fn record(&self) -> Record<'_> {
+        match self {
+            QueuedRecord::Line {
+                tab_index,
+                tab_name,
+                line,
+            } => Record::Line(SinkLine {
+                tab_index: *tab_index,
+                tab_name,
+                line,
+            }),
+            QueuedRecord::Exited {
+                tab_index,
+                tab_name,
+                exit_code,
+            } => Record::Exited {
+                tab_index: *tab_index,
+                tab_name,
+                exit_code: *exit_code,
+            },
+            QueuedRecord::Lifecycle { tab_name, event } => Record::Lifecycle { tab_name, event },
+        }
+    }
+}
+
+/// What happened to a sink while writing to it
+#[derive(Debug)]
+pub enum SinkReport {
+    /// A write failed; records are kept and written again after `retry_in`
+    Failed {
+        name: String,
+        error: io::Error,
+        retry_in: Duration,
+    },
+    /// A failing sink caught up with the records kept for it; `dropped`
+    /// didn't fit into the queue
+    Recovered { name: String, dropped: u64 },
+    /// A sink failed [`MAX_FAILURES`] times in a row and was removed
+    Disabled { name: String, error: io::Error },
+}
+
+/// Records a sink never wrote, reported when the session ends
+#[derive(Debug, PartialEq, Eq)]
+pub struct LostRecords {
+    pub name: String,
+    pub count: u64,
+}
+
+/// A sink with the records kept for it while it fails
+struct Slot {
+    sink: Box<dyn OutputSink>,
+    queue: VecDeque<QueuedRecord>,
+    /// Failed writes in a row
+    failures: u32,
+    /// When to write the queue again, while the sink is failing
+    retry_at: Option<Instant>,
+    /// Records dropped from a full queue since the sink started failing
+    dropped: u64,
+}
+
+impl Slot {
+    /// Count the records this sink will never write
+    fn lost(&self) -> LostRecords {
+        LostRecords {
+            name: self.sink.name(),
+            count: self.dropped + self.queue.len() as u64,
+        }
+    }
+
+    fn keep(&mut self, record: Record) {
+        if self.queue.len() == MAX_QUEUED {
+            self.queue.pop_front();
+            self.dropped += 1;
+        }
+        self.queue.push_back(record.to_owned());
+    }
+
+    /// Count a failed write, returning whether the sink should be disabled
+    fn fail(&mut self, now: Instant, error: io::Error, reports: &mut Vec<SinkReport>) -> bool {
+        self.failures += 1;
+        let name = self.sink.name();
+        if self.failures >= MAX_FAILURES {
+            reports.push(SinkReport::Disabled { name, error });
+            return true;
+        }
+        let retry_in = FIRST_RETRY * 2u32.pow(self.failures - 1);
+        self.retry_at = Some(now + retry_in);
+        reports.push(SinkReport::Failed {
+            name,
+            error,
+            retry_in,
+        });
+        false
+    }
+
+    /// Write the kept records, returning whether the sink should be disabled
+    fn flush(&mut self, now: Instant, reports: &mut Vec<SinkReport>) -> bool {
+        while let Some(queued) = self.queue.front() {
+            if let Err(e) = queued.record().write_to(self.sink.as_mut()) {
+                return self.fail(now, e, reports);
+            }
+            self.queue.pop_front();
+        }
+        self.failures = 0;
+        self.retry_at = None;
+        reports.push(SinkReport::Recovered {
+            name: self.sink.name(),
+            dropped: std::mem::take(&mut self.dropped),
+        });
+        false
+    }
+
+    /// Write a record, or keep it while the sink is failing
+    fn write(&mut self, now: Instant, record: Record, reports: &mut Vec<SinkReport>) -> bool {
+        match self.retry_at {
+            None => match record.write_to(self.sink.as_mut()) {
+                Ok(()) => false,
+                Err(e) => {
+                    self.keep(record);
+                    self.fail(now, e, reports)
+                }
+            },
+            Some(retry_at) => {
+                self.keep(record);
+                now >= retry_at && self.flush(now, reports)
+            }
+        }
+    }
+}
+
+/// The output sinks of a session
+///
+/// A sink whose write fails isn't dropped right away: what it should have
+/// written is kept (up to [`MAX_QUEUED`] records) and written again after a
+/// delay that doubles with each failure, so a disk that fills up and is
+/// cleaned again loses nothing. A sink that fails [`MAX_FAILURES`] times in
+/// a row is disabled.
+#[derive(Default)]
+pub struct Sinks {
+    slots: Vec<Slot>,
+    /// Records of disabled sinks, for [`Sinks::finish`]
+    lost: Vec<LostRecords>,
+}
+
+impl Sinks {
+    /// Add a sink
+    pub fn push(&mut self, sink: Box<dyn OutputSink>) {
+        self.slots.push(Slot {
+            sink,
+            queue: VecDeque::new(),
+            failures: 0,
+            retry_at: None,
+            dropped: 0,
+        });
+    }
+
+    /// Get the number of sinks still enabled
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Check if no sink is enabled
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Write an output line
+    pub fn write_line(&mut self, now: Instant, line: &SinkLine) -> Vec<SinkReport> {
+        self.write(now, Record::Line(*line))
+    }
+
+    /// Record that a tab's command exited
+    pub fn exited(
+        &mut self,
+        now: Instant,
+        tab_index: usize,
+        tab_name: &str,
+        exit_code: i32,
+    ) -> Vec<SinkReport> {
+        self.write(
+            now,
+            Record::Exited {
+                tab_index,
+                tab_name,
+                exit_code,
+            },
+        )
+    }
+
+    /// Record a lifecycle event
+    pub fn lifecycle(&mut self, now: Instant, tab_name: &str, event: &AppEvent) -> Vec<SinkReport> {
+        self.write(now, Record::Lifecycle { tab_name, event })
+    }
+
+    /// Write the records kept for failing sinks that are due to be retried
+    pub fn retry(&mut self, now: Instant) -> Vec<SinkReport> {
+        let mut reports = Vec::new();
+        self.disable_where(|slot| match slot.retry_at {
+            Some(retry_at) if now >= retry_at => slot.flush(now, &mut reports),
+            _ => false,
+        });
+        reports
+    }
+
+    /// Try once more to write what failing sinks kept and close all sinks
+    ///
+    /// Returns what each sink (including disabled ones) never wrote.
+    pub fn finish(&mut self, now: Instant) -> Vec<LostRecords> {
+        let mut lost = std::mem::take(&mut self.lost);
+        for mut slot in self.slots.drain(..) {
+            if !slot.queue.is_empty() {
+                slot.flush(now, &mut Vec::new());
+            }
+            let unwritten = slot.lost();
+            if unwritten.count > 0 {
+                lost.push(unwritten);
+            }
+        }
+        lost
+    }
+
+    fn write(&mut self, now: Instant, record: Record) -> Vec<SinkReport> {
+        let mut reports = Vec::new();
+        self.disable_where(|slot| slot.write(now, record, &mut reports));
+        reports
+    }
+
+    /// Remove the sinks for which `disable` returns `true`, remembering
+    /// the records they lose
+    fn disable_where(&mut self, mut disable: impl FnMut(&mut Slot) -> bool) {
+        let lost = &mut self.lost;
+        self.slots.retain_mut(|slot| {
+            if !disable(slot) {
+                return true;
+            }
+            lost.push(slot.lost());
+            false
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::OutputKind;
+    use std::sync::{Arc, Mutex};
+
+    type Shared<T> = Arc<Mutex<T>>;
+
+    /// Sink recording lines, failing while `full` is set
+    struct DiskSink {
+        lines: Shared<Vec<String>>,
+        full: Shared<bool>,
+    }
+
+    impl OutputSink for DiskSink {
+        fn write_line(&mut self, line: &SinkLine) -> io::Result<()> {
+            if *self.full.lock().unwrap() {
+                return Err(io::Error::other("No space left on device"));
+            }
+            self.lines.lock().unwrap().push(line.line.plain());
+            Ok(())
+        }
+
+        fn name(&self) -> String {
+            "out.log".to_string()
+        }
+    }
+
+    fn disk() -> (Sinks, Shared<Vec<String>>, Shared<bool>) {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let full = Arc::new(Mutex::new(false));
+        let mut sinks = Sinks::default();
+        sinks.push(Box::new(DiskSink {
+            lines: lines.clone(),
+            full: full.clone(),
+        }));
+        (sinks, lines, full)
+    }
+
+    fn write(sinks: &mut Sinks, now: Instant, text: &str) -> Vec<SinkReport> {
+        let line = OutputLine::new(OutputKind::Stdout, text.into());
+        sinks.write_line(
+            now,
+            &SinkLine {
+                tab_index: 0,
+                tab_name: "web",
+                line: &line,
+            },
+        )
+    }
+
+    #[test]
+    fn sinks_keep_lines_of_a_failing_sink_until_it_recovers() {
+        let (mut sinks, lines, full) = disk();
+        let start = Instant::now();
+
+        write(&mut sinks, start, "a");
+        *full.lock().unwrap() = true;
+        let reports = write(&mut sinks, start, "b");
+        assert!(matches!(
+            reports.as_slice(),
+            [SinkReport::Failed { name, retry_in, .. }]
+                if name == "out.log" && *retry_in == FIRST_RETRY
+        ));
+        // Kept without trying again before the retry is due
+        assert!(write(&mut sinks, start, "c").is_empty());
+
+        *full.lock().unwrap() = false;
+        assert!(sinks.retry(start).is_empty());
+        let reports = sinks.retry(start + FIRST_RETRY);
+        assert!(matches!(
+            reports.as_slice(),
+            [SinkReport::Recovered { dropped: 0, .. }]
+        ));
+        assert_eq!(*lines.lock().unwrap(), ["a", "b", "c"]);
+
+        write(&mut sinks, start + FIRST_RETRY, "d");
+        assert_eq!(lines.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn sinks_disable_a_sink_after_repeated_failures() {
+        let (mut sinks, _, full) = disk();
+        *full.lock().unwrap() = true;
+        let mut now = Instant::now();
+
+        let mut retries = Vec::new();
+        let mut reports = write(&mut sinks, now, "a");
+        while let Some(SinkReport::Failed { retry_in, .. }) = reports.first() {
+            retries.push(retry_in.as_secs());
+            now += *retry_in;
+            reports = sinks.retry(now);
+        }
+
+        assert_eq!(retries, [1, 2, 4, 8]);
+        assert!(matches!(
+            reports.as_slice(),
+            [SinkReport::Disabled { name, .. }] if name == "out.log"
+        ));
+        assert!(sinks.is_empty());
+        assert_eq!(
+            sinks.finish(now),
+            [LostRecords {
+                name: "out.log".to_string(),
+                count: 1
+            }]
+        );
+    }
+
+    #[rstest::rstest]
+    #[case(false, 0)]
+    #[case(true, 2)]
+    fn sinks_finish_writes_kept_records_once_more(#[case] still_full: bool, #[case] lost: u64) {
+        let (mut sinks, lines, full) = disk();
+        let now = Instant::now();
+        *full.lock().unwrap() = true;
+        write(&mut sinks, now, "a");
+        write(&mut sinks, now, "b");
+
+        *full.lock().unwrap() = still_full;
+        let unwritten = sinks.finish(now);
+
+        assert_eq!(unwritten.iter().map(|l| l.count).sum::<u64>(), lost);
+        assert_eq!(lines.lock().unwrap().len() as u64, 2 - lost);
+        assert!(sinks.is_empty());
+    }
+}
@@ -0,0 +1,79 @@
+//! Aggregate status for external status lines (tmux, wezterm, ...)
+//!
+//! The file holds a single line such as `3 running, 1 failed`, so a tmux
+//! status-line segment can show it with `#(cat PATH)` while the pane is in
+//! the background.
+
+use std::io;
+use std::path::PathBuf;
+
+use crate::tui::StatusSummary;
+
+/// File rewritten whenever the aggregate status changes
+pub struct StatusFile {
+    path: PathBuf,
+    /// Content of the last write, to skip unchanged updates
+    last: Option<String>,
+}
+
+impl StatusFile {
+    /// Create a status file writer (nothing is written until `update`)
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            last: None,
+        }
+    }
+
+    /// Write the summary if it changed since the last write
+    ///
+    /// The file is replaced atomically so readers never see a partial line.
+    pub fn update(&mut self, summary: &StatusSummary) -> io::Result<()> {
+        let content = format!("{}\n", summary);
+        if self.last.as_deref() == Some(content.as_str()) {
+            return Ok(());
+        }
+
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, &content)?;
+        std::fs::rename(&tmp, &self.path)?;
+        self.last = Some(content);
+        Ok(())
+    }
+
+    /// Remove the file (on exit, so no stale status is left behind)
+    pub fn remove(&self) -> io::Result<()> {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_file_writes_summary_and_removes_on_exit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("status");
+
+        let mut file = StatusFile::new(&path);
+        file.update(&StatusSummary {
+            running: 3,
+            failed: 1,
+            ..StatusSummary::default()
+        })
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "3 running, 1 failed\n"
+        );
+
+        file.remove().unwrap();
+        assert!(!path.exists());
+        file.remove().unwrap();
+    }
+}
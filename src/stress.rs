@@ -0,0 +1,71 @@
+//! Synthetic command output for benchmarks and stress testing
+//!
+//! Used by the criterion benches and by the hidden `--stress` flag, which
+//! turns `parallels` itself into a high-throughput output generator:
+//!
+//! ```bash
+//! parallels "parallels --stress" "parallels --stress"
+//! ```
+
+use std::io::{self, Write};
+
+/// Log levels with their ANSI colors, cycled through by [`sample_line`]
+const LEVELS: [(&str, &str); 4] = [
+    ("INFO", "\x1b[32m"),
+    ("DEBUG", "\x1b[36m"),
+    ("WARN", "\x1b[33m"),
+    ("ERROR", "\x1b[31m"),
+];
+
+/// Build the `index`-th synthetic log line
+///
+/// Lines look like typical colored server logs, with varying level and
+/// length, so ANSI parsing and search see realistic input.
+pub fn sample_line(index: u64) -> String {
+    let (level, color) = LEVELS[(index % LEVELS.len() as u64) as usize];
+    let padding = "x".repeat((index % 40) as usize);
+    format!(
+        "{}[{}]\x1b[0m request id={} path=/api/items/{} took={}ms {}",
+        color,
+        level,
+        index,
+        index % 997,
+        index % 250,
+        padding
+    )
+}
+
+/// Write synthetic lines to `writer` as fast as possible
+///
+/// Writes `count` lines, or until the writer fails (e.g. broken pipe) if
+/// `count` is `None`.
+pub fn generate(writer: &mut impl Write, count: Option<u64>) -> io::Result<()> {
+    let mut index = 0;
+    while count.is_none_or(|count| index < count) {
+        writeln!(writer, "{}", sample_line(index))?;
+        index += 1;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::{OutputKind, OutputLine};
+
+    #[test]
+    fn sample_line_parses_to_plain_log_line() {
+        let line = OutputLine::new(OutputKind::Stdout, sample_line(3));
+        assert_eq!(
+            line.plain(),
+            "[ERROR] request id=3 path=/api/items/3 took=3ms xxx"
+        );
+    }
+
+    #[test]
+    fn generate_writes_requested_number_of_lines() {
+        let mut out = Vec::new();
+        generate(&mut out, Some(5)).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 5);
+    }
+}
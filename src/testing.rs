@@ -0,0 +1,174 @@
+//! Scripted driver for end-to-end tests of the TUI (`test-driver` feature)
+//!
+//! A `Driver` runs the real `Session` event loop on a `TestBackend`, feeding
+//! it synthetic key and paste events, so tests can check what is on screen
+//! as commands run:
+//!
+//! ```no_run
+//! # async fn example() -> std::io::Result<()> {
+//! use crossterm::event::KeyCode;
+//! use parallels::app::App;
+//! use parallels::testing::Driver;
+//!
+//! let app = App::new(vec!["echo hello".into()], 100);
+//! let mut driver = Driver::start(app, 60, 10).await;
+//! driver.wait_for("[stdout] hello").await?;
+//! driver.press(KeyCode::Char('/'));
+//! driver.type_text("hello");
+//! driver.run_for(std::time::Duration::from_millis(50)).await?;
+//! insta::assert_snapshot!(driver.screen());
+//! driver.quit().await
+//! # }
+//! ```
+
+use std::io;
+use std::time::Duration;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded};
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+use tokio::time::Instant;
+
+use crate::app::App;
+use crate::session::{Session, TerminalControl};
+
+/// Frames per second the driven session draws at
+const FPS: u64 = 60;
+
+/// How long `wait_for` waits for text to appear
+const WAIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl TerminalControl for TestBackend {
+    fn set_mouse_capture(&mut self, _enabled: bool) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_title(&mut self, _title: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn io_error(error: std::convert::Infallible) -> io::Error {
+        match error {}
+    }
+}
+
+/// Runs a session on a test terminal, driven by scripted input
+pub struct Driver {
+    session: Session<TestBackend>,
+    input: UnboundedSender<io::Result<Event>>,
+    events: UnboundedReceiver<io::Result<Event>>,
+    quit: bool,
+}
+
+impl Driver {
+    /// Spawn the app's commands in a session on a `width`x`height` terminal
+    pub async fn start(mut app: App, width: u16, height: u16) -> Self {
+        app.spawn_commands().await;
+        let terminal =
+            Terminal::new(TestBackend::new(width, height)).expect("the test backend never fails");
+        let (input, events) = unbounded();
+        Self {
+            session: Session::new(terminal, app, FPS),
+            input,
+            events,
+            quit: false,
+        }
+    }
+
+    /// Queue a key press without modifiers
+    pub fn press(&self, code: KeyCode) {
+        self.press_key(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    /// Queue a key press with modifiers
+    pub fn press_key(&self, key: KeyEvent) {
+        self.send(Event::Key(key));
+    }
+
+    /// Queue a key press for each character of `text`
+    pub fn type_text(&self, text: &str) {
+        for c in text.chars() {
+            self.press(KeyCode::Char(c));
+        }
+    }
+
+    /// Queue a bracketed paste
+    pub fn paste(&self, text: &str) {
+        self.send(Event::Paste(text.to_string()));
+    }
+
+    /// Resize the terminal, queueing the resize event
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.session
+            .terminal_mut()
+            .backend_mut()
+            .resize(width, height);
+        self.send(Event::Resize(width, height));
+    }
+
+    fn send(&self, event: Event) {
+        // The receiver lives as long as the driver
+        let _ = self.input.unbounded_send(Ok(event));
+    }
+
+    /// Run the event loop for a while
+    ///
+    /// Returns `true` if the app quit (which stops the loop).
+    pub async fn run_for(&mut self, duration: Duration) -> io::Result<bool> {
+        let deadline = Instant::now() + duration;
+        while !self.quit && Instant::now() < deadline {
+            self.quit = self.session.turn(&mut self.events).await?;
+        }
+        Ok(self.quit)
+    }
+
+    /// Run the event loop until `text` is on screen
+    ///
+    /// Fails with the last screen if it does not show up within 5 seconds.
+    pub async fn wait_for(&mut self, text: &str) -> io::Result<()> {
+        let deadline = Instant::now() + WAIT_TIMEOUT;
+        while !self.screen().contains(text) {
+            if self.quit || Instant::now() >= deadline {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("{:?} not on screen:\n{}", text, self.screen()),
+                ));
+            }
+            self.quit = self.session.turn(&mut self.events).await?;
+        }
+        Ok(())
+    }
+
+    /// Get the screen as text, one line per row
+    pub fn screen(&self) -> String {
+        let buffer = self.session.terminal().backend().buffer();
+        let mut screen = String::new();
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                screen.push_str(buffer[(x, y)].symbol());
+            }
+            screen.push('\n');
+        }
+        screen
+    }
+
+    /// Get the app
+    pub fn app(&self) -> &App {
+        self.session.app()
+    }
+
+    /// Get the app mutably
+    pub fn app_mut(&mut self) -> &mut App {
+        self.session.app_mut()
+    }
+
+    /// Quit with Ctrl-C and wait until all commands are killed
+    pub async fn quit(mut self) -> io::Result<()> {
+        self.press_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+        while !self.quit {
+            self.quit = self.session.turn(&mut self.events).await?;
+        }
+        Ok(())
+    }
+}
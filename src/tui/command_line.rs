@@ -0,0 +1,324 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::NaiveTime;
+use thiserror::Error;
+
+use super::TabOrder;
+
+/// Line range (1-based, inclusive) as typed on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Command entered in Command mode (`:`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandLine {
+    /// Write the current tab's buffer (or a range of it) to a file
+    Write {
+        range: Option<LineRange>,
+        path: PathBuf,
+    },
+    /// Insert a marker line (with an optional label) into the current tab
+    Mark { label: Option<String> },
+    /// Export every tab's output to a directory or `.tar.gz`
+    ExportAll { path: PathBuf },
+    /// Run a shell command in a new tab
+    New { command: String },
+    /// Set (or, without text, remove) the current tab's note
+    Note { text: Option<String> },
+    /// Show only the current tab's lines read since a time of day (all
+    /// lines without a time)
+    Since { time: Option<NaiveTime> },
+    /// Show only the current tab's lines read in the last `duration`
+    Last { duration: Duration },
+    /// Reorder the tab bar (config order without an argument)
+    Sort { order: TabOrder },
+}
+
+/// Error while parsing a command line
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CommandLineError {
+    #[error("empty command")]
+    Empty,
+    #[error("unknown command: {0}")]
+    UnknownCommand(String),
+    #[error("missing file name")]
+    MissingPath,
+    #[error("invalid range: {0}")]
+    InvalidRange(String),
+    #[error("missing command")]
+    MissingCommand,
+    #[error("invalid time (expected HH:MM or HH:MM:SS): {0}")]
+    InvalidTime(String),
+    #[error("missing duration")]
+    MissingDuration,
+    #[error("invalid duration: {0}")]
+    InvalidDuration(String),
+    #[error("invalid sort order (expected status or activity): {0}")]
+    InvalidSortOrder(String),
+}
+
+impl CommandLine {
+    /// Parse a command line (without the leading `:`)
+    ///
+    /// Supported commands:
+    /// - `write [START,END] PATH` (alias `w`; the path is the rest of the line)
+    /// - `mark [LABEL]`
+    /// - `export-all PATH`
+    /// - `new COMMAND` (the rest of the line, run via `sh -c`)
+    /// - `note [TEXT]` (the rest of the line)
+    /// - `since [HH:MM[:SS]]`
+    /// - `last DURATION` (e.g. `5m`, `1h 30m`)
+    /// - `sort [status|activity]`
+    pub fn parse(input: &str) -> Result<Self, CommandLineError> {
+        let mut parts = input.split_whitespace();
+        let name = parts.next().ok_or(CommandLineError::Empty)?;
+
+        match name {
+            "write" | "w" => {
+                let rest = input.trim_start()[name.len()..].trim();
+                let (first, after) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                let (range, path) = if is_range(first) {
+                    (Some(parse_range(first)?), after.trim())
+                } else {
+                    (None, rest)
+                };
+                if path.is_empty() {
+                    return Err(CommandLineError::MissingPath);
+                }
+                Ok(CommandLine::Write {
+                    range,
+                    path: PathBuf::from(path),
+                })
+            }
+            "export-all" => {
+                let path = parts.next().ok_or(CommandLineError::MissingPath)?;
+                Ok(CommandLine::ExportAll {
+                    path: PathBuf::from(path),
+                })
+            }
+            "new" => {
+                // Keep the command's own spacing and quoting
+                let command = input.trim_start()[name.len()..].trim();
+                if command.is_empty() {
+                    return Err(CommandLineError::MissingCommand);
+                }
+                Ok(CommandLine::New {
+                    command: command.to_string(),
+                })
+            }
+            "note" => {
+                let text = input.trim_start()[name.len()..].trim();
+                Ok(CommandLine::Note {
+                    text: (!text.is_empty()).then(|| text.to_string()),
+                })
+            }
+            "since" => {
+                let time = parts.next().map(parse_time).transpose()?;
+                Ok(CommandLine::Since { time })
+            }
+            "last" => {
+                let duration = parts.collect::<Vec<_>>().join(" ");
+                if duration.is_empty() {
+                    return Err(CommandLineError::MissingDuration);
+                }
+                let duration = humantime::parse_duration(&duration)
+                    .map_err(|_| CommandLineError::InvalidDuration(duration))?;
+                Ok(CommandLine::Last { duration })
+            }
+            "sort" => {
+                let order = match parts.next() {
+                    Some(order) => TabOrder::parse(order)
+                        .ok_or_else(|| CommandLineError::InvalidSortOrder(order.to_string()))?,
+                    None => TabOrder::Config,
+                };
+                Ok(CommandLine::Sort { order })
+            }
+            "mark" => {
+                let label = parts.collect::<Vec<_>>().join(" ");
+                Ok(CommandLine::Mark {
+                    label: (!label.is_empty()).then_some(label),
+                })
+            }
+            _ => Err(CommandLineError::UnknownCommand(name.to_string())),
+        }
+    }
+}
+
+/// Parse a time of day as `HH:MM` or `HH:MM:SS`
+fn parse_time(s: &str) -> Result<NaiveTime, CommandLineError> {
+    NaiveTime::parse_from_str(s, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M"))
+        .map_err(|_| CommandLineError::InvalidTime(s.to_string()))
+}
+
+/// Parse `START,END` into a 1-based inclusive range
+/// Check if a `write` argument is a `START,END` range rather than a path
+fn is_range(s: &str) -> bool {
+    let digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    s.split_once(',')
+        .is_some_and(|(start, end)| digits(start) && digits(end))
+}
+
+fn parse_range(s: &str) -> Result<LineRange, CommandLineError> {
+    let invalid = || CommandLineError::InvalidRange(s.to_string());
+    let (start, end) = s.split_once(',').ok_or_else(invalid)?;
+    let start: usize = start.trim().parse().map_err(|_| invalid())?;
+    let end: usize = end.trim().parse().map_err(|_| invalid())?;
+    if start == 0 || start > end {
+        return Err(invalid());
+    }
+    Ok(LineRange { start, end })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn command_line_parse_write_without_range() {
+        assert_eq!(
+            CommandLine::parse("write out.txt"),
+            Ok(CommandLine::Write {
+                range: None,
+                path: PathBuf::from("out.txt"),
+            })
+        );
+    }
+
+    #[test]
+    fn command_line_parse_write_with_range() {
+        assert_eq!(
+            CommandLine::parse("w 100,250 out.txt"),
+            Ok(CommandLine::Write {
+                range: Some(LineRange {
+                    start: 100,
+                    end: 250
+                }),
+                path: PathBuf::from("out.txt"),
+            })
+        );
+    }
+
+    #[rstest]
+    #[case("w a,b.txt", "a,b.txt")]
+    #[case("write my logs/out 1.txt", "my logs/out 1.txt")]
+    #[case("w 1,2,3.txt", "1,2,3.txt")]
+    fn command_line_parse_write_takes_rest_of_line_as_path(
+        #[case] input: &str,
+        #[case] path: &str,
+    ) {
+        assert_eq!(
+            CommandLine::parse(input),
+            Ok(CommandLine::Write {
+                range: None,
+                path: PathBuf::from(path),
+            })
+        );
+    }
+
+    #[test]
+    fn command_line_parse_write_range_and_path_with_spaces() {
+        assert_eq!(
+            CommandLine::parse("w 3,5 build output.log"),
+            Ok(CommandLine::Write {
+                range: Some(LineRange { start: 3, end: 5 }),
+                path: PathBuf::from("build output.log"),
+            })
+        );
+    }
+
+    #[test]
+    fn command_line_parse_export_all() {
+        assert_eq!(
+            CommandLine::parse("export-all logs.tar.gz"),
+            Ok(CommandLine::ExportAll {
+                path: PathBuf::from("logs.tar.gz"),
+            })
+        );
+    }
+
+    #[test]
+    fn command_line_parse_new_keeps_command_verbatim() {
+        assert_eq!(
+            CommandLine::parse(" new  echo 'a  b' | tr a-z A-Z "),
+            Ok(CommandLine::New {
+                command: "echo 'a  b' | tr a-z A-Z".into(),
+            })
+        );
+    }
+
+    #[rstest]
+    #[case("mark", None)]
+    #[case("mark  before   click ", Some("before click"))]
+    fn command_line_parse_mark(#[case] input: &str, #[case] label: Option<&str>) {
+        assert_eq!(
+            CommandLine::parse(input),
+            Ok(CommandLine::Mark {
+                label: label.map(String::from),
+            })
+        );
+    }
+
+    #[rstest]
+    #[case("note", None)]
+    #[case(
+        "note  restarted  after migration ",
+        Some("restarted  after migration")
+    )]
+    fn command_line_parse_note(#[case] input: &str, #[case] text: Option<&str>) {
+        assert_eq!(
+            CommandLine::parse(input),
+            Ok(CommandLine::Note {
+                text: text.map(String::from),
+            })
+        );
+    }
+
+    #[rstest]
+    #[case("since", None)]
+    #[case("since 14:30", NaiveTime::from_hms_opt(14, 30, 0))]
+    #[case("since 9:05:12", NaiveTime::from_hms_opt(9, 5, 12))]
+    fn command_line_parse_since(#[case] input: &str, #[case] time: Option<NaiveTime>) {
+        assert_eq!(CommandLine::parse(input), Ok(CommandLine::Since { time }));
+    }
+
+    #[rstest]
+    #[case("last 5m", Duration::from_secs(300))]
+    #[case("last 1h 30m", Duration::from_secs(5400))]
+    fn command_line_parse_last(#[case] input: &str, #[case] duration: Duration) {
+        assert_eq!(
+            CommandLine::parse(input),
+            Ok(CommandLine::Last { duration })
+        );
+    }
+
+    #[rstest]
+    #[case("sort", TabOrder::Config)]
+    #[case("sort status", TabOrder::Status)]
+    #[case("sort activity", TabOrder::Activity)]
+    fn command_line_parse_sort(#[case] input: &str, #[case] order: TabOrder) {
+        assert_eq!(CommandLine::parse(input), Ok(CommandLine::Sort { order }));
+    }
+
+    #[rstest]
+    #[case("", CommandLineError::Empty)]
+    #[case("frobnicate", CommandLineError::UnknownCommand("frobnicate".into()))]
+    #[case("write", CommandLineError::MissingPath)]
+    #[case("write 1,2", CommandLineError::MissingPath)]
+    #[case("export-all", CommandLineError::MissingPath)]
+    #[case("new  ", CommandLineError::MissingCommand)]
+    #[case("since 25:00", CommandLineError::InvalidTime("25:00".into()))]
+    #[case("last", CommandLineError::MissingDuration)]
+    #[case("last soon", CommandLineError::InvalidDuration("soon".into()))]
+    #[case("sort name", CommandLineError::InvalidSortOrder("name".into()))]
+    #[case("write 5,2 out.txt", CommandLineError::InvalidRange("5,2".into()))]
+    #[case("write 0,2 out.txt", CommandLineError::InvalidRange("0,2".into()))]
+    fn command_line_parse_returns_error(#[case] input: &str, #[case] expected: CommandLineError) {
+        assert_eq!(CommandLine::parse(input), Err(expected));
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashSet;
+use std::os::unix::fs::PermissionsExt;
+use std::sync::OnceLock;
+
+/// Names of the `:` commands, completed as the first word
+const COMMAND_NAMES: &[&str] = &[
+    "export-all",
+    "last",
+    "mark",
+    "new",
+    "note",
+    "since",
+    "sort",
+    "write",
+];
+
+/// Tab completion cycling through candidates for the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// Complete command lines replacing the input
+    candidates: Vec<String>,
+    index: usize,
+}
+
+impl Completion {
+    /// Find completions for a command line (`None` if there are none)
+    ///
+    /// Completes command names, and for `new` whole commands from `history`
+    /// (most recent first) followed by executables for the first word.
+    pub fn new(input: &str, history: &[String], executables: &[String]) -> Option<Self> {
+        let candidates = candidates(input, history, executables);
+        (!candidates.is_empty()).then_some(Self {
+            candidates,
+            index: 0,
+        })
+    }
+
+    /// The selected candidate
+    pub fn current(&self) -> &str {
+        &self.candidates[self.index]
+    }
+
+    /// Select the next (or previous) candidate, wrapping around
+    pub fn cycle(&mut self, forward: bool) {
+        let len = self.candidates.len();
+        self.index = if forward {
+            (self.index + 1) % len
+        } else {
+            (self.index + len - 1) % len
+        };
+    }
+
+    /// 1-based position of the selected candidate and the candidate count
+    pub fn position(&self) -> (usize, usize) {
+        (self.index + 1, self.candidates.len())
+    }
+}
+
+/// Complete command lines for the input, without duplicates
+fn candidates(input: &str, history: &[String], executables: &[String]) -> Vec<String> {
+    let Some(command) = input.strip_prefix("new ") else {
+        if input.contains(' ') {
+            return Vec::new();
+        }
+        return COMMAND_NAMES
+            .iter()
+            .filter(|name| name.starts_with(input))
+            .map(|name| format!("{} ", name))
+            .collect();
+    };
+
+    let command = command.trim_start();
+    let programs = executables
+        .iter()
+        .filter(|_| !command.contains(char::is_whitespace));
+    let mut seen = HashSet::new();
+    history
+        .iter()
+        .chain(programs)
+        .filter(|candidate| candidate.starts_with(command) && candidate.as_str() != command)
+        .filter(|candidate| seen.insert(candidate.as_str()))
+        .map(|candidate| format!("new {}", candidate))
+        .collect()
+}
+
+/// Executable names found in `$PATH`, sorted (scanned once)
+pub(crate) fn path_executables() -> &'static [String] {
+    static EXECUTABLES: OnceLock<Vec<String>> = OnceLock::new();
+    EXECUTABLES.get_or_init(|| {
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        let mut names: Vec<String> = std::env::split_paths(&path)
+            .filter_map(|dir| std::fs::read_dir(dir).ok())
+            .flatten()
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let metadata = std::fs::metadata(entry.path()).ok()?;
+                let executable = metadata.is_file() && metadata.permissions().mode() & 0o111 != 0;
+                executable.then(|| entry.file_name().into_string().ok())?
+            })
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[rstest]
+    #[case("", &["export-all ", "last ", "mark ", "new ", "note ", "since ", "sort ", "write "])]
+    #[case("m", &["mark "])]
+    #[case("new ca", &["new cargo watch -x run", "new cargo", "new cat"])]
+    #[case("new cargo ", &["new cargo watch -x run"])]
+    #[case("new cat", &[])]
+    #[case("write out", &[])]
+    fn completion_candidates(#[case] input: &str, #[case] expected: &[&str]) {
+        let history = strings(&["cargo watch -x run", "npm run dev"]);
+        let executables = strings(&["cargo", "cat", "npm"]);
+
+        assert_eq!(candidates(input, &history, &executables), strings(expected));
+    }
+
+    #[test]
+    fn completion_cycles_through_candidates() {
+        let executables = strings(&["cargo", "cat"]);
+        let mut completion = Completion::new("new ca", &[], &executables).unwrap();
+        assert_eq!(completion.current(), "new cargo");
+
+        completion.cycle(true);
+        assert_eq!(completion.current(), "new cat");
+        assert_eq!(completion.position(), (2, 2));
+
+        completion.cycle(true);
+        assert_eq!(completion.current(), "new cargo");
+        completion.cycle(false);
+        assert_eq!(completion.current(), "new cat");
+    }
+
+    #[test]
+    fn completion_new_returns_none_without_candidates() {
+        assert!(Completion::new("frobnicate", &[], &[]).is_none());
+    }
+}
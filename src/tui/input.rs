@@ -1,7 +1,9 @@
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+use tui_input::InputRequest;
 use tui_input::backend::crossterm::to_input_request;
 
 use crate::app::{App, Mode};
+use crate::tui::{Wizard, WizardAction};
 
 /// Handle key event and update app state
 pub fn handle_key(app: &mut App, key: KeyEvent) {
@@ -11,15 +13,122 @@ pub fn handle_key(app: &mut App, key: KeyEvent) {
         return;
     }
 
+    // Any key dismisses the previous status message
+    app.clear_message();
+
+    // Any key keeps the TUI open instead of exiting automatically
+    if app.cancel_exit() {
+        app.set_message("Automatic exit cancelled - press Ctrl+C to quit");
+        return;
+    }
+
+    // The key after `|` picks the command to pipe lines to (Esc cancels)
+    if app.take_pipe_pending() {
+        if let KeyCode::Char(c) = key.code {
+            app.pipe_lines(c);
+        }
+        return;
+    }
+
     match app.mode() {
         Mode::Normal => handle_normal_mode(app, key),
         Mode::Search => handle_search_mode(app, key),
+        Mode::Visual => handle_visual_mode(app, key),
+        Mode::Cursor => handle_cursor_mode(app, key),
+        Mode::Command => handle_command_mode(app, key),
+    }
+}
+
+/// Lines scrolled per mouse wheel step
+const WHEEL_SCROLL_LINES: usize = 3;
+
+/// Handle mouse event (only delivered while mouse capture is on)
+pub fn handle_mouse(app: &mut App, mouse: MouseEvent) {
+    let tab = app.tab_manager_mut().current_tab_mut();
+    match mouse.kind {
+        MouseEventKind::ScrollDown => (0..WHEEL_SCROLL_LINES).for_each(|_| tab.scroll_down()),
+        MouseEventKind::ScrollUp => (0..WHEEL_SCROLL_LINES).for_each(|_| tab.scroll_up()),
+        _ => {}
+    }
+}
+
+/// Handle pasted text (bracketed paste)
+///
+/// The text is inserted as-is into the search or command line instead of being
+/// replayed as keystrokes. Only the first line is used, since both inputs are
+/// single-line. Pastes in other modes are ignored.
+pub fn handle_paste(app: &mut App, text: &str) {
+    let line = text.lines().next().unwrap_or_default();
+    let chars = line.chars().filter(|c| !c.is_control());
+    match app.mode() {
+        Mode::Search => {
+            for c in chars {
+                app.search_state_mut()
+                    .handle_input(InputRequest::InsertChar(c));
+            }
+            let query = app.search_state().query().to_string();
+            app.search_in_current_tab(&query);
+        }
+        Mode::Command => {
+            for c in chars {
+                app.handle_command_input(InputRequest::InsertChar(c));
+            }
+        }
+        Mode::Normal | Mode::Visual | Mode::Cursor => {}
+    }
+}
+
+/// Handle key event in the startup wizard
+pub fn handle_wizard_key(wizard: &mut Wizard, key: KeyEvent) -> WizardAction {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc => return WizardAction::Quit,
+        KeyCode::Char('c') if ctrl => return WizardAction::Quit,
+        KeyCode::Enter => return wizard.submit(),
+        KeyCode::Tab => wizard.choose_highlighted(),
+        KeyCode::Down => wizard.move_highlight(true),
+        KeyCode::Char('n') if ctrl => wizard.move_highlight(true),
+        KeyCode::Up => wizard.move_highlight(false),
+        KeyCode::Char('p') if ctrl => wizard.move_highlight(false),
+        KeyCode::Backspace if wizard.input().value().is_empty() => wizard.remove_last(),
+        _ => {
+            if let Some(req) = to_input_request(&Event::Key(key)) {
+                wizard.handle_input(req);
+            }
+        }
+    }
+    WizardAction::None
+}
+
+/// Handle pasted text in the startup wizard (first line only)
+pub fn handle_wizard_paste(wizard: &mut Wizard, text: &str) {
+    let line = text.lines().next().unwrap_or_default();
+    for c in line.chars().filter(|c| !c.is_control()) {
+        wizard.handle_input(InputRequest::InsertChar(c));
     }
 }
 
 /// Handle key event in Normal mode
 fn handle_normal_mode(app: &mut App, key: KeyEvent) {
+    // Keys the current tab's command binds in the config come first
+    if let KeyCode::Char(c) = key.code
+        && !key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        && app.press_tab_key(c)
+    {
+        return;
+    }
+
     match key.code {
+        // Quit once nothing is running anymore
+        KeyCode::Char('q') if app.tab_manager().all_done() => app.quit(),
+
+        // Pick a tab in the statistics panel
+        KeyCode::Char('j') | KeyCode::Down if app.stats_visible() => app.move_stats_selection(true),
+        KeyCode::Char('k') | KeyCode::Up if app.stats_visible() => app.move_stats_selection(false),
+        KeyCode::Enter if app.stats_visible() => app.open_stats_selection(),
+
         // Tab navigation (Ctrl-h/l)
         KeyCode::Char('h') if key.modifiers.contains(KeyModifiers::CONTROL) => {
             app.tab_manager_mut().prev_tab();
@@ -56,6 +165,9 @@ fn handle_normal_mode(app: &mut App, key: KeyEvent) {
         // Toggle auto-scroll
         KeyCode::Char('f') => app.tab_manager_mut().current_tab_mut().toggle_auto_scroll(),
 
+        // Hide the tabs that finished successfully
+        KeyCode::Char('F') => app.toggle_hide_finished(),
+
         // Enter search mode
         KeyCode::Char('/') => {
             app.search_state_mut().clear_input();
@@ -66,28 +178,112 @@ fn handle_normal_mode(app: &mut App, key: KeyEvent) {
                 .set_auto_scroll(false);
         }
 
-        // Navigate search matches (only when search is active)
-        KeyCode::Char('n') => {
-            if app.search_state().is_active()
-                && let Some(line) = app.search_state_mut().next_match()
-            {
-                app.tab_manager_mut().current_tab_mut().scroll_to_line(line);
+        // Navigate search matches, moving to other tabs when this one has none
+        KeyCode::Char('n') => app.jump_to_match(true),
+        KeyCode::Char('N') => app.jump_to_match(false),
+
+        // Highlight the word under the current match without leaving Normal mode
+        KeyCode::Char('*') => app.highlight_word(),
+
+        // Send the current match's line (or the top visible line) to a pipe command
+        KeyCode::Char('|') => app.start_pipe(),
+
+        // Copy the current match's line (or the top visible line)
+        KeyCode::Char('Y') => app.yank_line(),
+
+        // Enter visual line mode at the top visible line
+        KeyCode::Char('V') => {
+            let tab = app.tab_manager_mut().current_tab_mut();
+            if !tab.buffer().is_empty() {
+                let line = tab.scroll_offset();
+                tab.set_auto_scroll(false);
+                tab.start_selection(line);
+                app.set_mode(Mode::Visual);
             }
         }
-        KeyCode::Char('N') => {
-            if app.search_state().is_active()
-                && let Some(line) = app.search_state_mut().prev_match()
-            {
-                app.tab_manager_mut().current_tab_mut().scroll_to_line(line);
+
+        // Enter cursor mode at the start of the top visible line
+        KeyCode::Char('v') => {
+            let tab = app.tab_manager_mut().current_tab_mut();
+            if !tab.buffer().is_empty() {
+                let line = tab.scroll_offset();
+                tab.set_auto_scroll(false);
+                tab.start_cursor(line);
+                app.set_mode(Mode::Cursor);
             }
         }
 
+        // Enter command-line mode
+        KeyCode::Char(':') => app.start_command(""),
+
+        // Show/hide the output of a quiet command
+        KeyCode::Char('o') => app.tab_manager_mut().current_tab_mut().toggle_expanded(),
+
+        // Cycle the times shown in front of lines
+        KeyCode::Char('t') => app.cycle_timestamps(),
+
+        // Cycle how log prefixes are shown (shown, dimmed, hidden)
+        KeyCode::Char('z') => app.cycle_prefix_fold(),
+
+        // Align the columns of table-like output
+        KeyCode::Char('T') => app.toggle_table_view(),
+
+        // Edit the current tab's note
+        KeyCode::Char('a') => app.edit_note(),
+
+        // Pin/unpin the current match line (or the top visible line)
+        KeyCode::Char('p') => {
+            let line = match app.search_state().current_match() {
+                Some(m) if app.search_state().is_active() => m.line,
+                _ => app.tab_manager().current_tab().scroll_offset(),
+            };
+            app.tab_manager_mut().current_tab_mut().toggle_pin(line);
+        }
+        KeyCode::Char('P') => app.tab_manager_mut().current_tab_mut().clear_pins(),
+
+        // Show the full text of the current match line (or the top visible line)
+        KeyCode::Char('e') => {
+            let line = match app.search_state().current_match() {
+                Some(m) if app.search_state().is_active() => m.line,
+                _ => app.tab_manager().current_tab().scroll_offset(),
+            };
+            app.tab_manager_mut().current_tab_mut().expand_line(line);
+        }
+
         // Restart current tab's process
         KeyCode::Char('R') => {
             let tab_index = app.tab_manager().active_index();
             app.request_restart(tab_index);
         }
 
+        // Re-run a finished or failed command
+        KeyCode::Enter if app.tab_manager().current_tab().status().is_done() => {
+            let tab_index = app.tab_manager().active_index();
+            app.request_restart(tab_index);
+        }
+
+        // Duplicate current tab's command into a new tab
+        KeyCode::Char('D') => {
+            let tab_index = app.tab_manager().active_index();
+            app.duplicate_tab(tab_index);
+        }
+
+        // Insert a timestamped marker line
+        KeyCode::Char('m') => app.insert_marker(None),
+
+        // Toggle mouse capture (off passes selection through to the terminal)
+        KeyCode::Char('M') => app.toggle_mouse_capture(),
+
+        // Show/hide the current tab's info panel
+        KeyCode::Char('i') => app.toggle_info(),
+        KeyCode::Esc if app.info_visible() => app.toggle_info(),
+
+        // Show/hide the statistics of all tabs
+        KeyCode::Char('s') => app.toggle_stats(),
+        KeyCode::Esc if app.stats_visible() => app.toggle_stats(),
+        KeyCode::Char('E') => app.toggle_events(),
+        KeyCode::Esc if app.events_visible() => app.toggle_events(),
+
         _ => {}
     }
 }
@@ -116,10 +312,110 @@ fn handle_search_mode(app: &mut App, key: KeyEvent) {
     }
 }
 
+/// Handle key event in Visual mode
+fn handle_visual_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => {
+            app.tab_manager_mut().current_tab_mut().clear_selection();
+            app.set_mode(Mode::Normal);
+        }
+
+        // Extend selection
+        KeyCode::Char('j') | KeyCode::Down => app
+            .tab_manager_mut()
+            .current_tab_mut()
+            .extend_selection_down(),
+        KeyCode::Char('k') | KeyCode::Up => app
+            .tab_manager_mut()
+            .current_tab_mut()
+            .extend_selection_up(),
+
+        // Highlight the first word of the cursor line
+        KeyCode::Char('*') => app.highlight_word(),
+
+        // Copy the selected lines to the clipboard
+        KeyCode::Char('y') => app.yank_selection(),
+
+        // Send the selected lines to a pipe command
+        KeyCode::Char('|') => app.start_pipe(),
+
+        // Open command line with the selected range
+        KeyCode::Char(':') => {
+            if let Some(selection) = app.tab_manager().current_tab().selection() {
+                let range = format!("write {},{} ", selection.start() + 1, selection.end() + 1);
+                app.start_command(&range);
+            }
+        }
+
+        _ => {}
+    }
+}
+
+/// Handle key event in Cursor mode
+fn handle_cursor_mode(app: &mut App, key: KeyEvent) {
+    let tab = app.tab_manager_mut().current_tab_mut();
+    match key.code {
+        KeyCode::Esc => {
+            tab.clear_cursor();
+            app.set_mode(Mode::Normal);
+        }
+
+        // Move the cursor
+        KeyCode::Char('j') | KeyCode::Down => tab.move_cursor_vertical(false),
+        KeyCode::Char('k') | KeyCode::Up => tab.move_cursor_vertical(true),
+        KeyCode::Char('h') | KeyCode::Left => tab.move_cursor_horizontal(true),
+        KeyCode::Char('l') | KeyCode::Right => tab.move_cursor_horizontal(false),
+        KeyCode::Char('0') | KeyCode::Home => tab.move_cursor_to_line_edge(true),
+        KeyCode::Char('$') | KeyCode::End => tab.move_cursor_to_line_edge(false),
+        KeyCode::Char('w') => tab.move_cursor_word(true),
+        KeyCode::Char('b') => tab.move_cursor_word(false),
+
+        // Highlight the word under the cursor
+        KeyCode::Char('*') => app.highlight_word(),
+
+        // Mark text from the cursor and copy it, or copy the cursor line
+        KeyCode::Char('v') => tab.toggle_cursor_mark(),
+        KeyCode::Char('y') => app.yank_marked(),
+        KeyCode::Char('Y') => app.yank_line(),
+
+        // Open the file path under the cursor
+        KeyCode::Char('o') => app.open_path_under_cursor(),
+
+        // Switch to a line selection starting at the cursor line
+        KeyCode::Char('V') => {
+            if let Some(cursor) = tab.cursor() {
+                tab.clear_cursor();
+                tab.start_selection(cursor.line);
+                app.set_mode(Mode::Visual);
+            }
+        }
+
+        _ => {}
+    }
+}
+
+/// Handle key event in Command mode
+fn handle_command_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => app.cancel_command(),
+        KeyCode::Enter => app.execute_command(),
+        KeyCode::Tab => app.complete_command(true),
+        KeyCode::BackTab => app.complete_command(false),
+        KeyCode::Up => app.recall_command(true),
+        KeyCode::Down => app.recall_command(false),
+        _ => {
+            if let Some(req) = to_input_request(&Event::Key(key)) {
+                app.handle_command_input(req);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::buffer::{OutputKind, OutputLine};
+    use crate::tui::CommandStatus;
 
     fn create_app_with_output() -> App {
         let mut app = App::new(vec!["cmd1".into(), "cmd2".into()], 100);
@@ -144,6 +440,15 @@ mod tests {
         KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
     }
 
+    fn mouse(kind: MouseEventKind) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column: 0,
+            row: 0,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
     // Normal mode tests
 
     #[test]
@@ -155,6 +460,43 @@ mod tests {
         assert!(app.should_quit());
     }
 
+    #[test]
+    fn input_any_key_cancels_pending_automatic_exit() {
+        let mut app = create_app_with_output();
+        app.set_exit_when_done(Some(std::time::Duration::from_secs(5)));
+        for tab_index in 0..2 {
+            app.handle_app_event(crate::event::AppEvent::Exited {
+                tab_index,
+                exit_code: 0,
+            });
+        }
+        assert!(app.has_countdown());
+
+        handle_key(&mut app, key(KeyCode::Char('G')));
+
+        assert!(!app.has_countdown());
+        assert_eq!(app.tab_manager().current_tab().scroll_offset(), 0);
+        assert!(
+            app.message()
+                .unwrap()
+                .starts_with("Automatic exit cancelled")
+        );
+    }
+
+    #[test]
+    fn input_q_quits_only_when_all_commands_finished() {
+        let mut app = create_app_with_output();
+
+        handle_key(&mut app, key(KeyCode::Char('q')));
+        assert!(!app.should_quit());
+
+        for tab in app.tab_manager_mut().iter_mut() {
+            tab.set_status(CommandStatus::Finished { exit_code: 0 });
+        }
+        handle_key(&mut app, key(KeyCode::Char('q')));
+        assert!(app.should_quit());
+    }
+
     #[test]
     fn input_ctrl_c_quits_from_search_mode() {
         let mut app = App::new(vec!["cmd".into()], 100);
@@ -225,6 +567,22 @@ mod tests {
         assert_eq!(app.tab_manager().current_tab().scroll_offset(), 1);
     }
 
+    #[test]
+    fn input_normal_mode_tab_keys_take_precedence() {
+        let spec = crate::command::CommandSpec {
+            keys: [('j', crate::command::KeyAction::Send("next\n".into()))].into(),
+            .."repl".into()
+        };
+        let mut app = App::new(vec![spec, "cmd".into()], 100);
+
+        handle_key(&mut app, key(KeyCode::Char('j')));
+        assert_eq!(app.message(), Some("Error: repl: not running"));
+
+        // With Ctrl the key keeps its usual meaning
+        handle_key(&mut app, key_with_ctrl('j'));
+        assert_eq!(app.message(), None);
+    }
+
     #[test]
     fn input_normal_mode_k_scrolls_up() {
         let mut app = create_app_with_output();
@@ -280,6 +638,82 @@ mod tests {
         assert!(app.tab_manager().current_tab().auto_scroll());
     }
 
+    #[test]
+    fn input_normal_mode_shift_m_toggles_mouse_capture() {
+        let mut app = App::new(vec!["cmd".into()], 100);
+        app.set_mouse_capture(true);
+
+        handle_key(&mut app, key(KeyCode::Char('M')));
+        assert!(!app.mouse_capture());
+        assert!(app.message().unwrap().contains("select text"));
+
+        handle_key(&mut app, key(KeyCode::Char('M')));
+        assert!(app.mouse_capture());
+    }
+
+    #[rstest::rstest]
+    #[case(KeyCode::Char('i'))]
+    #[case(KeyCode::Esc)]
+    fn input_info_panel_opens_with_i_and_closes(#[case] close: KeyCode) {
+        let mut app = App::new(vec!["cmd".into()], 100);
+
+        handle_key(&mut app, key(KeyCode::Char('i')));
+        assert!(app.info_visible());
+
+        handle_key(&mut app, key(close));
+        assert!(!app.info_visible());
+    }
+
+    #[test]
+    fn input_events_panel_replaces_stats_panel() {
+        let mut app = App::new(vec!["cmd".into()], 100);
+
+        handle_key(&mut app, key(KeyCode::Char('s')));
+        handle_key(&mut app, key(KeyCode::Char('E')));
+        assert!(app.events_visible());
+        assert!(!app.stats_visible());
+
+        handle_key(&mut app, key(KeyCode::Esc));
+        assert!(!app.events_visible());
+    }
+
+    #[test]
+    fn input_stats_panel_replaces_info_panel() {
+        let mut app = App::new(vec!["cmd".into()], 100);
+
+        handle_key(&mut app, key(KeyCode::Char('i')));
+        handle_key(&mut app, key(KeyCode::Char('s')));
+        assert!(app.stats_visible());
+        assert!(!app.info_visible());
+
+        handle_key(&mut app, key(KeyCode::Esc));
+        assert!(!app.stats_visible());
+    }
+
+    #[test]
+    fn input_stats_panel_enter_jumps_to_selected_tab() {
+        let mut app = App::new(vec!["a".into(), "b".into(), "c".into()], 100);
+
+        handle_key(&mut app, key(KeyCode::Char('s')));
+        handle_key(&mut app, key(KeyCode::Char('k')));
+        assert_eq!(app.stats_selected(), 2);
+        handle_key(&mut app, key(KeyCode::Enter));
+
+        assert_eq!(app.tab_manager().active_index(), 2);
+        assert!(!app.stats_visible());
+    }
+
+    #[test]
+    fn input_mouse_wheel_scrolls_current_tab() {
+        let mut app = create_app_with_output();
+
+        handle_mouse(&mut app, mouse(MouseEventKind::ScrollDown));
+        assert_eq!(app.tab_manager().current_tab().scroll_offset(), 3);
+
+        handle_mouse(&mut app, mouse(MouseEventKind::ScrollUp));
+        assert_eq!(app.tab_manager().current_tab().scroll_offset(), 0);
+    }
+
     #[test]
     fn input_normal_mode_slash_enters_search_mode() {
         let mut app = App::new(vec!["cmd".into()], 100);
@@ -373,6 +807,34 @@ mod tests {
         assert_eq!(app.search_state().query(), "N");
     }
 
+    #[test]
+    fn input_paste_in_search_mode_inserts_first_line() {
+        let mut app = create_app_with_output();
+        app.set_mode(Mode::Search);
+
+        handle_paste(&mut app, "line1\tx\nline2");
+        assert_eq!(app.search_state().query(), "line1x");
+        assert_eq!(app.mode(), Mode::Search);
+    }
+
+    #[test]
+    fn input_paste_in_command_mode_inserts_text() {
+        let mut app = App::new(vec!["cmd".into()], 100);
+        app.start_command("");
+
+        handle_paste(&mut app, "write out.txt");
+        assert_eq!(app.command_input(), "write out.txt");
+    }
+
+    #[test]
+    fn input_paste_in_normal_mode_does_not_trigger_bindings() {
+        let mut app = App::new(vec!["cmd".into()], 100);
+
+        handle_paste(&mut app, "/q");
+        assert_eq!(app.mode(), Mode::Normal);
+        assert!(!app.should_quit());
+    }
+
     #[test]
     fn input_search_mode_enter_returns_to_normal() {
         let mut app = App::new(vec!["cmd".into()], 100);
@@ -409,6 +871,30 @@ mod tests {
         assert!(app.search_state().current_match_display().is_some());
     }
 
+    #[test]
+    fn input_normal_mode_star_highlights_word_of_current_match() {
+        let mut app = create_app_with_output();
+        app.search_in_current_tab("ne1");
+
+        handle_key(&mut app, key(KeyCode::Char('*')));
+
+        assert_eq!(app.mode(), Mode::Normal);
+        assert_eq!(app.search_state().query(), "line1");
+        // line1 and line10..line19
+        assert_eq!(app.search_state().match_count(), 11);
+    }
+
+    #[test]
+    fn input_normal_mode_star_uses_top_visible_line_without_search() {
+        let mut app = create_app_with_output();
+        app.tab_manager_mut().current_tab_mut().scroll_to_line(5);
+
+        handle_key(&mut app, key(KeyCode::Char('*')));
+
+        assert_eq!(app.search_state().query(), "line5");
+        assert_eq!(app.tab_manager().current_tab().scroll_offset(), 5);
+    }
+
     #[test]
     fn input_normal_mode_upper_r_requests_restart() {
         let mut app = App::new(vec!["cmd1".into(), "cmd2".into()], 100);
@@ -425,6 +911,145 @@ mod tests {
         assert_eq!(app.take_pending_restart(), Some(1));
     }
 
+    #[test]
+    fn input_normal_mode_p_pins_top_visible_line() {
+        let mut app = create_app_with_output();
+        app.tab_manager_mut().current_tab_mut().scroll_to_line(3);
+
+        handle_key(&mut app, key(KeyCode::Char('p')));
+
+        let pinned = app.tab_manager().current_tab().pinned_lines();
+        assert_eq!(pinned.len(), 1);
+        assert_eq!(pinned[0].plain(), "line3");
+    }
+
+    #[test]
+    fn input_normal_mode_p_pins_current_match_line_when_search_active() {
+        let mut app = create_app_with_output();
+        app.search_in_current_tab("line15");
+
+        handle_key(&mut app, key(KeyCode::Char('p')));
+
+        let pinned = app.tab_manager().current_tab().pinned_lines();
+        assert_eq!(pinned.len(), 1);
+        assert_eq!(pinned[0].plain(), "line15");
+    }
+
+    #[test]
+    fn input_normal_mode_e_expands_top_visible_line() {
+        let mut app = App::new(vec!["cmd".into()], 100);
+        let tab = app.tab_manager_mut().current_tab_mut();
+        tab.push_output(OutputLine::with_limit(
+            OutputKind::Stdout,
+            "a".repeat(20),
+            5,
+        ));
+        tab.scroll_to_top();
+
+        handle_key(&mut app, key(KeyCode::Char('e')));
+
+        let tab = app.tab_manager().current_tab();
+        assert_eq!(tab.buffer().get(0).unwrap().shown_len(), None);
+        assert_eq!(tab.expanded_lines(), 1);
+    }
+
+    #[test]
+    fn input_wizard_adds_typed_and_highlighted_commands_then_starts() {
+        let mut wizard = Wizard::new(Vec::new(), &["cargo test".to_string()]);
+
+        for c in "ls".chars() {
+            handle_wizard_key(&mut wizard, key(KeyCode::Char(c)));
+        }
+        assert_eq!(
+            handle_wizard_key(&mut wizard, key(KeyCode::Enter)),
+            WizardAction::None
+        );
+        handle_wizard_key(&mut wizard, key(KeyCode::Tab));
+
+        let commands: Vec<&str> = wizard.chosen().iter().map(|s| s.command.as_str()).collect();
+        assert_eq!(commands, vec!["ls", "cargo test"]);
+        assert_eq!(
+            handle_wizard_key(&mut wizard, key(KeyCode::Enter)),
+            WizardAction::Start
+        );
+    }
+
+    #[test]
+    fn input_wizard_backspace_on_empty_input_removes_last_command() {
+        let mut wizard = Wizard::new(Vec::new(), &["cargo test".to_string()]);
+        handle_wizard_key(&mut wizard, key(KeyCode::Tab));
+
+        handle_wizard_key(&mut wizard, key(KeyCode::Backspace));
+
+        assert!(wizard.chosen().is_empty());
+    }
+
+    #[rstest::rstest]
+    #[case(key(KeyCode::Esc))]
+    #[case(key_with_ctrl('c'))]
+    fn input_wizard_quits(#[case] event: KeyEvent) {
+        let mut wizard = Wizard::default();
+
+        assert_eq!(handle_wizard_key(&mut wizard, event), WizardAction::Quit);
+    }
+
+    #[test]
+    fn input_normal_mode_upper_p_clears_pins() {
+        let mut app = create_app_with_output();
+        handle_key(&mut app, key(KeyCode::Char('p')));
+        assert_eq!(app.tab_manager().current_tab().pinned_lines().len(), 1);
+
+        handle_key(&mut app, key(KeyCode::Char('P')));
+        assert!(app.tab_manager().current_tab().pinned_lines().is_empty());
+    }
+
+    #[test]
+    fn input_normal_mode_enter_reruns_finished_command() {
+        use crate::tui::CommandStatus;
+
+        let mut app = App::new(vec!["cmd".into()], 100);
+        app.tab_manager_mut()
+            .current_tab_mut()
+            .set_status(CommandStatus::Finished { exit_code: 1 });
+
+        handle_key(&mut app, key(KeyCode::Enter));
+
+        assert_eq!(app.take_pending_restart(), Some(0));
+    }
+
+    #[test]
+    fn input_normal_mode_enter_ignored_while_running() {
+        let mut app = App::new(vec!["cmd".into()], 100);
+
+        handle_key(&mut app, key(KeyCode::Enter));
+
+        assert!(app.take_pending_restart().is_none());
+    }
+
+    #[test]
+    fn input_normal_mode_m_inserts_marker() {
+        let mut app = App::new(vec!["cmd".into()], 100);
+
+        handle_key(&mut app, key(KeyCode::Char('m')));
+
+        let buffer = app.tab_manager().current_tab().buffer();
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(buffer.get(0).unwrap().kind, OutputKind::Marker);
+    }
+
+    #[test]
+    fn input_normal_mode_upper_d_duplicates_current_tab() {
+        let mut app = App::new(vec!["cmd1".into(), "cmd2".into()], 100);
+        app.tab_manager_mut().next_tab();
+
+        handle_key(&mut app, key(KeyCode::Char('D')));
+
+        assert_eq!(app.tab_manager().len(), 3);
+        assert_eq!(app.tab_manager().active_index(), 2);
+        assert_eq!(app.tab_manager().current_tab().command(), "cmd2");
+        assert_eq!(app.take_pending_restart(), Some(2));
+    }
+
     #[test]
     fn input_normal_mode_n_does_nothing_when_no_search() {
         let mut app = create_app_with_output();
@@ -439,6 +1064,166 @@ mod tests {
         );
     }
 
+    // Visual and Command mode tests
+
+    #[test]
+    fn input_normal_mode_upper_v_starts_selection_at_top_line() {
+        let mut app = create_app_with_output();
+        app.tab_manager_mut().current_tab_mut().scroll_to_line(4);
+
+        handle_key(&mut app, key(KeyCode::Char('V')));
+
+        assert_eq!(app.mode(), Mode::Visual);
+        let selection = app.tab_manager().current_tab().selection().unwrap();
+        assert_eq!((selection.start(), selection.end()), (4, 4));
+    }
+
+    #[test]
+    fn input_visual_mode_colon_prefills_write_range() {
+        let mut app = create_app_with_output();
+        handle_key(&mut app, key(KeyCode::Char('V')));
+        handle_key(&mut app, key(KeyCode::Char('j')));
+        handle_key(&mut app, key(KeyCode::Char('j')));
+
+        handle_key(&mut app, key(KeyCode::Char(':')));
+
+        assert_eq!(app.mode(), Mode::Command);
+        assert_eq!(app.command_input(), "write 1,3 ");
+    }
+
+    #[test]
+    fn input_visual_mode_star_highlights_word_of_cursor_line() {
+        let mut app = create_app_with_output();
+        handle_key(&mut app, key(KeyCode::Char('V')));
+        handle_key(&mut app, key(KeyCode::Char('j')));
+        handle_key(&mut app, key(KeyCode::Char('j')));
+
+        handle_key(&mut app, key(KeyCode::Char('*')));
+
+        assert_eq!(app.mode(), Mode::Visual);
+        assert_eq!(app.search_state().query(), "line2");
+    }
+
+    #[test]
+    fn input_normal_mode_v_enters_cursor_mode_at_top_line() {
+        let mut app = create_app_with_output();
+        app.tab_manager_mut().current_tab_mut().scroll_to_line(3);
+
+        handle_key(&mut app, key(KeyCode::Char('v')));
+
+        assert_eq!(app.mode(), Mode::Cursor);
+        let cursor = app.tab_manager().current_tab().cursor().unwrap();
+        assert_eq!((cursor.line, cursor.column), (3, 0));
+    }
+
+    #[test]
+    fn input_cursor_mode_moves_and_highlights_word_under_cursor() {
+        let mut app = App::new(vec!["cmd".into()], 100);
+        app.tab_manager_mut()
+            .current_tab_mut()
+            .push_output(OutputLine::new(
+                OutputKind::Stderr,
+                "error: cannot open config.toml".into(),
+            ));
+        handle_key(&mut app, key(KeyCode::Char('v')));
+
+        for c in ['w', 'w', 'l'] {
+            handle_key(&mut app, key(KeyCode::Char(c)));
+        }
+        handle_key(&mut app, key(KeyCode::Char('*')));
+
+        assert_eq!(app.mode(), Mode::Cursor);
+        assert_eq!(app.search_state().query(), "open");
+    }
+
+    #[test]
+    fn input_cursor_mode_switches_to_line_selection_and_exits() {
+        let mut app = create_app_with_output();
+        handle_key(&mut app, key(KeyCode::Char('v')));
+        handle_key(&mut app, key(KeyCode::Char('j')));
+
+        handle_key(&mut app, key(KeyCode::Char('V')));
+        assert_eq!(app.mode(), Mode::Visual);
+        let selection = app.tab_manager().current_tab().selection().unwrap();
+        assert_eq!((selection.start(), selection.end()), (1, 1));
+        assert!(app.tab_manager().current_tab().cursor().is_none());
+
+        handle_key(&mut app, key(KeyCode::Esc));
+        handle_key(&mut app, key(KeyCode::Char('v')));
+        handle_key(&mut app, key(KeyCode::Esc));
+        assert_eq!(app.mode(), Mode::Normal);
+        assert!(app.tab_manager().current_tab().cursor().is_none());
+    }
+
+    #[test]
+    fn input_visual_mode_esc_clears_selection() {
+        let mut app = create_app_with_output();
+        handle_key(&mut app, key(KeyCode::Char('V')));
+
+        handle_key(&mut app, key(KeyCode::Esc));
+
+        assert_eq!(app.mode(), Mode::Normal);
+        assert!(app.tab_manager().current_tab().selection().is_none());
+    }
+
+    #[test]
+    fn input_pipe_waits_for_the_key_of_a_pipe_command() {
+        let mut app = create_app_with_output();
+        handle_key(&mut app, key(KeyCode::Char('|')));
+        assert_eq!(
+            app.message(),
+            Some("No pipes for this command - add `pipes` to its config")
+        );
+        assert!(!app.take_pipe_pending());
+
+        let spec = crate::command::CommandSpec {
+            pipes: [('j', "jq .".into()), ('n', "notify-send".into())].into(),
+            .."cmd".into()
+        };
+        let mut app = App::new(vec![spec], 100);
+        handle_key(&mut app, key(KeyCode::Char('|')));
+        assert_eq!(app.message(), Some("Pipe to: j jq . · n notify-send"));
+
+        // Esc cancels, and the next key is handled as usual again
+        handle_key(&mut app, key(KeyCode::Esc));
+        assert_eq!(app.message(), None);
+        handle_key(&mut app, key(KeyCode::Char(':')));
+        assert_eq!(app.mode(), Mode::Command);
+    }
+
+    #[test]
+    fn input_command_mode_types_and_cancels() {
+        let mut app = create_app_with_output();
+        handle_key(&mut app, key(KeyCode::Char(':')));
+        assert_eq!(app.mode(), Mode::Command);
+
+        for c in "write".chars() {
+            handle_key(&mut app, key(KeyCode::Char(c)));
+        }
+        assert_eq!(app.command_input(), "write");
+
+        handle_key(&mut app, key(KeyCode::Esc));
+        assert_eq!(app.mode(), Mode::Normal);
+        assert_eq!(app.command_input(), "");
+    }
+
+    #[test]
+    fn input_command_mode_enter_executes_and_reports() {
+        let mut app = create_app_with_output();
+        handle_key(&mut app, key(KeyCode::Char(':')));
+        for c in "nope".chars() {
+            handle_key(&mut app, key(KeyCode::Char(c)));
+        }
+
+        handle_key(&mut app, key(KeyCode::Enter));
+        assert_eq!(app.mode(), Mode::Normal);
+        assert!(app.message().is_some());
+
+        // Next key press dismisses the message
+        handle_key(&mut app, key(KeyCode::Char('j')));
+        assert!(app.message().is_none());
+    }
+
     // Emacs-like keybindings tests (via tui-input)
 
     #[test]
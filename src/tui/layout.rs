@@ -0,0 +1,9 @@
+/// Where the list of commands is shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum TabLayout {
+    /// A tab bar above the output
+    #[default]
+    Tabs,
+    /// A vertical list left of the output, with status icons and unread counts
+    Sidebar,
+}
@@ -0,0 +1,108 @@
+use std::cell::{Ref, RefCell};
+
+use ratatui::text::{Line, Span};
+
+use crate::command::PrefixFold;
+use crate::tui::{LineSelection, OutputCursor, TimestampMode};
+
+/// Everything the rendered output rows of a tab depend on
+///
+/// Horizontal scroll is applied by the paragraph widget, so it is not part
+/// of the key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineCacheKey {
+    pub scroll_offset: usize,
+    pub height: usize,
+    /// Generation of the oldest retained line in the buffer
+    pub first_generation: u64,
+    /// Generation of the next line pushed to the buffer
+    pub buffer_generation: u64,
+    pub search_generation: u64,
+    pub selection: Option<LineSelection>,
+    /// Ends of the text marked in Cursor mode
+    pub marked: Option<(OutputCursor, OutputCursor)>,
+    /// Number of cut lines the tab has expanded
+    pub expanded_lines: u64,
+    pub timestamps: TimestampMode,
+    pub prefix_fold: PrefixFold,
+    pub table_view: bool,
+}
+
+/// Cache of the rendered output rows of a tab
+///
+/// Rebuilding rows means recloning spans and recomputing search highlights,
+/// so steady-state frames reuse the rows as long as the key is unchanged.
+#[derive(Default)]
+pub struct LineCache {
+    entry: RefCell<Option<(LineCacheKey, Vec<Line<'static>>)>>,
+}
+
+impl LineCache {
+    /// Get the cached rows for `key`, building them if the key changed
+    pub fn get_or_build(
+        &self,
+        key: LineCacheKey,
+        build: impl FnOnce() -> Vec<Line<'static>>,
+    ) -> Ref<'_, [Line<'static>]> {
+        let stale = self
+            .entry
+            .borrow()
+            .as_ref()
+            .is_none_or(|(cached, _)| *cached != key);
+        if stale {
+            *self.entry.borrow_mut() = Some((key, build()));
+        }
+        Ref::map(self.entry.borrow(), |entry| {
+            entry
+                .as_ref()
+                .map_or(&[][..], |(_, lines)| lines.as_slice())
+        })
+    }
+}
+
+/// Borrow a cached line without copying its text
+pub fn borrow_line<'a>(line: &'a Line<'static>) -> Line<'a> {
+    let spans: Vec<Span<'a>> = line
+        .spans
+        .iter()
+        .map(|span| Span::styled(span.content.as_ref(), span.style))
+        .collect();
+    Line::from(spans).style(line.style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(scroll_offset: usize) -> LineCacheKey {
+        LineCacheKey {
+            scroll_offset,
+            height: 10,
+            first_generation: 0,
+            buffer_generation: 5,
+            search_generation: 0,
+            selection: None,
+            marked: None,
+            expanded_lines: 0,
+            timestamps: TimestampMode::Off,
+            prefix_fold: PrefixFold::Off,
+            table_view: false,
+        }
+    }
+
+    #[test]
+    fn line_cache_rebuilds_only_when_key_changes() {
+        let cache = LineCache::default();
+        let mut builds = 0;
+
+        for scroll_offset in [0, 0, 1, 1] {
+            let lines = cache.get_or_build(key(scroll_offset), || {
+                builds += 1;
+                vec![Line::from(format!("offset {}", scroll_offset))]
+            });
+            assert_eq!(lines[0], Line::from(format!("offset {}", scroll_offset)));
+        }
+
+        assert_eq!(builds, 2);
+    }
+}
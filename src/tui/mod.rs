@@ -1,9 +1,28 @@
+mod command_line;
+mod completion;
 mod input;
+mod layout;
+mod line_cache;
+mod palette;
+mod prefix;
 mod renderer;
+mod status_bar;
 mod tab;
 mod tab_manager;
+mod table;
+mod timestamps;
+mod wizard;
 
-pub use input::handle_key;
+pub use command_line::{CommandLine, CommandLineError, LineRange};
+pub use completion::Completion;
+pub(crate) use completion::path_executables;
+pub use input::{handle_key, handle_mouse, handle_paste, handle_wizard_key, handle_wizard_paste};
+pub use layout::TabLayout;
+pub(crate) use prefix::prefix_len;
 pub use renderer::Renderer;
-pub use tab::{CommandStatus, Tab};
-pub use tab_manager::TabManager;
+pub use status_bar::{StatusBarConfig, StatusField, StatusTemplate, StatusTemplateError};
+pub use tab::{CommandStatus, LineCounts, LineSelection, OutputCursor, RunRecord, Tab};
+pub use tab_manager::{StatusSummary, TabManager, TabOrder};
+pub use timestamps::TimestampMode;
+pub(crate) use timestamps::{DELTA_WIDTH, RELATIVE_WIDTH, format_delta, format_relative};
+pub use wizard::{Candidate, Origin, Wizard, WizardAction};
@@ -0,0 +1,49 @@
+use ratatui::style::Color;
+
+/// Accent colors assigned to commands
+///
+/// Red is left out so stderr prefixes and failures stay distinct.
+const ACCENT_COLORS: &[Color] = &[
+    Color::Cyan,
+    Color::Green,
+    Color::Magenta,
+    Color::Blue,
+    Color::Yellow,
+    Color::LightCyan,
+    Color::LightGreen,
+    Color::LightMagenta,
+    Color::LightBlue,
+    Color::LightYellow,
+];
+
+/// Stable accent color for a command, derived from its tab title
+///
+/// Uses FNV-1a rather than the std hasher, whose output may change between
+/// Rust releases, so a service keeps its color across runs and machines.
+pub fn accent_color(title: &str) -> Color {
+    let hash = title.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    // The low bits of FNV-1a vary little between similar short strings
+    ACCENT_COLORS[((hash >> 32) % ACCENT_COLORS.len() as u64) as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accent_color_is_stable_for_a_title() {
+        assert_eq!(accent_color("npm:dev"), accent_color("npm:dev"));
+        assert_eq!(accent_color("web"), Color::LightYellow);
+    }
+
+    #[test]
+    fn accent_color_differs_between_typical_services() {
+        let colors: std::collections::HashSet<_> = ["web", "api", "db", "npm:dev"]
+            .iter()
+            .map(|title| accent_color(title))
+            .collect();
+        assert_eq!(colors.len(), 4);
+    }
+}
@@ -0,0 +1,63 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// One token of a log prefix followed by whitespace: a date and time, a
+/// time, a `[tag]`, a compose-style `service |` or a `module::path:`
+static PREFIX_TOKEN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"^(",
+        r"\d{4}-\d{2}-\d{2}(?:[T ]\d{2}:\d{2}(?::\d{2}(?:[.,]\d+)?)?)?(?:Z|[+-]\d{2}:?\d{2})?",
+        r"|\d{2}:\d{2}:\d{2}(?:[.,]\d+)?",
+        r"|\[[^\]]{1,64}\]",
+        r"|[\w.-]+ *\|",
+        r"|[A-Za-z_]\w*(?:::\w+)+:",
+        r")\s+",
+    ))
+    .expect("valid regex")
+});
+
+/// Log levels, which end a prefix instead of being folded into it
+const LEVELS: &[&str] = &[
+    "trace", "debug", "info", "warn", "warning", "error", "fatal", "critical",
+];
+
+/// Length in bytes of a line's log prefix (timestamps, tags, service and
+/// module names), or 0 if it has none
+///
+/// Levels like `[ERROR]` stay visible, and so does a line that would be
+/// all prefix.
+pub(crate) fn prefix_len(text: &str) -> usize {
+    let mut len = 0;
+    while let Some(captures) = PREFIX_TOKEN.captures(&text[len..]) {
+        let token = captures[1].trim_matches(|c| matches!(c, '[' | ']' | '|' | ' '));
+        if LEVELS.iter().any(|level| token.eq_ignore_ascii_case(level)) {
+            break;
+        }
+        len += captures[0].len();
+    }
+    if len == text.len() { 0 } else { len }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("2026-03-01T14:00:03.210Z GET /health 200", "GET /health 200")]
+    #[case("2026-03-01 14:00:03,210 [main] server started", "server started")]
+    #[case("14:00:03 [web] [INFO] listening", "[INFO] listening")]
+    #[case("web-1  | Listening on :3000", "Listening on :3000")]
+    #[case(
+        "2026-03-01T14:00:03Z  INFO app::server: ready",
+        "INFO app::server: ready"
+    )]
+    #[case("app::db::pool: connection opened", "connection opened")]
+    #[case("error[E0308]: mismatched types", "error[E0308]: mismatched types")]
+    #[case("plain output", "plain output")]
+    #[case("[web] ", "[web] ")]
+    fn prefix_len_finds_log_prefixes(#[case] text: &str, #[case] rest: &str) {
+        assert_eq!(&text[prefix_len(text)..], rest);
+    }
+}
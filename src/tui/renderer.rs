@@ -1,14 +1,75 @@
+use std::time::Instant;
+
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     symbols::border,
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table},
 };
 
 use crate::app::{App, Mode};
 use crate::buffer::OutputKind;
+use crate::command::{PrefixFold, SpawnFailure, auto_name, format_count, shell_quote, signal_name};
+use crate::search::SearchState;
+use crate::tui::line_cache::{LineCacheKey, borrow_line};
+use crate::tui::palette::accent_color;
+use crate::tui::status_bar::format_elapsed;
+use crate::tui::table::Columns;
+use crate::tui::{
+    CommandLine, CommandStatus, DELTA_WIDTH, LineCounts, Origin, RELATIVE_WIDTH, StatusField,
+    StatusTemplate, Tab, TabLayout, TimestampMode, Wizard, format_delta, format_relative,
+    prefix_len,
+};
+
+/// Prefix of the status bar in Search mode
+const SEARCH_PROMPT: &str = " SEARCH: ";
+
+/// Minimum width of the query input in Search mode
+const MIN_SEARCH_INPUT_WIDTH: u16 = 10;
+
+/// Prompt in front of the command typed in the startup wizard
+const WIZARD_PROMPT: &str = "> ";
+
+/// Key hints below the startup wizard
+const WIZARD_HINTS: &str =
+    " Enter:add typed/start Tab:add suggestion ↑/↓:select Backspace:remove Esc:quit";
+
+/// Smallest terminal width the full layout is rendered at
+const MIN_WIDTH: u16 = 20;
+
+/// Smallest terminal height the full layout is rendered at (tab bar, one
+/// output row with its bottom border, status bar)
+const MIN_HEIGHT: u16 = 5;
+
+/// The sidebar takes at most this fraction of the terminal width (1/3)
+const SIDEBAR_MAX_SHARE: u16 = 3;
+
+/// Columns of a sidebar row besides the name: borders, status icon, badge
+/// and the spaces between them
+const SIDEBAR_CHROME_WIDTH: u16 = 10;
+
+/// Unread counts above this are shown as `999+`
+const MAX_UNREAD_BADGE: u64 = 999;
+
+/// Finished runs listed in the info panel, newest last
+const INFO_RUN_HISTORY: usize = 5;
+
+/// Search matches highlighted at most per frame, so a screen full of them
+/// stays cheap to draw
+const MAX_HIGHLIGHTS_PER_FRAME: usize = 1_000;
+
+/// What the top border of the output area shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputHeader {
+    /// Junctions with the tab bar's dividers above it
+    TabBar,
+    /// A plain border, next to the sidebar
+    Plain,
+    /// The tab's name, in place of a tab bar (compact mode)
+    Title,
+}
 
 /// A highlight range in original text positions
 struct HighlightRange {
@@ -17,6 +78,29 @@ struct HighlightRange {
     is_current: bool,
 }
 
+/// Reverse the colors of a byte range of the spans (text marked in Cursor mode)
+fn reverse_range(spans: Vec<Span<'static>>, start: usize, end: usize) -> Vec<Span<'static>> {
+    let mut result = Vec::new();
+    let mut pos = 0;
+    for span in spans {
+        let text = span.content;
+        let from = text.floor_char_boundary(start.clamp(pos, pos + text.len()) - pos);
+        let to = text.ceil_char_boundary(end.clamp(pos, pos + text.len()) - pos);
+        let parts = [
+            (&text[..from], span.style),
+            (&text[from..to], span.style.add_modifier(Modifier::REVERSED)),
+            (&text[to..], span.style),
+        ];
+        for (part, style) in parts {
+            if !part.is_empty() {
+                result.push(Span::styled(part.to_string(), style));
+            }
+        }
+        pos += text.len();
+    }
+    result
+}
+
 /// Overlay search highlights on ANSI-parsed spans
 ///
 /// Takes spans from ansi-to-tui and applies highlight styles to matching ranges.
@@ -47,21 +131,27 @@ fn overlay_highlights(
             // No highlights - keep span as-is
             result.push(Span::styled(span_text, base_style));
         } else {
-            // Split span at highlight boundaries
-            let mut current_pos = span_start;
+            // Split span at highlight boundaries, rounded out to whole
+            // characters so a match edge inside a multi-byte one can't split it
+            let mut current = 0;
 
             for highlight in overlapping {
-                let hl_start = highlight.start.max(span_start);
-                let hl_end = highlight.end.min(span_end);
+                let hl_start = span_text
+                    .floor_char_boundary(highlight.start.saturating_sub(span_start))
+                    .max(current);
+                let hl_end = span_text.ceil_char_boundary(highlight.end.min(span_end) - span_start);
+                if hl_start >= hl_end {
+                    continue;
+                }
 
                 // Part before highlight
-                if current_pos < hl_start {
-                    let text = &span_text[current_pos - span_start..hl_start - span_start];
+                if current < hl_start {
+                    let text = &span_text[current..hl_start];
                     result.push(Span::styled(text.to_string(), base_style));
                 }
 
                 // Highlighted part - apply highlight style while preserving fg color
-                let text = &span_text[hl_start - span_start..hl_end - span_start];
+                let text = &span_text[hl_start..hl_end];
                 let highlight_style = if highlight.is_current {
                     Style::default().fg(Color::Black).bg(Color::Cyan)
                 } else {
@@ -69,12 +159,12 @@ fn overlay_highlights(
                 };
                 result.push(Span::styled(text.to_string(), highlight_style));
 
-                current_pos = hl_end;
+                current = hl_end;
             }
 
             // Part after all highlights
-            if current_pos < span_end {
-                let text = &span_text[current_pos - span_start..];
+            if current < span_text.len() {
+                let text = &span_text[current..];
                 result.push(Span::styled(text.to_string(), base_style));
             }
         }
@@ -85,24 +175,573 @@ fn overlay_highlights(
     result
 }
 
+/// Dim or drop the first `len` bytes of a line's spans (its log prefix)
+///
+/// Applied after search highlights, whose positions are in the full text.
+fn fold_prefix(spans: Vec<Span<'static>>, len: usize, fold: PrefixFold) -> Vec<Span<'static>> {
+    if len == 0 || fold == PrefixFold::Off {
+        return spans;
+    }
+    let mut result = Vec::with_capacity(spans.len() + 1);
+    let mut remaining = len;
+    for span in spans {
+        if remaining == 0 {
+            result.push(span);
+            continue;
+        }
+        let cut = span
+            .content
+            .floor_char_boundary(remaining.min(span.content.len()));
+        remaining -= cut;
+        let (folded, rest) = span.content.split_at(cut);
+        if fold == PrefixFold::Dim && !folded.is_empty() {
+            result.push(Span::styled(
+                folded.to_string(),
+                span.style.add_modifier(Modifier::DIM),
+            ));
+        }
+        if !rest.is_empty() {
+            result.push(Span::styled(rest.to_string(), span.style));
+        }
+    }
+    result
+}
+
 /// TUI rendering handler
 pub struct Renderer;
 
 impl Renderer {
     /// Render application state
     pub fn render(frame: &mut Frame, app: &App) {
+        let area = frame.area();
+        if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+            Self::render_too_small(frame, area);
+            return;
+        }
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(1),                                  // Commands and output
+                Constraint::Length(app.status_bar_visible() as u16), // Status bar
+            ])
+            .split(area);
+
+        let output_area = match app.layout() {
+            _ if app.compact_layout() => {
+                Self::render_output_area(frame, app, chunks[0], OutputHeader::Title);
+                chunks[0]
+            }
+            TabLayout::Tabs => {
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([
+                        Constraint::Length(2), // Tab bar (no bottom border)
+                        Constraint::Min(1),    // Output area
+                    ])
+                    .split(chunks[0]);
+                Self::render_tab_bar(frame, app, rows[0]);
+                Self::render_output_area(frame, app, rows[1], OutputHeader::TabBar);
+                rows[1]
+            }
+            TabLayout::Sidebar => {
+                let columns = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([
+                        Constraint::Length(Self::sidebar_width(app, area.width)),
+                        Constraint::Min(1),
+                    ])
+                    .split(chunks[0]);
+                Self::render_sidebar(frame, app, columns[0]);
+                Self::render_output_area(frame, app, columns[1], OutputHeader::Plain);
+                columns[1]
+            }
+        };
+        Self::render_status_bar(frame, app, chunks[1]);
+        if let Some(failure) = app.tab_manager().current_tab().spawn_failure() {
+            Self::render_spawn_failure(frame, failure, output_area);
+        }
+        if app.info_visible() {
+            Self::render_info_panel(frame, app, output_area);
+        }
+        if app.stats_visible() {
+            Self::render_stats_panel(frame, app, output_area);
+        }
+        if app.events_visible() {
+            Self::render_events_panel(frame, app, output_area);
+        }
+    }
+
+    /// Get the rows and columns of output shown in a terminal of this size
+    ///
+    /// Rows taken by pinned lines are included.
+    pub fn output_size(app: &App, width: u16, height: u16) -> (usize, usize) {
+        let status_height = app.status_bar_visible() as u16;
+        let (rows, columns) = match app.layout() {
+            // The output's top and bottom borders and its sides
+            _ if app.compact_layout() => (
+                height.saturating_sub(2 + status_height),
+                width.saturating_sub(2),
+            ),
+            // Tab bar, the output's top and bottom borders and its sides
+            TabLayout::Tabs => (
+                height.saturating_sub(4 + status_height),
+                width.saturating_sub(2),
+            ),
+            TabLayout::Sidebar => (
+                height.saturating_sub(2 + status_height),
+                width.saturating_sub(Self::sidebar_width(app, width) + 2),
+            ),
+        };
+        (rows as usize, columns as usize)
+    }
+
+    /// Width of the sidebar: the longest name, within a third of the terminal
+    fn sidebar_width(app: &App, width: u16) -> u16 {
+        let widest = app
+            .tab_manager()
+            .iter()
+            .map(|tab| tab.display_name().chars().count())
+            .max()
+            .unwrap_or(0) as u16;
+        (widest + SIDEBAR_CHROME_WIDTH).min(width / SIDEBAR_MAX_SHARE)
+    }
+
+    /// Icon and color showing a command's status in the sidebar
+    fn status_icon(status: &CommandStatus) -> (&'static str, Color) {
+        match status {
+            CommandStatus::Waiting => ("◌", Color::DarkGray),
+            CommandStatus::Running => ("●", Color::Yellow),
+            CommandStatus::Finished { exit_code: 0 } => ("✓", Color::Green),
+            CommandStatus::Finished { .. }
+            | CommandStatus::Signaled { .. }
+            | CommandStatus::Failed { .. } => ("✗", Color::Red),
+        }
+    }
+
+    /// Render the commands as a vertical list, scrolled to the active one
+    fn render_sidebar(frame: &mut Frame, app: &App, area: Rect) {
+        let tab_manager = app.tab_manager();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(Self::tabs_title(app));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let height = inner.height as usize;
+        let active_row = tab_manager
+            .shown()
+            .position(|(i, _)| i == tab_manager.active_index())
+            .unwrap_or(0);
+        let skip = (active_row + 1).saturating_sub(height);
+        let lines: Vec<Line> = tab_manager
+            .shown()
+            .skip(skip)
+            .take(height)
+            .map(|(i, tab)| {
+                let (icon, icon_color) = Self::status_icon(tab.status());
+                let name = tab.display_name();
+                let badge = match tab.unread_lines() {
+                    0 => String::new(),
+                    count if count > MAX_UNREAD_BADGE => format!("{}+", MAX_UNREAD_BADGE),
+                    count => count.to_string(),
+                };
+                // " icon name<padding>badge "
+                let used = 3 + name.chars().count() + badge.len() + 1;
+                let padding = " ".repeat((inner.width as usize).saturating_sub(used));
+                let accent = accent_color(&tab.spec().title());
+                if i == tab_manager.active_index() {
+                    return Line::from(format!(" {} {}{}{} ", icon, name, padding, badge)).style(
+                        Style::default()
+                            .fg(Color::Black)
+                            .bg(accent)
+                            .add_modifier(Modifier::BOLD),
+                    );
+                }
+                Line::from(vec![
+                    Span::raw(" "),
+                    Span::styled(icon, Style::default().fg(icon_color)),
+                    Span::raw(" "),
+                    Span::styled(name, Style::default().fg(accent)),
+                    Span::raw(padding),
+                    Span::styled(
+                        badge,
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(" "),
+                ])
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), inner);
+    }
+
+    /// Render the current tab's details in a box over the output area
+    fn render_info_panel(frame: &mut Frame, app: &App, area: Rect) {
+        let tab_index = app.tab_manager().active_index();
+        let tab = app.tab_manager().current_tab();
+        let spec = tab.spec();
+
+        let mut rows: Vec<(String, String)> = Vec::new();
+        if let Some(path) = &spec.tail {
+            rows.push(("Tail".into(), path.display().to_string()));
+        } else if let Some(source) = &spec.source {
+            rows.push(("Source".into(), source.to_string()));
+        } else {
+            rows.push(("Command".into(), spec.command.clone()));
+        }
+        if let Some(from) = &spec.stdin_from {
+            rows.push(("Stdin".into(), format!("stdout of {}", from)));
+        }
+        if let Some(destination) = &spec.ssh {
+            rows.push(("Host".into(), destination.clone()));
+        }
+        if let Some(cwd) = &spec.cwd {
+            rows.push(("Directory".into(), cwd.display().to_string()));
+        }
+        rows.push(("Status".into(), tab.status().to_string()));
+        if let Some(fix) = tab
+            .spawn_failure()
+            .and_then(|failure| failure.fix.as_deref())
+        {
+            rows.push(("Fix".into(), fix.to_string()));
+        }
+        if let Some(url) = tab.ready_url() {
+            rows.push(("URL".into(), url.to_string()));
+        }
+        if let Some(pid) = app.pid(tab_index) {
+            rows.push(("PID".into(), pid.to_string()));
+        }
+        rows.push(("Runs".into(), tab.run_count().to_string()));
+        let history = tab.run_history();
+        if !history.is_empty() {
+            let outcomes: Vec<String> = history
+                .iter()
+                .skip(history.len().saturating_sub(INFO_RUN_HISTORY))
+                .map(|run| {
+                    let (icon, _) = Self::status_icon(&run.status);
+                    format!("{} {}", icon, format_elapsed(run.duration))
+                })
+                .collect();
+            rows.push(("Last runs".into(), outcomes.join("  ")));
+        }
+        #[cfg(feature = "compress")]
+        {
+            let (lines, bytes) = tab.buffer().compressed();
+            if lines > 0 {
+                rows.push((
+                    "Compressed".into(),
+                    format!(
+                        "{} lines in {} KiB",
+                        format_count(lines as u64),
+                        bytes.div_ceil(1024)
+                    ),
+                ));
+            }
+        }
+        if let Some(note) = tab.note() {
+            rows.push(("Note".into(), note.to_string()));
+        }
+        for (variable, value) in tab.variables() {
+            rows.push((
+                format!("{{{}}}", variable.name()),
+                format!("{} (${})", value, variable.env_var()),
+            ));
+        }
+
+        let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+        let lines: Vec<Line> = rows
+            .into_iter()
+            .map(|(label, value)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!(" {:<width$}  ", label, width = label_width),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::raw(value),
+                ])
+            })
+            .collect();
+
+        // Fit the content, leaving a margin around the box
+        let content_width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 1;
+        let width = (content_width + 2).min(area.width.saturating_sub(4));
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} - i to close ", tab.display_name()));
+        frame.render_widget(Clear, popup);
+        frame.render_widget(Paragraph::new(lines).block(block), popup);
+    }
+
+    /// Render why the current tab's command couldn't be started at the
+    /// bottom of the output area, with what to try before retrying
+    fn render_spawn_failure(frame: &mut Frame, failure: &SpawnFailure, area: Rect) {
+        let label = |text| Span::styled(text, Style::default().fg(Color::DarkGray));
+        let mut lines = vec![Line::from(vec![
+            label(" Error  "),
+            Span::raw(failure.to_string()),
+        ])];
+        if let Some(fix) = &failure.fix {
+            lines.push(Line::from(vec![label(" Fix    "), Span::raw(fix.as_str())]));
+        }
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let banner = Rect {
+            y: area.y + area.height - height,
+            height,
+            ..area
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Red))
+            .title(" Failed to start - Enter to retry ");
+        frame.render_widget(Clear, banner);
+        frame.render_widget(Paragraph::new(lines).block(block), banner);
+    }
+
+    /// Render the statistics of all tabs over the output area, one row each
+    /// and the totals last
+    fn render_stats_panel(frame: &mut Frame, app: &App, area: Rect) {
+        let count_cell = |count: u64, color: Color| {
+            let style = if count > 0 {
+                Style::default().fg(color)
+            } else {
+                Style::default()
+            };
+            Cell::from(Line::from(format_count(count)).alignment(Alignment::Right)).style(style)
+        };
+        let right = |text: String| Cell::from(Line::from(text).alignment(Alignment::Right));
+        let header = Row::new(vec![
+            Cell::from("Tab"),
+            Cell::from("Status"),
+            right("Lines".into()),
+            right("Errors".into()),
+            right("Warnings".into()),
+            right("Restarts".into()),
+            right("Uptime".into()),
+        ])
+        .style(Style::default().fg(Color::DarkGray));
+
+        let tab_manager = app.tab_manager();
+        let mut rows = Vec::with_capacity(tab_manager.len() + 1);
+        let mut total = LineCounts::default();
+        let mut restarts = 0;
+        for (index, tab) in tab_manager.iter().enumerate() {
+            let counts = tab.line_counts();
+            total.lines += counts.lines;
+            total.errors += counts.errors;
+            total.warnings += counts.warnings;
+            let tab_restarts = tab.run_count().saturating_sub(1);
+            restarts += tab_restarts;
+
+            let (icon, color) = Self::status_icon(tab.status());
+            let uptime = tab.elapsed().map(format_elapsed).unwrap_or_default();
+            let row = Row::new(vec![
+                Cell::from(tab.display_name()),
+                Cell::from(Line::from(vec![
+                    Span::styled(icon, Style::default().fg(color)),
+                    Span::raw(format!(" {}", tab.status())),
+                ])),
+                right(format_count(counts.lines)),
+                count_cell(counts.errors, Color::Red),
+                count_cell(counts.warnings, Color::Yellow),
+                right(tab_restarts.to_string()),
+                right(uptime),
+            ]);
+            let mut style = Style::default();
+            if index == tab_manager.active_index() {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            if index == app.stats_selected() {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            rows.push(row.style(style));
+        }
+        rows.push(
+            Row::new(vec![
+                Cell::from("Total"),
+                Cell::from(""),
+                right(format_count(total.lines)),
+                count_cell(total.errors, Color::Red),
+                count_cell(total.warnings, Color::Yellow),
+                right(restarts.to_string()),
+                Cell::from(""),
+            ])
+            .style(Style::default().fg(Color::DarkGray))
+            .top_margin(1),
+        );
+
+        let widths = [
+            Constraint::Fill(2),
+            Constraint::Fill(2),
+            Constraint::Length(9),
+            Constraint::Length(7),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(7),
+        ];
+        let table = Table::new(rows, widths).header(header).block(
+            Block::default().borders(Borders::ALL).title(format!(
+                " Statistics: {} - Enter to open, s to close ",
+                tab_manager.summary()
+            )),
+        );
+        frame.render_widget(Clear, area);
+        frame.render_widget(table, area);
+    }
+
+    /// Render the logged sink and export errors over the output area,
+    /// newest at the bottom
+    fn render_events_panel(frame: &mut Frame, app: &App, area: Rect) {
+        let rows = area.height.saturating_sub(2) as usize;
+        let events: Vec<_> = app.events().collect();
+        let lines: Vec<Line> = if events.is_empty() {
+            vec![Line::styled(
+                " No errors so far",
+                Style::default().fg(Color::DarkGray),
+            )]
+        } else {
+            events[events.len().saturating_sub(rows)..]
+                .iter()
+                .map(|(time, message)| {
+                    let style = if message.starts_with("Error:") {
+                        Style::default().fg(Color::Red)
+                    } else {
+                        Style::default()
+                    };
+                    Line::from(vec![
+                        Span::styled(
+                            format!(" {}  ", time.format("%H:%M:%S")),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                        Span::styled(message.as_str(), style),
+                    ])
+                })
+                .collect()
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Events - E to close ");
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    /// Render the startup wizard: the input, the chosen commands and the
+    /// suggestions matching the input
+    pub fn render_wizard(frame: &mut Frame, wizard: &Wizard) {
+        let area = frame.area();
+        if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+            Self::render_too_small(frame, area);
+            return;
+        }
         let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(area);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" parallels - choose commands to run ");
+        let inner = block.inner(chunks[0]);
+        frame.render_widget(block, chunks[0]);
+
+        let chosen_height = (wizard.chosen().len() as u16 + 1).min(inner.height / 2);
+        let rows = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Length(2), // Tab bar (no bottom border)
-                Constraint::Min(1),    // Output area
-                Constraint::Length(1), // Status bar
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(chosen_height),
+                Constraint::Min(0),
+            ])
+            .split(inner);
+
+        // Input line, scrolled to keep the cursor in view
+        let input = wizard.input();
+        let prompt_width = WIZARD_PROMPT.len() as u16;
+        let input_width = rows[0].width.saturating_sub(prompt_width + 1);
+        let scroll = input.visual_scroll(input_width as usize);
+        let prompt = Line::from(vec![
+            Span::styled(WIZARD_PROMPT, Style::default().fg(Color::Cyan)),
+            Span::raw(input.value().to_string()),
+        ]);
+        frame.render_widget(Paragraph::new(prompt).scroll((0, scroll as u16)), rows[0]);
+        let cursor = prompt_width as usize + input.visual_cursor() - scroll;
+        frame.set_cursor_position((rows[0].x + cursor as u16, rows[0].y));
+
+        let heading = Style::default().fg(Color::DarkGray);
+        let mut chosen = vec![Line::styled("Commands to run:", heading)];
+        if wizard.chosen().is_empty() {
+            chosen.push(Line::styled("  (none yet)", heading));
+        }
+        chosen.extend(wizard.chosen().iter().enumerate().map(|(index, spec)| {
+            Line::from(vec![
+                Span::raw(format!("  {}. ", index + 1)),
+                Span::styled(
+                    spec.title(),
+                    Style::default().fg(accent_color(&spec.title())),
+                ),
+                Span::styled(format!("  {}", spec.command), heading),
             ])
-            .split(frame.area());
+        }));
+        // Keep the newest chosen commands in view
+        let hidden = (chosen.len() as u16).saturating_sub(rows[2].height);
+        frame.render_widget(Paragraph::new(chosen).scroll((hidden, 0)), rows[2]);
+
+        let matches = wizard.matches();
+        let mut suggestions = vec![Line::styled("Suggestions:", heading)];
+        let visible = (rows[3].height as usize).saturating_sub(1);
+        let skip = (wizard.highlighted() + 1).saturating_sub(visible);
+        suggestions.extend(matches.iter().enumerate().skip(skip).take(visible).map(
+            |(index, candidate)| {
+                let origin = match candidate.origin {
+                    Origin::Config => "config ",
+                    Origin::History => "history",
+                };
+                let line = Line::from(vec![
+                    Span::styled(format!("  {}  ", origin), heading),
+                    Span::raw(candidate.spec.command.clone()),
+                ]);
+                if index == wizard.highlighted() {
+                    line.patch_style(Modifier::REVERSED)
+                } else {
+                    line
+                }
+            },
+        ));
+        frame.render_widget(Paragraph::new(suggestions), rows[3]);
+
+        frame.render_widget(
+            Paragraph::new(WIZARD_HINTS).style(Style::default().fg(Color::Cyan)),
+            chunks[1],
+        );
+    }
 
-        Self::render_tab_bar(frame, app, chunks[0]);
-        Self::render_output_area(frame, app, chunks[1]);
-        Self::render_status_bar(frame, app, chunks[2]);
+    /// Render a placeholder instead of a layout that wouldn't fit
+    fn render_too_small(frame: &mut Frame, area: Rect) {
+        let size = format!(
+            "{}x{} (need {}x{})",
+            area.width, area.height, MIN_WIDTH, MIN_HEIGHT
+        );
+        let lines = vec![Line::from("Terminal too small"), Line::from(size)];
+        let top = area.height.saturating_sub(lines.len() as u16) / 2;
+        let placeholder = Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(
+            placeholder,
+            Rect {
+                y: area.y + top,
+                height: area.height - top,
+                ..area
+            },
+        );
     }
 
     /// Calculate tab divider positions (x coordinates where │ appears)
@@ -111,32 +750,49 @@ impl Renderer {
         let mut positions = Vec::new();
         let mut x: u16 = 1; // Start after left border
 
-        for (i, tab) in tab_manager.iter().enumerate() {
+        for (i, (_, tab)) in tab_manager.shown().enumerate() {
             if i > 0 {
                 positions.push(x);
                 x += 1; // For the │ divider
             }
-            // " name " = name.len() + 2 spaces
-            let tab_width = tab.display_name().chars().count() as u16 + 2;
+            let tab_width = Self::tab_label(tab).chars().count() as u16;
             x += tab_width;
-            if x >= area_width - 1 {
+            if x >= area_width.saturating_sub(1) {
                 break;
             }
         }
         positions
     }
 
+    /// Title of the tab bar or sidebar, with the number of hidden tabs
+    fn tabs_title(app: &App) -> String {
+        match app.tab_manager().hidden_count() {
+            0 => "Commands".to_string(),
+            hidden => format!("Commands ({} done hidden)", hidden),
+        }
+    }
+
+    /// Tab bar label: `" name "`, plus the signal for a tab killed by one
+    fn tab_label(tab: &Tab) -> String {
+        match tab.status() {
+            CommandStatus::Signaled { signal } => {
+                format!(" {} [{}] ", tab.display_name(), signal_name(*signal))
+            }
+            _ => format!(" {} ", tab.display_name()),
+        }
+    }
+
     /// Render the tab bar
     fn render_tab_bar(frame: &mut Frame, app: &App, area: Rect) {
         let tab_manager = app.tab_manager();
         let divider_positions = Self::calc_tab_divider_positions(app, area.width);
 
         // Build top border with title and ┬ at divider positions
-        let title = "Commands";
+        let title = Self::tabs_title(app);
         let title_len = title.chars().count() as u16;
         let mut top_border = String::with_capacity(area.width as usize);
         top_border.push('┌');
-        top_border.push_str(title);
+        top_border.push_str(&title);
         for x in (1 + title_len)..area.width.saturating_sub(1) {
             if divider_positions.contains(&x) {
                 top_border.push('┬');
@@ -148,19 +804,20 @@ impl Renderer {
 
         // Build tab content line
         let mut tab_spans: Vec<Span> = vec![Span::raw("│")];
-        for (i, tab) in tab_manager.iter().enumerate() {
-            if i > 0 {
+        for (position, (i, tab)) in tab_manager.shown().enumerate() {
+            if position > 0 {
                 tab_spans.push(Span::raw("│"));
             }
-            let name = format!(" {} ", tab.display_name());
+            let accent = accent_color(&tab.spec().title());
             let style = if i == tab_manager.active_index() {
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(Color::Black)
+                    .bg(accent)
                     .add_modifier(Modifier::BOLD)
             } else {
-                Style::default().fg(Color::White)
+                Style::default().fg(accent)
             };
-            tab_spans.push(Span::styled(name, style));
+            tab_spans.push(Span::styled(Self::tab_label(tab), style));
         }
         // Fill remaining space
         let used_width: u16 = tab_spans
@@ -179,70 +836,249 @@ impl Renderer {
         frame.render_widget(paragraph, area);
     }
 
-    /// Render the output area
-    fn render_output_area(frame: &mut Frame, app: &App, area: Rect) {
+    /// Render the output area, with a top border as given by `header`
+    fn render_output_area(frame: &mut Frame, app: &App, area: Rect, header: OutputHeader) {
         let tab = app.tab_manager().current_tab();
         let buffer = tab.buffer();
         let scroll_offset = tab.scroll_offset();
         let horizontal_scroll = tab.horizontal_scroll();
 
         let search_state = app.search_state();
-        let current_match_line = search_state.current_match().map(|m| m.line);
+        let selection = tab.selection();
 
-        // Build custom top border with ┴ at tab divider positions
-        let divider_positions = Self::calc_tab_divider_positions(app, area.width);
         let mut top_border = String::with_capacity(area.width as usize);
-        top_border.push('├');
-        for x in 1..area.width.saturating_sub(1) {
-            if divider_positions.contains(&x) {
-                top_border.push('┴');
-            } else {
-                top_border.push('─');
+        if header == OutputHeader::TabBar {
+            // Build custom top border with ┴ at tab divider positions
+            let divider_positions = Self::calc_tab_divider_positions(app, area.width);
+            top_border.push('├');
+            for x in 1..area.width.saturating_sub(1) {
+                if divider_positions.contains(&x) {
+                    top_border.push('┴');
+                } else {
+                    top_border.push('─');
+                }
             }
+            top_border.push('┤');
+        } else {
+            let inner_width = area.width.saturating_sub(2) as usize;
+            let title: String = match header {
+                OutputHeader::Title => Self::tab_label(tab).chars().take(inner_width).collect(),
+                _ => String::new(),
+            };
+            top_border.push('┌');
+            top_border.push_str(&title);
+            top_border.push_str(&"─".repeat(inner_width - title.chars().count()));
+            top_border.push('┐');
         }
-        top_border.push('┤');
 
-        // Split area: 1 line for top border, rest for content
+        // Split area: 1 line for top border, optional pinned area, rest for content
+        let pinned_height = tab.pinned_area_height() as u16;
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(1), Constraint::Min(1)])
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(pinned_height),
+                Constraint::Min(1),
+            ])
             .split(area);
 
+        // Frame the output in the tab's accent color
+        let border_style = Style::default().fg(accent_color(&tab.spec().title()));
+
         // Render custom top border
-        let top_border_line = Paragraph::new(Line::from(top_border));
+        let top_border_line = Paragraph::new(Line::from(top_border)).style(border_style);
         frame.render_widget(top_border_line, chunks[0]);
 
+        if pinned_height > 0 {
+            Self::render_pinned_lines(frame, tab, chunks[1]);
+        }
+
         // Account for border (subtract 1 for bottom border only, top is separate)
-        let visible_height = chunks[1].height.saturating_sub(1) as usize;
+        let visible_height = chunks[2].height.saturating_sub(1) as usize;
+
+        let key = LineCacheKey {
+            scroll_offset,
+            height: visible_height,
+            first_generation: buffer.first_generation(),
+            buffer_generation: buffer.generation(),
+            search_generation: search_state.generation(),
+            selection,
+            marked: tab.marked_range(),
+            expanded_lines: tab.expanded_lines(),
+            timestamps: app.timestamps(),
+            prefix_fold: tab.prefix_fold(),
+            table_view: tab.table_view(),
+        };
+        let collapsed = tab.collapsed();
+        let cached = (!collapsed).then(|| {
+            tab.line_cache().get_or_build(key, || {
+                Self::build_output_lines(app, scroll_offset, visible_height)
+            })
+        });
+        let lines: Vec<Line> = match &cached {
+            Some(cached) => cached.iter().map(borrow_line).collect(),
+            // A quiet command shows only how much it printed
+            None => vec![Line::from(vec![
+                Span::raw(format!(" {} ", tab.quiet_summary())),
+                Span::styled("- o to show output", Style::default().fg(Color::DarkGray)),
+            ])],
+        };
 
-        let lines: Vec<Line> = buffer
-            .iter()
-            .enumerate()
-            .skip(scroll_offset)
-            .take(visible_height)
-            .map(|(line_idx, output_line)| {
-                let prefix = match output_line.kind {
-                    OutputKind::Stdout => "[stdout] ",
-                    OutputKind::Stderr => "[stderr] ",
-                };
+        // Use block without top border (we drew it separately)
+        let output_border = border::Set {
+            top_left: "│",
+            top_right: "│",
+            ..border::PLAIN
+        };
+        let paragraph = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::LEFT | Borders::RIGHT | Borders::BOTTOM)
+                    .border_set(output_border)
+                    .border_style(border_style),
+            )
+            .scroll((
+                0,
+                if collapsed {
+                    0
+                } else {
+                    horizontal_scroll as u16
+                },
+            ));
 
-                let prefix_style = match output_line.kind {
-                    OutputKind::Stdout => Style::default().fg(Color::Green),
-                    OutputKind::Stderr => Style::default().fg(Color::Red),
-                };
+        frame.render_widget(paragraph, chunks[2]);
+
+        if app.mode() == Mode::Cursor && !collapsed {
+            Self::place_output_cursor(frame, tab, app.timestamps(), chunks[2], visible_height);
+        }
+    }
+
+    /// Show the terminal cursor at the output cursor, if it's on screen
+    fn place_output_cursor(
+        frame: &mut Frame,
+        tab: &Tab,
+        timestamps: TimestampMode,
+        area: Rect,
+        visible_height: usize,
+    ) {
+        let Some(cursor) = tab.cursor() else {
+            return;
+        };
+        let Some(output_line) = tab.buffer().get(cursor.line) else {
+            return;
+        };
+        let row = cursor.line.wrapping_sub(tab.scroll_offset());
+        if row >= visible_height {
+            return;
+        }
+
+        let plain = output_line.plain();
+        let byte = plain
+            .char_indices()
+            .nth(cursor.column)
+            .map_or(plain.len(), |(byte, _)| byte);
+        let Some(column) = Self::output_column(tab, timestamps, cursor.line, byte, visible_height)
+        else {
+            return;
+        };
+        let Some(column) = column.checked_sub(tab.horizontal_scroll()) else {
+            return;
+        };
+        // Inside the left/right borders
+        if column >= area.width.saturating_sub(2) as usize {
+            return;
+        }
+        frame.set_cursor_position((area.x + 1 + column as u16, area.y + row as u16));
+    }
 
-                let prefix_span = Span::styled(prefix, prefix_style);
+    /// Get the column at which a byte offset of a line's plain text is drawn,
+    /// from the start of its row before horizontal scrolling
+    ///
+    /// Counts the `[stdout]` prefix and the timestamp in front of the text,
+    /// and the moved cells of the table view or a hidden prefix fold.
+    pub(crate) fn output_column(
+        tab: &Tab,
+        timestamps: TimestampMode,
+        line_idx: usize,
+        byte: usize,
+        visible_height: usize,
+    ) -> Option<usize> {
+        let output_line = tab.buffer().get(line_idx)?;
+        let plain = output_line.plain();
+        let mut before = plain.get(..byte).unwrap_or(&plain).to_string();
+        if output_line.kind.is_output() {
+            if let Some(table) = Self::visible_table(tab, tab.scroll_offset(), visible_height) {
+                // Cells move in table view
+                let aligned = table.align(&plain);
+                before = aligned.text[..aligned.position(before.len())].to_string();
+            } else if tab.prefix_fold() == PrefixFold::Hide {
+                // Hidden prefixes take no columns
+                before.drain(..prefix_len(&plain).min(before.len()));
+            }
+        }
+        let stamp = Self::timestamp_span(timestamps, tab, line_idx);
+        Some(
+            Self::prefix_span(output_line.kind).width()
+                + stamp.map_or(0, |stamp| stamp.width())
+                + Span::raw(before).width(),
+        )
+    }
 
-                // Use pre-parsed spans from OutputLine
-                let base_spans: Vec<Span<'static>> = output_line.spans().to_vec();
+    /// Build the visible output rows with prefixes, highlights and selection
+    fn build_output_lines(
+        app: &App,
+        scroll_offset: usize,
+        visible_height: usize,
+    ) -> Vec<Line<'static>> {
+        let tab = app.tab_manager().current_tab();
+        let search_state = app.search_state();
+        let current_match = search_state.current_match();
+        let selection = tab.selection();
+        let marked = tab.marked_range();
+        let table = Self::visible_table(tab, scroll_offset, visible_height);
+        let visible_matches =
+            search_state.matches_in(scroll_offset..scroll_offset + visible_height);
+        let visible_matches =
+            &visible_matches[..visible_matches.len().min(MAX_HIGHLIGHTS_PER_FRAME)];
+
+        tab.buffer()
+            .range(scroll_offset..scroll_offset + visible_height)
+            .zip(scroll_offset..)
+            .map(|(output_line, line_idx)| {
+                let prefix_span = Self::prefix_span(output_line.kind);
+
+                // In table view, output lines are their plain text with the cells aligned
+                let aligned = table
+                    .as_ref()
+                    .filter(|_| output_line.kind.is_output())
+                    .map(|table| table.align(&output_line.plain()));
+
+                // Parse the line's spans (only visible lines are), dimming hook output
+                let mut base_spans: Vec<Span<'static>> = match &aligned {
+                    Some(aligned) => vec![Span::raw(aligned.text.clone())],
+                    None => output_line.spans(),
+                };
+                if output_line.kind == OutputKind::Hook {
+                    base_spans = base_spans
+                        .into_iter()
+                        .map(|span| span.patch_style(Modifier::DIM))
+                        .collect();
+                }
 
                 // Check for search highlights
-                let final_spans = if search_state.has_matches() {
+                let final_spans = if !visible_matches.is_empty() {
                     // Search active - overlay highlights on ANSI-parsed spans
-                    let matches: Vec<_> = search_state
-                        .matches()
+                    let matches: Vec<_> = visible_matches
                         .iter()
                         .filter(|m| m.line == line_idx)
+                        // Matches in the hidden part of a cut line have no span
+                        // (aligned lines are shown in full)
+                        .filter(|m| {
+                            aligned.is_some()
+                                || output_line
+                                    .shown_len()
+                                    .is_none_or(|shown| m.start + m.len <= shown)
+                        })
                         .collect();
 
                     if matches.is_empty() {
@@ -250,13 +1086,23 @@ impl Renderer {
                     } else {
                         // Search positions are in stripped text coordinates
                         // ansi-to-tui spans are also in stripped text coordinates
-                        // So we use the positions directly without conversion
+                        // So we use the positions directly without conversion,
+                        // except for the cells moved in table view
                         let highlights: Vec<HighlightRange> = matches
                             .iter()
-                            .map(|m| HighlightRange {
-                                start: m.start,
-                                end: m.start + m.len,
-                                is_current: current_match_line == Some(line_idx),
+                            .map(|m| {
+                                let (start, end) = (m.start, m.start + m.len);
+                                let (start, end) = match &aligned {
+                                    Some(aligned) => {
+                                        (aligned.position(start), aligned.position(end))
+                                    }
+                                    None => (start, end),
+                                };
+                                HighlightRange {
+                                    start,
+                                    end,
+                                    is_current: current_match == Some(*m),
+                                }
                             })
                             .collect();
 
@@ -266,27 +1112,161 @@ impl Renderer {
                     base_spans
                 };
 
+                // Reverse the text marked in Cursor mode
+                let final_spans = match marked
+                    .filter(|(start, end)| (start.line..=end.line).contains(&line_idx))
+                {
+                    Some((start, end)) => {
+                        let text = output_line.plain();
+                        let byte = |column: usize| {
+                            text.char_indices()
+                                .nth(column)
+                                .map_or(text.len(), |(i, _)| i)
+                        };
+                        let from = if line_idx == start.line {
+                            byte(start.column)
+                        } else {
+                            0
+                        };
+                        let to = if line_idx == end.line {
+                            byte(end.column + 1)
+                        } else {
+                            text.len()
+                        };
+                        let (from, to) = match &aligned {
+                            Some(aligned) => (aligned.position(from), aligned.position(to)),
+                            None => (from, to),
+                        };
+                        reverse_range(final_spans, from, to)
+                    }
+                    None => final_spans,
+                };
+
+                let final_spans = match tab.prefix_fold() {
+                    PrefixFold::Off => final_spans,
+                    _ if aligned.is_some() || !output_line.kind.is_output() => final_spans,
+                    fold => fold_prefix(final_spans, prefix_len(&output_line.plain()), fold),
+                };
+
                 let mut spans = vec![prefix_span];
+                spans.extend(Self::timestamp_span(app.timestamps(), tab, line_idx));
                 spans.extend(final_spans);
-                Line::from(spans)
+                let line = Line::from(spans);
+                if selection.is_some_and(|s| s.contains(line_idx)) {
+                    line.patch_style(Modifier::REVERSED)
+                } else {
+                    line
+                }
             })
+            .collect()
+    }
+
+    /// Infer the columns of the visible output lines, if in table view
+    fn visible_table(tab: &Tab, scroll_offset: usize, visible_height: usize) -> Option<Columns> {
+        if !tab.table_view() {
+            return None;
+        }
+        let lines: Vec<String> = tab
+            .buffer()
+            .range(scroll_offset..scroll_offset + visible_height)
+            .filter(|line| line.kind.is_output())
+            .map(|line| line.plain())
             .collect();
+        Some(Columns::infer(lines.iter().map(String::as_str)))
+    }
 
-        // Use block without top border (we drew it separately)
-        let output_border = border::Set {
-            top_left: "│",
-            top_right: "│",
-            ..border::PLAIN
+    /// Build the `[stdout]`/`[stderr]` prefix span for an output line
+    pub(crate) fn prefix_span(kind: OutputKind) -> Span<'static> {
+        match kind {
+            OutputKind::Stdout => Span::styled("[stdout] ", Style::default().fg(Color::Green)),
+            OutputKind::Stderr => Span::styled("[stderr] ", Style::default().fg(Color::Red)),
+            OutputKind::Divider => Span::styled("─────────", Style::default().fg(Color::DarkGray)),
+            OutputKind::Marker => Span::styled("[mark]   ", Style::default().fg(Color::Yellow)),
+            OutputKind::Hook => Span::styled("[hook]   ", Style::default().fg(Color::DarkGray)),
+        }
+    }
+
+    /// Build the times shown in front of an output line, if timestamps are on
+    ///
+    /// Lines read before any run (like a `tail` tab's) get blank times, so
+    /// the output stays lined up.
+    pub(crate) fn timestamp_span(
+        mode: TimestampMode,
+        tab: &Tab,
+        line_idx: usize,
+    ) -> Option<Span<'static>> {
+        if mode == TimestampMode::Off {
+            return None;
+        }
+        let time = tab.buffer().get(line_idx)?.time();
+        let text = match tab.run_start(time) {
+            Some(run_start) => {
+                let mut text = format_relative(time, run_start);
+                if mode == TimestampMode::Delta {
+                    // The first line of a run counts from the run's start
+                    let previous = line_idx
+                        .checked_sub(1)
+                        .and_then(|index| tab.buffer().get(index))
+                        .map(|line| line.time())
+                        .filter(|previous| *previous >= run_start)
+                        .unwrap_or(run_start);
+                    text.push(' ');
+                    text.push_str(&format_delta(time - previous));
+                }
+                text
+            }
+            None => {
+                let width = match mode {
+                    TimestampMode::Delta => RELATIVE_WIDTH + 1 + DELTA_WIDTH,
+                    _ => RELATIVE_WIDTH,
+                };
+                " ".repeat(width)
+            }
         };
+        Some(Span::styled(
+            format!("{} ", text),
+            Style::default().fg(Color::DarkGray),
+        ))
+    }
+
+    /// Whole seconds (rounded up) until the given instant
+    fn seconds_until(instant: Instant) -> u64 {
+        let remaining = instant.saturating_duration_since(Instant::now());
+        remaining.as_millis().div_ceil(1000) as u64
+    }
+
+    /// Render pinned lines followed by a separator border
+    fn render_pinned_lines(frame: &mut Frame, tab: &Tab, area: Rect) {
+        let lines: Vec<Line> = tab
+            .pinned_lines()
+            .iter()
+            .map(|pinned| {
+                let mut spans = vec![Self::prefix_span(pinned.kind)];
+                spans.extend(
+                    pinned
+                        .spans()
+                        .into_iter()
+                        .map(|s| s.patch_style(Modifier::BOLD)),
+                );
+                Line::from(spans)
+            })
+            .collect();
+
+        // Separator between pinned lines and scrolling output
+        let title = "Pinned";
+        let fill = (area.width as usize).saturating_sub(title.chars().count() + 2);
+        let separator = format!("├{}{}┤", title, "─".repeat(fill));
+
         let paragraph = Paragraph::new(lines)
-            .block(
-                Block::default()
-                    .borders(Borders::LEFT | Borders::RIGHT | Borders::BOTTOM)
-                    .border_set(output_border),
-            )
-            .scroll((0, horizontal_scroll as u16));
+            .block(Block::default().borders(Borders::LEFT | Borders::RIGHT))
+            .scroll((0, tab.horizontal_scroll() as u16));
 
-        frame.render_widget(paragraph, chunks[1]);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(area);
+        frame.render_widget(paragraph, chunks[0]);
+        frame.render_widget(Paragraph::new(Line::from(separator)), chunks[1]);
     }
 
     /// Render the status bar
@@ -295,6 +1275,44 @@ impl Renderer {
         let search_state = app.search_state();
         let tab = app.tab_manager().current_tab();
 
+        if let Some(message) = app.message() {
+            let paragraph =
+                Paragraph::new(format!(" {}", message)).style(Style::default().fg(Color::Yellow));
+            frame.render_widget(paragraph, area);
+            return;
+        }
+
+        // Nothing is live anymore: say so instead of showing a tab's status
+        if mode == Mode::Normal && app.tab_manager().all_done() {
+            let summary = app.tab_manager().summary();
+            let color = if summary.failed + summary.killed > 0 {
+                Color::Red
+            } else {
+                Color::Green
+            };
+            let paragraph = Paragraph::new(format!(
+                " All commands finished ({}) - press q to exit, Enter to re-run",
+                summary
+            ))
+            .style(
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(color)
+                    .add_modifier(Modifier::BOLD),
+            );
+            frame.render_widget(paragraph, area);
+            return;
+        }
+
+        if mode == Mode::Normal
+            && let Some(template) = &app.status_bar().format
+        {
+            let paragraph = Paragraph::new(Self::format_status_template(template, app))
+                .style(Style::default().fg(Color::Blue));
+            frame.render_widget(paragraph, area);
+            return;
+        }
+
         let content = match mode {
             Mode::Normal => {
                 let auto_scroll = if tab.auto_scroll() { "ON" } else { "OFF" };
@@ -303,85 +1321,998 @@ impl Renderer {
                 } else {
                     ""
                 };
+                let dropped_hint = match tab.dropped_lines() {
+                    0 => String::new(),
+                    count => format!(" Dropped: {} |", count),
+                };
+                let since_hint = tab
+                    .since()
+                    .map(|since| format!(" Since {} |", since.format("%H:%M:%S")))
+                    .unwrap_or_default();
+                let next_run = tab.next_run().map(Self::seconds_until);
+                let pid = app.pid(app.tab_manager().active_index());
+                let ended = tab
+                    .ended_at()
+                    .map(|time| format!(" at {}", time.format("%H:%M")))
+                    .unwrap_or_default();
+                let rerun_hint = match (tab.status(), next_run, pid) {
+                    (CommandStatus::Running, _, Some(pid)) if tab.spec().until_success => {
+                        format!(" Attempt {} (PID {}) |", tab.run_count(), pid)
+                    }
+                    (CommandStatus::Running, _, None) if tab.spec().until_success => {
+                        format!(" Attempt {} |", tab.run_count())
+                    }
+                    (CommandStatus::Running, _, Some(pid)) => format!(" Running (PID {}) |", pid),
+                    (CommandStatus::Running, _, None) => String::new(),
+                    (CommandStatus::Waiting, Some(secs), _) => format!(" Starting in {}s |", secs),
+                    (CommandStatus::Waiting, None, _) => " Waiting to start |".to_string(),
+                    (CommandStatus::Finished { exit_code }, Some(secs), _)
+                        if tab.spec().until_success =>
+                    {
+                        format!(
+                            " Attempt {} exited ({}) - retry in {}s |",
+                            tab.run_count(),
+                            exit_code,
+                            secs
+                        )
+                    }
+                    (CommandStatus::Finished { exit_code }, Some(secs), _) => {
+                        format!(" Exited ({}) - next run in {}s |", exit_code, secs)
+                    }
+                    (CommandStatus::Finished { exit_code }, None, _) => {
+                        format!(" Exited ({}){} - press Enter to re-run |", exit_code, ended)
+                    }
+                    (CommandStatus::Signaled { signal }, Some(secs), _) => {
+                        format!(
+                            " Killed by {} - next run in {}s |",
+                            signal_name(*signal),
+                            secs
+                        )
+                    }
+                    (CommandStatus::Signaled { signal }, None, _) => {
+                        format!(
+                            " Killed by {}{} - press Enter to re-run |",
+                            signal_name(*signal),
+                            ended
+                        )
+                    }
+                    (CommandStatus::Failed { reason }, _, _) => {
+                        format!(" Failed: {} - press Enter to re-run |", reason)
+                    }
+                };
                 format!(
-                    " NORMAL | Auto-scroll: {} | C-h/l:tabs h/l:horiz j/k:scroll /:search R:restart{} C-c:quit",
-                    auto_scroll, search_hint
+                    " NORMAL |{}{}{} Auto-scroll: {} | C-h/l:tabs h/l:horiz j/k:scroll /:search V:visual :cmd p:pin m:mark R:restart D:dup{} C-c:quit",
+                    rerun_hint, dropped_hint, since_hint, auto_scroll, search_hint
                 )
             }
             Mode::Search => {
-                let query = search_state.query();
-                let total = search_state.match_count();
-                let match_info = if let Some(current) = search_state.current_match_display() {
-                    format!(" ({}/{})", current, total)
-                } else if !query.is_empty() {
-                    " (no matches)".to_string()
-                } else {
-                    String::new()
-                };
-                format!(
-                    " SEARCH: {}{} | Enter:confirm Esc:cancel",
-                    query, match_info
-                )
+                Self::render_search_line(frame, app, area);
+                return;
+            }
+            Mode::Visual => match tab.selection() {
+                Some(selection) => format!(
+                    " VISUAL | Lines {}-{} ({}) | j/k:extend ::write Esc:cancel",
+                    selection.start() + 1,
+                    selection.end() + 1,
+                    selection.end() - selection.start() + 1
+                ),
+                None => " VISUAL".to_string(),
+            },
+            Mode::Cursor => match tab.cursor() {
+                Some(cursor) => format!(
+                    " CURSOR | Ln {}, Col {} | h/j/k/l:move w/b:word v:mark y:copy o:open *:highlight V:lines Esc:exit",
+                    cursor.line + 1,
+                    cursor.column + 1
+                ),
+                None => " CURSOR".to_string(),
+            },
+            Mode::Command => {
+                Self::render_command_line(frame, app, area);
+                return;
             }
         };
 
         let style = match mode {
             Mode::Normal => Style::default().fg(Color::Blue),
             Mode::Search => Style::default().fg(Color::Magenta),
+            Mode::Visual => Style::default().fg(Color::Green),
+            Mode::Cursor => Style::default().fg(Color::Cyan),
+            Mode::Command => Style::default(),
         };
 
         let paragraph = Paragraph::new(content).style(style);
         frame.render_widget(paragraph, area);
     }
+
+    /// Render the Command mode input line
+    ///
+    /// Shows the completion position while cycling with Tab, and for `:new`
+    /// a preview of how the command will be run and the tab's name.
+    fn render_command_line(frame: &mut Frame, app: &App, area: Rect) {
+        let input = app.command_input();
+        let hint = Style::default().fg(Color::DarkGray);
+        let mut spans = vec![Span::raw(format!(":{}", input))];
+        if let Some(completion) = app.completion() {
+            let (index, count) = completion.position();
+            spans.push(Span::styled(format!("  ({}/{})", index, count), hint));
+        }
+        if let Ok(CommandLine::New { command }) = CommandLine::parse(input) {
+            spans.push(Span::styled(
+                format!(
+                    "  → sh -c {} as \"{}\"",
+                    shell_quote(&command),
+                    auto_name(&command)
+                ),
+                hint,
+            ));
+        }
+        frame.render_widget(Paragraph::new(Line::from(spans)), area);
+    }
+
+    /// Fill in a user-defined status bar template for the current tab
+    fn format_status_template(template: &StatusTemplate, app: &App) -> String {
+        let tab = app.tab_manager().current_tab();
+        let search_state = app.search_state();
+        template.render(|field| match field {
+            StatusField::Mode => "NORMAL".to_string(),
+            StatusField::Tab => tab.name().to_string(),
+            StatusField::TabStatus => tab.status().to_string(),
+            StatusField::Elapsed => tab.elapsed().map(format_elapsed).unwrap_or_default(),
+            StatusField::Matches => search_state
+                .current_match_display()
+                .map(|current| {
+                    format!(
+                        "{}/{}{}",
+                        current,
+                        search_state.match_count(),
+                        Self::searching_mark(search_state)
+                    )
+                })
+                .unwrap_or_default(),
+            StatusField::AutoScroll => if tab.auto_scroll() { "ON" } else { "OFF" }.to_string(),
+            StatusField::Dropped => tab.dropped_lines().to_string(),
+        })
+    }
+
+    /// Mark after a match count that is still growing with a background search
+    fn searching_mark(search_state: &SearchState) -> &'static str {
+        if search_state.is_searching() { "+" } else { "" }
+    }
+
+    /// Render the Search mode input line
+    ///
+    /// The query scrolls horizontally to keep the cursor visible, and the
+    /// match count and hints stay fixed on the right.
+    fn render_search_line(frame: &mut Frame, app: &App, area: Rect) {
+        let search_state = app.search_state();
+        let input = search_state.input();
+        let style = Style::default().fg(Color::Magenta);
+
+        let match_info = if let Some(current) = search_state.current_match_display() {
+            format!(
+                " ({}/{}{})",
+                current,
+                search_state.match_count(),
+                Self::searching_mark(search_state)
+            )
+        } else if search_state.is_searching() {
+            " (searching…)".to_string()
+        } else if !input.value().is_empty() {
+            " (no matches)".to_string()
+        } else {
+            String::new()
+        };
+
+        // Keep room for the query (up to a minimum width), dropping the key
+        // hints and then truncating the match count on narrow terminals
+        let prompt_width = SEARCH_PROMPT.len() as u16;
+        let query_width = Span::raw(input.value()).width() as u16 + 1;
+        let available = area
+            .width
+            .saturating_sub(prompt_width + query_width.min(MIN_SEARCH_INPUT_WIDTH));
+        let mut hints = format!("{} | Enter:confirm Esc:cancel ", match_info);
+        if hints.len() as u16 > available {
+            hints = format!("{} ", match_info);
+        }
+        let hints_width = (hints.len() as u16).min(available);
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Length(prompt_width),
+                Constraint::Min(1),
+                Constraint::Length(hints_width),
+            ])
+            .split(area);
+        let input_area = chunks[1];
+
+        // Leave one column for the cursor at the end of the query
+        let scroll = input.visual_scroll(input_area.width.saturating_sub(1) as usize);
+        let cursor = input.visual_cursor() - scroll;
+
+        frame.render_widget(Paragraph::new(SEARCH_PROMPT).style(style), chunks[0]);
+        frame.render_widget(
+            Paragraph::new(input.value())
+                .style(style)
+                .scroll((0, scroll as u16)),
+            input_area,
+        );
+        frame.render_widget(Paragraph::new(hints).style(style), chunks[2]);
+
+        // Place the terminal cursor by display width, so wide characters and
+        // IME composition line up
+        frame.set_cursor_position((input_area.x + cursor as u16, input_area.y));
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::buffer::{OutputKind, OutputLine};
+    use crate::command::{CommandSpec, Variable};
+    use crate::tui::StatusBarConfig;
     use ansi_to_tui::IntoText;
+    use chrono::{Local, TimeZone};
     use ratatui::{Terminal, backend::TestBackend};
+    use rstest::rstest;
+
+    /// Convert terminal buffer to string for snapshot testing
+    fn buffer_to_string(terminal: &Terminal<TestBackend>) -> String {
+        let buffer = terminal.backend().buffer();
+        let mut result = String::new();
+
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                let cell = &buffer[(x, y)];
+                result.push_str(cell.symbol());
+            }
+            result.push('\n');
+        }
+
+        result
+    }
+
+    /// Create test app with given commands
+    fn create_test_app(commands: Vec<&str>) -> App {
+        App::new(commands.into_iter().map(CommandSpec::from).collect(), 100)
+    }
+
+    /// Create test app with output lines
+    fn create_test_app_with_output(commands: Vec<&str>, lines: Vec<(&str, OutputKind)>) -> App {
+        let mut app = create_test_app(commands);
+        // Disable auto-scroll to keep scroll at top
+        app.tab_manager_mut()
+            .current_tab_mut()
+            .set_auto_scroll(false);
+        for (content, kind) in lines {
+            app.tab_manager_mut()
+                .current_tab_mut()
+                .push_output(OutputLine::new(kind, content.to_string()));
+        }
+        app
+    }
+
+    #[test]
+    fn renderer_shows_info_panel() {
+        let mut app = create_test_app(vec!["npm run dev -- --port {port}"]);
+        let tab = app.tab_manager_mut().current_tab_mut();
+        let at = Local.with_ymd_and_hms(2026, 3, 1, 14, 30, 0).unwrap();
+        for exit_code in [1, 0] {
+            tab.begin_run(at);
+            tab.end_run(CommandStatus::Finished { exit_code }, at);
+        }
+        tab.set_variables(vec![(Variable::Port, "41235".to_string())]);
+        app.toggle_info();
+        let backend = TestBackend::new(60, 12);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_shows_stats_panel() {
+        let mut app = create_test_app(vec!["cargo build", "npm run dev"]);
+        let build = app.tab_manager_mut().get_tab_mut(0).unwrap();
+        for text in ["Compiling app", "warning: unused import", "error: aborting"] {
+            build.push_output(OutputLine::new(OutputKind::Stderr, text.into()));
+        }
+        let ended_at = Local.with_ymd_and_hms(2026, 3, 1, 14, 30, 0).unwrap();
+        build.end_run(CommandStatus::Finished { exit_code: 101 }, ended_at);
+        let server = app.tab_manager_mut().get_tab_mut(1).unwrap();
+        for _ in 0..1500 {
+            server.push_output(OutputLine::new(OutputKind::Stdout, "GET /".into()));
+        }
+        app.toggle_stats();
+        let backend = TestBackend::new(80, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_collapses_quiet_command_to_summary() {
+        let spec = CommandSpec {
+            quiet: true,
+            ..CommandSpec::new("cargo clippy")
+        };
+        let mut app = App::new(vec![spec], 100);
+        let tab = app.tab_manager_mut().current_tab_mut();
+        for _ in 0..142 {
+            tab.push_output(OutputLine::new(OutputKind::Stdout, "checking".into()));
+        }
+        let ended_at = Local.with_ymd_and_hms(2026, 3, 1, 14, 30, 0).unwrap();
+        tab.end_run(CommandStatus::Finished { exit_code: 0 }, ended_at);
+        let backend = TestBackend::new(50, 6);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_shows_wizard() {
+        let config = vec![CommandSpec {
+            name: Some("web".into()),
+            ..CommandSpec::new("npm run dev")
+        }];
+        let history = ["cargo watch -x run", "make lint"].map(String::from);
+        let mut wizard = Wizard::new(config, &history);
+        wizard.choose_highlighted();
+        for c in "a".chars() {
+            wizard.handle_input(tui_input::InputRequest::InsertChar(c));
+        }
+        wizard.move_highlight(true);
+        let backend = TestBackend::new(80, 12);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| Renderer::render_wizard(frame, &wizard))
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+        let cursor = terminal.get_cursor_position().unwrap();
+        assert_eq!((cursor.x, cursor.y), (4, 1));
+    }
+
+    #[test]
+    fn renderer_shows_sidebar_layout() {
+        let mut app = create_test_app_with_output(
+            vec!["npm run dev", "cargo test", "make lint", "sleep 5"],
+            vec![("ready on :3000", OutputKind::Stdout)],
+        );
+        app.set_layout(TabLayout::Sidebar);
+        let statuses = [
+            CommandStatus::Finished { exit_code: 1 },
+            CommandStatus::Finished { exit_code: 0 },
+            CommandStatus::Waiting,
+        ];
+        for (index, status) in statuses.into_iter().enumerate() {
+            app.tab_manager_mut()
+                .get_tab_mut(index + 1)
+                .unwrap()
+                .set_status(status);
+        }
+        for _ in 0..12 {
+            app.tab_manager_mut().get_tab_mut(1).unwrap().mark_unread();
+        }
+        let backend = TestBackend::new(60, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_hides_finished_tabs_from_tab_bar() {
+        let mut app = create_test_app_with_output(
+            vec!["npm run dev", "cargo test", "make lint", "cargo fmt"],
+            vec![("ready on :3000", OutputKind::Stdout)],
+        );
+        for (index, exit_code) in [(1, 0), (2, 1), (3, 0)] {
+            app.tab_manager_mut()
+                .get_tab_mut(index)
+                .unwrap()
+                .set_status(CommandStatus::Finished { exit_code });
+        }
+        app.toggle_hide_finished();
+        let backend = TestBackend::new(60, 6);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_compact_shows_header_instead_of_tab_bar() {
+        let mut app = create_test_app_with_output(
+            vec!["npm run dev"],
+            vec![("ready on :3000", OutputKind::Stdout)],
+        );
+        app.set_compact(true);
+        let backend = TestBackend::new(40, 5);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_shows_times_since_run_start_and_previous_line() {
+        let at = |millis| {
+            Local.with_ymd_and_hms(2026, 3, 1, 14, 0, 0).unwrap()
+                + chrono::TimeDelta::milliseconds(millis)
+        };
+        let mut app = create_test_app(vec!["cargo build"]);
+        app.set_timestamps(TimestampMode::Delta);
+        let tab = app.tab_manager_mut().current_tab_mut();
+        tab.set_visible_lines(3);
+        tab.push_output(OutputLine::new(OutputKind::Stdout, "before".into()).at(at(-1)));
+        tab.begin_run(at(0));
+        for (millis, text) in [(120, "Compiling app"), (3_210, "Finished")] {
+            tab.push_output(OutputLine::new(OutputKind::Stderr, text.into()).at(at(millis)));
+        }
+        let backend = TestBackend::new(60, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[rstest]
+    #[case(vec!["npm run dev"], (4, 38))]
+    #[case(vec!["npm run dev", "cargo test"], (2, 38))]
+    fn renderer_output_size_grows_in_compact_mode_with_one_tab(
+        #[case] commands: Vec<&str>,
+        #[case] expected: (usize, usize),
+    ) {
+        let mut app = create_test_app(commands);
+        app.set_compact(true);
+
+        assert_eq!(Renderer::output_size(&app, 40, 7), expected);
+    }
+
+    #[rstest]
+    #[case(TabLayout::Tabs, (3, 58))]
+    #[case(TabLayout::Sidebar, (5, 38))]
+    fn renderer_output_size_leaves_room_for_layout(
+        #[case] layout: TabLayout,
+        #[case] expected: (usize, usize),
+    ) {
+        let mut app = create_test_app(vec!["npm run dev", "cargo test"]);
+        app.set_layout(layout);
+
+        assert_eq!(Renderer::output_size(&app, 60, 8), expected);
+    }
+
+    #[test]
+    fn renderer_tiny_terminal_shows_placeholder() {
+        let app = create_test_app(vec!["echo hello"]);
+        let backend = TestBackend::new(24, 3);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[rstest]
+    #[case(0, 0)]
+    #[case(1, 1)]
+    #[case(MIN_WIDTH - 1, 40)]
+    #[case(80, MIN_HEIGHT - 1)]
+    #[case(MIN_WIDTH, MIN_HEIGHT)]
+    fn renderer_tiny_sizes_do_not_panic(#[case] width: u16, #[case] height: u16) {
+        let mut app = create_test_app_with_output(
+            vec!["a-very-long-command-name", "another-long-command"],
+            vec![("output line", OutputKind::Stdout)],
+        );
+        app.set_message("a message that is wider than the terminal");
+        let backend = TestBackend::new(width, height);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn renderer_tab_bar_single_tab() {
+        let app = create_test_app(vec!["echo hello"]);
+        let backend = TestBackend::new(40, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_colors_tabs_and_output_border_by_command() {
+        let app = create_test_app(vec!["web", "api"]);
+        let backend = TestBackend::new(40, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        // Active tab: accent background; other tabs: accent foreground
+        assert_eq!(buffer[(2, 1)].bg, accent_color("web"));
+        assert_eq!(buffer[(8, 1)].fg, accent_color("api"));
+        // Output frame of the active tab
+        assert_eq!(buffer[(0, 5)].fg, accent_color("web"));
+    }
+
+    #[test]
+    fn renderer_tab_bar_multiple_tabs() {
+        let mut app = create_test_app(vec!["cmd1", "cmd2", "cmd3"]);
+        app.tab_manager_mut().next_tab(); // Activate second tab
+        let backend = TestBackend::new(40, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_output_area_with_stdout_stderr() {
+        let app = create_test_app_with_output(
+            vec!["test"],
+            vec![
+                ("hello world", OutputKind::Stdout),
+                ("error message", OutputKind::Stderr),
+                ("another line", OutputKind::Stdout),
+            ],
+        );
+        let backend = TestBackend::new(50, 12);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_status_bar_normal_mode() {
+        let app = create_test_app(vec!["test"]);
+        let backend = TestBackend::new(50, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_status_bar_custom_format() {
+        let mut app =
+            create_test_app_with_output(vec!["test"], vec![("hello world", OutputKind::Stdout)]);
+        app.search_in_current_tab("hello");
+        app.set_status_bar(StatusBarConfig {
+            format: Some(
+                StatusTemplate::parse(" {mode} | {tab}: {tab_status} | {matches}").unwrap(),
+            ),
+            hidden: false,
+        });
+        let backend = TestBackend::new(50, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_status_bar_hidden_gives_output_area_the_row() {
+        let mut app = create_test_app(vec!["test"]);
+        app.set_status_bar(StatusBarConfig {
+            format: None,
+            hidden: true,
+        });
+        let backend = TestBackend::new(50, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_status_bar_search_mode() {
+        use crate::app::Mode;
+
+        let mut app = create_test_app_with_output(
+            vec!["test"],
+            vec![
+                ("hello world", OutputKind::Stdout),
+                ("hello there", OutputKind::Stdout),
+                ("goodbye", OutputKind::Stdout),
+            ],
+        );
+        app.set_mode(Mode::Search);
+        app.search_in_current_tab("hello");
+
+        let backend = TestBackend::new(50, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[rstest]
+    #[case("hello", 14)]
+    #[case("日本語", 15)]
+    fn renderer_search_mode_places_cursor_after_query(#[case] query: &str, #[case] x: u16) {
+        use crate::app::Mode;
+        use ratatui::backend::Backend;
+
+        let mut app = create_test_app(vec!["test"]);
+        app.set_mode(Mode::Search);
+        app.search_in_current_tab(query);
+
+        let backend = TestBackend::new(50, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        let cursor = terminal.backend_mut().get_cursor_position().unwrap();
+        assert_eq!((cursor.x, cursor.y), (x, 9));
+    }
+
+    #[test]
+    fn renderer_cursor_mode_redraws_marked_text_as_the_cursor_moves() {
+        use crate::app::Mode;
+
+        let mut app = create_test_app(vec!["test"]);
+        let tab = app.tab_manager_mut().current_tab_mut();
+        tab.set_auto_scroll(false);
+        tab.push_output(OutputLine::new(OutputKind::Stdout, "abcdef".into()));
+        tab.start_cursor(0);
+        tab.toggle_cursor_mark();
+        app.set_mode(Mode::Cursor);
+        let mut terminal = Terminal::new(TestBackend::new(40, 6)).unwrap();
+        // Border + "[stdout] " before the text on the first output row
+        let reversed = |terminal: &Terminal<TestBackend>| -> Vec<bool> {
+            (10..16)
+                .map(|x| {
+                    terminal.backend().buffer()[(x, 3)]
+                        .modifier
+                        .contains(Modifier::REVERSED)
+                })
+                .collect()
+        };
+
+        terminal
+            .draw(|frame| Renderer::render(frame, &app))
+            .unwrap();
+        assert_eq!(
+            reversed(&terminal),
+            [true, false, false, false, false, false]
+        );
+
+        let tab = app.tab_manager_mut().current_tab_mut();
+        for _ in 0..3 {
+            tab.move_cursor_horizontal(false);
+        }
+        terminal
+            .draw(|frame| Renderer::render(frame, &app))
+            .unwrap();
+        assert_eq!(reversed(&terminal), [true, true, true, true, false, false]);
+    }
+
+    #[test]
+    fn renderer_cursor_mode_places_cursor_on_output_character() {
+        use crate::app::Mode;
+        use ratatui::backend::Backend;
+
+        let mut app = create_test_app(vec!["test"]);
+        let tab = app.tab_manager_mut().current_tab_mut();
+        tab.set_auto_scroll(false);
+        tab.push_output(OutputLine::new(OutputKind::Stdout, "ok".into()));
+        tab.push_output(OutputLine::new(OutputKind::Stdout, "wörld map".into()));
+        tab.start_cursor(1);
+        tab.move_cursor_word(true);
+        app.set_mode(Mode::Cursor);
+
+        let backend = TestBackend::new(80, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        let cursor = terminal.backend_mut().get_cursor_position().unwrap();
+        // Border + "[stdout] " + "wörld "
+        assert_eq!((cursor.x, cursor.y), (16, 4));
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_search_mode_scrolls_long_query() {
+        use crate::app::Mode;
+        use ratatui::backend::Backend;
+
+        let mut app = create_test_app(vec!["test"]);
+        app.set_mode(Mode::Search);
+        app.search_in_current_tab("a-very-long-query-that-does-not-fit");
+
+        let backend = TestBackend::new(50, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        let cursor = terminal.backend_mut().get_cursor_position().unwrap();
+        assert_eq!((cursor.x, cursor.y), (35, 9));
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_full_layout() {
+        use crate::app::Mode;
+
+        let mut app = create_test_app_with_output(
+            vec!["cmd1", "cmd2"],
+            vec![
+                ("Starting...", OutputKind::Stdout),
+                ("Processing...", OutputKind::Stdout),
+                ("Warning: something", OutputKind::Stderr),
+                ("Done.", OutputKind::Stdout),
+            ],
+        );
+        app.set_mode(Mode::Search);
+        app.search_in_current_tab("Done");
+
+        let backend = TestBackend::new(60, 15);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_output_area_with_pinned_lines() {
+        let mut app = create_test_app_with_output(
+            vec!["server"],
+            vec![
+                ("Listening on http://localhost:3000", OutputKind::Stdout),
+                ("GET /", OutputKind::Stdout),
+                ("GET /favicon.ico", OutputKind::Stderr),
+            ],
+        );
+        app.tab_manager_mut().current_tab_mut().toggle_pin(0);
+
+        let backend = TestBackend::new(50, 12);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_visual_mode_selection() {
+        use crate::app::Mode;
 
-    /// Convert terminal buffer to string for snapshot testing
-    fn buffer_to_string(terminal: &Terminal<TestBackend>) -> String {
-        let buffer = terminal.backend().buffer();
-        let mut result = String::new();
+        let mut app = create_test_app_with_output(
+            vec!["test"],
+            vec![
+                ("first", OutputKind::Stdout),
+                ("second", OutputKind::Stdout),
+                ("third", OutputKind::Stdout),
+            ],
+        );
+        app.tab_manager_mut().current_tab_mut().start_selection(0);
+        app.tab_manager_mut()
+            .current_tab_mut()
+            .extend_selection_down();
+        app.set_mode(Mode::Visual);
 
-        for y in 0..buffer.area.height {
-            for x in 0..buffer.area.width {
-                let cell = &buffer[(x, y)];
-                result.push_str(cell.symbol());
-            }
-            result.push('\n');
-        }
+        let backend = TestBackend::new(60, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
 
-        result
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
     }
 
-    /// Create test app with given commands
-    fn create_test_app(commands: Vec<&str>) -> App {
-        App::new(commands.into_iter().map(String::from).collect(), 100)
+    #[test]
+    fn renderer_status_bar_finished_command_shows_rerun_hint() {
+        // Another command still runs, so the tab's own status is shown
+        let mut app = create_test_app(vec!["test", "server"]);
+        let ended_at = Local.with_ymd_and_hms(2026, 1, 2, 12, 34, 56).unwrap();
+        app.tab_manager_mut()
+            .current_tab_mut()
+            .end_run(CommandStatus::Finished { exit_code: 2 }, ended_at);
+
+        let backend = TestBackend::new(60, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
     }
 
-    /// Create test app with output lines
-    fn create_test_app_with_output(commands: Vec<&str>, lines: Vec<(&str, OutputKind)>) -> App {
-        let mut app = create_test_app(commands);
-        // Disable auto-scroll to keep scroll at top
+    #[test]
+    fn renderer_status_bar_failed_command_shows_reason() {
+        let mut app = create_test_app(vec!["missing", "server"]);
         app.tab_manager_mut()
             .current_tab_mut()
-            .set_auto_scroll(false);
-        for (content, kind) in lines {
+            .set_status(CommandStatus::Failed {
+                reason: "No such file or directory".to_string(),
+            });
+
+        let backend = TestBackend::new(80, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        let output = buffer_to_string(&terminal);
+        assert!(
+            output
+                .contains(" NORMAL | Failed: No such file or directory - press Enter to re-run |"),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn renderer_spawn_failure_shows_error_and_fix() {
+        let mut app = create_test_app(vec!["npm run dev", "server"]);
+        let spec = CommandSpec::new("npm run dev");
+        let error = std::io::Error::from_raw_os_error(nix::errno::Errno::EAGAIN as i32);
+        let failure = SpawnFailure::diagnose(&error, &spec);
+        let tab = app.tab_manager_mut().current_tab_mut();
+        tab.set_status(CommandStatus::Failed {
+            reason: failure.to_string(),
+        });
+        tab.set_spawn_failure(failure);
+
+        let backend = TestBackend::new(100, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_all_commands_finished_shows_exit_banner() {
+        let mut app = create_test_app(vec!["build", "lint"]);
+        for (tab_index, exit_code) in [(0, 0), (1, 1)] {
             app.tab_manager_mut()
-                .current_tab_mut()
-                .push_output(OutputLine::new(kind, content.to_string()));
+                .get_tab_mut(tab_index)
+                .unwrap()
+                .set_status(CommandStatus::Finished { exit_code });
         }
-        app
+
+        let backend = TestBackend::new(100, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+        assert_eq!(terminal.backend().buffer()[(1, 7)].bg, Color::Red);
     }
 
     #[test]
-    fn renderer_tab_bar_single_tab() {
-        let app = create_test_app(vec!["echo hello"]);
-        let backend = TestBackend::new(40, 10);
+    fn renderer_signaled_command_shows_signal_in_tab_and_status_bar() {
+        let mut app = create_test_app(vec!["server", "worker"]);
+        app.tab_manager_mut()
+            .current_tab_mut()
+            .set_status(CommandStatus::Signaled { signal: 11 });
+
+        let backend = TestBackend::new(60, 8);
         let mut terminal = Terminal::new(backend).unwrap();
 
         terminal
@@ -394,10 +2325,13 @@ mod tests {
     }
 
     #[test]
-    fn renderer_tab_bar_multiple_tabs() {
-        let mut app = create_test_app(vec!["cmd1", "cmd2", "cmd3"]);
-        app.tab_manager_mut().next_tab(); // Activate second tab
-        let backend = TestBackend::new(40, 10);
+    fn renderer_status_bar_periodic_command_shows_next_run() {
+        let mut app = create_test_app(vec!["test"]);
+        let tab = app.tab_manager_mut().current_tab_mut();
+        tab.set_status(CommandStatus::Finished { exit_code: 0 });
+        tab.set_next_run(Some(Instant::now() + std::time::Duration::from_secs(30)));
+
+        let backend = TestBackend::new(60, 8);
         let mut terminal = Terminal::new(backend).unwrap();
 
         terminal
@@ -410,16 +2344,82 @@ mod tests {
     }
 
     #[test]
-    fn renderer_output_area_with_stdout_stderr() {
-        let app = create_test_app_with_output(
+    fn renderer_status_bar_until_success_shows_attempt_and_retry() {
+        let spec = CommandSpec {
+            until_success: true,
+            ..CommandSpec::new("test")
+        };
+        let mut app = App::new(vec![spec], 100);
+        let started_at = Local.with_ymd_and_hms(2026, 1, 2, 12, 34, 56).unwrap();
+        let tab = app.tab_manager_mut().current_tab_mut();
+        tab.begin_run(started_at);
+        tab.begin_run(started_at);
+        tab.set_status(CommandStatus::Finished { exit_code: 1 });
+        tab.set_next_run(Some(Instant::now() + std::time::Duration::from_secs(5)));
+
+        let backend = TestBackend::new(60, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_status_bar_shows_dropped_lines() {
+        let mut app = create_test_app(vec!["test"]);
+        app.tab_manager_mut().current_tab_mut().record_dropped(42);
+
+        let backend = TestBackend::new(60, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_output_area_redraws_new_output_after_cached_frame() {
+        let mut app =
+            create_test_app_with_output(vec!["test"], vec![("first", OutputKind::Stdout)]);
+        let backend = TestBackend::new(60, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|frame| Renderer::render(frame, &app))
+            .unwrap();
+
+        app.tab_manager_mut()
+            .current_tab_mut()
+            .push_output(OutputLine::new(OutputKind::Stdout, "second".into()));
+        terminal
+            .draw(|frame| Renderer::render(frame, &app))
+            .unwrap();
+
+        assert!(buffer_to_string(&terminal).contains("[stdout] second"));
+    }
+
+    #[test]
+    fn renderer_output_area_with_marker() {
+        let mut app = create_test_app_with_output(
             vec!["test"],
             vec![
-                ("hello world", OutputKind::Stdout),
-                ("error message", OutputKind::Stderr),
-                ("another line", OutputKind::Stdout),
+                ("before", OutputKind::Stdout),
+                ("after", OutputKind::Stdout),
             ],
         );
-        let backend = TestBackend::new(50, 12);
+        app.tab_manager_mut()
+            .current_tab_mut()
+            .push_output(OutputLine::marker("Mark · 12:34:56"));
+
+        let backend = TestBackend::new(60, 8);
         let mut terminal = Terminal::new(backend).unwrap();
 
         terminal
@@ -432,8 +2432,57 @@ mod tests {
     }
 
     #[test]
-    fn renderer_status_bar_normal_mode() {
-        let app = create_test_app(vec!["test"]);
+    fn renderer_output_area_with_run_divider() {
+        let mut app =
+            create_test_app_with_output(vec!["test"], vec![("first", OutputKind::Stdout)]);
+        let started_at = Local.with_ymd_and_hms(2026, 1, 2, 12, 34, 56).unwrap();
+        let tab = app.tab_manager_mut().current_tab_mut();
+        tab.begin_run(started_at);
+        tab.begin_run(started_at);
+        tab.push_output(OutputLine::new(OutputKind::Stdout, "second".into()));
+
+        let backend = TestBackend::new(60, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_output_area_with_cut_line() {
+        let mut app = create_test_app_with_output(vec!["test"], vec![]);
+        app.tab_manager_mut()
+            .current_tab_mut()
+            .push_output(OutputLine::with_limit(
+                OutputKind::Stdout,
+                format!("{} needle", "x".repeat(40_000)),
+                12,
+            ));
+        // The match past the cut is not highlighted
+        app.search_in_current_tab("needle");
+
+        let backend = TestBackend::new(60, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    #[test]
+    fn renderer_status_bar_command_mode() {
+        let mut app = create_test_app(vec!["test"]);
+        app.start_command("write 1,2 out.txt");
+
         let backend = TestBackend::new(50, 8);
         let mut terminal = Terminal::new(backend).unwrap();
 
@@ -447,21 +2496,50 @@ mod tests {
     }
 
     #[test]
-    fn renderer_status_bar_search_mode() {
-        use crate::app::Mode;
+    fn renderer_command_mode_previews_new_command() {
+        let mut app = create_test_app(vec!["test"]);
+        app.start_command("new npm run dev");
+
+        let backend = TestBackend::new(70, 8);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Renderer::render(frame, &app);
+            })
+            .unwrap();
+
+        insta::assert_snapshot!(buffer_to_string(&terminal));
+    }
+
+    // Tests for overlay_highlights function
+    #[test]
+    fn overlay_highlights_with_no_highlights_returns_original_spans() {
+        let spans = vec![Span::raw("hello world".to_string())];
+        let result = overlay_highlights(spans.clone(), &[]);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].content, "hello world");
+    }
 
+    #[test]
+    fn renderer_hides_log_prefixes_of_output_lines() {
         let mut app = create_test_app_with_output(
             vec!["test"],
             vec![
-                ("hello world", OutputKind::Stdout),
-                ("hello there", OutputKind::Stdout),
-                ("goodbye", OutputKind::Stdout),
+                (
+                    "2026-03-01T14:00:03Z [api] listening on :3000",
+                    OutputKind::Stdout,
+                ),
+                ("web-1  | [ERROR] connection refused", OutputKind::Stderr),
+                ("no prefix here", OutputKind::Stdout),
             ],
         );
-        app.set_mode(Mode::Search);
-        app.search_in_current_tab("hello");
+        let tab = app.tab_manager_mut().current_tab_mut();
+        tab.cycle_prefix_fold();
+        tab.cycle_prefix_fold();
+        assert_eq!(tab.prefix_fold(), PrefixFold::Hide);
 
-        let backend = TestBackend::new(50, 10);
+        let backend = TestBackend::new(60, 8);
         let mut terminal = Terminal::new(backend).unwrap();
 
         terminal
@@ -474,22 +2552,20 @@ mod tests {
     }
 
     #[test]
-    fn renderer_full_layout() {
-        use crate::app::Mode;
-
+    fn renderer_table_view_aligns_visible_columns() {
         let mut app = create_test_app_with_output(
-            vec!["cmd1", "cmd2"],
+            vec!["kubectl get pods -w"],
             vec![
-                ("Starting...", OutputKind::Stdout),
-                ("Processing...", OutputKind::Stdout),
-                ("Warning: something", OutputKind::Stderr),
-                ("Done.", OutputKind::Stdout),
+                ("NAME    READY   STATUS    RESTARTS", OutputKind::Stdout),
+                ("web-7d9f   1/1   Running   0", OutputKind::Stdout),
+                ("db\t0/1\tCrashLoopBackOff\t12", OutputKind::Stdout),
+                ("watch: connection lost", OutputKind::Stderr),
             ],
         );
-        app.set_mode(Mode::Search);
-        app.search_in_current_tab("Done");
+        app.toggle_table_view();
+        app.search_in_current_tab("Running");
 
-        let backend = TestBackend::new(60, 15);
+        let backend = TestBackend::new(60, 9);
         let mut terminal = Terminal::new(backend).unwrap();
 
         terminal
@@ -499,15 +2575,47 @@ mod tests {
             .unwrap();
 
         insta::assert_snapshot!(buffer_to_string(&terminal));
+        // The match moved with its cell
+        let row: String = (1..59)
+            .map(|x| terminal.backend().buffer()[(x, 4)].symbol().to_string())
+            .collect();
+        let column = row.find("Running").unwrap() as u16 + 1;
+        assert_eq!(
+            terminal.backend().buffer()[(column, 4)].bg,
+            Color::Cyan,
+            "{}",
+            row
+        );
     }
 
-    // Tests for overlay_highlights function
     #[test]
-    fn overlay_highlights_with_no_highlights_returns_original_spans() {
-        let spans = vec![Span::raw("hello world".to_string())];
-        let result = overlay_highlights(spans.clone(), &[]);
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0].content, "hello world");
+    fn fold_prefix_dims_prefix_keeping_highlights() {
+        let text = "12:00:03 [db] query failed";
+        let highlights = vec![HighlightRange {
+            start: 10,
+            end: 20,
+            is_current: true,
+        }];
+        let spans = overlay_highlights(vec![Span::raw(text.to_string())], &highlights);
+
+        let result = fold_prefix(spans, prefix_len(text), PrefixFold::Dim);
+
+        let parts: Vec<(&str, bool, Option<Color>)> = result
+            .iter()
+            .map(|span| {
+                let dim = span.style.add_modifier.contains(Modifier::DIM);
+                (span.content.as_ref(), dim, span.style.bg)
+            })
+            .collect();
+        assert_eq!(
+            parts,
+            vec![
+                ("12:00:03 [", true, None),
+                ("db] ", true, Some(Color::Cyan)),
+                ("query ", false, Some(Color::Cyan)),
+                ("failed", false, None),
+            ]
+        );
     }
 
     #[test]
@@ -584,6 +2692,80 @@ mod tests {
         assert_eq!(result[2].content, ": Connection timeout");
     }
 
+    #[rstest]
+    #[case::inside_emoji("ok 🚀 done", 4, 6, "🚀")]
+    #[case::across_cjk("日本語のログ", 4, 7, "本語")]
+    #[case::end_inside_char("✗ failed", 0, 1, "✗")]
+    fn overlay_highlights_rounds_out_to_whole_chars(
+        #[case] text: &str,
+        #[case] start: usize,
+        #[case] end: usize,
+        #[case] expected: &str,
+    ) {
+        let spans = vec![Span::raw(text.to_string())];
+        let highlights = vec![HighlightRange {
+            start,
+            end,
+            is_current: true,
+        }];
+
+        let result = overlay_highlights(spans, &highlights);
+
+        let highlighted: String = result
+            .iter()
+            .filter(|s| s.style.bg == Some(Color::Cyan))
+            .map(|s| s.content.to_string())
+            .collect();
+        assert_eq!(highlighted, expected);
+    }
+
+    #[test]
+    fn reverse_range_reverses_across_spans_keeping_styles() {
+        let red = Style::default().fg(Color::Red);
+        let spans = vec![Span::raw("GET "), Span::styled("/héalth", red)];
+
+        // Ends inside "é", which is reversed whole
+        let result = reverse_range(spans, 2, 7);
+
+        let parts: Vec<_> = result
+            .iter()
+            .map(|s| (s.content.as_ref(), s.style))
+            .collect();
+        assert_eq!(
+            parts,
+            [
+                ("GE", Style::default()),
+                ("T ", Style::default().add_modifier(Modifier::REVERSED)),
+                ("/hé", red.add_modifier(Modifier::REVERSED)),
+                ("alth", red),
+            ]
+        );
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn overlay_highlights_keeps_text_for_any_byte_ranges(
+            parts in proptest::collection::vec("[a-z 日本語🚀é✗]{0,6}", 1..4),
+            ranges in proptest::collection::vec((0usize..40, 0usize..40), 0..4),
+        ) {
+            let text: String = parts.concat();
+            let spans: Vec<Span<'static>> = parts.into_iter().map(Span::raw).collect();
+            let highlights: Vec<HighlightRange> = ranges
+                .into_iter()
+                .map(|(a, b)| HighlightRange {
+                    start: a.min(b),
+                    end: a.max(b),
+                    is_current: false,
+                })
+                .collect();
+
+            let result = overlay_highlights(spans, &highlights);
+
+            let rebuilt: String = result.iter().map(|s| s.content.as_ref()).collect();
+            proptest::prop_assert_eq!(rebuilt, text);
+        }
+    }
+
     #[test]
     fn renderer_search_with_ansi_text_highlights_correct_position() {
         // Test the full flow: ANSI text + search
@@ -0,0 +1,209 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Status bar settings from the config file
+///
+/// ```toml
+/// [status_bar]
+/// format = "{mode} | {tab}: {tab_status} {elapsed} | {matches}"
+/// hidden = false
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatusBarConfig {
+    /// Template for the Normal mode status bar (defaults to the built-in layout)
+    #[serde(default)]
+    pub format: Option<StatusTemplate>,
+    /// Hide the status bar in Normal mode to gain an output row
+    #[serde(default)]
+    pub hidden: bool,
+}
+
+/// Value that can be placed in a status bar template
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusField {
+    /// Current mode (`NORMAL`)
+    Mode,
+    /// Current tab's name
+    Tab,
+    /// Current tab's command status (`running`, `exited (1)`, ...)
+    TabStatus,
+    /// Time since the current run started (until it finished)
+    Elapsed,
+    /// Current search match and total (`2/5`), empty without a search
+    Matches,
+    /// Auto-scroll state (`ON`/`OFF`)
+    AutoScroll,
+    /// Lines dropped because the UI could not keep up
+    Dropped,
+}
+
+impl FromStr for StatusField {
+    type Err = StatusTemplateError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "mode" => Ok(StatusField::Mode),
+            "tab" => Ok(StatusField::Tab),
+            "tab_status" => Ok(StatusField::TabStatus),
+            "elapsed" => Ok(StatusField::Elapsed),
+            "matches" => Ok(StatusField::Matches),
+            "auto_scroll" => Ok(StatusField::AutoScroll),
+            "dropped" => Ok(StatusField::Dropped),
+            _ => Err(StatusTemplateError::UnknownPlaceholder(name.to_string())),
+        }
+    }
+}
+
+/// Error while parsing a status bar template
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum StatusTemplateError {
+    #[error("unknown placeholder: {{{0}}}")]
+    UnknownPlaceholder(String),
+    #[error("unclosed placeholder in status bar format")]
+    Unclosed,
+}
+
+/// Piece of a parsed template
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Field(StatusField),
+}
+
+/// Status bar template with `{name}` placeholders (`{{`/`}}` for literal braces)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct StatusTemplate {
+    segments: Vec<Segment>,
+}
+
+impl StatusTemplate {
+    /// Parse a template string
+    pub fn parse(template: &str) -> Result<Self, StatusTemplateError> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(StatusTemplateError::Unclosed),
+                        }
+                    }
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(Segment::Field(name.trim().parse()?));
+                }
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Fill in the placeholders with values from `value`
+    pub fn render(&self, value: impl Fn(StatusField) -> String) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.clone(),
+                Segment::Field(field) => value(*field),
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<String> for StatusTemplate {
+    type Error = StatusTemplateError;
+
+    fn try_from(template: String) -> Result<Self, Self::Error> {
+        Self::parse(&template)
+    }
+}
+
+/// Format a duration compactly for the status bar (`42s`, `3m05s`, `1h02m`)
+pub(crate) fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn sample(field: StatusField) -> String {
+        match field {
+            StatusField::Mode => "NORMAL".into(),
+            StatusField::Tab => "web".into(),
+            StatusField::TabStatus => "running".into(),
+            StatusField::Elapsed => "3s".into(),
+            StatusField::Matches => "1/2".into(),
+            StatusField::AutoScroll => "ON".into(),
+            StatusField::Dropped => "0".into(),
+        }
+    }
+
+    #[test]
+    fn status_template_renders_placeholders_and_text() {
+        let template =
+            StatusTemplate::parse(" {mode} | {tab}: {tab_status} {elapsed} {{{matches}}}").unwrap();
+        assert_eq!(template.render(sample), " NORMAL | web: running 3s {1/2}");
+    }
+
+    #[rstest]
+    #[case("{bogus}", StatusTemplateError::UnknownPlaceholder("bogus".into()))]
+    #[case("{mode", StatusTemplateError::Unclosed)]
+    fn status_template_parse_returns_error(
+        #[case] input: &str,
+        #[case] expected: StatusTemplateError,
+    ) {
+        assert_eq!(StatusTemplate::parse(input), Err(expected));
+    }
+
+    #[test]
+    fn status_bar_config_deserializes_format_and_hidden() {
+        let config: StatusBarConfig = toml::from_str(
+            r#"
+            format = "{mode} {tab}"
+            hidden = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.format.unwrap().render(sample), "NORMAL web");
+        assert!(config.hidden);
+    }
+
+    #[rstest]
+    #[case(42, "42s")]
+    #[case(185, "3m05s")]
+    #[case(3720, "1h02m")]
+    fn format_elapsed_is_compact(#[case] secs: u64, #[case] expected: &str) {
+        assert_eq!(format_elapsed(Duration::from_secs(secs)), expected);
+    }
+}
@@ -1,55 +1,263 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+
 use crate::buffer::{OutputBuffer, OutputLine};
+use crate::command::{
+    CommandSpec, PrefixFold, Severity, SpawnFailure, Variable, format_count, signal_name,
+};
+use crate::search::{next_word_start, prev_word_start};
+use crate::tui::line_cache::LineCache;
 
 /// Command execution status
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CommandStatus {
+    /// Waiting for a delayed start
+    Waiting,
     /// Running
     Running,
     /// Finished with exit code
     Finished { exit_code: i32 },
+    /// Terminated by a signal (e.g. SIGSEGV, SIGKILL)
+    Signaled { signal: i32 },
     /// Failed to start
     Failed { reason: String },
 }
 
+impl fmt::Display for CommandStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandStatus::Waiting => write!(f, "waiting"),
+            CommandStatus::Running => write!(f, "running"),
+            CommandStatus::Finished { exit_code } => write!(f, "exited ({})", exit_code),
+            CommandStatus::Signaled { signal } => write!(f, "killed by {}", signal_name(*signal)),
+            CommandStatus::Failed { reason } => write!(f, "failed: {}", reason),
+        }
+    }
+}
+
+impl CommandStatus {
+    /// Check if the command is no longer running (finished, killed or failed)
+    pub fn is_done(&self) -> bool {
+        matches!(
+            self,
+            CommandStatus::Finished { .. }
+                | CommandStatus::Signaled { .. }
+                | CommandStatus::Failed { .. }
+        )
+    }
+}
+
+/// Line-wise selection made in Visual mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineSelection {
+    anchor: usize,
+    cursor: usize,
+}
+
+impl LineSelection {
+    /// First selected line (0-based)
+    pub fn start(&self) -> usize {
+        self.anchor.min(self.cursor)
+    }
+
+    /// Last selected line (0-based, inclusive)
+    pub fn end(&self) -> usize {
+        self.anchor.max(self.cursor)
+    }
+
+    /// Line the selection cursor is on
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Check if a line is inside the selection
+    pub fn contains(&self, line: usize) -> bool {
+        (self.start()..=self.end()).contains(&line)
+    }
+}
+
+/// Position of the output cursor (Cursor mode)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OutputCursor {
+    /// Line in the buffer (0-based)
+    pub line: usize,
+    /// Character index within the line's plain text
+    pub column: usize,
+}
+
 /// Maximum characters for tab name display
 const MAX_TAB_NAME_LEN: usize = 20;
 
+/// Maximum number of pinned lines per tab
+const MAX_PINNED_LINES: usize = 5;
+
+/// Maximum number of finished runs remembered per tab
+const MAX_RUN_HISTORY: usize = 10;
+
+/// Output lines a tab has read over all its runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineCounts {
+    pub lines: u64,
+    /// Lines that look like errors
+    pub errors: u64,
+    /// Lines that look like warnings
+    pub warnings: u64,
+}
+
+/// Outcome of a finished run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunRecord {
+    pub started_at: DateTime<Local>,
+    pub duration: Duration,
+    pub status: CommandStatus,
+}
+
 /// Tab structure representing a command and its output
 pub struct Tab {
-    command: String,
+    spec: CommandSpec,
     buffer: OutputBuffer,
     status: CommandStatus,
     scroll_offset: usize,
     horizontal_scroll: usize,
     auto_scroll: bool,
     visible_lines: usize,
+    /// Columns visible between the output area's borders
+    visible_columns: usize,
+    pinned_lines: Vec<OutputLine>,
+    selection: Option<LineSelection>,
+    cursor: Option<OutputCursor>,
+    /// Where the marked text starts (`v` in Cursor mode)
+    cursor_mark: Option<OutputCursor>,
+    /// Number of runs started since the last reset
+    run_count: usize,
+    /// When the next scheduled start is due
+    next_run: Option<Instant>,
+    /// When the current (or last) run started
+    started_at: Option<Instant>,
+    /// When the last run finished (unset while running)
+    finished_at: Option<Instant>,
+    /// Wall-clock start times of the runs whose output is in the buffer
+    run_starts: Vec<DateTime<Local>>,
+    /// Outcomes of the last finished runs, oldest first, kept across restarts
+    run_history: VecDeque<RunRecord>,
+    /// Wall-clock time the last run ended, for the status bar
+    ended_at: Option<DateTime<Local>>,
+    /// When the current run last printed a line (or started)
+    last_output: Option<Instant>,
+    /// Output lines printed by the current (or last) run
+    run_lines: u64,
+    /// Output lines printed by all runs, kept across restarts
+    line_counts: LineCounts,
+    /// Lines dropped because the UI could not keep up
+    dropped_lines: u64,
+    /// Output lines that arrived while another tab was shown
+    unread_lines: u64,
+    /// Free-text note about the tab, kept across restarts
+    note: Option<String>,
+    /// Address the current run listens on, once its first port is open
+    ready_url: Option<String>,
+    /// Why the last run couldn't be started, until the next one starts
+    spawn_failure: Option<SpawnFailure>,
+    /// Whether the output of a `quiet` command is shown anyway
+    expanded: bool,
+    /// How the log prefixes of lines are shown
+    prefix_fold: PrefixFold,
+    /// Whether the visible lines are aligned into columns
+    table_view: bool,
+    /// Hide the lines read before this time (`:since`, `:last`)
+    since: Option<DateTime<Local>>,
+    /// Values of the template variables the command uses
+    variables: Vec<(Variable, String)>,
+    /// Number of cut lines expanded, so cached rows are rebuilt
+    expanded_lines: u64,
+    /// Rendered output rows from the last frame
+    line_cache: LineCache,
 }
 
 impl Tab {
     /// Create a new tab
-    pub fn new(command: String, max_buffer_lines: usize) -> Self {
+    pub fn new(spec: CommandSpec, max_buffer_lines: usize) -> Self {
         Self {
-            command,
+            auto_scroll: spec.follows_output(),
+            prefix_fold: spec.fold_prefix.unwrap_or_default(),
+            table_view: spec.table,
+            spec,
             buffer: OutputBuffer::new(max_buffer_lines),
             status: CommandStatus::Running,
             scroll_offset: 0,
             horizontal_scroll: 0,
-            auto_scroll: true,
             visible_lines: 0,
+            visible_columns: 0,
+            pinned_lines: Vec::new(),
+            selection: None,
+            cursor: None,
+            cursor_mark: None,
+            run_count: 0,
+            next_run: None,
+            started_at: None,
+            finished_at: None,
+            run_starts: Vec::new(),
+            run_history: VecDeque::new(),
+            ended_at: None,
+            last_output: None,
+            run_lines: 0,
+            line_counts: LineCounts::default(),
+            dropped_lines: 0,
+            unread_lines: 0,
+            note: None,
+            ready_url: None,
+            spawn_failure: None,
+            expanded: false,
+            since: None,
+            variables: Vec::new(),
+            expanded_lines: 0,
+            line_cache: LineCache::default(),
         }
     }
 
     /// Get the command string
     pub fn command(&self) -> &str {
-        &self.command
+        &self.spec.command
+    }
+
+    /// Get the command specification
+    pub fn spec(&self) -> &CommandSpec {
+        &self.spec
     }
 
-    /// Get truncated command name for tab display
+    /// Apply the settings of the command's entry in a reloaded config that
+    /// changed from `old` to `new` (see [`CommandSpec::reload`])
+    pub fn reload_spec(&mut self, old: &CommandSpec, new: &CommandSpec) {
+        self.spec.reload(old, new);
+        if old.fold_prefix != new.fold_prefix {
+            self.prefix_fold = self.spec.fold_prefix.unwrap_or_default();
+        }
+        if old.table != new.table {
+            self.table_view = self.spec.table;
+        }
+    }
+
+    /// Get the full tab name (or command, followed file or source address,
+    /// if unnamed)
+    pub fn name(&self) -> &str {
+        let spec = &self.spec;
+        match (&spec.name, &spec.tail, &spec.source) {
+            (Some(name), _, _) => name,
+            (None, Some(path), _) => path.to_str().unwrap_or_default(),
+            (None, None, Some(source)) => source.as_str(),
+            (None, None, None) => &spec.command,
+        }
+    }
+
+    /// Get truncated tab title (name, or derived from the command) for display
     pub fn display_name(&self) -> String {
-        if self.command.len() <= MAX_TAB_NAME_LEN {
-            self.command.clone()
-        } else {
-            format!("{}...", &self.command[..MAX_TAB_NAME_LEN])
+        let name = self.spec.title();
+        match name.char_indices().nth(MAX_TAB_NAME_LEN) {
+            Some((end, _)) => format!("{}...", &name[..end]),
+            None => name.to_string(),
         }
     }
 
@@ -60,12 +268,277 @@ impl Tab {
 
     /// Set command status
     pub fn set_status(&mut self, status: CommandStatus) {
+        if status.is_done() && self.finished_at.is_none() {
+            self.finished_at = Some(Instant::now());
+        }
         self.status = status;
     }
 
+    /// Get why the last run couldn't be started, if it couldn't
+    pub fn spawn_failure(&self) -> Option<&SpawnFailure> {
+        self.spawn_failure.as_ref()
+    }
+
+    /// Record why the current run couldn't be started
+    pub fn set_spawn_failure(&mut self, failure: SpawnFailure) {
+        self.spawn_failure = Some(failure);
+    }
+
+    /// Get how long the current run has been running (or the last run took)
+    pub fn elapsed(&self) -> Option<Duration> {
+        let started_at = self.started_at?;
+        let end = self.finished_at.unwrap_or_else(Instant::now);
+        Some(end.saturating_duration_since(started_at))
+    }
+
+    /// Get the number of runs started since the last reset
+    pub fn run_count(&self) -> usize {
+        self.run_count
+    }
+
+    /// Get the values of the template variables the command uses
+    pub fn variables(&self) -> &[(Variable, String)] {
+        &self.variables
+    }
+
+    /// Set the values of the template variables
+    pub fn set_variables(&mut self, variables: Vec<(Variable, String)>) {
+        self.variables = variables;
+    }
+
+    /// Get when the next scheduled start is due
+    pub fn next_run(&self) -> Option<Instant> {
+        self.next_run
+    }
+
+    /// Set when the next scheduled start is due
+    pub fn set_next_run(&mut self, next_run: Option<Instant>) {
+        self.next_run = next_run;
+    }
+
+    /// Begin a new run of the command
+    ///
+    /// Every run after the first starts with a divider showing the run
+    /// number and start time.
+    pub fn begin_run(&mut self, started_at: DateTime<Local>) {
+        if self.run_count > 0 {
+            let noun = if self.spec.until_success {
+                "Attempt"
+            } else {
+                "Run"
+            };
+            let label = format!(
+                "{} #{} · {}",
+                noun,
+                self.run_count + 1,
+                started_at.format("%H:%M:%S")
+            );
+            self.push_output(OutputLine::divider(&label).at(started_at));
+        }
+        self.run_count += 1;
+        self.run_starts.push(started_at);
+        self.ready_url = None;
+        self.spawn_failure = None;
+        self.status = CommandStatus::Running;
+        self.next_run = None;
+        self.started_at = Some(Instant::now());
+        self.finished_at = None;
+        self.ended_at = None;
+        self.last_output = self.started_at;
+        self.run_lines = 0;
+    }
+
+    /// Get when the run that printed a line at `time` started
+    ///
+    /// Returns `None` for lines read before the first run (or without runs,
+    /// like `tail` tabs).
+    pub fn run_start(&self, time: DateTime<Local>) -> Option<DateTime<Local>> {
+        let runs = self.run_starts.partition_point(|start| *start <= time);
+        runs.checked_sub(1).map(|run| self.run_starts[run])
+    }
+
+    /// End the current run with a final status at the given time
+    ///
+    /// The run is added to the run history if it was begun with `begin_run`.
+    pub fn end_run(&mut self, status: CommandStatus, ended_at: DateTime<Local>) {
+        self.set_status(status);
+        self.ended_at = Some(ended_at);
+        if let (Some(&started_at), Some(duration)) = (self.run_starts.last(), self.elapsed()) {
+            if self.run_history.len() == MAX_RUN_HISTORY {
+                self.run_history.pop_front();
+            }
+            self.run_history.push_back(RunRecord {
+                started_at,
+                duration,
+                status: self.status.clone(),
+            });
+        }
+    }
+
+    /// Get the outcomes of the last finished runs, oldest first
+    pub fn run_history(&self) -> &VecDeque<RunRecord> {
+        &self.run_history
+    }
+
+    /// Get when the last run ended, if it was ended with `end_run`
+    pub fn ended_at(&self) -> Option<DateTime<Local>> {
+        self.ended_at
+    }
+
+    /// Get the number of output lines printed by the current (or last) run
+    pub fn run_lines(&self) -> u64 {
+        self.run_lines
+    }
+
+    /// Get the address the current run listens on, once it is ready
+    pub fn ready_url(&self) -> Option<&str> {
+        self.ready_url.as_deref()
+    }
+
+    /// Note that the current run listens on `url`, with a line in the output
+    pub fn set_ready(&mut self, url: String) {
+        self.push_output(OutputLine::divider(&format!("listening on {}", url)));
+        self.ready_url = Some(url);
+    }
+
+    /// Get when the tab last printed a line or started a run (`None` if never started)
+    pub fn last_activity(&self) -> Option<Instant> {
+        self.last_output
+    }
+
+    /// Get how long the current run has printed nothing (`None` if never started)
+    pub fn silent_for(&self, now: Instant) -> Option<Duration> {
+        self.last_output
+            .map(|last_output| now.saturating_duration_since(last_output))
+    }
+
+    /// Restart the silence timer without any output
+    pub fn reset_silence(&mut self) {
+        self.last_output = Some(Instant::now());
+    }
+
+    /// Get the cache of rendered output rows
+    pub(crate) fn line_cache(&self) -> &LineCache {
+        &self.line_cache
+    }
+
+    /// Get the number of lines dropped because the UI could not keep up
+    pub fn dropped_lines(&self) -> u64 {
+        self.dropped_lines
+    }
+
+    /// Get the output lines read over all runs
+    pub fn line_counts(&self) -> LineCounts {
+        self.line_counts
+    }
+
+    /// Get the number of output lines that arrived while another tab was shown
+    pub fn unread_lines(&self) -> u64 {
+        self.unread_lines
+    }
+
+    /// Count an output line that arrived while another tab was shown
+    pub fn mark_unread(&mut self) {
+        self.unread_lines += 1;
+    }
+
+    /// Forget the unread lines, once the tab is shown
+    pub fn mark_read(&mut self) {
+        self.unread_lines = 0;
+    }
+
+    /// Check if only a summary of the output is shown
+    ///
+    /// `quiet` commands are collapsed until they fail or the output is
+    /// expanded with `toggle_expanded`.
+    pub fn collapsed(&self) -> bool {
+        let failed = match &self.status {
+            CommandStatus::Finished { exit_code } => *exit_code != 0,
+            CommandStatus::Signaled { .. } | CommandStatus::Failed { .. } => true,
+            CommandStatus::Waiting | CommandStatus::Running => false,
+        };
+        self.spec.quiet && !self.expanded && !failed
+    }
+
+    /// Show or hide the output of a `quiet` command
+    pub fn toggle_expanded(&mut self) {
+        self.expanded = !self.expanded;
+    }
+
+    /// Get how the log prefixes of lines are shown
+    pub fn prefix_fold(&self) -> PrefixFold {
+        self.prefix_fold
+    }
+
+    /// Switch to the next way of showing log prefixes (shown, dimmed, hidden)
+    pub fn cycle_prefix_fold(&mut self) {
+        self.prefix_fold = self.prefix_fold.next();
+    }
+
+    /// Check if the visible lines are aligned into columns
+    pub fn table_view(&self) -> bool {
+        self.table_view
+    }
+
+    /// Switch between aligning the visible lines into columns and showing them as printed
+    pub fn toggle_table_view(&mut self) {
+        self.table_view = !self.table_view;
+    }
+
+    /// Get the summary shown instead of a collapsed tab's output
+    pub fn quiet_summary(&self) -> String {
+        let lines = match self.run_lines {
+            1 => "1 line".to_string(),
+            count => format!("{} lines", format_count(count)),
+        };
+        match &self.status {
+            CommandStatus::Waiting => "waiting to start".to_string(),
+            CommandStatus::Running => format!("{} so far", lines),
+            CommandStatus::Finished { exit_code } => format!("{}, exit {}", lines, exit_code),
+            status => format!("{}, {}", lines, status),
+        }
+    }
+
+    /// Get the tab's note
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    /// Set the tab's note (`None` or blank to remove it)
+    pub fn set_note(&mut self, note: Option<String>) {
+        self.note = note.filter(|note| !note.trim().is_empty());
+    }
+
+    /// Record dropped lines and note the gap in the output
+    pub fn record_dropped(&mut self, count: u64) {
+        self.dropped_lines += count;
+        let label = format!("dropped {} lines (display too slow)", format_count(count));
+        self.push_output(OutputLine::divider(&label));
+    }
+
+    /// Insert a timestamped marker line into the output
+    pub fn insert_marker(&mut self, label: Option<&str>, at: DateTime<Local>) {
+        let time = at.format("%H:%M:%S");
+        let text = match label {
+            Some(label) => format!("Mark: {} · {}", label, time),
+            None => format!("Mark · {}", time),
+        };
+        self.push_output(OutputLine::marker(&text).at(at));
+    }
+
     /// Add an output line
     pub fn push_output(&mut self, line: OutputLine) {
+        if line.kind.is_output() {
+            self.run_lines += 1;
+            self.line_counts.lines += 1;
+            match line.severity() {
+                Some(Severity::Error) => self.line_counts.errors += 1,
+                Some(Severity::Warning) => self.line_counts.warnings += 1,
+                None => {}
+            }
+        }
         self.buffer.push(line);
+        self.last_output = Some(Instant::now());
         if self.auto_scroll {
             self.scroll_to_bottom();
         }
@@ -76,11 +549,29 @@ impl Tab {
         &self.buffer
     }
 
+    /// Keep all but the newest `lines` output lines compressed
+    #[cfg(feature = "compress")]
+    pub fn set_compress_after(&mut self, lines: usize) {
+        self.buffer.set_compress_after(lines);
+    }
+
+    /// Compress old output lines that are out of view again
+    #[cfg(feature = "compress")]
+    pub fn compact_buffer(&mut self) {
+        let top = self.scroll_offset;
+        self.buffer.compact(top..top + self.visible_lines.max(1));
+    }
+
     /// Set the number of visible lines
     pub fn set_visible_lines(&mut self, lines: usize) {
         self.visible_lines = lines;
     }
 
+    /// Get the number of visible lines
+    pub fn visible_lines(&self) -> usize {
+        self.visible_lines
+    }
+
     /// Get current scroll offset
     pub fn scroll_offset(&self) -> usize {
         self.scroll_offset
@@ -96,7 +587,7 @@ impl Tab {
 
     /// Scroll up by one line
     pub fn scroll_up(&mut self) {
-        self.scroll_offset = self.scroll_offset.saturating_sub(1);
+        self.scroll_offset = self.scroll_offset.saturating_sub(1).max(self.first_line());
     }
 
     /// Scroll down by half page
@@ -109,12 +600,15 @@ impl Tab {
     /// Scroll up by half page
     pub fn scroll_half_page_up(&mut self) {
         let half_page = self.visible_lines / 2;
-        self.scroll_offset = self.scroll_offset.saturating_sub(half_page);
+        self.scroll_offset = self
+            .scroll_offset
+            .saturating_sub(half_page)
+            .max(self.first_line());
     }
 
     /// Scroll to top
     pub fn scroll_to_top(&mut self) {
-        self.scroll_offset = 0;
+        self.scroll_offset = self.first_line();
     }
 
     /// Scroll to bottom
@@ -125,7 +619,27 @@ impl Tab {
     /// Scroll to specified line
     pub fn scroll_to_line(&mut self, line: usize) {
         let max_offset = self.max_scroll_offset();
-        self.scroll_offset = line.min(max_offset);
+        self.scroll_offset = line.clamp(self.first_line(), max_offset);
+    }
+
+    /// Get the time before which lines are hidden
+    pub fn since(&self) -> Option<DateTime<Local>> {
+        self.since
+    }
+
+    /// Hide the lines read before `since` (`None` to show all lines)
+    ///
+    /// Lines are read in order, so the shown lines are the end of the
+    /// buffer; scrolling stops at the first of them.
+    pub fn set_since(&mut self, since: Option<DateTime<Local>>) {
+        self.since = since;
+        if self.auto_scroll {
+            self.scroll_to_bottom();
+        } else {
+            self.scroll_offset = self
+                .scroll_offset
+                .clamp(self.first_line(), self.max_scroll_offset());
+        }
     }
 
     /// Check if auto scroll is enabled
@@ -163,20 +677,309 @@ impl Tab {
         self.horizontal_scroll = 0;
     }
 
+    /// Set the number of columns visible between the output borders
+    pub fn set_visible_columns(&mut self, columns: usize) {
+        self.visible_columns = columns;
+    }
+
+    /// Scroll horizontally so the columns `first..last` of a line's row are
+    /// visible (see `Renderer::output_column`)
+    ///
+    /// The offset is kept if the range is already on screen; otherwise the
+    /// range is brought in as far left as possible, showing the line from
+    /// its start when it fits.
+    pub fn scroll_to_columns(&mut self, first: usize, last: usize) {
+        if self.visible_columns == 0 {
+            return;
+        }
+        if first >= self.horizontal_scroll && last <= self.horizontal_scroll + self.visible_columns
+        {
+            return;
+        }
+        self.horizontal_scroll = last.saturating_sub(self.visible_columns).min(first);
+    }
+
+    /// Get pinned lines
+    pub fn pinned_lines(&self) -> &[OutputLine] {
+        &self.pinned_lines
+    }
+
+    /// Pin the line at the specified index, or unpin it if already pinned
+    ///
+    /// Lines are compared by their plain text. When the limit is reached,
+    /// the oldest pinned line is dropped.
+    pub fn toggle_pin(&mut self, index: usize) {
+        let Some(line) = self.buffer.get(index).map(|l| l.to_line()) else {
+            return;
+        };
+
+        let plain = line.plain();
+        if let Some(pos) = self.pinned_lines.iter().position(|l| l.plain() == plain) {
+            self.pinned_lines.remove(pos);
+            return;
+        }
+
+        if self.pinned_lines.len() >= MAX_PINNED_LINES {
+            self.pinned_lines.remove(0);
+        }
+        self.pinned_lines.push(line);
+    }
+
+    /// Show the full text of the line at the specified index if it was cut
+    pub fn expand_line(&mut self, index: usize) {
+        if self.buffer.expand(index) {
+            self.expanded_lines += 1;
+        }
+    }
+
+    /// Get the number of cut lines expanded so far
+    pub fn expanded_lines(&self) -> u64 {
+        self.expanded_lines
+    }
+
+    /// Remove all pinned lines
+    pub fn clear_pins(&mut self) {
+        self.pinned_lines.clear();
+    }
+
+    /// Height of the pinned header area (pinned lines plus separator)
+    pub fn pinned_area_height(&self) -> usize {
+        if self.pinned_lines.is_empty() {
+            0
+        } else {
+            self.pinned_lines.len() + 1
+        }
+    }
+
+    /// Get the current line selection
+    pub fn selection(&self) -> Option<LineSelection> {
+        self.selection
+    }
+
+    /// Start a line selection at the specified line
+    pub fn start_selection(&mut self, line: usize) {
+        let line = line.min(self.buffer.len().saturating_sub(1));
+        self.selection = Some(LineSelection {
+            anchor: line,
+            cursor: line,
+        });
+    }
+
+    /// Clear the line selection
+    pub fn clear_selection(&mut self) {
+        self.selection = None;
+    }
+
+    /// Move the selection cursor down by one line
+    pub fn extend_selection_down(&mut self) {
+        let last = self.buffer.len().saturating_sub(1);
+        if let Some(selection) = &mut self.selection {
+            selection.cursor = (selection.cursor + 1).min(last);
+        }
+        self.scroll_to_selection_cursor();
+    }
+
+    /// Move the selection cursor up by one line
+    pub fn extend_selection_up(&mut self) {
+        if let Some(selection) = &mut self.selection {
+            selection.cursor = selection.cursor.saturating_sub(1);
+        }
+        self.scroll_to_selection_cursor();
+    }
+
+    /// Adjust scroll offset so that the selection cursor stays visible
+    fn scroll_to_selection_cursor(&mut self) {
+        if let Some(selection) = self.selection {
+            self.scroll_into_view(selection.cursor);
+        }
+    }
+
+    /// Adjust scroll offset so that a line is visible
+    fn scroll_into_view(&mut self, line: usize) {
+        if line < self.scroll_offset {
+            self.scroll_offset = line.max(self.first_line());
+        } else if self.visible_lines > 0 && line >= self.scroll_offset + self.visible_lines {
+            self.scroll_offset = line + 1 - self.visible_lines;
+        }
+    }
+
+    /// Get the output cursor
+    pub fn cursor(&self) -> Option<OutputCursor> {
+        self.cursor
+    }
+
+    /// Place the output cursor at the start of a line
+    pub fn start_cursor(&mut self, line: usize) {
+        let line = line.min(self.buffer.len().saturating_sub(1));
+        self.cursor = Some(OutputCursor { line, column: 0 });
+        self.cursor_mark = None;
+    }
+
+    /// Remove the output cursor
+    pub fn clear_cursor(&mut self) {
+        self.cursor = None;
+        self.cursor_mark = None;
+    }
+
+    /// Start marking text at the cursor, or stop if already marking
+    pub fn toggle_cursor_mark(&mut self) {
+        self.cursor_mark = match self.cursor_mark {
+            Some(_) => None,
+            None => self.cursor,
+        };
+    }
+
+    /// Get the ends of the marked text, earlier end first (both included)
+    pub fn marked_range(&self) -> Option<(OutputCursor, OutputCursor)> {
+        let (mark, cursor) = (self.cursor_mark?, self.cursor?);
+        Some((mark.min(cursor), mark.max(cursor)))
+    }
+
+    /// Get the plain text between the mark and the cursor, both included
+    pub fn marked_text(&self) -> Option<String> {
+        let (start, end) = self.marked_range()?;
+        let lines: Vec<String> = (start.line..=end.line)
+            .filter_map(|index| {
+                let text = self.buffer.get(index)?.plain();
+                let first = if index == start.line { start.column } else { 0 };
+                let last = if index == end.line {
+                    end.column + 1
+                } else {
+                    usize::MAX
+                };
+                Some(
+                    text.chars()
+                        .skip(first)
+                        .take(last.saturating_sub(first))
+                        .collect(),
+                )
+            })
+            .collect();
+        Some(lines.join("\n"))
+    }
+
+    /// Move the cursor one line up or down, keeping it in view
+    pub fn move_cursor_vertical(&mut self, up: bool) {
+        let last = self.buffer.len().saturating_sub(1);
+        let Some(cursor) = &mut self.cursor else {
+            return;
+        };
+        cursor.line = if up {
+            cursor.line.saturating_sub(1)
+        } else {
+            (cursor.line + 1).min(last)
+        };
+        let line = cursor.line;
+        self.clamp_cursor_column();
+        self.scroll_into_view(line);
+    }
+
+    /// Move the cursor one character left or right within its line
+    pub fn move_cursor_horizontal(&mut self, left: bool) {
+        if let Some(cursor) = &mut self.cursor {
+            cursor.column = if left {
+                cursor.column.saturating_sub(1)
+            } else {
+                cursor.column + 1
+            };
+        }
+        self.clamp_cursor_column();
+    }
+
+    /// Move the cursor to the start (`true`) or end of its line
+    pub fn move_cursor_to_line_edge(&mut self, start: bool) {
+        if let Some(cursor) = &mut self.cursor {
+            cursor.column = if start { 0 } else { usize::MAX };
+        }
+        self.clamp_cursor_column();
+    }
+
+    /// Move the cursor to the next (`true`) or previous word in its line
+    pub fn move_cursor_word(&mut self, forward: bool) {
+        let Some(cursor) = self.cursor else {
+            return;
+        };
+        let text = self.cursor_line_text();
+        let column = if forward {
+            next_word_start(&text, cursor.column)
+        } else {
+            prev_word_start(&text, cursor.column)
+        };
+        if let (Some(column), Some(cursor)) = (column, &mut self.cursor) {
+            cursor.column = column;
+        }
+    }
+
+    /// Byte offset of the cursor within its line's plain text
+    pub fn cursor_byte_offset(&self) -> usize {
+        let Some(cursor) = self.cursor else {
+            return 0;
+        };
+        let text = self.cursor_line_text();
+        text.char_indices()
+            .nth(cursor.column)
+            .map_or(text.len(), |(i, _)| i)
+    }
+
+    /// Plain text of the line the cursor is on
+    fn cursor_line_text(&self) -> String {
+        self.cursor
+            .and_then(|cursor| self.buffer.get(cursor.line))
+            .map(|line| line.plain())
+            .unwrap_or_default()
+    }
+
+    /// Keep the cursor column on a character of its line
+    fn clamp_cursor_column(&mut self) {
+        let len = self.cursor_line_text().chars().count();
+        if let Some(cursor) = &mut self.cursor {
+            cursor.column = cursor.column.min(len.saturating_sub(1));
+        }
+    }
+
     /// Reset the tab to initial state
     ///
-    /// Clears the buffer, resets status to Running, and resets scroll positions.
+    /// Clears the output, resets status to Running, and resets scroll positions.
+    /// Pinned lines and the run count are kept so they survive restarts.
     pub fn reset(&mut self) {
-        self.buffer.clear();
+        self.clear_output();
+        self.reset_view();
         self.status = CommandStatus::Running;
+        self.next_run = None;
+    }
+
+    /// Clear the output of previous runs, with the selection and cursor in it
+    ///
+    /// The scroll positions are kept, so the view stays anchored where it
+    /// was while new output arrives.
+    pub fn clear_output(&mut self) {
+        self.buffer.clear();
+        self.run_starts.clear();
+        self.selection = None;
+        self.cursor = None;
+        self.cursor_mark = None;
+        self.dropped_lines = 0;
+    }
+
+    /// Scroll back to the top left, following new output again unless the
+    /// command starts at the top
+    pub fn reset_view(&mut self) {
         self.scroll_offset = 0;
         self.horizontal_scroll = 0;
-        self.auto_scroll = true;
+        self.auto_scroll = self.spec.follows_output();
+    }
+
+    /// Get the index of the first line shown (after `since`)
+    fn first_line(&self) -> usize {
+        self.since.map_or(0, |since| self.buffer.first_since(since))
     }
 
     /// Calculate maximum scroll offset
     fn max_scroll_offset(&self) -> usize {
-        self.buffer.len().saturating_sub(self.visible_lines)
+        self.buffer
+            .len()
+            .saturating_sub(self.visible_lines)
+            .max(self.first_line())
     }
 }
 
@@ -184,6 +987,8 @@ impl Tab {
 mod tests {
     use super::*;
     use crate::buffer::OutputKind;
+    use crate::command::StartAt;
+    use chrono::TimeZone;
     use rstest::rstest;
 
     #[test]
@@ -195,13 +1000,45 @@ mod tests {
     }
 
     #[rstest]
-    #[case("cargo build", "cargo build")]
-    #[case("cargo build --release --features foo bar", "cargo build --releas...")]
+    #[case("cargo build", "cargo:build")]
+    #[case("./bin/a-very-long-server-name --port 80", "a-very-long-server-n...")]
     fn tab_display_name_returns_correct_name(#[case] command: &str, #[case] expected: &str) {
         let tab = Tab::new(command.into(), 100);
         assert_eq!(tab.display_name(), expected);
     }
 
+    #[rstest]
+    #[case("データベースサーバー", "データベースサーバー")]
+    #[case(
+        "データベースサーバーの監視プロセスを起動",
+        "データベースサーバーの監視プロセスを起動"
+    )]
+    #[case(
+        "データベースサーバーの監視プロセスを起動する",
+        "データベースサーバーの監視プロセスを起動..."
+    )]
+    fn tab_display_name_truncates_multibyte_names_by_chars(
+        #[case] name: &str,
+        #[case] expected: &str,
+    ) {
+        let spec = CommandSpec {
+            name: Some(name.into()),
+            ..CommandSpec::new("db")
+        };
+        assert_eq!(Tab::new(spec, 100).display_name(), expected);
+    }
+
+    #[test]
+    fn tab_display_name_prefers_spec_name() {
+        let spec = CommandSpec {
+            name: Some("web".into()),
+            ..CommandSpec::new("npm run dev")
+        };
+        let tab = Tab::new(spec, 100);
+        assert_eq!(tab.display_name(), "web");
+        assert_eq!(tab.command(), "npm run dev");
+    }
+
     #[test]
     fn tab_scroll_down_increases_offset() {
         let mut tab = Tab::new("test".into(), 100);
@@ -348,6 +1185,32 @@ mod tests {
         assert_eq!(tab.scroll_offset(), expected_offset);
     }
 
+    #[rstest]
+    #[case(StartAt::Bottom, true, 15)]
+    #[case(StartAt::Top, false, 0)]
+    fn tab_starts_where_the_spec_says_also_after_reset(
+        #[case] start_at: StartAt,
+        #[case] follows: bool,
+        #[case] expected_offset: usize,
+    ) {
+        let spec = CommandSpec {
+            start_at: Some(start_at),
+            ..CommandSpec::new("make")
+        };
+        let mut tab = Tab::new(spec, 100);
+        tab.set_visible_lines(5);
+        assert_eq!(tab.auto_scroll(), follows);
+
+        tab.toggle_auto_scroll();
+        tab.reset();
+        for i in 0..20 {
+            tab.push_output(OutputLine::new(OutputKind::Stdout, format!("line{}", i)));
+        }
+
+        assert_eq!(tab.auto_scroll(), follows);
+        assert_eq!(tab.scroll_offset(), expected_offset);
+    }
+
     #[test]
     fn tab_set_status_updates_status() {
         let mut tab = Tab::new("test".into(), 100);
@@ -409,6 +1272,35 @@ mod tests {
         assert_eq!(tab.horizontal_scroll(), 0);
     }
 
+    #[rstest]
+    // Fits from the start of the line: no scrolling
+    #[case(0, 4, 3, 0)]
+    // Past the right edge: match ends at the right border
+    #[case(0, 30, 5, 24)]
+    // Left of the view: scroll back so the whole line start shows again
+    #[case(20, 4, 3, 0)]
+    // Already visible: offset kept
+    #[case(20, 25, 5, 20)]
+    // Wider than the view: start of the match at the left border
+    #[case(0, 10, 30, 19)]
+    fn tab_scroll_to_columns_brings_match_into_view(
+        #[case] offset: usize,
+        #[case] start: usize,
+        #[case] len: usize,
+        #[case] expected: usize,
+    ) {
+        let mut tab = Tab::new("test".into(), 100);
+        tab.set_visible_columns(20);
+        for _ in 0..offset {
+            tab.scroll_right();
+        }
+
+        // After a `[stdout] ` prefix
+        tab.scroll_to_columns(9 + start, 9 + start + len);
+
+        assert_eq!(tab.horizontal_scroll(), expected);
+    }
+
     #[test]
     fn tab_reset_clears_buffer_and_resets_state() {
         let mut tab = Tab::new("test".into(), 100);
@@ -433,4 +1325,373 @@ mod tests {
         assert_eq!(tab.horizontal_scroll(), 0);
         assert!(tab.auto_scroll());
     }
+
+    #[test]
+    fn tab_toggle_pin_pins_and_unpins_line() {
+        let mut tab = Tab::new("test".into(), 100);
+        for i in 0..5 {
+            tab.push_output(OutputLine::new(OutputKind::Stdout, format!("line{}", i)));
+        }
+
+        tab.toggle_pin(2);
+        assert_eq!(tab.pinned_lines().len(), 1);
+        assert_eq!(tab.pinned_lines()[0].plain(), "line2");
+        assert_eq!(tab.pinned_area_height(), 2);
+
+        tab.toggle_pin(2);
+        assert!(tab.pinned_lines().is_empty());
+        assert_eq!(tab.pinned_area_height(), 0);
+    }
+
+    #[test]
+    fn tab_toggle_pin_drops_oldest_when_limit_reached() {
+        let mut tab = Tab::new("test".into(), 100);
+        for i in 0..10 {
+            tab.push_output(OutputLine::new(OutputKind::Stdout, format!("line{}", i)));
+        }
+
+        for i in 0..=MAX_PINNED_LINES {
+            tab.toggle_pin(i);
+        }
+
+        assert_eq!(tab.pinned_lines().len(), MAX_PINNED_LINES);
+        assert_eq!(tab.pinned_lines()[0].plain(), "line1");
+    }
+
+    #[test]
+    fn tab_toggle_pin_ignores_out_of_range_index() {
+        let mut tab = Tab::new("test".into(), 100);
+        tab.toggle_pin(0);
+        assert!(tab.pinned_lines().is_empty());
+    }
+
+    #[test]
+    fn tab_selection_extends_in_both_directions() {
+        let mut tab = Tab::new("test".into(), 100);
+        tab.set_visible_lines(5);
+        for i in 0..20 {
+            tab.push_output(OutputLine::new(OutputKind::Stdout, format!("line{}", i)));
+        }
+        tab.scroll_to_top();
+
+        tab.start_selection(3);
+        tab.extend_selection_down();
+        tab.extend_selection_down();
+        let selection = tab.selection().unwrap();
+        assert_eq!((selection.start(), selection.end()), (3, 5));
+
+        for _ in 0..4 {
+            tab.extend_selection_up();
+        }
+        let selection = tab.selection().unwrap();
+        assert_eq!((selection.start(), selection.end()), (1, 3));
+        assert!(selection.contains(2));
+        assert!(!selection.contains(4));
+    }
+
+    #[test]
+    fn tab_selection_keeps_cursor_visible() {
+        let mut tab = Tab::new("test".into(), 100);
+        tab.set_visible_lines(5);
+        for i in 0..20 {
+            tab.push_output(OutputLine::new(OutputKind::Stdout, format!("line{}", i)));
+        }
+        tab.scroll_to_top();
+
+        tab.start_selection(4);
+        tab.extend_selection_down();
+        assert_eq!(tab.scroll_offset(), 1);
+
+        tab.clear_selection();
+        assert!(tab.selection().is_none());
+    }
+
+    #[test]
+    fn tab_cursor_moves_within_lines_and_stays_visible() {
+        let mut tab = Tab::new("test".into(), 100);
+        tab.set_visible_lines(2);
+        tab.push_output(OutputLine::new(
+            OutputKind::Stdout,
+            "error in src/main.rs".into(),
+        ));
+        tab.push_output(OutputLine::new(OutputKind::Stdout, "ok".into()));
+        tab.push_output(OutputLine::new(OutputKind::Stdout, "done".into()));
+        tab.scroll_to_top();
+
+        tab.start_cursor(0);
+        tab.move_cursor_word(true);
+        tab.move_cursor_word(true);
+        assert_eq!(tab.cursor(), Some(OutputCursor { line: 0, column: 9 }));
+
+        // The column is clamped to the shorter line
+        tab.move_cursor_vertical(false);
+        assert_eq!(tab.cursor(), Some(OutputCursor { line: 1, column: 1 }));
+        tab.move_cursor_vertical(false);
+        assert_eq!(tab.scroll_offset(), 1);
+
+        tab.move_cursor_to_line_edge(true);
+        tab.move_cursor_horizontal(false);
+        tab.move_cursor_horizontal(true);
+        tab.move_cursor_horizontal(true);
+        assert_eq!(tab.cursor(), Some(OutputCursor { line: 2, column: 0 }));
+
+        tab.clear_cursor();
+        assert!(tab.cursor().is_none());
+    }
+
+    #[test]
+    fn tab_marked_text_spans_from_mark_to_cursor() {
+        let mut tab = Tab::new("test".into(), 100);
+        tab.push_output(OutputLine::new(
+            OutputKind::Stdout,
+            "GET /health 200".into(),
+        ));
+        tab.push_output(OutputLine::new(OutputKind::Stdout, "ユーザー 42".into()));
+
+        tab.start_cursor(0);
+        tab.move_cursor_word(true);
+        tab.toggle_cursor_mark();
+        assert_eq!(tab.marked_text().as_deref(), Some("h"));
+        tab.move_cursor_vertical(false);
+        assert_eq!(tab.marked_text().as_deref(), Some("health 200\nユーザー 4"));
+
+        // Marking backwards gives the same text order
+        tab.move_cursor_vertical(true);
+        tab.move_cursor_to_line_edge(true);
+        assert_eq!(tab.marked_text().as_deref(), Some("GET /h"));
+
+        tab.toggle_cursor_mark();
+        assert!(tab.marked_text().is_none());
+    }
+
+    #[test]
+    fn tab_begin_run_inserts_divider_after_first_run() {
+        let mut tab = Tab::new("test".into(), 100);
+        let started_at = Local.with_ymd_and_hms(2026, 1, 2, 12, 34, 56).unwrap();
+
+        tab.begin_run(started_at);
+        tab.push_output(OutputLine::new(OutputKind::Stdout, "first".into()));
+        tab.set_status(CommandStatus::Finished { exit_code: 0 });
+        tab.set_next_run(Some(Instant::now()));
+        tab.begin_run(started_at);
+
+        assert_eq!(tab.run_count(), 2);
+        assert_eq!(tab.status(), &CommandStatus::Running);
+        assert!(tab.next_run().is_none());
+        assert_eq!(tab.buffer().len(), 2);
+        let divider = tab.buffer().get(1).unwrap();
+        assert_eq!(divider.kind, OutputKind::Divider);
+        assert!(divider.plain().contains("Run #2 · 12:34:56"));
+    }
+
+    #[test]
+    fn tab_run_start_finds_run_of_line() {
+        let at = |minute| Local.with_ymd_and_hms(2026, 3, 1, 14, minute, 0).unwrap();
+        let mut tab = Tab::new("test".into(), 100);
+        tab.begin_run(at(10));
+        tab.begin_run(at(20));
+
+        assert_eq!(tab.run_start(at(5)), None);
+        assert_eq!(tab.run_start(at(15)), Some(at(10)));
+        assert_eq!(tab.run_start(at(20)), Some(at(20)));
+        assert_eq!(tab.buffer().get(0).unwrap().time(), at(20));
+
+        tab.clear_output();
+        assert_eq!(tab.run_start(at(25)), None);
+    }
+
+    #[test]
+    fn tab_elapsed_stops_when_run_finishes() {
+        let mut tab = Tab::new("test".into(), 100);
+        assert!(tab.elapsed().is_none());
+
+        tab.begin_run(Local::now());
+        tab.set_status(CommandStatus::Finished { exit_code: 0 });
+        let elapsed = tab.elapsed().unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+
+        assert_eq!(tab.elapsed(), Some(elapsed));
+    }
+
+    #[test]
+    fn tab_run_history_keeps_last_outcomes_across_resets() {
+        let mut tab = Tab::new("test".into(), 100);
+        tab.end_run(CommandStatus::Finished { exit_code: 0 }, Local::now());
+        assert!(tab.run_history().is_empty());
+
+        for exit_code in 0..12 {
+            tab.reset();
+            tab.begin_run(Local::now());
+            tab.end_run(CommandStatus::Finished { exit_code }, Local::now());
+        }
+
+        let codes: Vec<_> = tab
+            .run_history()
+            .iter()
+            .map(|run| run.status.clone())
+            .collect();
+        assert_eq!(codes.len(), MAX_RUN_HISTORY);
+        assert_eq!(codes[0], CommandStatus::Finished { exit_code: 2 });
+        assert_eq!(codes[9], CommandStatus::Finished { exit_code: 11 });
+    }
+
+    #[rstest]
+    #[case(None, "── Mark · 09:05:00 ──")]
+    #[case(Some("clicked button"), "── Mark: clicked button · 09:05:00 ──")]
+    fn tab_insert_marker_appends_marker_line(#[case] label: Option<&str>, #[case] expected: &str) {
+        let mut tab = Tab::new("test".into(), 100);
+        let at = Local.with_ymd_and_hms(2026, 1, 2, 9, 5, 0).unwrap();
+
+        tab.insert_marker(label, at);
+
+        let line = tab.buffer().get(0).unwrap();
+        assert_eq!(line.kind, OutputKind::Marker);
+        assert_eq!(line.plain(), expected);
+    }
+
+    #[test]
+    fn tab_record_dropped_counts_and_inserts_divider() {
+        let mut tab = Tab::new("test".into(), 100);
+
+        tab.record_dropped(1500);
+        tab.record_dropped(10);
+
+        assert_eq!(tab.dropped_lines(), 1510);
+        let line = tab.buffer().get(0).unwrap();
+        assert_eq!(line.kind, OutputKind::Divider);
+        assert_eq!(line.plain(), "── dropped 1,500 lines (display too slow) ──");
+    }
+
+    #[test]
+    fn tab_reset_keeps_run_count() {
+        let mut tab = Tab::new("test".into(), 100);
+        tab.begin_run(Local::now());
+        tab.begin_run(Local::now());
+
+        tab.reset();
+        tab.begin_run(Local::now());
+
+        assert_eq!(tab.run_count(), 3);
+        assert_eq!(tab.buffer().len(), 1);
+        assert_eq!(tab.buffer().get(0).unwrap().kind, OutputKind::Divider);
+    }
+
+    #[test]
+    fn tab_clear_output_keeps_scroll_positions() {
+        let mut tab = Tab::new("test".into(), 100);
+        tab.set_visible_lines(3);
+        for i in 0..10 {
+            tab.push_output(OutputLine::new(OutputKind::Stdout, format!("line{}", i)));
+        }
+        tab.set_auto_scroll(false);
+        tab.scroll_to_line(5);
+        tab.scroll_right();
+        tab.start_selection(6);
+
+        tab.clear_output();
+
+        assert!(tab.buffer().is_empty());
+        assert!(tab.selection().is_none());
+        assert_eq!(tab.scroll_offset(), 5);
+        assert_eq!(tab.horizontal_scroll(), 1);
+        assert!(!tab.auto_scroll());
+    }
+
+    #[test]
+    fn tab_since_hides_older_lines() {
+        let at = |minute| Local.with_ymd_and_hms(2026, 3, 1, 14, minute, 0).unwrap();
+        let mut tab = Tab::new("test".into(), 100);
+        tab.set_visible_lines(3);
+        for minute in 0..10 {
+            tab.push_output(OutputLine::new(OutputKind::Stdout, minute.to_string()).at(at(minute)));
+        }
+
+        tab.set_since(Some(at(8)));
+        assert_eq!(tab.scroll_offset(), 8);
+        tab.set_auto_scroll(false);
+        tab.scroll_to_top();
+        assert_eq!(tab.scroll_offset(), 8);
+        tab.scroll_up();
+        assert_eq!(tab.scroll_offset(), 8);
+
+        tab.set_since(None);
+        tab.scroll_to_top();
+        assert_eq!(tab.scroll_offset(), 0);
+    }
+
+    #[test]
+    fn tab_counts_errors_and_warnings_across_restarts() {
+        let mut tab = Tab::new("test".into(), 2);
+        for (kind, text) in [
+            (OutputKind::Stdout, "Compiling app"),
+            (
+                OutputKind::Stderr,
+                "\x1b[33mwarning\x1b[0m: unused variable",
+            ),
+            (OutputKind::Stderr, "error[E0425]: cannot find value"),
+        ] {
+            tab.push_output(OutputLine::new(kind, text.into()));
+        }
+        tab.insert_marker(Some("error"), Local::now());
+
+        tab.reset();
+        tab.push_output(OutputLine::new(OutputKind::Stdout, "[ERROR] boom".into()));
+
+        assert_eq!(
+            tab.line_counts(),
+            LineCounts {
+                lines: 4,
+                errors: 2,
+                warnings: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn tab_quiet_collapses_until_failure_or_expanded() {
+        let spec = CommandSpec {
+            quiet: true,
+            ..CommandSpec::new("cargo clippy")
+        };
+        let mut tab = Tab::new(spec, 100);
+        tab.begin_run(Local::now());
+        for _ in 0..142 {
+            tab.push_output(OutputLine::new(OutputKind::Stdout, "checking".into()));
+        }
+        assert!(tab.collapsed());
+        assert_eq!(tab.quiet_summary(), "142 lines so far");
+
+        tab.set_status(CommandStatus::Finished { exit_code: 0 });
+        assert!(tab.collapsed());
+        assert_eq!(tab.quiet_summary(), "142 lines, exit 0");
+        tab.toggle_expanded();
+        assert!(!tab.collapsed());
+
+        tab.toggle_expanded();
+        tab.set_status(CommandStatus::Finished { exit_code: 1 });
+        assert!(!tab.collapsed());
+    }
+
+    #[test]
+    fn tab_reset_keeps_note() {
+        let mut tab = Tab::new("test".into(), 100);
+        tab.set_note(Some("restarted after migration".into()));
+
+        tab.reset();
+
+        assert_eq!(tab.note(), Some("restarted after migration"));
+        tab.set_note(Some("  ".into()));
+        assert_eq!(tab.note(), None);
+    }
+
+    #[test]
+    fn tab_reset_keeps_pinned_lines() {
+        let mut tab = Tab::new("test".into(), 100);
+        tab.push_output(OutputLine::new(OutputKind::Stdout, "listening".into()));
+        tab.toggle_pin(0);
+
+        tab.reset();
+
+        assert_eq!(tab.pinned_lines().len(), 1);
+    }
 }
@@ -1,24 +1,178 @@
-use crate::tui::tab::Tab;
+use std::cmp::Reverse;
+use std::fmt;
+
+use crate::command::CommandSpec;
+use crate::tui::tab::{CommandStatus, Tab};
+
+/// Number of tabs in each state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatusSummary {
+    pub running: usize,
+    pub waiting: usize,
+    /// Exited with code 0
+    pub succeeded: usize,
+    /// Exited with a non-zero code or failed to start
+    pub failed: usize,
+    /// Terminated by a signal
+    pub killed: usize,
+}
+
+impl fmt::Display for StatusSummary {
+    /// Formats as e.g. `3 running, 1 failed`, omitting zero counts
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = [
+            (self.running, "running"),
+            (self.waiting, "waiting"),
+            (self.succeeded, "done"),
+            (self.failed, "failed"),
+            (self.killed, "killed"),
+        ];
+        let parts: Vec<String> = counts
+            .iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, label)| format!("{} {}", count, label))
+            .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Order of the tabs in the tab bar (`:sort`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TabOrder {
+    /// As given on the command line or in the config file
+    #[default]
+    Config,
+    /// Failed first, then running and waiting, then finished successfully
+    Status,
+    /// Most recent output first
+    Activity,
+}
+
+impl TabOrder {
+    /// Parse the argument of `:sort`
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "status" => Some(TabOrder::Status),
+            "activity" => Some(TabOrder::Activity),
+            _ => None,
+        }
+    }
+
+    /// Get the name shown when the tabs are sorted
+    pub fn label(self) -> &'static str {
+        match self {
+            TabOrder::Config => "config order",
+            TabOrder::Status => "status",
+            TabOrder::Activity => "activity",
+        }
+    }
+}
+
+/// Rank of a status when sorting by status (lower comes first)
+fn status_rank(status: &CommandStatus) -> u8 {
+    match status {
+        CommandStatus::Finished { exit_code: 0 } => 2,
+        CommandStatus::Running | CommandStatus::Waiting => 1,
+        CommandStatus::Finished { .. }
+        | CommandStatus::Failed { .. }
+        | CommandStatus::Signaled { .. } => 0,
+    }
+}
 
 /// Multiple tab manager
 pub struct TabManager {
     tabs: Vec<Tab>,
     active_index: usize,
+    max_buffer_lines: usize,
+    /// Indices of the tabs in the order they are shown in
+    ///
+    /// Tabs themselves never move, so events addressed by index keep
+    /// reaching the right tab whatever the order.
+    order: Vec<usize>,
+    /// Leave tabs that finished successfully out of the tab bar (`F`)
+    hide_finished: bool,
+    /// Newest lines of each tab kept uncompressed, if older ones are compressed
+    #[cfg(feature = "compress")]
+    compress_after: Option<usize>,
 }
 
 impl TabManager {
-    /// Create TabManager from command list
-    pub fn new(commands: Vec<String>, max_buffer_lines: usize) -> Self {
-        let tabs = commands
+    /// Create TabManager from command specs
+    pub fn new(commands: Vec<CommandSpec>, max_buffer_lines: usize) -> Self {
+        let tabs: Vec<Tab> = commands
             .into_iter()
-            .map(|cmd| Tab::new(cmd, max_buffer_lines))
+            .map(|spec| Tab::new(spec, max_buffer_lines))
             .collect();
         Self {
+            order: (0..tabs.len()).collect(),
             tabs,
             active_index: 0,
+            max_buffer_lines,
+            hide_finished: false,
+            #[cfg(feature = "compress")]
+            compress_after: None,
+        }
+    }
+
+    /// Keep all but the newest `lines` lines of every tab compressed,
+    /// including tabs added later
+    #[cfg(feature = "compress")]
+    pub fn set_compress_after(&mut self, lines: usize) {
+        self.compress_after = Some(lines);
+        for tab in &mut self.tabs {
+            tab.set_compress_after(lines);
+        }
+    }
+
+    /// Compress old output lines of every tab that are out of view again
+    #[cfg(feature = "compress")]
+    pub fn compact_buffers(&mut self) {
+        for tab in &mut self.tabs {
+            tab.compact_buffer();
+        }
+    }
+
+    /// Add a new tab at the end and return its index
+    pub fn add_tab(&mut self, spec: CommandSpec) -> usize {
+        self.tabs.push(Tab::new(spec, self.max_buffer_lines));
+        #[cfg(feature = "compress")]
+        if let (Some(lines), Some(tab)) = (self.compress_after, self.tabs.last_mut()) {
+            tab.set_compress_after(lines);
+        }
+        self.order.push(self.tabs.len() - 1);
+        self.tabs.len() - 1
+    }
+
+    /// Switch to the tab at the specified index
+    ///
+    /// Does nothing if the index is out of range.
+    pub fn select_tab(&mut self, index: usize) {
+        if index < self.tabs.len() {
+            self.active_index = index;
+            self.tabs[index].mark_read();
         }
     }
 
+    /// Find a tab by name, title or command, or by its 1-based position
+    ///
+    /// Names take precedence, so a tab named `2` is found by name.
+    pub fn find_tab(&self, name_or_index: &str) -> Option<usize> {
+        self.tabs
+            .iter()
+            .position(|tab| {
+                tab.name() == name_or_index
+                    || tab.command() == name_or_index
+                    || tab.spec().title() == name_or_index
+            })
+            .or_else(|| {
+                name_or_index
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|n| n.checked_sub(1))
+                    .filter(|&index| index < self.tabs.len())
+            })
+    }
+
     /// Get tab count
     pub fn len(&self) -> usize {
         self.tabs.len()
@@ -34,24 +188,93 @@ impl TabManager {
         self.active_index
     }
 
-    /// Switch to next tab (wrapping)
+    /// Switch to next shown tab (wrapping)
     pub fn next_tab(&mut self) {
-        if !self.tabs.is_empty() {
-            self.active_index = (self.active_index + 1) % self.tabs.len();
-        }
+        self.step_tab(true);
     }
 
-    /// Switch to previous tab (wrapping)
+    /// Switch to previous shown tab (wrapping)
     pub fn prev_tab(&mut self) {
-        if !self.tabs.is_empty() {
-            self.active_index = if self.active_index == 0 {
-                self.tabs.len() - 1
-            } else {
-                self.active_index - 1
-            };
+        self.step_tab(false);
+    }
+
+    /// Switch to the nearest shown tab after (or before) the active one in
+    /// tab bar order
+    fn step_tab(&mut self, forward: bool) {
+        let len = self.order.len();
+        let Some(position) = self.order.iter().position(|&i| i == self.active_index) else {
+            return;
+        };
+        if let Some(index) = (1..len)
+            .map(|n| {
+                let position = if forward {
+                    position + n
+                } else {
+                    position + len - n
+                };
+                self.order[position % len]
+            })
+            .find(|&index| self.is_shown(index))
+        {
+            self.select_tab(index);
+        }
+    }
+
+    /// Reorder the tab bar
+    ///
+    /// The order is taken once: run `:sort` again to bring it up to date.
+    /// Tabs that compare equal keep their config order.
+    pub fn sort(&mut self, order: TabOrder) {
+        self.order = (0..self.tabs.len()).collect();
+        match order {
+            TabOrder::Config => {}
+            TabOrder::Status => self
+                .order
+                .sort_by_key(|&i| status_rank(self.tabs[i].status())),
+            TabOrder::Activity => self
+                .order
+                .sort_by_key(|&i| Reverse(self.tabs[i].last_activity())),
         }
     }
 
+    /// Check if tabs that finished successfully are hidden
+    pub fn hides_finished(&self) -> bool {
+        self.hide_finished
+    }
+
+    /// Hide or show again the tabs that finished successfully
+    pub fn toggle_hide_finished(&mut self) {
+        self.hide_finished = !self.hide_finished;
+    }
+
+    /// Check if the tab at `index` is in the tab bar
+    ///
+    /// With `hide_finished`, tabs that exited with code 0 and won't run
+    /// again are left out, except the active one. Hidden tabs keep their
+    /// index, so events and `select_tab` still reach them.
+    pub fn is_shown(&self, index: usize) -> bool {
+        let Some(tab) = self.tabs.get(index) else {
+            return false;
+        };
+        !self.hide_finished
+            || index == self.active_index
+            || tab.next_run().is_some()
+            || *tab.status() != CommandStatus::Finished { exit_code: 0 }
+    }
+
+    /// Get the shown tabs with their indices, in tab bar order
+    pub fn shown(&self) -> impl Iterator<Item = (usize, &Tab)> {
+        self.order
+            .iter()
+            .filter(|&&index| self.is_shown(index))
+            .map(|&index| (index, &self.tabs[index]))
+    }
+
+    /// Count the tabs left out of the tab bar
+    pub fn hidden_count(&self) -> usize {
+        self.tabs.len() - self.shown().count()
+    }
+
     /// Get reference to current tab
     pub fn current_tab(&self) -> &Tab {
         &self.tabs[self.active_index]
@@ -81,6 +304,30 @@ impl TabManager {
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Tab> {
         self.tabs.iter_mut()
     }
+
+    /// Check if every command has finished and no further run is scheduled
+    pub fn all_done(&self) -> bool {
+        self.tabs
+            .iter()
+            .all(|tab| tab.status().is_done() && tab.next_run().is_none())
+    }
+
+    /// Count tabs by status
+    pub fn summary(&self) -> StatusSummary {
+        let mut summary = StatusSummary::default();
+        for tab in &self.tabs {
+            match tab.status() {
+                CommandStatus::Running => summary.running += 1,
+                CommandStatus::Waiting => summary.waiting += 1,
+                CommandStatus::Finished { exit_code: 0 } => summary.succeeded += 1,
+                CommandStatus::Signaled { .. } => summary.killed += 1,
+                CommandStatus::Finished { .. } | CommandStatus::Failed { .. } => {
+                    summary.failed += 1
+                }
+            }
+        }
+        summary
+    }
 }
 
 #[cfg(test)]
@@ -97,9 +344,163 @@ mod tests {
         assert_eq!(manager.active_index(), 0);
     }
 
+    #[test]
+    fn tab_manager_summary_counts_statuses() {
+        let commands = vec!["a".into(), "b".into(), "c".into(), "d".into(), "e".into()];
+        let mut manager = TabManager::new(commands, 100);
+        manager
+            .get_tab_mut(1)
+            .unwrap()
+            .set_status(CommandStatus::Failed {
+                reason: "not found".into(),
+            });
+        manager
+            .get_tab_mut(2)
+            .unwrap()
+            .set_status(CommandStatus::Finished { exit_code: 0 });
+        manager
+            .get_tab_mut(4)
+            .unwrap()
+            .set_status(CommandStatus::Signaled { signal: 11 });
+
+        let summary = manager.summary();
+
+        assert_eq!(summary.running, 2);
+        assert_eq!(summary.succeeded, 1);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(summary.killed, 1);
+        assert_eq!(summary.to_string(), "2 running, 1 done, 1 failed, 1 killed");
+    }
+
+    #[test]
+    fn tab_manager_all_done_requires_finished_tabs_without_next_run() {
+        let mut manager = TabManager::new(vec!["a".into(), "b".into()], 100);
+        manager
+            .get_tab_mut(0)
+            .unwrap()
+            .set_status(CommandStatus::Finished { exit_code: 0 });
+        assert!(!manager.all_done());
+
+        manager
+            .get_tab_mut(1)
+            .unwrap()
+            .set_status(CommandStatus::Signaled { signal: 9 });
+        assert!(manager.all_done());
+
+        manager
+            .get_tab_mut(0)
+            .unwrap()
+            .set_next_run(Some(std::time::Instant::now()));
+        assert!(!manager.all_done());
+    }
+
+    #[test]
+    fn tab_manager_hide_finished_skips_succeeded_tabs() {
+        let commands = vec!["a".into(), "b".into(), "c".into(), "d".into()];
+        let mut manager = TabManager::new(commands, 100);
+        for (index, exit_code) in [(1, 0), (2, 1)] {
+            manager
+                .get_tab_mut(index)
+                .unwrap()
+                .set_status(CommandStatus::Finished { exit_code });
+        }
+
+        manager.toggle_hide_finished();
+
+        let shown: Vec<usize> = manager.shown().map(|(index, _)| index).collect();
+        assert_eq!(shown, vec![0, 2, 3]);
+        assert_eq!(manager.hidden_count(), 1);
+        manager.next_tab();
+        assert_eq!(manager.active_index(), 2);
+        manager.prev_tab();
+        assert_eq!(manager.active_index(), 0);
+        manager.prev_tab();
+        assert_eq!(manager.active_index(), 3);
+
+        // The active tab stays shown even once it is hidden
+        manager.select_tab(1);
+        assert!(manager.is_shown(1));
+        manager.toggle_hide_finished();
+        assert_eq!(manager.hidden_count(), 0);
+    }
+
+    #[test]
+    fn tab_manager_sort_by_status_keeps_indices() {
+        let commands = vec!["a".into(), "b".into(), "c".into(), "d".into()];
+        let mut manager = TabManager::new(commands, 100);
+        let statuses = [
+            CommandStatus::Finished { exit_code: 0 },
+            CommandStatus::Running,
+            CommandStatus::Finished { exit_code: 2 },
+            CommandStatus::Waiting,
+        ];
+        for (index, status) in statuses.into_iter().enumerate() {
+            manager.get_tab_mut(index).unwrap().set_status(status);
+        }
+
+        manager.sort(TabOrder::Status);
+
+        let shown: Vec<usize> = manager.shown().map(|(index, _)| index).collect();
+        assert_eq!(shown, vec![2, 1, 3, 0]);
+        assert_eq!(manager.get_tab(2).unwrap().command(), "c");
+        manager.next_tab();
+        assert_eq!(manager.active_index(), 2);
+        manager.prev_tab();
+        assert_eq!(manager.active_index(), 0);
+
+        manager.sort(TabOrder::Config);
+        let shown: Vec<usize> = manager.shown().map(|(index, _)| index).collect();
+        assert_eq!(shown, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn tab_manager_sort_by_activity_puts_recent_output_first() {
+        let commands = vec!["a".into(), "b".into(), "c".into()];
+        let mut manager = TabManager::new(commands, 100);
+        for index in [2, 0] {
+            manager.get_tab_mut(index).unwrap().reset_silence();
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        manager.sort(TabOrder::Activity);
+
+        // Tabs that never started come last
+        let shown: Vec<usize> = manager.shown().map(|(index, _)| index).collect();
+        assert_eq!(shown, vec![0, 2, 1]);
+    }
+
+    #[rstest::rstest]
+    #[case("web", Some(1))]
+    #[case("cargo test", Some(2))]
+    #[case("make:build", Some(0))]
+    #[case("1", Some(0))]
+    #[case("2", Some(2))]
+    #[case("4", None)]
+    #[case("0", None)]
+    #[case("db", None)]
+    fn tab_manager_find_tab_by_name_or_position(
+        #[case] name_or_index: &str,
+        #[case] expected: Option<usize>,
+    ) {
+        let named = |name: &str, command: &str| CommandSpec {
+            name: Some(name.into()),
+            ..CommandSpec::new(command)
+        };
+        let manager = TabManager::new(
+            vec![
+                "make -j8 build".into(),
+                named("web", "npm start"),
+                named("2", "cargo test"),
+            ],
+            100,
+        );
+
+        assert_eq!(manager.find_tab(name_or_index), expected);
+    }
+
     #[test]
     fn tab_manager_new_with_empty_commands() {
-        let commands: Vec<String> = vec![];
+        let commands: Vec<CommandSpec> = vec![];
         let manager = TabManager::new(commands, 100);
 
         assert_eq!(manager.len(), 0);
@@ -196,9 +597,42 @@ mod tests {
         assert_eq!(tab_commands, vec!["cmd1", "cmd2"]);
     }
 
+    #[test]
+    fn tab_manager_add_tab_appends_tab() {
+        let mut manager = TabManager::new(vec!["cmd1".into()], 100);
+
+        let index = manager.add_tab("cmd2".into());
+
+        assert_eq!(index, 1);
+        assert_eq!(manager.len(), 2);
+        assert_eq!(manager.get_tab(1).unwrap().command(), "cmd2");
+        assert_eq!(manager.active_index(), 0);
+    }
+
+    #[test]
+    fn tab_manager_switching_marks_tab_read() {
+        let mut tm = TabManager::new(vec!["a".into(), "b".into()], 100);
+        tm.get_tab_mut(1).unwrap().mark_unread();
+
+        tm.next_tab();
+
+        assert_eq!(tm.current_tab().unread_lines(), 0);
+    }
+
+    #[test]
+    fn tab_manager_select_tab_ignores_out_of_range() {
+        let mut manager = TabManager::new(vec!["cmd1".into(), "cmd2".into()], 100);
+
+        manager.select_tab(1);
+        assert_eq!(manager.active_index(), 1);
+
+        manager.select_tab(5);
+        assert_eq!(manager.active_index(), 1);
+    }
+
     #[test]
     fn tab_manager_next_prev_on_empty_does_nothing() {
-        let commands: Vec<String> = vec![];
+        let commands: Vec<CommandSpec> = vec![];
         let mut manager = TabManager::new(commands, 100);
 
         manager.next_tab();
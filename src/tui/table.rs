@@ -0,0 +1,174 @@
+use std::ops::Range;
+use std::sync::LazyLock;
+
+use ratatui::text::Span;
+use regex::Regex;
+
+/// Space put between aligned columns
+const GAP: &str = "  ";
+
+/// Column separators: tabs (with any spaces around them) or runs of two or
+/// more spaces
+static SEPARATOR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r" *\t[\t ]*| {2,}").expect("valid regex"));
+
+/// Byte ranges of a line's cells, ignoring leading and trailing separators
+fn cells(text: &str) -> Vec<Range<usize>> {
+    let mut cells = Vec::new();
+    let mut start = 0;
+    for separator in SEPARATOR.find_iter(text) {
+        if separator.start() > start {
+            cells.push(start..separator.start());
+        }
+        start = separator.end();
+    }
+    if start < text.len() {
+        cells.push(start..text.len());
+    }
+    cells
+}
+
+/// Display width of some text
+fn width(text: &str) -> usize {
+    Span::raw(text).width()
+}
+
+/// Columns inferred from the lines of a table view
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct Columns {
+    /// Widest cell of each column
+    widths: Vec<usize>,
+}
+
+impl Columns {
+    /// Infer the columns of some lines; lines with a single cell don't count
+    pub(crate) fn infer<'a>(lines: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut widths: Vec<usize> = Vec::new();
+        for text in lines {
+            let cells = cells(text);
+            if cells.len() < 2 {
+                continue;
+            }
+            for (column, cell) in cells.into_iter().enumerate() {
+                let cell_width = width(&text[cell]);
+                match widths.get_mut(column) {
+                    Some(widest) => *widest = (*widest).max(cell_width),
+                    None => widths.push(cell_width),
+                }
+            }
+        }
+        Self { widths }
+    }
+
+    /// Pad a line's cells to the column widths
+    ///
+    /// A line with a single cell is left as it is.
+    pub(crate) fn align(&self, text: &str) -> AlignedLine {
+        let cells = cells(text);
+        if cells.len() < 2 {
+            return AlignedLine {
+                text: text.to_string(),
+                cells: vec![(0..text.len(), 0)],
+            };
+        }
+        let mut aligned = String::with_capacity(text.len());
+        let mut positions = Vec::with_capacity(cells.len());
+        for (column, cell) in cells.iter().enumerate() {
+            if column > 0 {
+                aligned.push_str(GAP);
+            }
+            positions.push((cell.clone(), aligned.len()));
+            let content = &text[cell.clone()];
+            aligned.push_str(content);
+            if column + 1 < cells.len() {
+                let widest = self.widths.get(column).copied().unwrap_or_default();
+                aligned.extend(std::iter::repeat_n(
+                    ' ',
+                    widest.saturating_sub(width(content)),
+                ));
+            }
+        }
+        AlignedLine {
+            text: aligned,
+            cells: positions,
+        }
+    }
+}
+
+/// A line with its cells padded to the columns of a table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AlignedLine {
+    pub(crate) text: String,
+    /// Byte range of each cell in the original line, and where it starts in `text`
+    cells: Vec<(Range<usize>, usize)>,
+}
+
+impl AlignedLine {
+    /// Translate a byte offset in the original line (e.g. of a search match)
+    /// into the aligned text
+    ///
+    /// Offsets in a separator move to the start of the next cell.
+    pub(crate) fn position(&self, offset: usize) -> usize {
+        self.cells
+            .iter()
+            .find(|(cell, _)| offset <= cell.end)
+            .map_or(self.text.len(), |(cell, start)| {
+                start + offset.saturating_sub(cell.start)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const PODS: [&str; 3] = [
+        "NAME    READY   STATUS    RESTARTS",
+        "web-7d9f   1/1   Running   0",
+        "db\t0/1\tCrashLoopBackOff\t12",
+    ];
+
+    #[test]
+    fn table_aligns_cells_to_widest_of_each_column() {
+        let table = Columns::infer(PODS);
+
+        let aligned: Vec<String> = PODS.iter().map(|line| table.align(line).text).collect();
+
+        assert_eq!(
+            aligned,
+            [
+                "NAME      READY  STATUS            RESTARTS",
+                "web-7d9f  1/1    Running           0",
+                "db        0/1    CrashLoopBackOff  12",
+            ]
+        );
+    }
+
+    #[test]
+    fn table_leaves_single_cell_lines_alone() {
+        let prose = "No resources found in the default namespace, retrying";
+        let table = Columns::infer(PODS.into_iter().chain([prose]));
+
+        assert_eq!(table, Columns::infer(PODS));
+        assert_eq!(table.align(prose).text, prose);
+        assert_eq!(table.align(prose).position(7), 7);
+    }
+
+    #[rstest]
+    #[case(0, 0)]
+    #[case(3, 3)]
+    #[case(9, 10)]
+    #[case(11, 10)]
+    #[case(16, 17)]
+    #[case(27, 35)]
+    #[case(99, 36)]
+    fn aligned_line_maps_offsets_into_cells(#[case] offset: usize, #[case] expected: usize) {
+        let table = Columns::infer(PODS);
+
+        // "web-7d9f   1/1   Running   0" -> "web-7d9f  1/1    Running           0"
+        let line = table.align("web-7d9f   1/1   Running   0");
+
+        assert_eq!(line.position(offset), expected);
+    }
+}
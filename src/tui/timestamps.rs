@@ -0,0 +1,86 @@
+use chrono::{DateTime, Local, TimeDelta};
+
+/// Width of a time since the run started, below an hour
+pub const RELATIVE_WIDTH: usize = "+00:00.000".len();
+
+/// Width a time since the previous line is padded to
+pub const DELTA_WIDTH: usize = "+000.000s".len();
+
+/// Times shown in front of output lines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum TimestampMode {
+    /// No times
+    #[default]
+    Off,
+    /// Time since the run started (`+00:03.210`)
+    Relative,
+    /// Time since the run started and since the previous line
+    Delta,
+}
+
+impl TimestampMode {
+    /// Get the mode after this one, wrapping around
+    pub fn next(self) -> Self {
+        match self {
+            TimestampMode::Off => TimestampMode::Relative,
+            TimestampMode::Relative => TimestampMode::Delta,
+            TimestampMode::Delta => TimestampMode::Off,
+        }
+    }
+
+    /// Get the name shown when the mode is switched
+    pub fn label(self) -> &'static str {
+        match self {
+            TimestampMode::Off => "off",
+            TimestampMode::Relative => "relative to run start",
+            TimestampMode::Delta => "relative to run start and previous line",
+        }
+    }
+}
+
+/// Format the time since the run started as `+MM:SS.mmm` (`+H:MM:SS.mmm`
+/// from an hour on)
+pub fn format_relative(time: DateTime<Local>, run_start: DateTime<Local>) -> String {
+    let millis = (time - run_start).num_milliseconds().max(0);
+    let (secs, millis) = (millis / 1000, millis % 1000);
+    let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("+{}:{:02}:{:02}.{:03}", hours, minutes, secs, millis)
+    } else {
+        format!("+{:02}:{:02}.{:03}", minutes, secs, millis)
+    }
+}
+
+/// Format the time since the previous line as `+S.mmms`, padded to line up
+pub fn format_delta(delta: TimeDelta) -> String {
+    let millis = delta.num_milliseconds().max(0);
+    let delta = format!("+{}.{:03}s", millis / 1000, millis % 1000);
+    format!("{:>width$}", delta, width = DELTA_WIDTH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(3_210, "+00:03.210")]
+    #[case(754_005, "+12:34.005")]
+    #[case(3_723_004, "+1:02:03.004")]
+    #[case(-5, "+00:00.000")]
+    fn format_relative_shows_time_since_run_start(#[case] millis: i64, #[case] expected: &str) {
+        let start = Local.with_ymd_and_hms(2026, 3, 1, 14, 0, 0).unwrap();
+
+        let time = start + TimeDelta::milliseconds(millis);
+
+        assert_eq!(format_relative(time, start), expected);
+    }
+
+    #[rstest]
+    #[case(512, "  +0.512s")]
+    #[case(83_004, " +83.004s")]
+    fn format_delta_pads_to_line_up(#[case] millis: i64, #[case] expected: &str) {
+        assert_eq!(format_delta(TimeDelta::milliseconds(millis)), expected);
+    }
+}
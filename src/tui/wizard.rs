@@ -0,0 +1,265 @@
+use tui_input::{Input, InputRequest};
+
+use crate::command::CommandSpec;
+
+/// Where a command offered by the wizard comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    /// The config file in the current directory
+    Config,
+    /// Commands run in earlier sessions
+    History,
+}
+
+/// A command the wizard offers to pick
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub spec: CommandSpec,
+    pub origin: Origin,
+}
+
+/// What the main loop should do after a key in the wizard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WizardAction {
+    /// Keep asking
+    None,
+    /// Start the session with the chosen commands
+    Start,
+    /// Quit without starting anything
+    Quit,
+}
+
+/// State of the startup wizard shown when no commands are given
+///
+/// Commands are typed, or picked from the config file and history; the
+/// input filters the offered commands as it is typed.
+#[derive(Debug, Default)]
+pub struct Wizard {
+    input: Input,
+    candidates: Vec<Candidate>,
+    /// Index into the offered commands matching the input
+    highlighted: usize,
+    chosen: Vec<CommandSpec>,
+    /// Command lines typed rather than picked, for the history
+    typed: Vec<String>,
+}
+
+impl Wizard {
+    /// Create a wizard offering config commands, then history newest first
+    ///
+    /// History entries already offered by the config are left out.
+    pub fn new(config: Vec<CommandSpec>, history: &[String]) -> Self {
+        let mut candidates: Vec<Candidate> = config
+            .into_iter()
+            .map(|spec| Candidate {
+                spec,
+                origin: Origin::Config,
+            })
+            .collect();
+        for entry in history.iter().rev() {
+            if !candidates.iter().any(|c| c.spec.command == *entry) {
+                candidates.push(Candidate {
+                    spec: CommandSpec::parse(entry),
+                    origin: Origin::History,
+                });
+            }
+        }
+        Self {
+            candidates,
+            ..Self::default()
+        }
+    }
+
+    /// Get the command line being typed
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+
+    /// Edit the command line, filtering the offered commands
+    pub fn handle_input(&mut self, request: InputRequest) {
+        if self.input.handle(request).is_some() {
+            self.highlighted = 0;
+        }
+    }
+
+    /// Offered commands matching the input (case-insensitive), not yet chosen
+    pub fn matches(&self) -> Vec<&Candidate> {
+        let query = self.input.value().to_lowercase();
+        self.candidates
+            .iter()
+            .filter(|c| {
+                !self
+                    .chosen
+                    .iter()
+                    .any(|spec| spec.command == c.spec.command)
+            })
+            .filter(|c| {
+                c.spec.command.to_lowercase().contains(&query)
+                    || c.spec.title().to_lowercase().contains(&query)
+            })
+            .collect()
+    }
+
+    /// Get the index of the highlighted command in [`Wizard::matches`]
+    pub fn highlighted(&self) -> usize {
+        self.highlighted
+    }
+
+    /// Move the highlight down, or up with `forward` false, wrapping around
+    pub fn move_highlight(&mut self, forward: bool) {
+        let count = self.matches().len();
+        if count == 0 {
+            return;
+        }
+        self.highlighted = if forward {
+            (self.highlighted + 1) % count
+        } else {
+            (self.highlighted + count - 1) % count
+        };
+    }
+
+    /// Choose the highlighted command, clearing the input
+    pub fn choose_highlighted(&mut self) {
+        let Some(candidate) = self.matches().get(self.highlighted).map(|c| c.spec.clone()) else {
+            return;
+        };
+        self.chosen.push(candidate);
+        self.input.reset();
+        self.highlighted = 0;
+    }
+
+    /// Choose the typed command, or start once the input is empty
+    ///
+    /// Returns [`WizardAction::Start`] on an empty input if at least one
+    /// command was chosen.
+    pub fn submit(&mut self) -> WizardAction {
+        let line = self.input.value().trim().to_string();
+        if line.is_empty() {
+            return if self.chosen.is_empty() {
+                WizardAction::None
+            } else {
+                WizardAction::Start
+            };
+        }
+        self.chosen.push(CommandSpec::parse(&line));
+        self.typed.push(line);
+        self.input.reset();
+        self.highlighted = 0;
+        WizardAction::None
+    }
+
+    /// Remove the most recently chosen command
+    pub fn remove_last(&mut self) {
+        if let Some(spec) = self.chosen.pop() {
+            self.typed.retain(|line| *line != spec.command);
+        }
+    }
+
+    /// Get the chosen commands, in the order they were chosen
+    pub fn chosen(&self) -> &[CommandSpec] {
+        &self.chosen
+    }
+
+    /// Get the command lines that were typed rather than picked
+    pub fn typed(&self) -> &[String] {
+        &self.typed
+    }
+
+    /// Take the chosen commands to start the session with
+    pub fn into_commands(self) -> Vec<CommandSpec> {
+        self.chosen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_text(wizard: &mut Wizard, text: &str) {
+        for c in text.chars() {
+            wizard.handle_input(InputRequest::InsertChar(c));
+        }
+    }
+
+    fn wizard() -> Wizard {
+        let config = vec![CommandSpec {
+            name: Some("web".into()),
+            ..CommandSpec::new("npm run dev")
+        }];
+        // Oldest first, as in the history file
+        let history = ["make", "cargo test", "npm run dev"].map(String::from);
+        Wizard::new(config, &history)
+    }
+
+    fn offered(wizard: &Wizard) -> Vec<&str> {
+        wizard
+            .matches()
+            .iter()
+            .map(|c| c.spec.command.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn wizard_offers_config_then_newest_history() {
+        let wizard = wizard();
+
+        assert_eq!(offered(&wizard), vec!["npm run dev", "cargo test", "make"]);
+        assert_eq!(wizard.matches()[0].origin, Origin::Config);
+    }
+
+    #[test]
+    fn wizard_filters_by_input_and_name() {
+        let mut wizard = wizard();
+
+        type_text(&mut wizard, "WEB");
+
+        assert_eq!(offered(&wizard), vec!["npm run dev"]);
+    }
+
+    #[test]
+    fn wizard_chooses_highlighted_command() {
+        let mut wizard = wizard();
+        wizard.move_highlight(true);
+
+        wizard.choose_highlighted();
+
+        assert_eq!(wizard.chosen()[0].command, "cargo test");
+        assert_eq!(offered(&wizard), vec!["npm run dev", "make"]);
+        assert!(wizard.typed().is_empty());
+    }
+
+    #[test]
+    fn wizard_move_highlight_wraps_around() {
+        let mut wizard = wizard();
+
+        wizard.move_highlight(false);
+
+        assert_eq!(wizard.highlighted(), 2);
+    }
+
+    #[test]
+    fn wizard_submit_chooses_typed_command_then_starts() {
+        let mut wizard = wizard();
+        assert_eq!(wizard.submit(), WizardAction::None);
+
+        type_text(&mut wizard, "tail -f app.log");
+        assert_eq!(wizard.submit(), WizardAction::None);
+        assert_eq!(wizard.input().value(), "");
+        assert_eq!(wizard.typed(), ["tail -f app.log"]);
+
+        assert_eq!(wizard.submit(), WizardAction::Start);
+        assert_eq!(wizard.into_commands()[0].command, "tail -f app.log");
+    }
+
+    #[test]
+    fn wizard_remove_last_unchooses_command() {
+        let mut wizard = wizard();
+        type_text(&mut wizard, "ls");
+        wizard.submit();
+
+        wizard.remove_last();
+
+        assert!(wizard.chosen().is_empty());
+        assert!(wizard.typed().is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+//! End-to-end tests driving the real event loop with scripted input
+//!
+//! Run with `cargo test --features test-driver`.
+
+use std::time::Duration;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use parallels::app::{App, Mode};
+use parallels::command::{CommandSpec, Script, ScriptedSpawner};
+use parallels::testing::Driver;
+
+/// Time for queued keys to be handled and the next frame drawn
+const SETTLE: Duration = Duration::from_millis(100);
+
+/// App running each command in its own tab
+fn app(commands: &[&str]) -> App {
+    App::new(
+        commands
+            .iter()
+            .map(|command| CommandSpec::new(*command))
+            .collect(),
+        100,
+    )
+}
+
+fn ctrl(c: char) -> KeyEvent {
+    KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+}
+
+#[tokio::test]
+async fn shows_output_of_each_tab() {
+    let mut driver = Driver::start(app(&["echo first", "echo second"]), 60, 8).await;
+    driver.wait_for("All commands finished").await.unwrap();
+    insta::assert_snapshot!("first_tab", driver.screen());
+
+    driver.press_key(ctrl('l'));
+    driver.wait_for("[stdout] second").await.unwrap();
+    driver.run_for(SETTLE).await.unwrap();
+    insta::assert_snapshot!("second_tab", driver.screen());
+
+    driver.quit().await.unwrap();
+}
+
+#[tokio::test]
+async fn search_highlights_matches_and_counts_them() {
+    let mut driver = Driver::start(app(&["printf 'alpha\\nbeta\\nalphabet\\n'"]), 60, 8).await;
+    driver.wait_for("[stdout] alphabet").await.unwrap();
+
+    driver.press(KeyCode::Char('/'));
+    driver.type_text("alpha");
+    driver.wait_for("(1/2)").await.unwrap();
+    insta::assert_snapshot!(driver.screen());
+
+    driver.press(KeyCode::Enter);
+    driver.run_for(SETTLE).await.unwrap();
+    assert_eq!(driver.app().mode(), Mode::Normal);
+
+    driver.quit().await.unwrap();
+}
+
+#[tokio::test]
+async fn restart_runs_the_command_again() {
+    let mut driver = Driver::start(app(&["echo run"]), 60, 10).await;
+    driver.wait_for("All commands finished").await.unwrap();
+
+    driver.press(KeyCode::Char('R'));
+    driver.run_for(SETTLE).await.unwrap();
+    driver.wait_for("All commands finished").await.unwrap();
+
+    // The previous run's output is cleared
+    let runs = driver.screen().matches("[stdout] run").count();
+    assert_eq!(runs, 1, "{}", driver.screen());
+    assert_eq!(driver.app().tab_manager().current_tab().run_count(), 2);
+
+    driver.quit().await.unwrap();
+}
+
+#[tokio::test]
+async fn info_panel_opens_and_closes() {
+    let mut driver = Driver::start(app(&["echo info"]), 60, 12).await;
+    driver.wait_for("All commands finished").await.unwrap();
+
+    driver.press(KeyCode::Char('i'));
+    driver.wait_for("i to close").await.unwrap();
+    assert!(driver.screen().contains("Command"));
+
+    driver.press(KeyCode::Esc);
+    driver.run_for(SETTLE).await.unwrap();
+    assert!(!driver.screen().contains("i to close"));
+
+    driver.quit().await.unwrap();
+}
+
+#[tokio::test]
+async fn new_command_opens_a_tab() {
+    let mut driver = Driver::start(app(&["echo one"]), 60, 8).await;
+    driver.wait_for("All commands finished").await.unwrap();
+
+    driver.press(KeyCode::Char(':'));
+    driver.type_text("new echo two");
+    driver.press(KeyCode::Enter);
+    driver.wait_for("[stdout] two").await.unwrap();
+
+    assert_eq!(driver.app().tab_manager().len(), 2);
+    assert_eq!(driver.app().tab_manager().active_index(), 1);
+
+    driver.quit().await.unwrap();
+}
+
+#[tokio::test]
+async fn q_quits_once_all_commands_are_done() {
+    let mut driver = Driver::start(app(&["echo done"]), 60, 8).await;
+    driver.wait_for("All commands finished").await.unwrap();
+
+    driver.press(KeyCode::Char('q'));
+
+    assert!(driver.run_for(Duration::from_secs(1)).await.unwrap());
+}
+
+#[tokio::test]
+async fn scripted_commands_show_their_output_and_exit() {
+    let mut app = app(&["cargo test"]);
+    app.set_spawner(
+        ScriptedSpawner::new().script(
+            "cargo test",
+            Script::new()
+                .stdout("test parses ... ok")
+                .stderr("error: test failed")
+                .exit(101),
+        ),
+    );
+    let mut driver = Driver::start(app, 60, 8).await;
+    driver.wait_for("All commands finished").await.unwrap();
+
+    let screen = driver.screen();
+    assert!(screen.contains("[stdout] test parses ... ok"), "{}", screen);
+    assert!(screen.contains("[stderr] error: test failed"), "{}", screen);
+
+    driver.quit().await.unwrap();
+}